@@ -0,0 +1,313 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::defaults::{DirectusMeta, FilterOperator, QueryBuilder};
+use crate::utilities::date_util::format_utc_str_to_local_str;
+
+/// The state-changing actions of the admin portal that are recorded in the audit log.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditAction {
+    ApproveQuotes,
+    RejectQuotes,
+    EditQuotes,
+    EditTradeExpiry,
+    UpdateIv,
+    ApproveTermSheet,
+    RejectTermSheet,
+    UpdateInterestRate,
+    CreateCounterparty,
+    EditCounterparty,
+    DeactivateCounterparty,
+    ReactivateCounterparty,
+    OverrideCreditLimit,
+    ApproveTransfer,
+    ConfirmTransfer,
+    RejectTransfer,
+    CreateWalletTransaction,
+    CreateQuotes,
+    SubmitTermSheet,
+}
+
+impl AuditAction {
+    pub const ALL: [AuditAction; 19] = [
+        AuditAction::ApproveQuotes,
+        AuditAction::RejectQuotes,
+        AuditAction::EditQuotes,
+        AuditAction::EditTradeExpiry,
+        AuditAction::UpdateIv,
+        AuditAction::ApproveTermSheet,
+        AuditAction::RejectTermSheet,
+        AuditAction::UpdateInterestRate,
+        AuditAction::CreateCounterparty,
+        AuditAction::EditCounterparty,
+        AuditAction::DeactivateCounterparty,
+        AuditAction::ReactivateCounterparty,
+        AuditAction::OverrideCreditLimit,
+        AuditAction::ApproveTransfer,
+        AuditAction::ConfirmTransfer,
+        AuditAction::RejectTransfer,
+        AuditAction::CreateWalletTransaction,
+        AuditAction::CreateQuotes,
+        AuditAction::SubmitTermSheet,
+    ];
+
+    /// Returns the value stored in the `action` field of the audit log.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::ApproveQuotes => "approve_quotes",
+            AuditAction::RejectQuotes => "reject_quotes",
+            AuditAction::EditQuotes => "edit_quotes",
+            AuditAction::EditTradeExpiry => "edit_trade_expiry",
+            AuditAction::UpdateIv => "update_iv",
+            AuditAction::ApproveTermSheet => "approve_term_sheet",
+            AuditAction::RejectTermSheet => "reject_term_sheet",
+            AuditAction::UpdateInterestRate => "update_interest_rate",
+            AuditAction::CreateCounterparty => "create_counterparty",
+            AuditAction::EditCounterparty => "edit_counterparty",
+            AuditAction::DeactivateCounterparty => "deactivate_counterparty",
+            AuditAction::ReactivateCounterparty => "reactivate_counterparty",
+            AuditAction::OverrideCreditLimit => "override_credit_limit",
+            AuditAction::ApproveTransfer => "approve_transfer",
+            AuditAction::ConfirmTransfer => "confirm_transfer",
+            AuditAction::RejectTransfer => "reject_transfer",
+            AuditAction::CreateWalletTransaction => "create_wallet_transaction",
+            AuditAction::CreateQuotes => "create_quotes",
+            AuditAction::SubmitTermSheet => "submit_term_sheet",
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            AuditAction::ApproveQuotes => "Approve Quotes",
+            AuditAction::RejectQuotes => "Reject Quotes",
+            AuditAction::EditQuotes => "Edit Quotes",
+            AuditAction::EditTradeExpiry => "Edit Trade Expiry",
+            AuditAction::UpdateIv => "Update IV",
+            AuditAction::ApproveTermSheet => "Approve Term Sheet",
+            AuditAction::RejectTermSheet => "Reject Term Sheet",
+            AuditAction::UpdateInterestRate => "Update Interest Rate",
+            AuditAction::CreateCounterparty => "Create Counterparty",
+            AuditAction::EditCounterparty => "Edit Counterparty",
+            AuditAction::DeactivateCounterparty => "Deactivate Counterparty",
+            AuditAction::ReactivateCounterparty => "Reactivate Counterparty",
+            AuditAction::OverrideCreditLimit => "Override Credit Limit",
+            AuditAction::ApproveTransfer => "Approve Transfer",
+            AuditAction::ConfirmTransfer => "Confirm Transfer",
+            AuditAction::RejectTransfer => "Reject Transfer",
+            AuditAction::CreateWalletTransaction => "Create Wallet Transaction",
+            AuditAction::CreateQuotes => "Create Quotes",
+            AuditAction::SubmitTermSheet => "Submit Term Sheet",
+        }
+    }
+
+    pub fn from_value(action: &str) -> Option<Self> {
+        AuditAction::ALL.into_iter().find(|a| a.as_str() == action)
+    }
+}
+
+/// Struct for a new record of the `audit_log` collection.
+/// `before` holds the records as they were before the change, `after` the change that was sent.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditLogRequest {
+    pub actor: String,
+    pub action: String,
+    pub collection: String,
+    pub target_ids: Vec<String>,
+    pub group_ids: Vec<String>,
+    pub before: Value,
+    pub after: Value,
+    pub timestamp: String,
+}
+
+impl AuditLogRequest {
+    pub fn new(action: AuditAction, collection: &str, after: Value) -> Self {
+        Self {
+            actor: String::new(),
+            action: action.as_str().to_string(),
+            collection: collection.to_string(),
+            target_ids: vec![],
+            group_ids: vec![],
+            before: Value::Null,
+            after,
+            timestamp: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+        }
+    }
+
+    pub fn with_target_ids(mut self, target_ids: Vec<String>) -> Self {
+        self.target_ids = target_ids;
+        self
+    }
+
+    pub fn with_group_ids(mut self, group_ids: Vec<String>) -> Self {
+        self.group_ids = group_ids;
+        self
+    }
+
+    /// Sets the records before the change, the target and group ids not set yet are taken from them.
+    pub fn with_before(mut self, before: Value) -> Self {
+        let records = before.as_array().cloned().unwrap_or_default();
+        let collect = |key: &str| -> Vec<String> {
+            let mut values = records
+                .iter()
+                .filter_map(|r| r.get(key))
+                .map(|v| v.as_str().map_or_else(|| v.to_string(), |s| s.to_string()))
+                .collect::<Vec<String>>();
+            values.sort();
+            values.dedup();
+            values
+        };
+        if self.target_ids.is_empty() {
+            self.target_ids = collect("id");
+        }
+        if self.group_ids.is_empty() {
+            self.group_ids = collect("group_id");
+        }
+        self.before = before;
+        self
+    }
+}
+
+/// Struct for a record of the `audit_log` collection.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct AuditLog {
+    pub id: u32,
+    pub actor: String,
+    pub action: String,
+    pub collection: String,
+    #[serde(default)]
+    pub target_ids: Option<Vec<String>>,
+    #[serde(default)]
+    pub group_ids: Option<Vec<String>>,
+    #[serde(default)]
+    pub before: Value,
+    #[serde(default)]
+    pub after: Value,
+    pub timestamp: String,
+}
+
+impl AuditLog {
+    pub fn get_query() -> String {
+        String::from("id, actor, action, collection, target_ids, group_ids, before, after, timestamp")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct AuditLogResponse {
+    pub data: Vec<AuditLog>,
+    /// Only returned for paginated requests.
+    #[serde(default)]
+    pub meta: Option<DirectusMeta>,
+}
+
+impl AuditLogResponse {
+    /// Returns the number of records matching the request, falls back to the loaded records.
+    pub fn get_filter_count(&self) -> usize {
+        self.meta
+            .as_ref()
+            .and_then(|m| m.filter_count)
+            .unwrap_or(self.data.len())
+    }
+
+    /// Extract the audit logs into a Vector that can be shown in the data table.
+    pub fn extract(&self) -> Vec<ExtractedAuditLog> {
+        self.data
+            .iter()
+            .map(|a| ExtractedAuditLog {
+                id: a.id,
+                timestamp: format_utc_str_to_local_str(a.timestamp.clone()),
+                actor: a.actor.clone(),
+                action: AuditAction::from_value(a.action.as_str())
+                    .map_or_else(|| a.action.clone(), |action| action.get_name().to_string()),
+                collection: a.collection.clone(),
+                target_ids: a.target_ids.clone().unwrap_or_default().join(" "),
+                group_ids: a.group_ids.clone().unwrap_or_default().join(" "),
+                before: if a.before.is_null() { String::from("- -") } else { a.before.to_string() },
+                after: a.after.to_string(),
+            })
+            .collect()
+    }
+
+    pub fn extract_csv(&self) -> String {
+        let header = String::from("Timestamp,Actor,Action,Collection,Target Ids,Group Ids,Before,After\n");
+        self.extract().iter().fold(header, |mut csv, a| {
+            let line = [
+                &a.timestamp,
+                &a.actor,
+                &a.action,
+                &a.collection,
+                &a.target_ids,
+                &a.group_ids,
+                &a.before,
+                &a.after,
+            ]
+            .iter()
+            .map(|v| format!("\"{}\"", v.replace('"', "\"\"")))
+            .collect::<Vec<String>>()
+            .join(",");
+            csv.push_str(line.as_str());
+            csv.push('\n');
+            csv
+        })
+    }
+}
+
+/// Struct of an audit log as shown in the data table.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ExtractedAuditLog {
+    pub id: u32,
+    pub timestamp: String,
+    pub actor: String,
+    pub action: String,
+    pub collection: String,
+    pub target_ids: String,
+    pub group_ids: String,
+    pub before: String,
+    pub after: String,
+}
+
+impl ExtractedAuditLog {
+    /// Returns the Directus field used to sort a column of the data table on the server.
+    pub fn get_sort_field(key: &str) -> String {
+        match key {
+            "timestamp" => String::from("timestamp"),
+            "actor" => String::from("actor"),
+            "action" => String::from("action"),
+            "collection" => String::from("collection"),
+            _ => String::new(),
+        }
+    }
+}
+
+/// The filters of the Audit page, empty values are not filtered.
+/// The dates are local `YYYY-MM-DD` dates from the date inputs.
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AuditFilter {
+    pub actor: String,
+    pub action: String,
+    pub date_from: String,
+    pub date_to: String,
+}
+
+impl AuditFilter {
+    pub fn apply(&self, query: QueryBuilder) -> QueryBuilder {
+        let mut query = query;
+        if !self.actor.trim().is_empty() {
+            query = query.filter("actor", FilterOperator::Contains, json!(self.actor.trim()));
+        }
+        if !self.action.is_empty() {
+            query = query.filter("action", FilterOperator::Eq, json!(self.action));
+        }
+        if !self.date_from.is_empty() {
+            query = query.filter("timestamp", FilterOperator::Gte, json!(format!("{}T00:00:00", self.date_from)));
+        }
+        if !self.date_to.is_empty() {
+            query = query.filter("timestamp", FilterOperator::Lte, json!(format!("{}T23:59:59", self.date_to)));
+        }
+        query
+    }
+}
@@ -2,13 +2,20 @@ use serde::{Deserialize, Serialize};
 
 /// This struct is used to get the details of a counterparty.
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct CounterParty {
     pub id: u16,
     pub ticker: String,
     pub name: String,
     pub short_name: Option<String>,
     pub is_exchange: bool,
+    /// A deactivated counterparty is kept for its history but no longer offered in the selectors.
+    #[serde(default = "default_is_active")]
+    pub is_active: bool,
+}
+
+fn default_is_active() -> bool {
+    true
 }
 
 impl CounterParty {
@@ -18,6 +25,15 @@ impl CounterParty {
             key, key, key, key, key
         )
     }
+
+    /// Returns the name shown in the selectors, a deactivated counterparty is marked so its history can still be found.
+    pub fn get_display_name(&self) -> String {
+        if self.is_active {
+            self.name.clone()
+        } else {
+            format!("{} (inactive)", self.name)
+        }
+    }
 }
 
 /// This struct is the response of the [`get_counter_parties`] server function.
@@ -32,3 +48,70 @@ impl GetCounterPartiesResponse {
         self.data.iter().find(|cp| cp.name == name)
     }
 }
+
+/// Struct for the request to create or edit a counterparty.
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CounterPartyRequest {
+    pub ticker: String,
+    pub name: String,
+    pub short_name: Option<String>,
+    pub is_exchange: bool,
+}
+
+impl CounterPartyRequest {
+    pub fn new(ticker: String, name: String, short_name: String, is_exchange: bool) -> Self {
+        let short_name = short_name.trim().to_string();
+        Self {
+            ticker: ticker.trim().to_uppercase(),
+            name: name.trim().to_string(),
+            short_name: if short_name.is_empty() { None } else { Some(short_name) },
+            is_exchange,
+        }
+    }
+
+    pub fn from_counterparty(counterparty: &CounterParty) -> Self {
+        Self {
+            ticker: counterparty.ticker.clone(),
+            name: counterparty.name.clone(),
+            short_name: counterparty.short_name.clone(),
+            is_exchange: counterparty.is_exchange,
+        }
+    }
+
+    /// Checks the request against the existing counterparties.
+    /// The ticker has to be upper-case letters, digits or `_`, and unique, `id` is the counterparty being edited.
+    ///
+    /// # Returns
+    ///
+    /// The message shown to the user when the request is not valid.
+    pub fn validate(&self, existing: &[CounterParty], id: Option<u16>) -> Result<(), String> {
+        if self.ticker.is_empty() {
+            return Err(String::from("The ticker is required."));
+        }
+        if !self
+            .ticker
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        {
+            return Err(String::from("The ticker can only contain upper-case letters, digits and _."));
+        }
+        if self.name.is_empty() {
+            return Err(String::from("The name is required."));
+        }
+        if existing
+            .iter()
+            .any(|cp| Some(cp.id) != id && cp.ticker.eq_ignore_ascii_case(self.ticker.as_str()))
+        {
+            return Err(format!("The ticker {} is already used by another counterparty.", self.ticker));
+        }
+        Ok(())
+    }
+}
+
+/// Struct for the request to deactivate or reactivate a counterparty.
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CounterPartyStatusRequest {
+    pub is_active: bool,
+}
@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::quote::QuoteOption;
+use crate::utilities::number_util::format_money;
+
+/// Struct for a record of the `credit_limit` collection, one per counterparty.
+/// The limits are in USD, a limit that is not set is not checked.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct CreditLimit {
+    pub id: u32,
+    /// Maximum notional of the open trades and pending quotes, in each currency pair.
+    pub max_notional_per_pair: Option<f64>,
+    /// Maximum net delta of the open trades and pending quotes, over all the currency pairs.
+    pub max_net_delta: Option<f64>,
+    /// Maximum mark-to-market owed by the counterparty that is not covered by its collateral.
+    pub max_unsecured_mtm: Option<f64>,
+}
+
+impl CreditLimit {
+    pub fn get_query() -> String {
+        String::from("id, max_notional_per_pair, max_net_delta, max_unsecured_mtm")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct GetCreditLimitResponse {
+    pub data: Vec<CreditLimit>,
+}
+
+/// The exposure of JABRA to a counterparty, in USD.
+/// The delta and the mark-to-market are seen from JABRA, a positive mark-to-market is owed by the counterparty.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct CreditExposure {
+    /// Current spot of each currency pair, used to convert the quotes to USD.
+    pub spots: BTreeMap<String, f64>,
+    pub notional_per_pair: BTreeMap<String, f64>,
+    pub net_delta: f64,
+    pub mtm: f64,
+    pub collateral: f64,
+}
+
+impl CreditExposure {
+    /// Returns the mark-to-market owed by the counterparty that is not covered by its collateral.
+    pub fn unsecured_mtm(&self) -> f64 {
+        (self.mtm - self.collateral).max(0.0)
+    }
+
+    /// Adds the quotes of the counterparty, as if they were approved.
+    /// A pending quote is priced at its fair value, so it adds to the notional and the delta but not to the mark-to-market.
+    /// The delta of a quote is the delta of one long option, it is signed by the side of the counterparty.
+    /// A quote saved without its delta is priced locally, so it is never counted as delta neutral.
+    pub fn add_quotes(&mut self, quotes: &[QuoteOption]) {
+        for quote in quotes {
+            let pair = quote.pair_id.name.clone();
+            let spot = self.spots.get(&pair).copied().unwrap_or(quote.spot);
+            *self.notional_per_pair.entry(pair).or_insert(0.0) += quote.amount.abs() * spot;
+            let counterparty_delta = quote.get_delta(spot) * quote.amount.abs() * spot;
+            if quote.side == "Buy" {
+                self.net_delta -= counterparty_delta;
+            } else {
+                self.net_delta += counterparty_delta;
+            }
+        }
+    }
+}
+
+/// The utilisation of one credit limit.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct LimitUtilisation {
+    pub name: String,
+    pub used: f64,
+    pub limit: Option<f64>,
+}
+
+impl LimitUtilisation {
+    /// Returns the used share of the limit in percent, `None` when no limit is set.
+    pub fn get_percentage(&self) -> Option<f64> {
+        match self.limit {
+            Some(limit) if limit > 0.0 => Some(self.used / limit * 100.0),
+            Some(_) => Some(if self.used > 0.0 { f64::INFINITY } else { 0.0 }),
+            None => None,
+        }
+    }
+
+    pub fn is_breached(&self) -> bool {
+        self.limit.is_some_and(|limit| self.used > limit)
+    }
+
+    pub fn get_breach_message(&self) -> String {
+        format!(
+            "{} of {} USD exceeds the limit of {} USD",
+            self.name,
+            format_money(format!("{:.2}", self.used), ",", 2),
+            format_money(format!("{:.2}", self.limit.unwrap_or_default()), ",", 2)
+        )
+    }
+}
+
+/// The credit limit of a counterparty and the exposure it is checked against.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct CreditUtilisation {
+    pub ticker: String,
+    pub limit: Option<CreditLimit>,
+    pub exposure: CreditExposure,
+}
+
+impl CreditUtilisation {
+    /// Returns the utilisation of every limit, with one line per currency pair for the notional.
+    pub fn get_utilisations(&self) -> Vec<LimitUtilisation> {
+        let limit = self.limit.clone().unwrap_or_default();
+        let mut utilisations = self
+            .exposure
+            .notional_per_pair
+            .iter()
+            .map(|(pair, notional)| LimitUtilisation {
+                name: format!("Notional {}", pair),
+                used: *notional,
+                limit: limit.max_notional_per_pair,
+            })
+            .collect::<Vec<LimitUtilisation>>();
+        utilisations.push(LimitUtilisation {
+            name: String::from("Net Delta"),
+            used: self.exposure.net_delta.abs(),
+            limit: limit.max_net_delta,
+        });
+        utilisations.push(LimitUtilisation {
+            name: String::from("Unsecured MTM"),
+            used: self.exposure.unsecured_mtm(),
+            limit: limit.max_unsecured_mtm,
+        });
+        utilisations
+    }
+
+    /// Returns a message for every breached limit, prefixed by the ticker of the counterparty.
+    pub fn get_breaches(&self) -> Vec<String> {
+        self.get_utilisations()
+            .into_iter()
+            .filter(|u| u.is_breached())
+            .map(|u| format!("{}: {}", self.ticker, u.get_breach_message()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::option_pricer::price_option;
+
+    fn quote(side: &str, delta: Option<f64>) -> QuoteOption {
+        let mut quote = QuoteOption {
+            amount: 2.0,
+            option_kind: String::from("Call"),
+            strike: 65000.0,
+            iv: 0.6,
+            r2: 0.05,
+            side: String::from(side),
+            spot: 60000.0,
+            ttm: 30.0,
+            delta,
+            ..Default::default()
+        };
+        quote.pair_id.name = String::from("BTC/USD");
+        quote
+    }
+
+    fn get_exposure(quotes: &[QuoteOption]) -> CreditExposure {
+        let mut exposure = CreditExposure::default();
+        exposure.spots.insert(String::from("BTC/USD"), 65000.0);
+        exposure.add_quotes(quotes);
+        exposure
+    }
+
+    #[test]
+    fn add_quotes_signs_the_delta_by_the_side_of_the_counterparty() {
+        let exposure = get_exposure(&[quote("Sell", Some(0.5))]);
+        assert_eq!(exposure.notional_per_pair["BTC/USD"], 130_000.0);
+        assert_eq!(exposure.net_delta, 65_000.0);
+        assert_eq!(get_exposure(&[quote("Buy", Some(0.5))]).net_delta, -65_000.0);
+    }
+
+    #[test]
+    fn add_quotes_prices_the_quotes_saved_without_a_delta() {
+        let priced = get_exposure(&[quote("Sell", None)]);
+        let unit_delta = price_option("Call", 65000.0, 65000.0, 30.0, 0.0, 0.05, 0.6).delta;
+        assert!(unit_delta > 0.5);
+        assert!((priced.net_delta - unit_delta * 2.0 * 65000.0).abs() < 1e-6);
+        assert_eq!(priced.net_delta, get_exposure(&[quote("Sell", Some(unit_delta))]).net_delta);
+    }
+}
@@ -21,4 +21,23 @@ impl CurrencyPair {
             key, key, key, base_currency, quote_currency
         )
     }
+    /// Returns the product of the spot price of the pair, e.g. `BTC-USD`.
+    pub fn get_spot_product(&self) -> String {
+        format!("{}-{}", self.base.ticker, self.quote.ticker).to_uppercase()
+    }
+
+    pub fn get_default_query() -> String {
+        format!(
+            "id, name, is_active, {}, {}",
+            Currency::get_query("base"),
+            Currency::get_query("quote")
+        )
+    }
+}
+
+/// Struct for the Currency Pair Response.
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CurrencyPairResponse {
+    pub data: Vec<CurrencyPair>,
 }
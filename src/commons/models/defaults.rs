@@ -23,6 +23,46 @@ where
     pub data: Option<T>,
 }
 
+/// Represents the `meta` object of a Directus items response, requested with `meta=filter_count`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct DirectusMeta {
+    /// Number of items matching the filter and search of the request.
+    #[serde(default)]
+    pub filter_count: Option<usize>,
+    /// Number of items in the collection.
+    #[serde(default)]
+    pub total_count: Option<usize>,
+}
+
+/// Represents the page requested by a server side paginated table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRequest {
+    /// The current page, starting at 1.
+    pub page: usize,
+    pub page_size: usize,
+    /// The Directus sort field, prefixed with `-` for a descending sort. Empty for the default sort.
+    pub sort: String,
+    /// The search text, empty when not searching.
+    pub search: String,
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self {
+            page: 1,
+            page_size: 25,
+            sort: String::new(),
+            search: String::new(),
+        }
+    }
+}
+
+impl PageRequest {
+    pub fn get_offset(&self) -> usize {
+        (self.page.max(1) - 1) * self.page_size
+    }
+}
+
 /// Represents the query object for Directus API Request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Query {
@@ -56,32 +96,238 @@ pub struct Filter {
     pub _and: Vec<FilterCondition>,
 }
 
+/// Represents the filter operators of the Directus API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOperator {
+    Eq,
+    Neq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    In,
+    Nin,
+    Null,
+    NNull,
+    Contains,
+    Between,
+}
+
+impl FilterOperator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterOperator::Eq => "_eq",
+            FilterOperator::Neq => "_neq",
+            FilterOperator::Lt => "_lt",
+            FilterOperator::Lte => "_lte",
+            FilterOperator::Gt => "_gt",
+            FilterOperator::Gte => "_gte",
+            FilterOperator::In => "_in",
+            FilterOperator::Nin => "_nin",
+            FilterOperator::Null => "_null",
+            FilterOperator::NNull => "_nnull",
+            FilterOperator::Contains => "_contains",
+            FilterOperator::Between => "_between",
+        }
+    }
+}
+
+/// Builds a filter rule on a field, relational fields are separated by a dot.
+///
+/// # Example
+///
+/// ```ignore
+/// // {"counterparty_id":{"ticker":{"_eq":"JABRA"}}}
+/// let rule = filter_rule("counterparty_id.ticker", FilterOperator::Eq, json!("JABRA"));
+/// ```
+pub fn filter_rule(field: &str, operator: FilterOperator, value: Value) -> Value {
+    field
+        .split('.')
+        .rev()
+        .fold(json!({ (operator.as_str()): value }), |rule, name| json!({ name: rule }))
+}
+
+/// Groups filter rules with `_and`.
+pub fn and_group(rules: Vec<Value>) -> Value {
+    json!({ "_and": rules })
+}
+
+/// Groups filter rules with `_or`.
+pub fn or_group(rules: Vec<Value>) -> Value {
+    json!({ "_or": rules })
+}
+
+/// Builds the query string of a Directus items request.
+/// The filter is sent as JSON and every parameter is URL encoded.
+///
+/// # Example
+///
+/// ```ignore
+/// let path = QueryBuilder::new()
+///     .filter("counterparty_id.ticker", FilterOperator::Eq, json!("JABRA"))
+///     .filter("expiry_timestamp", FilterOperator::Gte, json!("$NOW"))
+///     .sort("-expiry_timestamp")
+///     .fields(Trade::get_query())
+///     .to_path("trade");
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct QueryBuilder {
     filters: Vec<(String, Value)>,
+    rules: Vec<Value>,
+    sort: Vec<String>,
+    limit: Option<i64>,
+    offset: Option<u64>,
+    fields: Option<String>,
+    aggregate: Vec<(String, String)>,
+    group_by: Vec<String>,
+    search: Option<String>,
+    meta: Option<String>,
 }
 
 impl QueryBuilder {
     pub fn new() -> Self {
-        QueryBuilder {
-            filters: Vec::new(),
-        }
+        QueryBuilder::default()
     }
 
     pub fn add_filter(&mut self, field: &str, value: Value) {
         self.filters.push((field.to_string(), value));
     }
 
+    /// Adds a rule on a field, rules are combined with `_and`.
+    pub fn filter(mut self, field: &str, operator: FilterOperator, value: Value) -> Self {
+        self.rules.push(filter_rule(field, operator, value));
+        self
+    }
+
+    /// Adds a rule built with `filter_rule`, `and_group` or `or_group`.
+    pub fn rule(mut self, rule: Value) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Adds a sort field, prefix it with `-` for a descending sort.
+    pub fn sort(mut self, field: &str) -> Self {
+        self.sort.push(field.to_string());
+        self
+    }
+
+    /// Use `-1` to return every item.
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Sets the fields to return, usually the `get_query()` of the model.
+    pub fn fields(mut self, fields: String) -> Self {
+        self.fields = Some(fields);
+        self
+    }
+
+    /// Adds an aggregate function, e.g. `count` or `sum`, on a field.
+    pub fn aggregate(mut self, function: &str, field: &str) -> Self {
+        self.aggregate.push((function.to_string(), field.to_string()));
+        self
+    }
+
+    pub fn group_by(mut self, field: &str) -> Self {
+        self.group_by.push(field.to_string());
+        self
+    }
+
+    /// Searches every string field of the collection.
+    pub fn search(mut self, search: &str) -> Self {
+        self.search = Some(search.to_string());
+        self
+    }
+
+    /// Requests the `meta` of the response, e.g. `filter_count` for the number of matching items.
+    pub fn meta(mut self, meta: &str) -> Self {
+        self.meta = Some(meta.to_string());
+        self
+    }
+
+    /// Applies the page of a server side paginated table.
+    /// The sort of the page takes precedence over the default sort of the query.
+    pub fn paginate(mut self, page: &PageRequest) -> Self {
+        if !page.sort.is_empty() {
+            self.sort.insert(0, page.sort.clone());
+        }
+        if !page.search.trim().is_empty() {
+            self.search = Some(page.search.trim().to_string());
+        }
+        self.limit = Some(page.page_size as i64);
+        self.offset = Some(page.get_offset() as u64);
+        self.meta = Some(String::from("filter_count"));
+        self
+    }
+
+    /// Returns the filter of the query, rules are combined with `_and`.
     pub fn build(&self) -> Value {
-        let mut filter_json = json!({});
-        for (field, value) in &self.filters {
-            let field_json = json!({ field: value });
-            filter_json
-                .as_object_mut()
-                .unwrap()
-                .extend(field_json.as_object().unwrap().clone());
+        let mut rules = self.rules.clone();
+        if !self.filters.is_empty() {
+            let mut filter_json = json!({});
+            for (field, value) in &self.filters {
+                filter_json
+                    .as_object_mut()
+                    .unwrap()
+                    .insert(field.to_string(), value.clone());
+            }
+            rules.insert(0, filter_json);
+        }
+        match rules.len() {
+            0 => json!({}),
+            1 => rules.remove(0),
+            _ => and_group(rules),
+        }
+    }
+
+    /// Returns the URL encoded query string, without the leading `?`.
+    pub fn to_query_string(&self) -> String {
+        let mut params: Vec<(String, String)> = Vec::new();
+        let filter = self.build();
+        if filter.as_object().map_or(false, |f| !f.is_empty()) {
+            params.push((String::from("filter"), filter.to_string()));
+        }
+        if !self.sort.is_empty() {
+            params.push((String::from("sort"), self.sort.join(",")));
+        }
+        if let Some(limit) = self.limit {
+            params.push((String::from("limit"), limit.to_string()));
+        }
+        if let Some(offset) = self.offset {
+            params.push((String::from("offset"), offset.to_string()));
+        }
+        if let Some(fields) = &self.fields {
+            params.push((String::from("fields"), fields.clone()));
+        }
+        for (function, field) in &self.aggregate {
+            params.push((format!("aggregate[{}]", function), field.clone()));
+        }
+        if !self.group_by.is_empty() {
+            params.push((String::from("groupBy"), self.group_by.join(",")));
+        }
+        if let Some(search) = &self.search {
+            params.push((String::from("search"), search.clone()));
+        }
+        if let Some(meta) = &self.meta {
+            params.push((String::from("meta"), meta.clone()));
+        }
+        serde_urlencoded::to_string(params).unwrap_or_default()
+    }
+
+    /// Returns the path of the items request on a collection.
+    pub fn to_path(&self, collection: &str) -> String {
+        let query_string = self.to_query_string();
+        if query_string.is_empty() {
+            format!("/items/{}", collection)
+        } else {
+            format!("/items/{}?{}", collection, query_string)
         }
-        filter_json
     }
 }
 
@@ -93,4 +339,141 @@ pub struct Greeks {
     pub gamma: f64,
     /// The theta.
     pub theta: f64,
+    /// The vega, per 1 vol point.
+    #[serde(default)]
+    pub vega: f64,
+    /// The rho, per 1% move of the quote currency rate.
+    #[serde(default)]
+    pub rho: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the decoded parameters of a path, in order.
+    fn params(path: &str) -> Vec<(String, String)> {
+        let query_string = path.split_once('?').map_or("", |(_, q)| q);
+        serde_urlencoded::from_str(query_string).unwrap()
+    }
+
+    fn param(path: &str, name: &str) -> Option<String> {
+        params(path).into_iter().find(|(k, _)| k == name).map(|(_, v)| v)
+    }
+
+    fn filter(path: &str) -> Value {
+        serde_json::from_str(&param(path, "filter").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn empty_query_has_no_query_string() {
+        assert_eq!(QueryBuilder::new().to_path("trade"), "/items/trade");
+    }
+
+    #[test]
+    fn relational_filters_are_nested() {
+        let path = QueryBuilder::new()
+            .filter("counterparty_id.ticker", FilterOperator::Eq, json!("JABRA"))
+            .to_path("trade");
+        assert_eq!(
+            path,
+            "/items/trade?filter=%7B%22counterparty_id%22%3A%7B%22ticker%22%3A%7B%22_eq%22%3A%22JABRA%22%7D%7D%7D"
+        );
+        assert_eq!(
+            filter(&path),
+            json!({"counterparty_id": {"ticker": {"_eq": "JABRA"}}})
+        );
+    }
+
+    #[test]
+    fn rules_are_combined_with_and() {
+        let path = QueryBuilder::new()
+            .filter("party_b.ticker", FilterOperator::Eq, json!("ACME"))
+            .filter("expiry_timestamp", FilterOperator::Gte, json!("$NOW"))
+            .to_path("trade");
+        assert_eq!(
+            filter(&path),
+            json!({"_and": [
+                {"party_b": {"ticker": {"_eq": "ACME"}}},
+                {"expiry_timestamp": {"_gte": "$NOW"}}
+            ]})
+        );
+    }
+
+    #[test]
+    fn groups_in_and_between_are_built() {
+        let path = QueryBuilder::new()
+            .rule(or_group(vec![
+                filter_rule("quote_status", FilterOperator::In, json!(["active", "approved"])),
+                and_group(vec![
+                    filter_rule("date_created", FilterOperator::Between, json!(["2024-01-01", "2024-01-31"])),
+                    filter_rule("counterparty_id.ticker", FilterOperator::Neq, json!("JABRA")),
+                ]),
+            ]))
+            .to_path("quotes_option");
+        assert_eq!(
+            filter(&path),
+            json!({"_or": [
+                {"quote_status": {"_in": ["active", "approved"]}},
+                {"_and": [
+                    {"date_created": {"_between": ["2024-01-01", "2024-01-31"]}},
+                    {"counterparty_id": {"ticker": {"_neq": "JABRA"}}}
+                ]}
+            ]})
+        );
+    }
+
+    #[test]
+    fn values_are_url_encoded() {
+        let path = QueryBuilder::new()
+            .filter("description", FilterOperator::Contains, json!("a&b=c d"))
+            .search("x&y=z w")
+            .to_path("wallet_transaction");
+        let query_string = path.split_once('?').unwrap().1;
+        assert!(!query_string.contains(' '));
+        assert_eq!(query_string.matches('&').count(), 1, "only the separator of the two parameters is left");
+        assert_eq!(query_string.matches('=').count(), 2, "only the separators of the names are left");
+        assert!(query_string.ends_with("&search=x%26y%3Dz+w"));
+        assert_eq!(filter(&path), json!({"description": {"_contains": "a&b=c d"}}));
+        assert_eq!(param(&path, "search").unwrap(), "x&y=z w");
+    }
+
+    #[test]
+    fn parameters_are_added_in_order() {
+        let path = QueryBuilder::new()
+            .sort("-date_created")
+            .sort("id")
+            .limit(-1)
+            .offset(50)
+            .fields(String::from("id, amount, counterparty_id.ticker"))
+            .aggregate("sum", "amount")
+            .group_by("currency_id")
+            .group_by("transaction_type")
+            .meta("filter_count")
+            .to_path("wallet_transaction");
+        assert_eq!(
+            path,
+            "/items/wallet_transaction?sort=-date_created%2Cid&limit=-1&offset=50\
+             &fields=id%2C+amount%2C+counterparty_id.ticker&aggregate%5Bsum%5D=amount\
+             &groupBy=currency_id%2Ctransaction_type&meta=filter_count"
+        );
+        assert_eq!(param(&path, "aggregate[sum]").unwrap(), "amount");
+        assert_eq!(param(&path, "limit").unwrap(), "-1");
+    }
+
+    #[test]
+    fn page_sort_takes_precedence() {
+        let page = PageRequest {
+            page: 3,
+            page_size: 25,
+            sort: String::from("amount"),
+            search: String::from("  BTC  "),
+        };
+        let path = QueryBuilder::new().sort("-date_created").paginate(&page).to_path("trade");
+        assert_eq!(param(&path, "sort").unwrap(), "amount,-date_created");
+        assert_eq!(param(&path, "limit").unwrap(), "25");
+        assert_eq!(param(&path, "offset").unwrap(), "50");
+        assert_eq!(param(&path, "search").unwrap(), "BTC");
+        assert_eq!(param(&path, "meta").unwrap(), "filter_count");
+    }
 }
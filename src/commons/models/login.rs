@@ -6,11 +6,19 @@ use serde::{Deserialize, Serialize};
 pub struct DirectusLoginRequest {
     pub email: String,
     pub password: String,
+    /// The one-time password of the authenticator app, only sent for users with two-factor authentication.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub otp: Option<String>,
 }
 
 impl DirectusLoginRequest {
     pub fn new(email: String, password: String) -> Self {
-        Self { email, password }
+        Self { email, password, otp: None }
+    }
+
+    pub fn with_otp(mut self, otp: Option<String>) -> Self {
+        self.otp = otp.filter(|o| !o.trim().is_empty()).map(|o| o.trim().to_string());
+        self
     }
 }
 
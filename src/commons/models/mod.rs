@@ -1,4 +1,5 @@
 pub mod counterparty;
+pub mod credit_limit;
 pub mod currency;
 pub mod currency_pair;
 pub mod defaults;
@@ -9,4 +10,5 @@ pub mod user;
 pub mod loan;
 pub mod coinbase;
 pub mod wallet;
-pub mod interestrates;
\ No newline at end of file
+pub mod interestrates;
+pub mod audit;
\ No newline at end of file
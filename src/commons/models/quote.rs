@@ -1,9 +1,10 @@
 use std::collections::BTreeMap;
 
-use crate::utilities::{date_util::format_utc_str_to_local_str, number_util::format_currency};
+use crate::utilities::{date_util::format_utc_str_to_local_str, number_util::format_currency, option_pricer::{price_option, PayoffLeg}};
 
 use super::{
-    counterparty::CounterParty, currency::Currency, currency_pair::CurrencyPair, user::User,
+    counterparty::CounterParty, currency::Currency, currency_pair::CurrencyPair, defaults::DirectusMeta,
+    user::User,
 };
 use leptos::*;
 use serde::{Deserialize, Serialize};
@@ -67,6 +68,41 @@ impl QuoteOption {
             CounterParty::get_query("party_b"),
         )
     }
+
+    /// Returns the payoff leg of the quote with the given amount and leg premium, so an edit can be charted before it is saved.
+    pub fn get_payoff_leg(&self, amount: f64, premium: f64) -> PayoffLeg {
+        PayoffLeg {
+            strike: self.strike,
+            side: self.side.clone(),
+            option_kind: self.option_kind.clone(),
+            amount: amount.abs(),
+            premium: premium.abs(),
+            ttm: self.ttm,
+            iv: self.iv,
+            r2: self.r2,
+        }
+    }
+
+    /// Returns the row of the other party for the same leg of the quote.
+    /// A quote of several legs has one row per leg and party in its group, the rows of a leg are matched
+    /// by their instrument and opposite amounts.
+    pub fn find_other_party_row<'a>(&self, quotes: &'a [QuoteOption]) -> Option<&'a QuoteOption> {
+        quotes.iter().find(|q| {
+            q.id != self.id
+                && q.group_id == self.group_id
+                && q.counterparty_id.id != self.counterparty_id.id
+                && q.instrument_name == self.instrument_name
+                && q.amount == -self.amount
+        })
+    }
+
+    /// Returns the delta of one long option of the quote.
+    /// A quote saved without its delta is priced locally at `spot`, with the vol, rates and time to maturity of the quote.
+    pub fn get_delta(&self, spot: f64) -> f64 {
+        self.delta.unwrap_or_else(|| {
+            price_option(self.option_kind.as_str(), spot, self.strike, self.ttm, self.r1, self.r2, self.iv).delta
+        })
+    }
 }
 
 /// This is the response struct for the [`get_quotes_option`] server function.
@@ -175,9 +211,20 @@ impl Default for ApproveTradeQuoteResponse {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct QuoteOptionHistory {
     pub data: Vec<QuoteOption>,
+    /// Only returned for paginated requests.
+    #[serde(default)]
+    pub meta: Option<DirectusMeta>,
 }
 
 impl QuoteOptionHistory {
+    /// Returns the number of quotes matching the request, falls back to the loaded quotes.
+    pub fn get_filter_count(&self) -> usize {
+        self.meta
+            .as_ref()
+            .and_then(|m| m.filter_count)
+            .unwrap_or(self.data.len())
+    }
+
     /// Extract Trade Struct into a Vector that can be shown in the data Table.
     pub fn extract(&self) -> Vec<ExtractedQuoteOption> {
         self.data
@@ -259,3 +306,58 @@ pub struct ExtractedQuoteOption {
     pub date_created: String,
     pub premium_ccy: String,
 }
+
+impl ExtractedQuoteOption {
+    /// Returns the Directus field used to sort a column of the data table on the server.
+    /// Returns an empty string for the columns that cannot be sorted on the server.
+    pub fn get_sort_field(key: &str) -> String {
+        match key {
+            "market" => String::from("instrument_name"),
+            "status" => String::from("quote_status"),
+            "side" => String::from("side"),
+            "kind" => String::from("option_kind"),
+            "size" => String::from("amount"),
+            "price" => String::from("px_in_quote_ccy"),
+            "date_created" => String::from("date_created"),
+            _ => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: u32, counterparty_id: u16, instrument_name: &str, amount: f64) -> QuoteOption {
+        let mut row = QuoteOption {
+            id,
+            instrument_name: String::from(instrument_name),
+            amount,
+            group_id: String::from("group-1"),
+            ..Default::default()
+        };
+        row.counterparty_id.id = counterparty_id;
+        row
+    }
+
+    #[test]
+    fn find_other_party_row_matches_the_same_leg_of_the_group() {
+        let quotes = vec![
+            row(500, 1, "BTC-28JUN30-60000-P", -2.0),
+            row(501, 2, "BTC-28JUN30-60000-P", 2.0),
+            row(502, 1, "BTC-28JUN30-70000-C", 1.0),
+            row(503, 2, "BTC-28JUN30-70000-C", -1.0),
+        ];
+        assert_eq!(quotes[0].find_other_party_row(&quotes).map(|q| q.id), Some(501));
+        assert_eq!(quotes[2].find_other_party_row(&quotes).map(|q| q.id), Some(503));
+        assert_eq!(quotes[3].find_other_party_row(&quotes).map(|q| q.id), Some(502));
+    }
+
+    #[test]
+    fn find_other_party_row_ignores_the_other_groups() {
+        let mut other = row(501, 2, "BTC-28JUN30-60000-P", 2.0);
+        other.group_id = String::from("group-2");
+        let quotes = vec![row(500, 1, "BTC-28JUN30-60000-P", -2.0), other];
+        assert!(quotes[0].find_other_party_row(&quotes).is_none());
+    }
+}
@@ -2,13 +2,17 @@ use leptos::ServerFnError;
 use serde::{Deserialize, Serialize};
 
 use super::{
-    counterparty::CounterParty, currency::Currency, currency_pair::CurrencyPair, defaults::Greeks,
+    counterparty::CounterParty,
+    currency::Currency,
+    currency_pair::CurrencyPair,
+    defaults::{DirectusMeta, Greeks},
     user::User,
 };
 use crate::utilities::cookies::{get_jabra_cookie, refresh_token, set_jabra_cookie, JabraCookie};
 use crate::utilities::date_util::{extract_date, format_utc_str_to_local_str, time_to_expiry};
 use crate::utilities::http_wrapper::{call, HttpMethod};
 use crate::utilities::number_util::format_currency;
+use crate::utilities::option_pricer::PayoffLeg;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
@@ -29,6 +33,8 @@ pub struct RiskSlideTrade {
     pub delta: f64,
     pub gamma: f64,
     pub theta: f64,
+    pub vega: f64,
+    pub rho: f64,
     pub pnl: f64,
     pub pnl_percentage: f64,
     pub last_updated: String,
@@ -79,6 +85,8 @@ impl TradeHistoryWithSpot {
                 delta: 0.0,
                 gamma: 0.0,
                 theta: 0.0,
+                vega: 0.0,
+                rho: 0.0,
                 pnl: t.pnl.unwrap_or(0.0),
                 pnl_percentage: 0.0,
                 last_updated: match t.date_updated.clone() {
@@ -130,9 +138,31 @@ impl TradeHistoryWithSpot {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TradeHistory {
     pub data: Vec<Trade>,
+    /// Only returned for paginated requests.
+    #[serde(default)]
+    pub meta: Option<DirectusMeta>,
 }
 
 impl TradeHistory {
+    /// Returns the number of trades matching the request, falls back to the loaded trades.
+    pub fn get_filter_count(&self) -> usize {
+        self.meta
+            .as_ref()
+            .and_then(|m| m.filter_count)
+            .unwrap_or(self.data.len())
+    }
+
+    /// Returns the payoff legs of the option trades, grouped by the same expiry date as `extract_and_group_by_date`.
+    pub fn extract_payoff_legs_by_date(&self) -> HashMap<String, Vec<PayoffLeg>> {
+        let mut result: HashMap<String, Vec<PayoffLeg>> = HashMap::new();
+        for t in &self.data {
+            if let Some(leg) = t.get_payoff_leg() {
+                result.entry(extract_date(t.expiry_timestamp.clone())).or_default().push(leg);
+            }
+        }
+        result
+    }
+
     /// Extract Trade Struct into a Vector that can be shown in the data Table.
     pub fn extract(&self) -> Vec<ExtractedTrade> {
         self.data
@@ -743,6 +773,29 @@ pub struct ExtractedTrade {
     pub last_updated: String,
 }
 
+impl ExtractedTrade {
+    /// Returns the Directus field used to sort a column of the data table on the server.
+    /// Returns an empty string for the columns that cannot be sorted on the server.
+    pub fn get_sort_field(key: &str) -> String {
+        match key {
+            "date_created" => String::from("date_created"),
+            "market" => String::from("venue_instrument_name"),
+            "party_a" => String::from("party_a.name"),
+            "party_b" => String::from("party_b.name"),
+            "side_status" => String::from("side"),
+            "trans_type" => String::from("instrument_kind"),
+            "trade_type" => String::from("trade_type"),
+            "kind" => String::from("option_kind"),
+            "size" => String::from("amount"),
+            "price" => String::from("px_in_quote_ccy"),
+            "index_price" => String::from("index_price"),
+            "realized_pnl" => String::from("pnl"),
+            "trade_status" => String::from("trade_status"),
+            _ => String::new(),
+        }
+    }
+}
+
 /// Struct for the Trade data.
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -815,6 +868,21 @@ impl Trade {
             User::get_query("user_created")
         )
     }
+
+    /// Returns the payoff leg of an option trade, `None` for the other instruments.
+    pub fn get_payoff_leg(&self) -> Option<PayoffLeg> {
+        let option_kind = self.option_kind.clone().filter(|k| !k.is_empty())?;
+        Some(PayoffLeg {
+            strike: self.strike,
+            side: self.side.clone(),
+            option_kind,
+            amount: self.amount.unwrap_or_default().abs(),
+            premium: self.px_in_quote_ccy.unwrap_or_default().abs(),
+            ttm: time_to_expiry(self.expiry_timestamp.as_str()),
+            iv: self.iv.unwrap_or_default(),
+            r2: self.r2.unwrap_or_default(),
+        })
+    }
 }
 
 /// Function that sorts the data table.
@@ -1136,6 +1204,10 @@ pub struct AtmRiskSlide {
     pub delta: f64,
     pub gamma: f64,
     pub theta: f64,
+    #[serde(default)]
+    pub vega: f64,
+    #[serde(default)]
+    pub rho: f64,
     pub pnl: f64,
 }
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 /// Struct for the data of a user.
@@ -18,3 +20,161 @@ impl User {
         )
     }
 }
+
+/// Actions of the admin portal that are restricted by the Directus role and policies of the user.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Permission {
+    ApproveQuotes,
+    EditQuotes,
+    EditTrades,
+    ApproveTermSheets,
+    UpdateIv,
+    ViewAuditLog,
+    ManageCounterparties,
+    ApproveTransfers,
+    RecordWalletTransactions,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 9] = [
+        Permission::ApproveQuotes,
+        Permission::EditQuotes,
+        Permission::EditTrades,
+        Permission::ApproveTermSheets,
+        Permission::UpdateIv,
+        Permission::ViewAuditLog,
+        Permission::ManageCounterparties,
+        Permission::ApproveTransfers,
+        Permission::RecordWalletTransactions,
+    ];
+
+    /// Returns the Directus collection, action and field that have to be granted for the permission.
+    pub fn get_directus_grant(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Permission::ApproveQuotes => ("quotes_option", "update", "quote_status"),
+            Permission::EditQuotes => ("quotes_option", "update", "amount"),
+            Permission::EditTrades => ("trade", "update", "expiry_timestamp"),
+            Permission::ApproveTermSheets => ("dcl", "update", "term_sheet_status"),
+            Permission::UpdateIv => ("quotes_option", "update", "iv"),
+            Permission::ViewAuditLog => ("audit_log", "read", "action"),
+            Permission::ManageCounterparties => ("counterparty", "update", "is_active"),
+            Permission::ApproveTransfers => ("wallet_transaction", "update", "approval_status"),
+            Permission::RecordWalletTransactions => ("wallet_transaction", "create", "amount"),
+        }
+    }
+}
+
+/// Struct for the permissions of the logged in user, stored in the session cookie.
+/// An empty struct has no permissions, so a failed lookup never grants an action.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct UserPermissions {
+    pub role: String,
+    pub admin_access: bool,
+    pub permissions: Vec<Permission>,
+}
+
+impl UserPermissions {
+    pub fn has(&self, permission: Permission) -> bool {
+        self.admin_access || self.permissions.contains(&permission)
+    }
+}
+
+/// Struct for the response of `/users/me` with the two-factor authentication of the user.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct CurrentUserTfaResponse {
+    pub data: CurrentUserTfa,
+}
+
+/// Directus never returns the secret itself, only a masked value when the user is enrolled.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct CurrentUserTfa {
+    #[serde(default)]
+    pub tfa_secret: Option<String>,
+}
+
+impl CurrentUserTfa {
+    pub fn get_query() -> String {
+        String::from("tfa_secret")
+    }
+
+    pub fn is_enrolled(&self) -> bool {
+        self.tfa_secret.as_ref().is_some_and(|s| !s.is_empty())
+    }
+}
+
+/// Struct for the response of `/users/me` with the role and policies of the user.
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CurrentUserResponse {
+    pub data: CurrentUser,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CurrentUser {
+    pub id: String,
+    pub role: Option<UserRole>,
+    #[serde(default)]
+    pub policies: Vec<UserPolicy>,
+}
+
+impl CurrentUser {
+    pub fn get_query() -> String {
+        String::from("id, role.name, role.policies.policy.admin_access, policies.policy.admin_access")
+    }
+
+    /// Returns `true` when one of the policies of the user or of their role has admin access.
+    pub fn has_admin_access(&self) -> bool {
+        self.policies
+            .iter()
+            .chain(self.role.iter().flat_map(|r| r.policies.iter()))
+            .any(|p| p.policy.as_ref().is_some_and(|p| p.admin_access))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UserRole {
+    pub name: String,
+    #[serde(default)]
+    pub policies: Vec<UserPolicy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UserPolicy {
+    pub policy: Option<Policy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Policy {
+    #[serde(default)]
+    pub admin_access: bool,
+}
+
+/// Struct for the response of `/permissions/me`, the access of the user per collection and action.
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CollectionPermissionsResponse {
+    pub data: HashMap<String, HashMap<String, ActionPermission>>,
+}
+
+impl CollectionPermissionsResponse {
+    /// Returns `true` when the action is granted on the collection, with the field when the access is limited to some fields.
+    pub fn is_granted(&self, collection: &str, action: &str, field: &str) -> bool {
+        match self.data.get(collection).and_then(|c| c.get(action)) {
+            Some(p) if p.access != "none" => p
+                .fields
+                .as_ref()
+                .map_or(true, |fields| fields.iter().any(|f| f == "*" || f == field)),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ActionPermission {
+    pub access: String,
+    pub fields: Option<Vec<String>>,
+}
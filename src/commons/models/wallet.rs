@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-use crate::{commons::models::user::User, utilities::{date_util::format_utc_str_to_local_str, number_util::format_currency}};
+use crate::{commons::models::user::User, utilities::{date_util::{format_utc_str_to_local_str, parse_str_to_utc_datetime_str}, number_util::format_currency}};
 
-use super::{counterparty::CounterParty, currency::Currency};
+use super::{
+    counterparty::CounterParty,
+    currency::Currency,
+    defaults::{and_group, filter_rule, FilterOperator},
+};
 
 
 
@@ -26,6 +31,10 @@ impl WalletTransactionHistory {
                 time: format_utc_str_to_local_str(t.venue_transaction_datetime.clone()),
                 fee_amount: format_currency(t.fee_amount, t.currency_id.display_scale),
                 description: t.description.clone(),
+                txn_hash: t.txn_hash.clone(),
+                reference: t.reference.clone(),
+                submitted: String::from(if t.is_submitted { "Yes" } else { "No" }),
+                approval_status: t.get_approval_status().get_name().to_string(),
             })
             .collect()
     }
@@ -41,6 +50,10 @@ pub struct ExtractedWalletTransaction {
     pub time: String,
     pub fee_amount: String,
     pub description: String,
+    pub txn_hash: String,
+    pub reference: String,
+    pub submitted: String,
+    pub approval_status: String,
 }
 /// Struct for the wallet transaction data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,17 +71,308 @@ pub struct WalletTransaction {
     pub is_submitted: bool,
     pub reference: String,
     pub counterparty_id: CounterParty,
+    /// The step of the approval workflow, empty for the transactions booked before the workflow.
+    #[serde(default)]
+    pub approval_status: Option<String>,
+    /// The id of the admin who approved first.
+    #[serde(default)]
+    pub first_approved_by: Option<String>,
+    /// The id of the admin who confirmed the approval.
+    #[serde(default)]
+    pub approved_by: Option<String>,
+    /// The id of the admin who rejected the transaction.
+    #[serde(default)]
+    pub rejected_by: Option<String>,
 }
 
 impl WalletTransaction {
     pub fn get_query() -> String {
         format!(
-            "id, date_created, amount, txn_hash, transaction_type, fee_amount, venue_transaction_datetime, description, reference, is_submitted, {}, {}, {}",
+            "id, date_created, amount, txn_hash, transaction_type, fee_amount, venue_transaction_datetime, description, reference, is_submitted, approval_status, first_approved_by, approved_by, rejected_by, {}, {}, {}",
             Currency::get_query("currency_id"),
             CounterParty::get_query("counterparty_id"),
             User::get_query("user_created")
         )
     }
+
+    pub fn get_approval_status(&self) -> TransferApprovalStatus {
+        TransferApprovalStatus::from_value(self.approval_status.as_deref())
+    }
+
+    pub fn is_withdrawal(&self) -> bool {
+        self.transaction_type.to_uppercase() == "WITHDRAWAL"
+    }
+}
+
+/// The steps of the four-eyes approval of a deposit or withdrawal.
+/// A transaction is approved by a first admin, then confirmed by a second one, and can be rejected at either step.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransferApprovalStatus {
+    Pending,
+    FirstApproved,
+    Approved,
+    Rejected,
+    /// Booked before the approval workflow, nothing to act on.
+    NotRequired,
+}
+
+impl TransferApprovalStatus {
+    /// Returns the value stored in the `approval_status` field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferApprovalStatus::Pending => "pending",
+            TransferApprovalStatus::FirstApproved => "first_approved",
+            TransferApprovalStatus::Approved => "approved",
+            TransferApprovalStatus::Rejected => "rejected",
+            TransferApprovalStatus::NotRequired => "",
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            TransferApprovalStatus::Pending => "Pending",
+            TransferApprovalStatus::FirstApproved => "Awaiting Confirmation",
+            TransferApprovalStatus::Approved => "Approved",
+            TransferApprovalStatus::Rejected => "Rejected",
+            TransferApprovalStatus::NotRequired => "- -",
+        }
+    }
+
+    pub fn from_value(value: Option<&str>) -> Self {
+        match value {
+            Some("pending") => TransferApprovalStatus::Pending,
+            Some("first_approved") => TransferApprovalStatus::FirstApproved,
+            Some("approved") => TransferApprovalStatus::Approved,
+            Some("rejected") => TransferApprovalStatus::Rejected,
+            _ => TransferApprovalStatus::NotRequired,
+        }
+    }
+
+    /// Returns `true` while the transaction waits for an approval.
+    pub fn is_pending(&self) -> bool {
+        matches!(self, TransferApprovalStatus::Pending | TransferApprovalStatus::FirstApproved)
+    }
+}
+
+/// Struct for the update of the `wallet_transaction` collection by a step of the approval.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TransferApprovalRequest {
+    pub approval_status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_approved_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approved_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected_by: Option<String>,
+}
+
+/// Struct for the conditional update of a wallet transaction by a step of the approval.
+/// The filter holds the id and the approval state the step was checked against, so the update
+/// matches no transaction when another admin changed it in between.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TransferApprovalUpdate {
+    pub query: TransferApprovalQuery,
+    pub data: TransferApprovalRequest,
+}
+
+/// Struct for the query of [`TransferApprovalUpdate`].
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TransferApprovalQuery {
+    pub filter: Value,
+}
+
+impl TransferApprovalUpdate {
+    pub fn new(transaction: &WalletTransaction, data: TransferApprovalRequest) -> Self {
+        let first_approved_by = match &transaction.first_approved_by {
+            Some(user_id) => filter_rule("first_approved_by", FilterOperator::Eq, json!(user_id)),
+            None => filter_rule("first_approved_by", FilterOperator::Null, json!(true)),
+        };
+        Self {
+            query: TransferApprovalQuery {
+                filter: and_group(vec![
+                    filter_rule("id", FilterOperator::Eq, json!(transaction.id)),
+                    filter_rule("approval_status", FilterOperator::Eq, json!(transaction.approval_status)),
+                    first_approved_by,
+                ]),
+            },
+            data,
+        }
+    }
+}
+
+/// Response Struct of [`TransferApprovalUpdate`], Directus returns the updated transactions.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferApprovalResponse {
+    pub data: Vec<UpdatedWalletTransaction>,
+}
+
+/// Id of a wallet transaction updated by [`TransferApprovalUpdate`].
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatedWalletTransaction {
+    pub id: u32,
+}
+
+/// The types of the wallet transactions that can be booked from the portal.
+/// Deposits and withdrawals are entered as positive amounts, transfers and fee adjustments are signed.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WalletTransactionType {
+    Deposit,
+    Withdrawal,
+    Transfer,
+    FeeAdjustment,
+}
+
+impl WalletTransactionType {
+    pub const ALL: [WalletTransactionType; 4] = [
+        WalletTransactionType::Deposit,
+        WalletTransactionType::Withdrawal,
+        WalletTransactionType::Transfer,
+        WalletTransactionType::FeeAdjustment,
+    ];
+
+    /// Returns the value stored in the `transaction_type` field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WalletTransactionType::Deposit => "deposit",
+            WalletTransactionType::Withdrawal => "withdrawal",
+            WalletTransactionType::Transfer => "transfer",
+            WalletTransactionType::FeeAdjustment => "fee_adjustment",
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            WalletTransactionType::Deposit => "Deposit",
+            WalletTransactionType::Withdrawal => "Withdrawal",
+            WalletTransactionType::Transfer => "Transfer",
+            WalletTransactionType::FeeAdjustment => "Fee Adjustment",
+        }
+    }
+
+    pub fn from_value(value: &str) -> Option<Self> {
+        WalletTransactionType::ALL.into_iter().find(|t| t.as_str() == value)
+    }
+
+    /// Returns `true` when the transaction waits for the four-eyes approval before it counts.
+    pub fn requires_approval(&self) -> bool {
+        matches!(self, WalletTransactionType::Deposit | WalletTransactionType::Withdrawal)
+    }
+}
+
+/// Struct for a new record of the `wallet_transaction` collection, booked manually from the portal.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WalletTransactionRequest {
+    pub counterparty_id: u16,
+    pub currency_id: u16,
+    pub transaction_type: String,
+    pub amount: f64,
+    pub fee_amount: f64,
+    pub txn_hash: String,
+    pub reference: String,
+    pub description: String,
+    pub venue_transaction_datetime: String,
+    pub is_submitted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approval_status: Option<String>,
+}
+
+impl WalletTransactionRequest {
+    /// Builds the request from the values of the form.
+    /// The amounts cannot have more decimals than the display scale of the currency,
+    /// `datetime` is the local time of the transaction at the venue.
+    ///
+    /// # Returns
+    ///
+    /// The message shown to the user when the values are not valid.
+    pub fn new(
+        counterparty: &CounterParty,
+        currency: &Currency,
+        transaction_type: WalletTransactionType,
+        amount: &str,
+        fee_amount: &str,
+        txn_hash: String,
+        reference: String,
+        description: String,
+        datetime: &str,
+    ) -> Result<Self, String> {
+        if !currency.is_active {
+            return Err(format!("The currency {} is not active.", currency.ticker));
+        }
+        let amount = parse_amount(amount, "amount", currency)?;
+        match transaction_type {
+            WalletTransactionType::Deposit | WalletTransactionType::Withdrawal if amount <= 0.0 => {
+                return Err(format!("The amount of a {} has to be positive.", transaction_type.get_name().to_lowercase()));
+            }
+            _ if amount == 0.0 => return Err(String::from("The amount cannot be zero.")),
+            _ => (),
+        }
+        let fee_amount = if fee_amount.trim().is_empty() {
+            0.0
+        } else {
+            parse_amount(fee_amount, "fee amount", currency)?
+        };
+        if fee_amount < 0.0 {
+            return Err(String::from("The fee amount cannot be negative."));
+        }
+        let venue_transaction_datetime = parse_str_to_utc_datetime_str(datetime);
+        if venue_transaction_datetime.is_empty() {
+            return Err(String::from("Enter a valid transaction time."));
+        }
+        Ok(Self {
+            counterparty_id: counterparty.id,
+            currency_id: currency.id,
+            transaction_type: transaction_type.as_str().to_string(),
+            amount,
+            fee_amount,
+            txn_hash: txn_hash.trim().to_string(),
+            reference: reference.trim().to_string(),
+            description: description.trim().to_string(),
+            venue_transaction_datetime,
+            is_submitted: false,
+            approval_status: if transaction_type.requires_approval() {
+                Some(TransferApprovalStatus::Pending.as_str().to_string())
+            } else {
+                None
+            },
+        })
+    }
+
+    /// Returns the change of the balance of the counterparty once the transaction counts, net of the fee.
+    pub fn get_balance_impact(&self) -> f64 {
+        let amount = match WalletTransactionType::from_value(&self.transaction_type) {
+            Some(WalletTransactionType::Deposit) => self.amount.abs(),
+            Some(WalletTransactionType::Withdrawal) => -self.amount.abs(),
+            _ => self.amount,
+        };
+        amount - self.fee_amount.abs()
+    }
+}
+
+/// Parses an amount of the form, checking it has no more decimals than the display scale of the currency.
+
+fn parse_amount(value: &str, name: &str, currency: &Currency) -> Result<f64, String> {
+    let value = value.trim().replace(',', "");
+    let amount = value
+        .parse::<f64>()
+        .ok()
+        .filter(|a| a.is_finite())
+        .ok_or_else(|| format!("Enter a valid {}.", name))?;
+    let decimals = value.split_once('.').map_or(0, |(_, d)| d.len());
+    if decimals > usize::from(currency.display_scale) {
+        return Err(format!(
+            "The {} cannot have more than {} decimals in {}.",
+            name, currency.display_scale, currency.ticker
+        ));
+    }
+    Ok(amount)
 }
 
 /// Function for Sorting the data table.
@@ -126,6 +430,22 @@ pub fn sort(
                 data_table.sort_by(|a, b| b.time.cmp(&a.time));
             }
         },
+        "TXN HASH" => match sort_type {
+            true => {
+                data_table.sort_by(|a, b| a.txn_hash.cmp(&b.txn_hash));
+            }
+            false => {
+                data_table.sort_by(|a, b| b.txn_hash.cmp(&a.txn_hash));
+            }
+        },
+        "REFERENCE" => match sort_type {
+            true => {
+                data_table.sort_by(|a, b| a.reference.cmp(&b.reference));
+            }
+            false => {
+                data_table.sort_by(|a, b| b.reference.cmp(&a.reference));
+            }
+        },
         "DESCRIPTION" => match sort_type {
             true => {
                 data_table.sort_by(|a, b| a.description.cmp(&b.description));
@@ -0,0 +1,179 @@
+use leptos::*;
+use serde_json::Value;
+
+use crate::commons::models::audit::{AuditFilter, AuditLog, AuditLogRequest, AuditLogResponse};
+use crate::commons::models::defaults::{BlankRequest, PageRequest, QueryBuilder};
+use crate::utilities::cookies::get_cookie_user_id;
+use crate::utilities::http_wrapper::{ApiClient, Backend, HttpMethod, COOKIE_NAME};
+
+/// Maximum number of audit logs exported to CSV at once.
+pub const AUDIT_EXPORT_LIMIT: i64 = 10_000;
+
+/// Returns the records matched by `query` as they are before a change, to be stored as the `before` of the audit log.
+/// Returns `null` when they cannot be fetched, so the change itself is never blocked by the audit.
+
+pub async fn get_audit_snapshot(collection: &str, query: QueryBuilder) -> Value {
+    let path = query.to_path(collection);
+    let response = ApiClient::new(Backend::Directus)
+        .call_and_parse::<BlankRequest, Value>(Option::None, path, HttpMethod::GET)
+        .await;
+    match response {
+        Ok(res) => res.get("data").cloned().unwrap_or(Value::Null),
+        Err(e) => {
+            log::error!("error fetching the audit snapshot of {}: {:?}", collection, e);
+            Value::Null
+        }
+    }
+}
+
+/// Writes a record to the `audit_log` collection with the user of the session as the actor.
+/// This is called after a successful change, a failure is logged but not returned since the change is already saved.
+
+pub async fn record_audit(request: AuditLogRequest) {
+    let request = AuditLogRequest {
+        actor: get_cookie_user_id(COOKIE_NAME),
+        ..request
+    };
+    let path = String::from("/items/audit_log");
+    let response = ApiClient::new(Backend::Directus)
+        .call::<AuditLogRequest>(Some(request.clone()), path, HttpMethod::POST)
+        .await;
+    if let Err(e) = response {
+        log::error!("error recording the audit log {:?}: {:?}", request, e);
+    }
+}
+
+/// Server function to fetch a page of the audit logs matching the filter.
+
+pub async fn get_audit_logs(filter: AuditFilter, page: PageRequest) -> Result<AuditLogResponse, ServerFnError> {
+    let path = filter
+        .apply(QueryBuilder::new())
+        .sort("-timestamp")
+        .fields(AuditLog::get_query())
+        .paginate(&page)
+        .to_path("audit_log");
+
+    let response = ApiClient::new(Backend::Directus)
+        .call_and_parse::<BlankRequest, AuditLogResponse>(Option::None, path, HttpMethod::GET)
+        .await;
+    match response {
+        Ok(res) => Ok(res),
+        Err(e) => {
+            log::error!("error fetching the audit logs: {:?}", e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+/// Server function to fetch the audit logs matching the filter for the CSV export, up to `AUDIT_EXPORT_LIMIT`.
+
+pub async fn export_audit_logs(filter: AuditFilter) -> Result<AuditLogResponse, ServerFnError> {
+    let path = filter
+        .apply(QueryBuilder::new())
+        .sort("-timestamp")
+        .fields(AuditLog::get_query())
+        .limit(AUDIT_EXPORT_LIMIT)
+        .to_path("audit_log");
+
+    let response = ApiClient::new(Backend::Directus)
+        .call_and_parse::<BlankRequest, AuditLogResponse>(Option::None, path, HttpMethod::GET)
+        .await;
+    match response {
+        Ok(res) => Ok(res),
+        Err(e) => {
+            log::error!("error exporting the audit logs: {:?}", e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use serde_json::json;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::commons::models::audit::AuditAction;
+    use crate::commons::models::defaults::FilterOperator;
+    use crate::utilities::mock_backend::{assert_error_paths, url, MockBackend, ACCESS_TOKEN, USER_ID};
+
+    #[wasm_bindgen_test]
+    async fn get_audit_snapshot_returns_the_records_of_the_query() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let query = QueryBuilder::new().filter("id", FilterOperator::Eq, json!(2));
+        let snapshot = get_audit_snapshot("counterparty", query).await;
+        assert_eq!(snapshot, json!([{"id": 2, "ticker": "ACME", "name": "Acme Capital"}]));
+        let requests = backend.requests_to(HttpMethod::GET, &url(Backend::Directus, "/items/counterparty"));
+        assert_eq!(requests[0].get_filter(), json!({"id": {"_eq": 2}}));
+        assert_eq!(requests[0].authorization, format!("Bearer {}", ACCESS_TOKEN));
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_audit_snapshot_is_null_when_it_cannot_be_fetched() {
+        let backend = MockBackend::start().await;
+        backend.fail_all(503, "");
+        assert_eq!(get_audit_snapshot("counterparty", QueryBuilder::new()).await, Value::Null);
+    }
+
+    #[wasm_bindgen_test]
+    async fn record_audit_sets_the_user_of_the_session_as_the_actor() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        record_audit(AuditLogRequest::new(AuditAction::EditQuotes, "quotes_option", json!({"amount": 2.0}))).await;
+        let requests = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log"));
+        assert_eq!(requests.len(), 1);
+        let body = requests[0].get_body();
+        assert_eq!(body["actor"], USER_ID);
+        assert_eq!(body["collection"], "quotes_option");
+        assert_eq!(body["after"], json!({"amount": 2.0}));
+    }
+
+    #[wasm_bindgen_test]
+    async fn record_audit_does_not_fail_when_the_audit_is_rejected() {
+        let backend = MockBackend::start().await;
+        backend.fail_all(503, "");
+        record_audit(AuditLogRequest::new(AuditAction::EditQuotes, "quotes_option", json!({}))).await;
+        assert_eq!(backend.requests().len(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_audit_logs_fetches_a_page_of_the_filtered_logs() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let filter = AuditFilter {
+            action: String::from("update"),
+            ..Default::default()
+        };
+        let page = PageRequest {
+            page: 2,
+            page_size: 10,
+            ..Default::default()
+        };
+        let logs = get_audit_logs(filter, page).await.unwrap();
+        assert_eq!(logs.data.len(), 1);
+        assert_eq!(logs.data[0].collection, "trade");
+        assert_eq!(logs.meta.and_then(|m| m.filter_count), Some(1));
+        let request = &backend.requests()[0];
+        assert_eq!(request.get_filter(), json!({"action": {"_eq": "update"}}));
+        assert_eq!(request.get_param("sort"), "-timestamp");
+        assert_eq!(request.get_param("limit"), "10");
+        assert_eq!(request.get_param("offset"), "10");
+        assert_eq!(request.get_param("meta"), "filter_count");
+    }
+
+    #[wasm_bindgen_test]
+    async fn export_audit_logs_fetches_up_to_the_export_limit() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let logs = export_audit_logs(AuditFilter::default()).await.unwrap();
+        assert_eq!(logs.data.len(), 1);
+        assert_eq!(backend.requests()[0].get_param("limit"), AUDIT_EXPORT_LIMIT.to_string());
+    }
+
+    #[wasm_bindgen_test]
+    async fn audit_logs_return_the_errors_of_the_backend() {
+        assert_error_paths(|| get_audit_logs(AuditFilter::default(), PageRequest::default())).await;
+        assert_error_paths(|| export_audit_logs(AuditFilter::default())).await;
+    }
+}
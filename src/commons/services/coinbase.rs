@@ -1,11 +1,10 @@
 use leptos::ServerFnError;
 
-use crate::{commons::models::{coinbase::CoinbaseSpotPriceResponse, defaults::BlankRequest}, utilities::http_wrapper::{call_and_parse, HttpMethod}};
+use crate::{commons::models::{coinbase::CoinbaseSpotPriceResponse, defaults::BlankRequest}, utilities::{configuration::get_config, http_wrapper::{call_and_parse, HttpMethod}}};
 
 pub async fn get_spot_price(pair: String) -> Result<CoinbaseSpotPriceResponse, ServerFnError> {
     // let url = std::env::var("COINBASE_V2").unwrap();
-    let url = option_env!("COINBASE_V2");
-    let path = format!("{}/prices/{}/spot", url.unwrap_or_default(), pair);
+    let path = format!("{}/prices/{}/spot", get_config().coinbase_v2, pair);
 
     let call_and_parse = call_and_parse::<BlankRequest, CoinbaseSpotPriceResponse>(
         Option::None,
@@ -18,7 +17,30 @@ pub async fn get_spot_price(pair: String) -> Result<CoinbaseSpotPriceResponse, S
         Ok(res) => Ok(res),
         Err(e) => {
             log::info!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::mock_backend::{assert_api_errors, MockBackend};
+
+    #[wasm_bindgen_test]
+    async fn get_spot_price_sends_no_session_to_coinbase() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let spot = get_spot_price(String::from("BTC-USD")).await.unwrap();
+        assert_eq!(spot.data.amount, "65000.00");
+        assert_eq!(spot.data.base, "BTC");
+        assert!(backend.requests()[0].authorization.is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_spot_price_returns_the_errors_of_coinbase() {
+        assert_api_errors(|| get_spot_price(String::from("BTC-USD"))).await;
+    }
+}
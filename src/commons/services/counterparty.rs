@@ -1,34 +1,22 @@
 use leptos::*;
+use serde_json::json;
 
-use crate::commons::models::counterparty::GetCounterPartiesResponse;
-use crate::commons::models::defaults::BlankRequest;
-use crate::utilities::cookies::JabraCookie;
-use crate::utilities::http_wrapper::{call_and_parse, HttpMethod};
-/// Server function to get the counterparties.
+use crate::commons::models::audit::{AuditAction, AuditLogRequest};
+use crate::commons::models::counterparty::{CounterPartyRequest, CounterPartyStatusRequest, GetCounterPartiesResponse};
+use crate::commons::models::defaults::{BlankRequest, FilterOperator, QueryBuilder};
+use crate::commons::services::audit::{get_audit_snapshot, record_audit};
+use crate::utilities::http_wrapper::{ApiClient, Backend, HttpMethod};
+/// Server function to get the active counterparties.
 
 pub async fn get_counter_parties() -> Result<GetCounterPartiesResponse, ServerFnError> {
-    let (cookie, _set_cookie) =
-        leptos_use::use_cookie::<String, leptos_use::utils::FromToStringCodec>("admin_portal_csr");
-    let cookie = cookie.get_untracked().map(|c| c).unwrap_or_default();
-
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    if jwt_cookie.is_expired() {
-        return Ok(super::counterparty::GetCounterPartiesResponse::default());
-    }
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/counterparty?sort=name", url.unwrap_or_default());
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
-
-    let response = call_and_parse::<BlankRequest, GetCounterPartiesResponse>(
+    let path = QueryBuilder::new()
+        .filter("is_active", FilterOperator::Eq, json!(true))
+        .sort("name")
+        .to_path("counterparty");
+
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, GetCounterPartiesResponse>(
         Option::None,
         path,
-        headers,
         HttpMethod::GET,
     )
     .await;
@@ -36,7 +24,215 @@ pub async fn get_counter_parties() -> Result<GetCounterPartiesResponse, ServerFn
         Ok(res) => Ok(res),
         Err(e) => {
             log::error!("error6: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+/// Server function to get all the counterparties, including the deactivated ones.
+
+pub async fn get_all_counter_parties() -> Result<GetCounterPartiesResponse, ServerFnError> {
+    let path = QueryBuilder::new().sort("name").limit(-1).to_path("counterparty");
+
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, GetCounterPartiesResponse>(
+        Option::None,
+        path,
+        HttpMethod::GET,
+    )
+    .await;
+    match response {
+        Ok(res) => Ok(res),
+        Err(e) => {
+            log::error!("error fetching all counterparties: {:?}", e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+/// Server function to create a counterparty.
+/// The ticker is unique in Directus too, so a ticker taken in the meantime is rejected with a validation error.
+
+pub async fn create_counter_party(request: CounterPartyRequest) -> Result<bool, ServerFnError> {
+    let audit = AuditLogRequest::new(AuditAction::CreateCounterparty, "counterparty", json!(request))
+        .with_target_ids(vec![request.ticker.clone()]);
+    let path = String::from("/items/counterparty");
+
+    let response = ApiClient::new(Backend::Directus)
+        .call::<CounterPartyRequest>(Some(request), path, HttpMethod::POST)
+        .await;
+    match response {
+        Ok(res) => {
+            record_audit(audit).await;
+            Ok(res)
+        }
+        Err(e) => {
+            log::error!("error creating counterparty: {:?}", e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+/// Server function to edit a counterparty.
+
+pub async fn update_counter_party(id: u16, request: CounterPartyRequest) -> Result<bool, ServerFnError> {
+    let before = get_counter_party_snapshot(id).await;
+    let audit = AuditLogRequest::new(AuditAction::EditCounterparty, "counterparty", json!(request)).with_before(before);
+    let path = format!("/items/counterparty/{}", id);
+
+    let response = ApiClient::new(Backend::Directus)
+        .call::<CounterPartyRequest>(Some(request), path, HttpMethod::PATCH)
+        .await;
+    match response {
+        Ok(res) => {
+            record_audit(audit).await;
+            Ok(res)
+        }
+        Err(e) => {
+            log::error!("error updating counterparty {}: {:?}", id, e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+/// Server function to deactivate or reactivate a counterparty, counterparties are never deleted.
+
+pub async fn set_counter_party_active(id: u16, is_active: bool) -> Result<bool, ServerFnError> {
+    let request = CounterPartyStatusRequest { is_active };
+    let before = get_counter_party_snapshot(id).await;
+    let action = if is_active {
+        AuditAction::ReactivateCounterparty
+    } else {
+        AuditAction::DeactivateCounterparty
+    };
+    let audit = AuditLogRequest::new(action, "counterparty", json!(request)).with_before(before);
+    let path = format!("/items/counterparty/{}", id);
+
+    let response = ApiClient::new(Backend::Directus)
+        .call::<CounterPartyStatusRequest>(Some(request), path, HttpMethod::PATCH)
+        .await;
+    match response {
+        Ok(res) => {
+            record_audit(audit).await;
+            Ok(res)
         }
+        Err(e) => {
+            log::error!("error changing the status of counterparty {}: {:?}", id, e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+async fn get_counter_party_snapshot(id: u16) -> serde_json::Value {
+    get_audit_snapshot(
+        "counterparty",
+        QueryBuilder::new()
+            .filter("id", FilterOperator::Eq, json!(id))
+            .fields(String::from("id, ticker, name, short_name, is_exchange, is_active")),
+    )
+    .await
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use serde_json::json;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::mock_backend::{assert_error_paths, fixture, url, MockBackend, USER_ID};
+
+    fn get_request() -> CounterPartyRequest {
+        CounterPartyRequest::new(String::from("acme"), String::from("Acme Capital"), String::from("Acme"), false)
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_counter_parties_fetches_the_active_ones_by_name() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures().respond(
+            HttpMethod::GET,
+            url(Backend::Directus, "/items/counterparty"),
+            200,
+            fixture!("counterparties_active.json"),
+        );
+        let counterparties = get_counter_parties().await.unwrap();
+        assert_eq!(counterparties.data.len(), 2);
+        assert!(counterparties.data.iter().all(|c| c.is_active));
+        let request = &backend.requests()[0];
+        assert_eq!(request.get_filter(), json!({"is_active": {"_eq": true}}));
+        assert_eq!(request.get_param("sort"), "name");
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_all_counter_parties_includes_the_deactivated_ones() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let counterparties = get_all_counter_parties().await.unwrap();
+        assert_eq!(counterparties.data.len(), 3);
+        assert!(counterparties.data.iter().any(|c| !c.is_active));
+        let request = &backend.requests()[0];
+        assert_eq!(request.get_filter(), serde_json::Value::Null);
+        assert_eq!(request.get_param("limit"), "-1");
+    }
+
+    #[wasm_bindgen_test]
+    async fn create_counter_party_posts_the_request_and_records_the_audit() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        assert!(create_counter_party(get_request()).await.unwrap());
+        let created = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/counterparty"));
+        assert_eq!(
+            created[0].get_body(),
+            json!({"ticker": "ACME", "name": "Acme Capital", "short_name": "Acme", "is_exchange": false})
+        );
+        let audits = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log"));
+        assert_eq!(audits.len(), 1);
+        assert_eq!(audits[0].get_body()["target_ids"], json!(["ACME"]));
+        assert_eq!(audits[0].get_body()["actor"], USER_ID);
+    }
+
+    #[wasm_bindgen_test]
+    async fn create_counter_party_records_no_audit_when_it_fails() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures().respond(
+            HttpMethod::POST,
+            url(Backend::Directus, "/items/counterparty"),
+            400,
+            fixture!("error_invalid_payload.json"),
+        );
+        assert!(create_counter_party(get_request()).await.is_err());
+        assert!(backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log")).is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn update_counter_party_patches_the_record_and_audits_its_snapshot() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures().respond(
+            HttpMethod::GET,
+            url(Backend::Directus, "/items/counterparty"),
+            200,
+            fixture!("audit_snapshot.json"),
+        );
+        assert!(update_counter_party(2, get_request()).await.unwrap());
+        let updates = backend.requests_to(HttpMethod::PATCH, &url(Backend::Directus, "/items/counterparty/2"));
+        assert_eq!(updates[0].get_body()["ticker"], "ACME");
+        let audits = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log"));
+        assert_eq!(audits[0].get_body()["before"], json!([{"id": 2, "ticker": "ACME", "name": "Acme Capital"}]));
+    }
+
+    #[wasm_bindgen_test]
+    async fn set_counter_party_active_only_patches_the_status() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        assert!(set_counter_party_active(2, false).await.unwrap());
+        let updates = backend.requests_to(HttpMethod::PATCH, &url(Backend::Directus, "/items/counterparty/2"));
+        assert_eq!(updates[0].get_body(), json!({"is_active": false}));
+    }
+
+    #[wasm_bindgen_test]
+    async fn counterparty_services_return_the_errors_of_the_backend() {
+        assert_error_paths(get_counter_parties).await;
+        assert_error_paths(get_all_counter_parties).await;
+        assert_error_paths(|| create_counter_party(get_request())).await;
+        assert_error_paths(|| update_counter_party(2, get_request())).await;
+        assert_error_paths(|| set_counter_party_active(2, true)).await;
     }
 }
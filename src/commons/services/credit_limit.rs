@@ -0,0 +1,319 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use leptos::*;
+use serde_json::json;
+
+use crate::commons::models::credit_limit::{CreditExposure, CreditLimit, CreditUtilisation, GetCreditLimitResponse};
+use crate::commons::models::defaults::{BlankRequest, FilterOperator, QueryBuilder};
+use crate::commons::models::quote::{GetQuoteOptionResponse, QuoteOption};
+use crate::commons::models::trade::{
+    CollateralRequest, CollateralResponseData, PositionGreekRequest, PositionsGreeksRequest, PositionsGreeksResponse,
+    Trade, TradeHistory, TradeHistoryWithSpot,
+};
+use crate::commons::services::coinbase::get_spot_price;
+use crate::commons::services::interestrates::get_interest_rates;
+use crate::utilities::http_wrapper::{ApiClient, Backend, HttpMethod};
+
+/// Server function to get the credit limit of a counterparty, `None` when it has none.
+
+pub async fn get_credit_limit(ticker: String) -> Result<Option<CreditLimit>, ServerFnError> {
+    let path = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!(ticker))
+        .fields(CreditLimit::get_query())
+        .limit(1)
+        .to_path("credit_limit");
+
+    let response = ApiClient::new(Backend::Directus)
+        .call_and_parse::<BlankRequest, GetCreditLimitResponse>(Option::None, path, HttpMethod::GET)
+        .await;
+    match response {
+        Ok(res) => Ok(res.data.into_iter().next()),
+        Err(e) => {
+            log::error!("error fetching the credit limit of {}: {:?}", ticker, e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+/// Server function to get the utilisation of the credit limit of a counterparty,
+/// from its open trades and its pending quotes.
+
+pub async fn get_credit_utilisation(ticker: String) -> Result<CreditUtilisation, ServerFnError> {
+    let limit = get_credit_limit(ticker.clone()).await?;
+    let path = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!(ticker))
+        .filter("quote_status", FilterOperator::Eq, json!("active"))
+        .fields(QuoteOption::get_query())
+        .limit(-1)
+        .to_path("quotes_option");
+    let quotes = get_quotes(path).await?;
+    let mut exposure = get_trade_exposure(ticker.clone(), &quotes).await?;
+    exposure.add_quotes(&quotes);
+    Ok(CreditUtilisation { ticker, limit, exposure })
+}
+
+/// Returns the credit limits that the approval of the quotes would breach, as messages for the user.
+/// Each counterparty is checked with its open trades and the quotes being approved, the other pending quotes are left out.
+/// A counterparty without a credit limit is not checked.
+
+pub async fn check_credit_limits(quote_ids: Vec<u32>) -> Result<Vec<String>, ServerFnError> {
+    let path = QueryBuilder::new()
+        .filter("id", FilterOperator::In, json!(quote_ids))
+        .filter("counterparty_id.ticker", FilterOperator::Neq, json!("JABRA"))
+        .fields(QuoteOption::get_query())
+        .limit(-1)
+        .to_path("quotes_option");
+    let mut quotes_per_counterparty = BTreeMap::<String, Vec<QuoteOption>>::new();
+    for quote in get_quotes(path).await? {
+        quotes_per_counterparty
+            .entry(quote.counterparty_id.ticker.clone())
+            .or_default()
+            .push(quote);
+    }
+
+    let mut breaches = Vec::<String>::new();
+    for (ticker, quotes) in quotes_per_counterparty {
+        let Some(limit) = get_credit_limit(ticker.clone()).await? else {
+            continue;
+        };
+        let mut exposure = get_trade_exposure(ticker.clone(), &quotes).await?;
+        exposure.add_quotes(&quotes);
+        let utilisation = CreditUtilisation {
+            ticker,
+            limit: Some(limit),
+            exposure,
+        };
+        breaches.append(&mut utilisation.get_breaches());
+    }
+    Ok(breaches)
+}
+
+async fn get_quotes(path: String) -> Result<Vec<QuoteOption>, ServerFnError> {
+    let response = ApiClient::new(Backend::Directus)
+        .call_and_parse::<BlankRequest, GetQuoteOptionResponse>(Option::None, path, HttpMethod::GET)
+        .await;
+    match response {
+        Ok(res) => Ok(res.data),
+        Err(e) => {
+            log::error!("error fetching the quotes of the credit check: {:?}", e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+/// Prices the open trades of the counterparty at the current spot of their pairs, and adds the collateral it holds.
+/// The pairs of the quotes are included so their spots are fetched too.
+
+async fn get_trade_exposure(ticker: String, quotes: &[QuoteOption]) -> Result<CreditExposure, ServerFnError> {
+    let path = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!("JABRA"))
+        .filter("party_b.ticker", FilterOperator::Eq, json!(ticker))
+        .filter("expiry_timestamp", FilterOperator::Gte, json!("$NOW"))
+        .filter("activity", FilterOperator::Eq, json!("open"))
+        .fields(Trade::get_query())
+        .limit(-1)
+        .to_path("trade");
+    let trades = match ApiClient::new(Backend::Directus)
+        .call_and_parse::<BlankRequest, TradeHistory>(Option::None, path, HttpMethod::GET)
+        .await
+    {
+        Ok(res) => res.data,
+        Err(e) => {
+            log::error!("error fetching the open trades of {}: {:?}", ticker, e);
+            return Err(ServerFnError::from(e));
+        }
+    };
+
+    let mut trades_per_pair = BTreeMap::<String, Vec<Trade>>::new();
+    for quote in quotes {
+        trades_per_pair.entry(quote.pair_id.name.clone()).or_default();
+    }
+    for trade in trades {
+        trades_per_pair.entry(trade.pair_id.name.clone()).or_default().push(trade);
+    }
+
+    let r2 = if trades_per_pair.values().any(|t| !t.is_empty()) {
+        get_interest_rates().await?.get_newest_interest_rate().rate
+    } else {
+        0.0
+    };
+    let mut exposure = CreditExposure::default();
+    let mut collateral_currencies = BTreeSet::<String>::new();
+    for (pair, trades) in trades_per_pair {
+        let spot_response = get_spot_price(pair.replace('/', "-")).await?;
+        let spot = spot_response.data.amount.parse::<f64>().unwrap_or_default();
+        exposure.spots.insert(pair.clone(), spot);
+        if trades.is_empty() {
+            continue;
+        }
+
+        let notional = trades.iter().map(|t| t.amount.unwrap_or(0.0).abs() * spot).sum::<f64>();
+        exposure.notional_per_pair.insert(pair.clone(), notional);
+        let positions = TradeHistoryWithSpot { data: trades, spot }.extract_risk_slide_positions_by_currency_pair();
+        let request = PositionsGreeksRequest {
+            positions: positions
+                .into_iter()
+                .map(|p| {
+                    PositionGreekRequest::new(
+                        p.side,
+                        p.option_kind,
+                        p.amount.abs(),
+                        p.strike,
+                        p.time_to_expiry,
+                        p.inception_price.abs(),
+                        Some(spot),
+                        Some(r2),
+                        Some(0.0),
+                        Some(p.iv),
+                        Some(p.expiry_timestamp),
+                        Some(p.id.to_string()),
+                    )
+                })
+                .collect(),
+            current_spot: spot,
+            spot_bump: 0.01,
+            bump_times: 1,
+        };
+        let greeks = match ApiClient::new(Backend::Pricer)
+            .call_and_parse::<PositionsGreeksRequest, PositionsGreeksResponse>(
+                Some(request),
+                String::from("/quote/greeks"),
+                HttpMethod::POST,
+            )
+            .await
+        {
+            Ok(res) => res.data.atm_risk_slide,
+            Err(e) => {
+                log::error!("error pricing the open trades of {} in {}: {:?}", ticker, pair, e);
+                return Err(ServerFnError::from(e));
+            }
+        };
+        exposure.net_delta += greeks.delta * spot;
+        exposure.mtm += greeks.pnl;
+
+        // The collateral is held per currency, so a currency shared by two pairs is only counted once.
+        let currency = pair.split('/').next().unwrap_or("").to_string();
+        if collateral_currencies.insert(currency.clone()) {
+            let request = CollateralRequest {
+                currency,
+                counterparty: ticker.clone(),
+                current_spot: spot,
+            };
+            match ApiClient::new(Backend::Pricer)
+                .call_and_parse::<CollateralRequest, CollateralResponseData>(
+                    Some(request),
+                    String::from("/risk/collateral"),
+                    HttpMethod::POST,
+                )
+                .await
+            {
+                Ok(res) => exposure.collateral += res.data.unwind_risk_slide.total_current_usd,
+                Err(e) => {
+                    log::error!("error fetching the collateral of {}: {:?}", ticker, e);
+                    return Err(ServerFnError::from(e));
+                }
+            }
+        }
+    }
+    Ok(exposure)
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::mock_backend::{assert_error_paths, coinbase_url, fixture, url, MockBackend};
+    use crate::utilities::option_pricer::price_option;
+
+    #[wasm_bindgen_test]
+    async fn get_credit_limit_returns_the_limit_of_the_counterparty() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let limit = get_credit_limit(String::from("ACME")).await.unwrap().unwrap();
+        assert_eq!(limit.max_notional_per_pair, Some(5_000_000.0));
+        assert_eq!(
+            backend.requests()[0].get_filter(),
+            json!({"counterparty_id": {"ticker": {"_eq": "ACME"}}})
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_credit_limit_is_none_without_a_limit() {
+        let backend = MockBackend::start().await;
+        backend
+            .serve_fixtures()
+            .respond(HttpMethod::GET, url(Backend::Directus, "/items/credit_limit"), 200, r#"{"data": []}"#);
+        assert_eq!(get_credit_limit(String::from("ACME")).await.unwrap(), None);
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_credit_utilisation_prices_the_open_trades_and_adds_the_pending_quotes() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let utilisation = get_credit_utilisation(String::from("ACME")).await.unwrap();
+        let exposure = utilisation.exposure;
+        assert_eq!(exposure.spots.get("BTC/USD"), Some(&65_000.0));
+        // The open trade of 1 BTC and the quote of 2 BTC.
+        assert_eq!(exposure.notional_per_pair.get("BTC/USD"), Some(&195_000.0));
+        // The pricer delta of the trade, less the delta of the quote sold by JABRA.
+        assert!((exposure.net_delta - (0.42 * 65_000.0 - 0.35 * 2.0 * 65_000.0)).abs() < 1e-6);
+        assert_eq!(exposure.mtm, 150.0);
+        assert_eq!(exposure.collateral, 250_150.0);
+        assert!(utilisation.limit.is_some());
+        let collateral = backend.requests_to(HttpMethod::POST, &url(Backend::Pricer, "/risk/collateral"));
+        assert_eq!(collateral[0].get_body(), json!({"currency": "BTC", "counterparty": "ACME", "current_spot": 65000.0}));
+        assert_eq!(backend.requests_to(HttpMethod::GET, &coinbase_url("/prices/BTC-USD/spot")).len(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_credit_utilisation_prices_the_quotes_saved_without_a_delta() {
+        let backend = MockBackend::start().await;
+        let mut quotes = serde_json::from_str::<serde_json::Value>(fixture!("quotes_option.json")).unwrap();
+        quotes["data"][0]["delta"] = serde_json::Value::Null;
+        backend.serve_fixtures().respond(
+            HttpMethod::GET,
+            url(Backend::Directus, "/items/quotes_option"),
+            200,
+            &quotes.to_string(),
+        );
+        let exposure = get_credit_utilisation(String::from("ACME")).await.unwrap().exposure;
+        let quote = serde_json::from_value::<QuoteOption>(quotes["data"][0].clone()).unwrap();
+        let unit_delta = price_option("Put", 65_000.0, quote.strike, quote.ttm, quote.r1, quote.r2, quote.iv).delta;
+        assert!(unit_delta < 0.0);
+        assert!((exposure.net_delta - (0.42 * 65_000.0 + unit_delta * 2.0 * 65_000.0)).abs() < 1e-6);
+    }
+
+    #[wasm_bindgen_test]
+    async fn check_credit_limits_is_empty_within_the_limits() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        assert!(check_credit_limits(vec![501]).await.unwrap().is_empty());
+        let quotes = backend.requests_to(HttpMethod::GET, &url(Backend::Directus, "/items/quotes_option"));
+        assert_eq!(
+            quotes[0].get_filter(),
+            json!({"_and": [{"id": {"_in": [501]}}, {"counterparty_id": {"ticker": {"_neq": "JABRA"}}}]})
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn check_credit_limits_returns_the_breached_limits() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures().respond(
+            HttpMethod::GET,
+            url(Backend::Directus, "/items/credit_limit"),
+            200,
+            r#"{"data": [{"id": 1, "max_notional_per_pair": 100000.0}]}"#,
+        );
+        let breaches = check_credit_limits(vec![501]).await.unwrap();
+        assert_eq!(breaches.len(), 1);
+        assert!(breaches[0].starts_with("ACME: Notional BTC/USD"), "{}", breaches[0]);
+    }
+
+    #[wasm_bindgen_test]
+    async fn credit_limits_return_the_errors_of_the_backend() {
+        assert_error_paths(|| get_credit_limit(String::from("ACME"))).await;
+        assert_error_paths(|| get_credit_utilisation(String::from("ACME"))).await;
+        assert_error_paths(|| check_credit_limits(vec![501])).await;
+    }
+}
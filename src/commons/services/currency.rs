@@ -1,34 +1,18 @@
 use leptos::*;
-use crate::utilities::{
-    cookies::{get_jabra_cookie, JabraCookie},
-    http_wrapper::{call_and_parse, HttpMethod},
-};
+use crate::utilities::http_wrapper::{ApiClient, Backend, HttpMethod};
 
-use crate::commons::models::defaults::BlankRequest;
+use crate::commons::models::defaults::{BlankRequest, QueryBuilder};
 use crate::commons::models::currency::{Currency, CurrencyConfigurationResponse};
 /// Server function to fetch the currencies.
 
 pub async fn fetch_currencies() -> Result<CurrencyConfigurationResponse, ServerFnError> {
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
-    let path = format!(
-        "{}/items/supported_ccy?fields={}",
-        url.unwrap_or_default(),
-        Currency::get_default_query()
-    );
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
+    let path = QueryBuilder::new()
+        .fields(Currency::get_default_query())
+        .to_path("supported_ccy");
 
-    let response = call_and_parse::<BlankRequest, CurrencyConfigurationResponse>(
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, CurrencyConfigurationResponse>(
         Option::None,
         path,
-        headers,
         HttpMethod::GET,
     )
     .await;
@@ -36,7 +20,32 @@ pub async fn fetch_currencies() -> Result<CurrencyConfigurationResponse, ServerF
         Ok(res) => Ok(res),
         Err(e) => {
             log::error!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::mock_backend::{assert_error_paths, MockBackend};
+
+    #[wasm_bindgen_test]
+    async fn fetch_currencies_returns_the_supported_currencies() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let currencies = fetch_currencies().await.unwrap();
+        assert_eq!(
+            currencies.data.iter().map(|c| c.ticker.as_str()).collect::<Vec<&str>>(),
+            vec!["BTC", "USD", "ETH"]
+        );
+        assert_eq!(backend.requests()[0].get_param("fields"), Currency::get_default_query());
+    }
+
+    #[wasm_bindgen_test]
+    async fn fetch_currencies_returns_the_errors_of_the_backend() {
+        assert_error_paths(fetch_currencies).await;
+    }
+}
@@ -0,0 +1,54 @@
+use leptos::*;
+use crate::utilities::http_wrapper::{ApiClient, Backend, HttpMethod};
+
+use crate::commons::models::currency_pair::{CurrencyPair, CurrencyPairResponse};
+use crate::commons::models::defaults::{BlankRequest, FilterOperator, QueryBuilder};
+use serde_json::json;
+/// Server function to fetch the active currency pairs.
+
+pub async fn get_currency_pairs() -> Result<CurrencyPairResponse, ServerFnError> {
+    let path = QueryBuilder::new()
+        .filter("is_active", FilterOperator::Eq, json!(true))
+        .sort("id")
+        .fields(CurrencyPair::get_default_query())
+        .to_path("currency_pair");
+
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, CurrencyPairResponse>(
+        Option::None,
+        path,
+        HttpMethod::GET,
+    )
+    .await;
+    match response {
+        Ok(res) => Ok(res),
+        Err(e) => {
+            log::error!("error fetching currency pairs: {:?}", e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::mock_backend::{assert_error_paths, MockBackend};
+
+    #[wasm_bindgen_test]
+    async fn get_currency_pairs_fetches_the_active_pairs() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let pairs = get_currency_pairs().await.unwrap();
+        assert_eq!(pairs.data[0].name, "BTC/USD");
+        assert_eq!(pairs.data[0].quote.ticker, "USD");
+        let request = &backend.requests()[0];
+        assert_eq!(request.get_filter(), json!({"is_active": {"_eq": true}}));
+        assert_eq!(request.get_param("sort"), "id");
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_currency_pairs_returns_the_errors_of_the_backend() {
+        assert_error_paths(get_currency_pairs).await;
+    }
+}
@@ -1,29 +1,22 @@
 use leptos::ServerFnError;
-use crate::commons::models::defaults::BlankRequest;
+use serde_json::json;
+use crate::commons::models::audit::{AuditAction, AuditLogRequest};
+use crate::commons::models::defaults::{BlankRequest, FilterOperator, QueryBuilder};
+use crate::commons::services::audit::{get_audit_snapshot, record_audit};
 use crate::commons::models::interestrates::{InterestRate, InterestRateRequest, InterestRatesResponse};
-use crate::utilities::cookies::{get_jabra_cookie, JabraCookie};
-use crate::utilities::http_wrapper::{call_and_parse,call, HttpMethod};
+use crate::utilities::http_wrapper::{ApiClient, Backend, HttpMethod};
 
 pub async fn get_interest_rates() -> Result<InterestRatesResponse, ServerFnError> {
     
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    if jwt_cookie.is_expired() {
-        return Ok(Default::default());
-    }
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/interest_rates?fields={}&sort=-id&limit=1", url.unwrap_or_default(), InterestRate::get_query());
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
+    let path = QueryBuilder::new()
+        .fields(InterestRate::get_query())
+        .sort("-id")
+        .limit(1)
+        .to_path("interest_rates");
 
-    let response = call_and_parse::<BlankRequest, InterestRatesResponse>(
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, InterestRatesResponse>(
         Option::None,
         path,
-        headers,
         HttpMethod::GET,
     )
     .await;
@@ -31,39 +24,77 @@ pub async fn get_interest_rates() -> Result<InterestRatesResponse, ServerFnError
         Ok(res) => Ok(res),
         Err(e) => {
             log::error!("error fetching interest rates: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
 
 pub async fn update_interest_rate(interest_rate: InterestRateRequest) -> Result<bool, ServerFnError> {
+    let before = get_audit_snapshot(
+        "interest_rates",
+        QueryBuilder::new()
+            .filter("currency_id", FilterOperator::Eq, json!(interest_rate.currency_id))
+            .fields(String::from("id, rate, currency_id"))
+            .sort("-id")
+            .limit(1),
+    )
+    .await;
+    let audit = AuditLogRequest::new(AuditAction::UpdateInterestRate, "interest_rates", json!(interest_rate))
+        .with_before(before);
+    let path = String::from("/items/interest_rates");
 
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    if jwt_cookie.is_expired() {
-        return Ok(Default::default());
-    }
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/interest_rates", url.unwrap_or_default());
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
-
-    let response = call::<InterestRateRequest>(
+    let response = ApiClient::new(Backend::Directus).call::<InterestRateRequest>(
         Some(interest_rate),
         path,
-        headers,
         HttpMethod::POST,
     )
     .await;
     match response {
-        Ok(res) => Ok(res),
+        Ok(res) => {
+            record_audit(audit).await;
+            Ok(res)
+        }
         Err(e) => {
             log::error!("error updating interest rates: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::mock_backend::{assert_error_paths, url, MockBackend};
+
+    #[wasm_bindgen_test]
+    async fn get_interest_rates_fetches_the_newest_rate() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let rates = get_interest_rates().await.unwrap();
+        assert_eq!(rates.data[0].rate, 0.05);
+        let request = &backend.requests()[0];
+        assert_eq!(request.get_param("sort"), "-id");
+        assert_eq!(request.get_param("limit"), "1");
+    }
+
+    #[wasm_bindgen_test]
+    async fn update_interest_rate_posts_a_new_rate_and_records_the_audit() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let request = InterestRateRequest { rate: 0.045, currency_id: 2 };
+        assert!(update_interest_rate(request).await.unwrap());
+        let posted = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/interest_rates"));
+        assert_eq!(posted[0].get_body(), json!({"rate": 0.045, "currency_id": 2}));
+        let snapshots = backend.requests_to(HttpMethod::GET, &url(Backend::Directus, "/items/interest_rates"));
+        assert_eq!(snapshots[0].get_filter(), json!({"currency_id": {"_eq": 2}}));
+        assert_eq!(backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log")).len(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    async fn interest_rates_return_the_errors_of_the_backend() {
+        assert_error_paths(get_interest_rates).await;
+        assert_error_paths(|| update_interest_rate(InterestRateRequest { rate: 0.045, currency_id: 2 })).await;
+    }
+}
@@ -1,5 +1,12 @@
 pub mod counterparty;
+pub mod credit_limit;
 pub mod quote;
 pub mod coinbase;
 pub mod interestrates;
-pub mod currency;
\ No newline at end of file
+pub mod currency;
+pub mod currency_pair;
+pub mod spot_stream;
+pub mod user;
+pub mod audit;
+pub mod session;
+
@@ -1,17 +1,19 @@
 use crate::{
-    commons::models::{
-        defaults::BlankRequest,
+    commons::{models::{
+        audit::{AuditAction, AuditLogRequest},
+        defaults::{BlankRequest, FilterOperator, QueryBuilder},
         quote::{
             GetQuoteOptionResponse, QuoteOption, QuotesOptionForStatusChange,
             QuotesOptionsForModification,
         },
-    },
+    }, services::{audit::{get_audit_snapshot, record_audit}, credit_limit::check_credit_limits}},
     utilities::{
-        cookies::{refresh_token, set_jabra_cookie, JabraCookie},
-        http_wrapper::{call, call_and_parse, HttpMethod},
+        errors::JabraError,
+        http_wrapper::{ApiClient, Backend, HttpMethod},
     },
 };
 use leptos::*;
+use serde_json::json;
 
 /// This is a server function that gets the quotes option based on the quote status.
 /// The input string variations are `active`, `approved`, `rejected`, and `expired`.
@@ -19,91 +21,65 @@ use leptos::*;
 pub async fn get_quotes_option(
     quote_status: String,
 ) -> Result<std::collections::BTreeMap<String, Vec<QuoteOption>>, ServerFnError> {
-    use std::collections::BTreeMap;
-    let (cookie, _set_cookie) =
-        leptos_use::use_cookie::<String, leptos_use::utils::FromToStringCodec>("admin_portal_csr");
-    let cookie = cookie.get_untracked().map(|c| c).unwrap_or_default();
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
     // let path = format!("{}/items/quotes_option?filter[quote_status][_eq]={}&filter[modified_date][_between]=[{}, {}]&fields={}", url, quote_status, QuoteOption::get_query());
-    let path = format!(
-        "{}/items/quotes_option?filter[quote_status][_eq]={}&fields={}",
-        url.unwrap_or_default(),
-        quote_status,
-        QuoteOption::get_query()
-    );
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
-
-    let response = call_and_parse::<BlankRequest, GetQuoteOptionResponse>(
+    let path = QueryBuilder::new()
+        .filter("quote_status", FilterOperator::Eq, json!(quote_status))
+        .fields(QuoteOption::get_query())
+        .to_path("quotes_option");
+
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, GetQuoteOptionResponse>(
         Option::None,
         path,
-        headers,
         HttpMethod::GET,
     )
     .await;
 
     match response {
-        Ok(res) => {
-            let mut trade_quotes_map: BTreeMap<String, Vec<QuoteOption>> = BTreeMap::new();
-            let mut admin_trade_quotes: Vec<QuoteOption> = Vec::<QuoteOption>::default();
-            for trade_quote in res.data {
-                if trade_quote.counterparty_id.ticker != "JABRA" {
-                    let key = format!(
-                        "{}~{}",
-                        trade_quote.counterparty_id.name, trade_quote.counterparty_id.id
-                    );
-                    trade_quotes_map
-                        .entry(key)
-                        .or_insert(vec![])
-                        .push(trade_quote);
-                } else {
-                    admin_trade_quotes.push(trade_quote);
-                }
-            }
+        Ok(res) => Ok(group_by_counterparty(res.data)),
+        Err(e) => {
+            log::info!("error: {:?}", e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
 
-            let mut admin_trade_quotes_map = BTreeMap::<String, Vec<QuoteOption>>::new();
-            //Iterate Over BTreeMap
-            for (_, value) in trade_quotes_map.iter_mut() {
-                //Iterate over Vector in Traders Quotes
-                for trade_quote in value.iter_mut() {
-                    //Iterate over Vector in Admin Quotes
-                    for tq in admin_trade_quotes.iter() {
-                        if trade_quote.group_id == tq.group_id {
-                            let key = format!(
-                                "{}~{}",
-                                trade_quote.counterparty_id.name, trade_quote.counterparty_id.id
-                            );
-                            admin_trade_quotes_map
-                                .entry(key)
-                                .or_insert(vec![])
-                                .push(tq.clone());
-                        }
-                    }
-                }
-            }
+/// Groups the quotes by their counterparty, keyed by `name~id`, with the JABRA rows of each group after the rows of the counterparty.
+/// A quote of several legs has several rows per party in its group, each JABRA row is added once.
 
-            for (key, value) in admin_trade_quotes_map.iter_mut() {
-                if trade_quotes_map.contains_key(key) {
-                    let v = trade_quotes_map.get_mut(key).unwrap();
-                    v.append(value);
-                } else {
-                    trade_quotes_map.insert(key.clone(), value.clone());
-                }
-            }
-            // log::info!("trade_quotes_map: {:?}", trade_quotes_map);
-            Ok(trade_quotes_map)
+fn group_by_counterparty(quotes: Vec<QuoteOption>) -> std::collections::BTreeMap<String, Vec<QuoteOption>> {
+    use std::collections::BTreeMap;
+    let mut trade_quotes_map: BTreeMap<String, Vec<QuoteOption>> = BTreeMap::new();
+    let mut admin_trade_quotes: Vec<QuoteOption> = Vec::<QuoteOption>::default();
+    for trade_quote in quotes {
+        if trade_quote.counterparty_id.ticker != "JABRA" {
+            let key = format!(
+                "{}~{}",
+                trade_quote.counterparty_id.name, trade_quote.counterparty_id.id
+            );
+            trade_quotes_map
+                .entry(key)
+                .or_insert(vec![])
+                .push(trade_quote);
+        } else {
+            admin_trade_quotes.push(trade_quote);
         }
-        Err(e) => {
-            log::info!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+    }
+
+    let mut admin_trade_quotes_map = BTreeMap::<String, Vec<QuoteOption>>::new();
+    for tq in admin_trade_quotes {
+        let key = trade_quotes_map
+            .iter()
+            .find(|(_, trade_quotes)| trade_quotes.iter().any(|q| q.group_id == tq.group_id))
+            .map(|(key, _)| key.clone());
+        if let Some(key) = key {
+            admin_trade_quotes_map.entry(key).or_insert(vec![]).push(tq);
         }
     }
+
+    for (key, mut value) in admin_trade_quotes_map {
+        trade_quotes_map.entry(key).or_insert(vec![]).append(&mut value);
+    }
+    trade_quotes_map
 }
 
 /// This is a server function that gets the quotes option based on the quote status and date range.
@@ -112,15 +88,7 @@ pub async fn get_quotes_option(
 pub async fn get_quotes_option_under_24_hrs(
     quote_status: String,
 ) -> Result<std::collections::BTreeMap<String, Vec<QuoteOption>>, ServerFnError> {
-    use std::collections::BTreeMap;
 
-    let (cookie, _set_cookie) =
-        leptos_use::use_cookie::<String, leptos_use::utils::FromToStringCodec>("admin_portal_csr");
-    let cookie = cookie.get_untracked().map(|c| c).unwrap_or_default();
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
 
     let start_date = (chrono::Utc::now() - chrono::Duration::hours(24))
         .format("%Y-%m-%dT%H:%M:%S%.3fZ")
@@ -129,140 +97,101 @@ pub async fn get_quotes_option_under_24_hrs(
         .format("%Y-%m-%dT%H:%M:%S%.3fZ")
         .to_string();
 
-    let path = format!("{}/items/quotes_option?filter[quote_expiry][_nnull]=true&filter[quote_status][_eq]={}&filter[modified_date][_between]=[{}, {}]&fields={}", url.unwrap_or_default(), quote_status, start_date, end_date, QuoteOption::get_query());
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
+    let path = QueryBuilder::new()
+        .filter("quote_expiry", FilterOperator::NNull, json!(true))
+        .filter("quote_status", FilterOperator::Eq, json!(quote_status))
+        .filter("modified_date", FilterOperator::Between, json!([start_date, end_date]))
+        .fields(QuoteOption::get_query())
+        .to_path("quotes_option");
 
-    let response = call_and_parse::<BlankRequest, GetQuoteOptionResponse>(
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, GetQuoteOptionResponse>(
         Option::None,
         path,
-        headers,
         HttpMethod::GET,
     )
     .await;
 
     match response {
-        Ok(res) => {
-            let mut trade_quotes_map: BTreeMap<String, Vec<QuoteOption>> = BTreeMap::new();
-            let mut admin_trade_quotes: Vec<QuoteOption> = Vec::<QuoteOption>::default();
-            for trade_quote in res.data {
-                if trade_quote.counterparty_id.ticker != "JABRA" {
-                    let key = format!(
-                        "{}~{}",
-                        trade_quote.counterparty_id.name, trade_quote.counterparty_id.id
-                    );
-                    trade_quotes_map
-                        .entry(key)
-                        .or_insert(vec![])
-                        .push(trade_quote);
-                } else {
-                    admin_trade_quotes.push(trade_quote);
-                }
-            }
-
-            let mut admin_trade_quotes_map = BTreeMap::<String, Vec<QuoteOption>>::new();
-            //Iterate Over BTreeMap
-            for (_, value) in trade_quotes_map.iter_mut() {
-                //Iterate over Vector in Traders Quotes
-                for trade_quote in value.iter_mut() {
-                    //Iterate over Vector in Admin Quotes
-                    for tq in admin_trade_quotes.iter() {
-                        if trade_quote.group_id == tq.group_id {
-                            let key = format!(
-                                "{}~{}",
-                                trade_quote.counterparty_id.name, trade_quote.counterparty_id.id
-                            );
-                            admin_trade_quotes_map
-                                .entry(key)
-                                .or_insert(vec![])
-                                .push(tq.clone());
-                        }
-                    }
-                }
-            }
-
-            for (key, value) in admin_trade_quotes_map.iter_mut() {
-                if trade_quotes_map.contains_key(key) {
-                    let v = trade_quotes_map.get_mut(key).unwrap();
-                    v.append(value);
-                } else {
-                    trade_quotes_map.insert(key.clone(), value.clone());
-                }
-            }
-            // log::info!("trade_quotes_map: {:?}", trade_quotes_map);
-            Ok(trade_quotes_map)
-        }
+        Ok(res) => Ok(group_by_counterparty(res.data)),
         Err(e) => {
             log::info!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
 
 /// This is a server function that approves or rejects a quote option.
+/// An approval is first checked against the credit limits of the counterparties,
+/// a breach is returned as a `CreditLimitBreach` unless an override reason is given, and the override is audited.
 /// ## Examples
 ///
 /// ```rust
-/// approve_reject_quotes_option(vec![QuotesOptionForStatusChange::new(1, "approved".to_string())]) {
+/// approve_reject_quotes_option(vec![QuotesOptionForStatusChange::new(1, "approved".to_string())], None) {
 ///     Ok(true)
 /// };
 /// ```
 ///
 /// ```rust
-/// approve_reject_quotes_option(vec![QuotesOptionForStatusChange::new(2, "rejected".to_string())]) {
+/// approve_reject_quotes_option(vec![QuotesOptionForStatusChange::new(2, "rejected".to_string())], None) {
 ///     Ok(true)
 /// };
 /// ```
 
 pub async fn approve_reject_quotes_option(
     request: Vec<QuotesOptionForStatusChange>,
+    override_reason: Option<String>,
 ) -> Result<bool, ServerFnError> {
     log::info!("request: {:?}", request);
 
-    let (cookie, _set_cookie) =
-        leptos_use::use_cookie::<String, leptos_use::utils::FromToStringCodec>("admin_portal_csr");
-    let cookie = cookie.get_untracked().map(|c| c).unwrap_or_default();
-
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let mut bearer = format!("Bearer {}", jwt_cookie.access_token);
-
-    // Check if token expires, this checking will be available only to actions and server action
-    // Other resources will still work due to 10 minutes buffer time
-    if jwt_cookie.is_expired() {
-        let refresh = refresh_token(jwt_cookie.user_id, jwt_cookie.refresh_token).await;
-        match refresh {
-            Ok(r) => {
-                bearer = format!("Bearer {}", r.access_token);
-                set_jabra_cookie(r, "admin_portal_csr".to_string()).await;
-            }
-            Err(e) => {
-                log::error!("error-token: {:?}", e);
-                // directus_wrapper::set_jabra_cookie(None, "admin_portal_csr".to_string()).await;
-                return Err(ServerFnError::ServerError(e.to_string()));
+    let action = match request.first().map(|q| q.quote_status.as_str()) {
+        Some("approved") => AuditAction::ApproveQuotes,
+        _ => AuditAction::RejectQuotes,
+    };
+    let ids = request.iter().map(|q| q.id).collect::<Vec<u32>>();
+    let mut override_audit = None;
+    if action == AuditAction::ApproveQuotes {
+        let breaches = check_credit_limits(ids.clone()).await?;
+        if !breaches.is_empty() {
+            match override_reason.filter(|r| !r.trim().is_empty()) {
+                Some(reason) => {
+                    override_audit = Some(
+                        AuditLogRequest::new(
+                            AuditAction::OverrideCreditLimit,
+                            "quotes_option",
+                            json!({ "reason": reason.trim(), "breaches": breaches }),
+                        )
+                        .with_target_ids(ids.iter().map(|id| id.to_string()).collect()),
+                    );
+                }
+                None => return Err(ServerFnError::from(JabraError::CreditLimitBreach(breaches))),
             }
         }
     }
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/quotes_option", url.unwrap_or_default());
-
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
+    let before = get_audit_snapshot(
+        "quotes_option",
+        QueryBuilder::new()
+            .filter("id", FilterOperator::In, json!(ids))
+            .fields(String::from("id, group_id, quote_status, modified_date"))
+            .limit(-1),
+    )
+    .await;
+    let audit = AuditLogRequest::new(action, "quotes_option", json!(request)).with_before(before);
+    let path = String::from("/items/quotes_option");
 
     let response =
-        call::<Vec<QuotesOptionForStatusChange>>(Some(request), path, headers, HttpMethod::PATCH)
+        ApiClient::new(Backend::Directus).call::<Vec<QuotesOptionForStatusChange>>(Some(request), path, HttpMethod::PATCH)
             .await;
     match response {
-        Ok(res) => Ok(res),
+        Ok(res) => {
+            if let Some(override_audit) = override_audit {
+                record_audit(override_audit).await;
+            }
+            record_audit(audit).await;
+            Ok(res)
+        }
         Err(e) => {
             log::info!("error-: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
     // Err(ServerFnError::ServerError("Transaction failed".to_string()))
@@ -278,51 +207,196 @@ pub async fn edit_quotes_option(
 ) -> Result<bool, ServerFnError> {
     log::info!("request: {:?}", request);
 
-    let (cookie, _set_cookie) =
-        leptos_use::use_cookie::<String, leptos_use::utils::FromToStringCodec>("admin_portal_csr");
-    let cookie = cookie.get_untracked().map(|c| c).unwrap_or_default();
-
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let mut bearer = format!("Bearer {}", jwt_cookie.access_token);
-
-    // Check if token expires, this checking will be available only to actions and server action
-    // Other resources will still work due to 10 minutes buffer time
-    if jwt_cookie.is_expired() {
-        let refresh = refresh_token(jwt_cookie.user_id, jwt_cookie.refresh_token).await;
-        match refresh {
-            Ok(r) => {
-                bearer = format!("Bearer {}", r.access_token);
-                set_jabra_cookie(r, "admin_portal_csr".to_string()).await;
-            }
-            Err(e) => {
-                log::error!("error-token: {:?}", e);
-                // directus_wrapper::set_jabra_cookie(None, "admin_portal_csr".to_string()).await;
-                return Err(ServerFnError::ServerError(e.to_string()));
-            }
-        }
-    }
     // log::debug!("request: {:?}", request.deserialize());
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/quotes_option", url.unwrap_or_default());
-
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
+    let before = get_audit_snapshot(
+        "quotes_option",
+        QueryBuilder::new()
+            .filter("id", FilterOperator::In, json!(request.iter().map(|q| q.id).collect::<Vec<u32>>()))
+            .fields(String::from("id, group_id, amount, counterparty_id, px_in_base_ccy, px_in_quote_ccy, quote_expiry, payout_ccy, party_a, party_b, gtc"))
+            .limit(-1),
+    )
+    .await;
+    let audit = AuditLogRequest::new(AuditAction::EditQuotes, "quotes_option", json!(request)).with_before(before);
+    let path = String::from("/items/quotes_option");
 
     let response =
-        call::<Vec<QuotesOptionsForModification>>(Some(request), path, headers, HttpMethod::PATCH)
+        ApiClient::new(Backend::Directus).call::<Vec<QuotesOptionsForModification>>(Some(request), path, HttpMethod::PATCH)
             .await;
     match response {
-        Ok(res) => Ok(res),
+        Ok(res) => {
+            record_audit(audit).await;
+            Ok(res)
+        }
         Err(e) => {
             log::info!("error-: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
     // // Err(ServerFnError::ServerError("Transaction failed".to_string()))
     // log::info!("request: {:?}", request);
     // Ok(true)
 }
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::mock_backend::{assert_error_paths, fixture, get_error, url, MockBackend};
+
+    const BREACHED_LIMIT: &str = r#"{"data": [{"id": 1, "max_notional_per_pair": 100000.0}]}"#;
+
+    fn get_status_change(quote_status: &str) -> Vec<QuotesOptionForStatusChange> {
+        vec![QuotesOptionForStatusChange::new(
+            501,
+            quote_status.to_string(),
+            String::from("2030-01-02T09:00:00.000Z"),
+        )]
+    }
+
+    fn get_modification() -> Vec<QuotesOptionsForModification> {
+        vec![QuotesOptionsForModification::new(
+            501,
+            3.0,
+            2,
+            0.06,
+            7800.0,
+            String::from("2030-01-03T08:00:00.000Z"),
+            Some(String::from("USD")),
+            2,
+            1,
+            false,
+        )]
+    }
+
+    fn get_audits(backend: &MockBackend) -> Vec<serde_json::Value> {
+        backend
+            .requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log"))
+            .iter()
+            .map(|r| r.get_body())
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_quotes_option_groups_the_jabra_rows_with_their_counterparty() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures().respond(
+            HttpMethod::GET,
+            url(Backend::Directus, "/items/quotes_option"),
+            200,
+            fixture!("quotes_option_with_jabra.json"),
+        );
+        let quotes = get_quotes_option(String::from("active")).await.unwrap();
+        assert_eq!(quotes.keys().collect::<Vec<&String>>(), vec!["Acme Capital~2"]);
+        let ids = quotes["Acme Capital~2"].iter().map(|q| q.id).collect::<Vec<u32>>();
+        assert_eq!(ids, vec![501, 500]);
+        assert_eq!(backend.requests()[0].get_filter(), json!({"quote_status": {"_eq": "active"}}));
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_quotes_option_adds_each_jabra_row_of_a_multi_leg_quote_once() {
+        let backend = MockBackend::start().await;
+        let mut quotes = serde_json::from_str::<serde_json::Value>(fixture!("quotes_option_with_jabra.json")).unwrap();
+        for (index, id) in [(0, 502), (1, 503)] {
+            let mut leg = quotes["data"][index].clone();
+            leg["id"] = json!(id);
+            leg["instrument_name"] = json!("BTC-28JUN30-70000-C");
+            quotes["data"].as_array_mut().unwrap().push(leg);
+        }
+        backend.serve_fixtures().respond(
+            HttpMethod::GET,
+            url(Backend::Directus, "/items/quotes_option"),
+            200,
+            &quotes.to_string(),
+        );
+        let quotes = get_quotes_option(String::from("active")).await.unwrap();
+        let ids = quotes["Acme Capital~2"].iter().map(|q| q.id).collect::<Vec<u32>>();
+        assert_eq!(ids, vec![501, 503, 500, 502]);
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_quotes_option_under_24_hrs_filters_on_the_last_day() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures().respond(
+            HttpMethod::GET,
+            url(Backend::Directus, "/items/quotes_option"),
+            200,
+            fixture!("quotes_option_with_jabra.json"),
+        );
+        let quotes = get_quotes_option_under_24_hrs(String::from("approved")).await.unwrap();
+        assert_eq!(quotes["Acme Capital~2"].len(), 2);
+        let filter = backend.requests()[0].get_filter();
+        assert_eq!(filter["_and"][1], json!({"quote_status": {"_eq": "approved"}}));
+        assert_eq!(filter["_and"][2]["modified_date"]["_between"].as_array().map(Vec::len), Some(2));
+    }
+
+    #[wasm_bindgen_test]
+    async fn approve_quotes_option_within_the_credit_limits() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        assert!(approve_reject_quotes_option(get_status_change("approved"), None).await.unwrap());
+        let updates = backend.requests_to(HttpMethod::PATCH, &url(Backend::Directus, "/items/quotes_option"));
+        assert_eq!(updates[0].get_body(), json!(get_status_change("approved")));
+        let audits = get_audits(&backend);
+        assert_eq!(audits.len(), 1);
+        assert_eq!(audits[0]["action"], AuditAction::ApproveQuotes.as_str());
+    }
+
+    #[wasm_bindgen_test]
+    async fn approve_quotes_option_is_refused_when_it_breaches_a_credit_limit() {
+        let backend = MockBackend::start().await;
+        backend
+            .serve_fixtures()
+            .respond(HttpMethod::GET, url(Backend::Directus, "/items/credit_limit"), 200, BREACHED_LIMIT);
+        match get_error(approve_reject_quotes_option(get_status_change("approved"), Some(String::from(" "))).await) {
+            JabraError::CreditLimitBreach(breaches) => assert_eq!(breaches.len(), 1),
+            e => panic!("expected a credit limit breach, got {:?}", e),
+        }
+        assert!(backend.requests_to(HttpMethod::PATCH, &url(Backend::Directus, "/items/quotes_option")).is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn approve_quotes_option_audits_the_override_of_a_credit_limit() {
+        let backend = MockBackend::start().await;
+        backend
+            .serve_fixtures()
+            .respond(HttpMethod::GET, url(Backend::Directus, "/items/credit_limit"), 200, BREACHED_LIMIT);
+        let reason = Some(String::from("Collateral posted this morning"));
+        assert!(approve_reject_quotes_option(get_status_change("approved"), reason).await.unwrap());
+        let audits = get_audits(&backend);
+        assert_eq!(audits.len(), 2);
+        assert_eq!(audits[0]["action"], AuditAction::OverrideCreditLimit.as_str());
+        assert_eq!(audits[0]["after"]["reason"], "Collateral posted this morning");
+        assert_eq!(audits[0]["target_ids"], json!(["501"]));
+    }
+
+    #[wasm_bindgen_test]
+    async fn reject_quotes_option_skips_the_credit_check() {
+        let backend = MockBackend::start().await;
+        backend
+            .serve_fixtures()
+            .respond(HttpMethod::GET, url(Backend::Directus, "/items/credit_limit"), 200, BREACHED_LIMIT);
+        assert!(approve_reject_quotes_option(get_status_change("rejected"), None).await.unwrap());
+        assert!(backend.requests_to(HttpMethod::GET, &url(Backend::Directus, "/items/credit_limit")).is_empty());
+        assert_eq!(get_audits(&backend)[0]["action"], AuditAction::RejectQuotes.as_str());
+    }
+
+    #[wasm_bindgen_test]
+    async fn edit_quotes_option_patches_the_quotes_and_records_the_audit() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        assert!(edit_quotes_option(get_modification()).await.unwrap());
+        let updates = backend.requests_to(HttpMethod::PATCH, &url(Backend::Directus, "/items/quotes_option"));
+        assert_eq!(updates[0].get_body()[0]["amount"], 3.0);
+        assert_eq!(get_audits(&backend)[0]["action"], AuditAction::EditQuotes.as_str());
+    }
+
+    #[wasm_bindgen_test]
+    async fn quotes_option_services_return_the_errors_of_the_backend() {
+        assert_error_paths(|| get_quotes_option(String::from("active"))).await;
+        assert_error_paths(|| get_quotes_option_under_24_hrs(String::from("active"))).await;
+        assert_error_paths(|| approve_reject_quotes_option(get_status_change("approved"), None)).await;
+        assert_error_paths(|| approve_reject_quotes_option(get_status_change("rejected"), None)).await;
+        assert_error_paths(|| edit_quotes_option(get_modification())).await;
+    }
+}
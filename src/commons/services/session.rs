@@ -0,0 +1,146 @@
+use leptos::wasm_bindgen::{closure::Closure, JsCast};
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use web_sys::{BroadcastChannel, MessageEvent};
+
+use crate::utilities::configuration::get_config;
+use crate::utilities::cookies::{get_session_cookie, remove_jabra_cookie};
+use crate::utilities::http_wrapper::{call, HttpMethod, COOKIE_NAME};
+
+/// The channel used to tell the other open tabs of the admin portal that the user logged out.
+const SESSION_CHANNEL: &str = "admin_portal_csr_session";
+
+thread_local! {
+    /// Identifies this tab, the channel also delivers the messages to the other listeners of the same tab.
+    static TAB_ID: String = uuid::Uuid::new_v4().to_string();
+}
+
+/// Message sent on the session channel when the user logs out.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LogoutMessage {
+    tab_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LogoutRequest {
+    refresh_token: String,
+    mode: String,
+}
+
+/// Revokes the refresh token of the session on Directus, then drops the cookie and tells the other tabs.
+/// The local session is always ended, a failed revoke is only logged.
+///
+/// # Returns
+///
+/// `true` when the refresh token was revoked.
+
+pub async fn logout() -> Result<bool, ServerFnError> {
+    let revoked = match get_session_cookie(COOKIE_NAME) {
+        Some(cookie) => {
+            let request = LogoutRequest {
+                refresh_token: cookie.refresh_token,
+                mode: String::from("json"),
+            };
+            let path = format!("{}/auth/logout", get_config().directus_url);
+            match call::<LogoutRequest>(Some(request), path, reqwest::header::HeaderMap::new(), HttpMethod::POST).await {
+                Ok(res) => res,
+                Err(e) => {
+                    log::error!("error revoking the refresh token: {:?}", e);
+                    false
+                }
+            }
+        }
+        None => false,
+    };
+    remove_jabra_cookie(COOKIE_NAME);
+    broadcast_logout();
+    Ok(revoked)
+}
+
+fn broadcast_logout() {
+    let message = LogoutMessage {
+        tab_id: TAB_ID.with(|id| id.clone()),
+    };
+    let message = match serde_json::to_string(&message) {
+        Ok(message) => message,
+        Err(e) => {
+            log::error!("error broadcasting the logout: {:?}", e);
+            return;
+        }
+    };
+    match BroadcastChannel::new(SESSION_CHANNEL) {
+        Ok(channel) => {
+            if let Err(e) = channel.post_message(&message.into()) {
+                log::error!("error broadcasting the logout: {:?}", e);
+            }
+            channel.close();
+        }
+        Err(e) => log::error!("error opening the session channel: {:?}", e),
+    }
+}
+
+/// Calls `on_logout` when the user logs out in another tab of the admin portal.
+/// The channel stays open for the lifetime of the app, so this is called once.
+pub fn on_logout_broadcast(on_logout: impl Fn() + 'static) {
+    let channel = match BroadcastChannel::new(SESSION_CHANNEL) {
+        Ok(channel) => channel,
+        Err(e) => {
+            log::error!("error opening the session channel: {:?}", e);
+            return;
+        }
+    };
+    let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
+        let Some(text) = e.data().as_string() else {
+            return;
+        };
+        match serde_json::from_str::<LogoutMessage>(text.as_str()) {
+            Ok(message) if TAB_ID.with(|id| *id != message.tab_id) => on_logout(),
+            Ok(_) => {}
+            Err(e) => log::error!("error parsing the session channel: {:?}", e),
+        }
+    });
+    channel.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    on_message.forget();
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use serde_json::json;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::http_wrapper::Backend;
+    use crate::utilities::mock_backend::{settle, url, MockBackend, REFRESH_TOKEN};
+
+    #[wasm_bindgen_test]
+    async fn logout_revokes_the_refresh_token_and_drops_the_cookie() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        assert!(logout().await.unwrap());
+        settle().await;
+        let requests = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/auth/logout"));
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].get_body(), json!({"refresh_token": REFRESH_TOKEN, "mode": "json"}));
+        assert!(get_session_cookie(COOKIE_NAME).is_none());
+    }
+
+    #[wasm_bindgen_test]
+    async fn logout_drops_the_cookie_when_the_revoke_fails() {
+        let backend = MockBackend::start().await;
+        backend.fail_all(503, "");
+        assert!(!logout().await.unwrap());
+        settle().await;
+        assert_eq!(backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/auth/logout")).len(), 1);
+        assert!(get_session_cookie(COOKIE_NAME).is_none());
+    }
+
+    #[wasm_bindgen_test]
+    async fn logout_without_a_session_revokes_nothing() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        backend.sign_out().await;
+        assert!(!logout().await.unwrap());
+        assert!(backend.requests().is_empty());
+    }
+}
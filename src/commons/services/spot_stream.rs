@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use leptos::wasm_bindgen::{closure::Closure, JsCast};
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use web_sys::{MessageEvent, WebSocket};
+
+use crate::utilities::configuration::get_config;
+use crate::utilities::option_pricer::relative_difference;
+
+/// Move of the spot, in percent, after which the greeks are recomputed.
+pub const DEFAULT_SPOT_REFRESH_THRESHOLD: f64 = 0.5;
+/// Age of the last tick, in seconds, after which the spot is shown as stale.
+pub const SPOT_STALE_AFTER_SECONDS: i64 = 15;
+/// Delay before reconnecting a closed feed.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// This struct holds the last tick of a product.
+/// `last_updated` is the local time of the tick in milliseconds.
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SpotPrice {
+    pub price: f64,
+    pub last_updated: i64,
+}
+
+/// Message of the ticker channel, only the fields used by the admin portal.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TickerMessage {
+    #[serde(rename = "type")]
+    message_type: String,
+    #[serde(default)]
+    product_id: String,
+    #[serde(default)]
+    price: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SubscribeMessage {
+    #[serde(rename = "type")]
+    message_type: String,
+    product_ids: Vec<String>,
+    channels: Vec<String>,
+}
+
+impl SubscribeMessage {
+    fn new(product_ids: Vec<String>) -> Self {
+        Self {
+            message_type: String::from("subscribe"),
+            product_ids,
+            channels: vec![String::from("ticker")],
+        }
+    }
+}
+
+/// Returns the product id of the feed for a currency pair, e.g. `BTC/USD` is `BTC-USD`.
+pub fn get_product_id(pair: &str) -> String {
+    pair.replace('/', "-").to_uppercase()
+}
+
+/// Shared spot price service, provided as context by the `App`.
+/// The ticker feed is opened on the first subscription and reconnects when it is closed.
+/// Without a `spot_stream_url` in the configuration the pages keep the spot of the REST call.
+
+#[derive(Copy, Clone)]
+pub struct SpotPrices {
+    prices: RwSignal<HashMap<String, SpotPrice>>,
+    product_ids: StoredValue<Vec<String>>,
+    socket: StoredValue<Option<WebSocket>>,
+}
+
+impl SpotPrices {
+    pub fn new() -> Self {
+        Self {
+            prices: RwSignal::new(HashMap::new()),
+            product_ids: store_value(Vec::new()),
+            socket: store_value(None),
+        }
+    }
+
+    /// Subscribes to the ticker of a currency pair, opening the feed if needed.
+    pub fn subscribe(&self, pair: &str) {
+        let product_id = get_product_id(pair);
+        if product_id.is_empty() || self.product_ids.with_value(|p| p.contains(&product_id)) {
+            return;
+        }
+        self.product_ids.update_value(|p| p.push(product_id.clone()));
+        match self.socket.get_value() {
+            Some(socket) if socket.ready_state() == WebSocket::OPEN => {
+                send_subscribe(&socket, vec![product_id]);
+            }
+            Some(_) => {
+                // Still connecting, the products are subscribed when the feed opens.
+            }
+            None => self.connect(),
+        }
+    }
+
+    /// Returns the live spot of a currency pair, or zero before the first tick.
+    /// This is reactive, use it inside a `Signal::derive`.
+    pub fn get_spot(&self, pair: &str) -> f64 {
+        let product_id = get_product_id(pair);
+        self.prices
+            .with(|p| p.get(&product_id).map(|s| s.price))
+            .unwrap_or_default()
+    }
+
+    /// Returns the local time of the last tick of a currency pair in milliseconds.
+    pub fn get_last_updated(&self, pair: &str) -> Option<i64> {
+        let product_id = get_product_id(pair);
+        self.prices.with(|p| p.get(&product_id).map(|s| s.last_updated))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !get_config().spot_stream_url.is_empty()
+    }
+
+    fn connect(&self) {
+        let url = get_config().spot_stream_url;
+        if url.is_empty() {
+            log::info!("spot stream is not configured, using the REST spot");
+            return;
+        }
+        let socket = match WebSocket::new(url.as_str()) {
+            Ok(socket) => socket,
+            Err(e) => {
+                log::error!("error opening the spot stream: {:?}", e);
+                self.reconnect();
+                return;
+            }
+        };
+
+        let this = *self;
+        let on_open = Closure::<dyn FnMut()>::new(move || {
+            if let Some(socket) = this.socket.get_value() {
+                send_subscribe(&socket, this.product_ids.get_value());
+            }
+        });
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        on_open.forget();
+
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
+            let Some(text) = e.data().as_string() else {
+                return;
+            };
+            match serde_json::from_str::<TickerMessage>(text.as_str()) {
+                Ok(message) if message.message_type == "ticker" => {
+                    if let Ok(price) = message.price.parse::<f64>() {
+                        this.prices.update(|p| {
+                            p.insert(
+                                message.product_id,
+                                SpotPrice {
+                                    price,
+                                    last_updated: chrono::Utc::now().timestamp_millis(),
+                                },
+                            );
+                        });
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => log::error!("error parsing the spot stream: {:?}", e),
+            }
+        });
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        on_message.forget();
+
+        let on_close = Closure::<dyn FnMut()>::new(move || {
+            log::info!("spot stream closed, reconnecting");
+            this.socket.set_value(None);
+            this.reconnect();
+        });
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+        on_close.forget();
+
+        self.socket.set_value(Some(socket));
+    }
+
+    fn reconnect(&self) {
+        let this = *self;
+        set_timeout(
+            move || {
+                if this.socket.get_value().is_none() {
+                    this.connect();
+                }
+            },
+            RECONNECT_DELAY,
+        );
+    }
+}
+
+fn send_subscribe(socket: &WebSocket, product_ids: Vec<String>) {
+    if product_ids.is_empty() {
+        return;
+    }
+    match serde_json::to_string(&SubscribeMessage::new(product_ids)) {
+        Ok(message) => {
+            if let Err(e) = socket.send_with_str(message.as_str()) {
+                log::error!("error subscribing to the spot stream: {:?}", e);
+            }
+        }
+        Err(e) => log::error!("error subscribing to the spot stream: {:?}", e),
+    }
+}
+
+/// Returns the spot used to price the positions of a currency pair.
+/// It follows the live spot only when it moves beyond the refresh threshold, so the greeks are not
+/// recomputed on every tick, and falls back to the REST spot until the first tick.
+pub fn create_pricing_spot(pair: Signal<String>, fallback: Signal<f64>) -> Signal<f64> {
+    let spot_prices = expect_context::<SpotPrices>();
+    let threshold = get_config()
+        .spot_refresh_threshold
+        .unwrap_or(DEFAULT_SPOT_REFRESH_THRESHOLD);
+    let pricing_spot = RwSignal::new(fallback.get_untracked());
+
+    create_effect(move |_| spot_prices.subscribe(pair.get().as_str()));
+    create_effect(move |_| {
+        let live_spot = spot_prices.get_spot(pair.get().as_str());
+        let next_spot = if live_spot > 0.0 { live_spot } else { fallback.get() };
+        let current_spot = pricing_spot.get_untracked();
+        if current_spot <= 0.0 || relative_difference(next_spot, current_spot) * 100.0 >= threshold {
+            pricing_spot.set(next_spot);
+        }
+    });
+    pricing_spot.into()
+}
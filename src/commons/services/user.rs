@@ -0,0 +1,136 @@
+use leptos::*;
+
+use crate::commons::models::defaults::{BlankRequest, QueryBuilder};
+use crate::commons::models::user::{CollectionPermissionsResponse, CurrentUser, CurrentUserResponse, CurrentUserTfa, CurrentUserTfaResponse, Permission, UserPermissions};
+use crate::utilities::configuration::get_config;
+use crate::utilities::http_wrapper::{call_and_parse, ApiClient, Backend, HttpMethod};
+
+/// Server function to fetch the role, policies and permissions of the user of an access token.
+/// This is called right after the login, before the session cookie is set, so the token is passed in.
+
+pub async fn get_user_permissions(access_token: String) -> Result<UserPermissions, ServerFnError> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(
+        "Authorization",
+        reqwest::header::HeaderValue::from_str(format!("Bearer {}", access_token).as_str())
+            .map_err(|e| ServerFnError::new(e.to_string()))?,
+    );
+    let directus_url = get_config().directus_url;
+
+    let user_path = format!(
+        "{}/users/me?{}",
+        directus_url,
+        QueryBuilder::new().fields(CurrentUser::get_query()).to_query_string()
+    );
+    let user = call_and_parse::<BlankRequest, CurrentUserResponse>(
+        Option::None,
+        user_path,
+        headers.clone(),
+        HttpMethod::GET,
+    )
+    .await
+    .map_err(|e| {
+        log::error!("error fetching the current user: {:?}", e);
+        ServerFnError::from(e)
+    })?;
+
+    let permissions_path = format!("{}/permissions/me", directus_url);
+    let permissions = call_and_parse::<BlankRequest, CollectionPermissionsResponse>(
+        Option::None,
+        permissions_path,
+        headers,
+        HttpMethod::GET,
+    )
+    .await
+    .map_err(|e| {
+        log::error!("error fetching the permissions: {:?}", e);
+        ServerFnError::from(e)
+    })?;
+
+    Ok(UserPermissions {
+        role: user.data.role.clone().map(|r| r.name).unwrap_or_default(),
+        admin_access: user.data.has_admin_access(),
+        permissions: Permission::ALL
+            .into_iter()
+            .filter(|p| {
+                let (collection, action, field) = p.get_directus_grant();
+                permissions.is_granted(collection, action, field)
+            })
+            .collect(),
+    })
+}
+
+/// Server function to fetch whether the logged in user is enrolled in two-factor authentication.
+
+pub async fn get_tfa_status() -> Result<bool, ServerFnError> {
+    let path = format!(
+        "/users/me?{}",
+        QueryBuilder::new().fields(CurrentUserTfa::get_query()).to_query_string()
+    );
+    let response = ApiClient::new(Backend::Directus)
+        .call_and_parse::<BlankRequest, CurrentUserTfaResponse>(Option::None, path, HttpMethod::GET)
+        .await;
+    match response {
+        Ok(res) => Ok(res.data.is_enrolled()),
+        Err(e) => {
+            log::error!("error fetching the two-factor authentication status: {:?}", e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::mock_backend::{
+        assert_api_errors, assert_error_paths, fixture, url, MockBackend, REFRESHED_ACCESS_TOKEN,
+    };
+
+    #[wasm_bindgen_test]
+    async fn get_user_permissions_maps_the_directus_grants() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let permissions = get_user_permissions(String::from(REFRESHED_ACCESS_TOKEN)).await.unwrap();
+        assert_eq!(permissions.role, "Administrator");
+        assert!(permissions.admin_access);
+        // Only the trades can be updated in `fixtures/permissions_me.json`.
+        assert_eq!(permissions.permissions, vec![Permission::EditTrades]);
+        let requests = backend.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests
+            .iter()
+            .all(|r| r.authorization == format!("Bearer {}", REFRESHED_ACCESS_TOKEN)));
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_user_permissions_returns_the_errors_of_the_backend() {
+        assert_api_errors(|| get_user_permissions(String::from(REFRESHED_ACCESS_TOKEN))).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_tfa_status_is_true_for_an_enrolled_user() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures().respond(
+            HttpMethod::GET,
+            url(Backend::Directus, "/users/me"),
+            200,
+            fixture!("users_me_tfa.json"),
+        );
+        assert!(get_tfa_status().await.unwrap());
+        assert_eq!(backend.requests()[0].get_param("fields"), CurrentUserTfa::get_query());
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_tfa_status_is_false_without_a_secret() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        assert!(!get_tfa_status().await.unwrap());
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_tfa_status_returns_the_errors_of_the_backend() {
+        assert_error_paths(get_tfa_status).await;
+    }
+}
@@ -0,0 +1,52 @@
+use leptos::*;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::js_sys::{Function, Reflect};
+
+/// Copies the text to the clipboard of the browser.
+/// `navigator.clipboard` is looked up at runtime, since its web-sys bindings are still unstable.
+pub fn copy_to_clipboard(text: &str) -> bool {
+    let clipboard = Reflect::get(&window(), &JsValue::from_str("navigator"))
+        .and_then(|navigator| Reflect::get(&navigator, &JsValue::from_str("clipboard")))
+        .ok()
+        .filter(|c| !c.is_undefined());
+    let Some(clipboard) = clipboard else {
+        log::error!("the clipboard is not available");
+        return false;
+    };
+    match Reflect::get(&clipboard, &JsValue::from_str("writeText")).map(|f| f.dyn_into::<Function>()) {
+        Ok(Ok(write_text)) => write_text.call1(&clipboard, &JsValue::from_str(text)).is_ok(),
+        _ => false,
+    }
+}
+
+/// Component for a text with a button that copies it to the clipboard.
+/// The text is shortened to its first and last characters when `shorten` is set, the full text is in the tooltip.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn CopyButton(text: String, #[prop(optional)] shorten: bool) -> impl IntoView {
+    let copied = RwSignal::new(false);
+    let shown = if shorten && text.chars().count() > 14 {
+        let chars = text.chars().collect::<Vec<char>>();
+        format!(
+            "{}...{}",
+            chars[..6].iter().collect::<String>(),
+            chars[chars.len() - 6..].iter().collect::<String>()
+        )
+    } else {
+        text.clone()
+    };
+    let title = text.clone();
+    view! {
+        <div class="flex items-center gap-1">
+            <span class="font-mono" title=title>{shown}</span>
+            <button
+                class="btn btn-xs btn-ghost"
+                title="Copy"
+                on:click=move |_| copied.set(copy_to_clipboard(&text))
+            >
+                {move || if copied.get() { "COPIED" } else { "COPY" }}
+            </button>
+        </div>
+    }
+}
@@ -0,0 +1,250 @@
+use leptos::*;
+use serde_json::Value;
+
+use crate::commons::models::defaults::PageRequest;
+use crate::components::{
+    arrow_down::ArrowDown,
+    arrow_up::ArrowUp,
+    data_table_local::{TableCell, TablePagination, TableRowMobile},
+    download_anchor::DownloadCsvAnchor,
+};
+
+/// Sibling of the `DataTable` for the tables paginated by Directus.
+/// `data` is only the current page, the page, sort and search are written to `page_request`
+/// which the parent uses to fetch the page, and `total_entries` comes from the `filter_count` of the response.
+/// `sort_fields` holds the Directus field of each column, an empty field disables the sort of the column.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn ServerDataTable(
+    headers: RwSignal<Vec<String>>,
+    keys: RwSignal<Vec<String>>,
+    sort_fields: RwSignal<Vec<String>>,
+    data: Signal<Vec<Value>>,
+    total_entries: Signal<usize>,
+    page_request: RwSignal<PageRequest>,
+    #[prop(optional)] key_to_display_in_mobile: RwSignal<String>,
+    #[prop(optional)] color: RwSignal<Vec<Value>>,
+    #[prop(optional)] currency: RwSignal<Vec<Value>>,
+    #[prop(optional)] hasdownload: bool,
+    #[prop(optional)] hasfilter: bool,
+    nodatamessage: RwSignal<String>,
+    #[prop(optional)] file_content: RwSignal<String>,
+    #[prop(optional)] file_name: RwSignal<String>,
+    #[prop(optional)] haspageslenght: bool,
+) -> impl IntoView {
+    let pages_entries = RwSignal::new(vec![5, 10, 15, 20, 25, 50, 100]);
+    let header_length = headers.get_untracked().len();
+    let filter_text = RwSignal::new(page_request.get_untracked().search);
+
+    // The pagination component works on its own signals, keep them in sync with the page request.
+    let selected_page = RwSignal::new(page_request.get_untracked().page);
+    let entries_start = RwSignal::new(0);
+    let entries_end = RwSignal::new(0);
+    let total_entries_signal = RwSignal::new(0);
+
+    create_effect(move |_| {
+        let page = selected_page.get();
+        if page_request.get_untracked().page != page {
+            page_request.update(|r| r.page = page);
+        }
+    });
+    create_effect(move |_| {
+        let request = page_request.get();
+        if selected_page.get_untracked() != request.page {
+            selected_page.set(request.page);
+        }
+        let start = request.get_offset();
+        entries_start.set(start);
+        entries_end.set(start + data.get().len());
+        total_entries_signal.set(total_entries.get());
+    });
+
+    let page_count = Signal::derive(move || {
+        let page_size = page_request.get().page_size.max(1);
+        (total_entries.get() + page_size - 1) / page_size
+    });
+
+    let on_sort = move |field: String| {
+        if field.is_empty() {
+            return;
+        }
+        page_request.update(|r| {
+            r.sort = if r.sort == field {
+                format!("-{}", field)
+            } else {
+                field
+            };
+            r.page = 1;
+        });
+    };
+
+    view! {
+        <Show
+            when = move || { total_entries.get() > 0 || !page_request.get().search.is_empty() }
+            fallback = move || view! {
+                <div class = "p-5">
+                    <span class = "opacity-50 font-extralight">{nodatamessage}</span>
+                </div>
+            } >
+            <div class = "p-4 overflow-auto">
+                <div class="flex justify-between w-full">
+                    <div class="flex items-center justify-start gap-4 join">
+                    {
+                        if haspageslenght {
+                            view! {
+                                <select class = "block w-full mr-1 text-xs border-gray-800 rounded shadow-md select-sm hover:shadow-sm hover:shadow-success bg-base-100" name="row_slice"
+                                    on:change = move |e| {
+                                        let val = event_target_value(&e).parse::<usize>().unwrap_or(25);
+                                        page_request.update(|r| {
+                                            r.page_size = val;
+                                            r.page = 1;
+                                        });
+                                    }
+                                >
+                                {
+                                    move || {
+                                        pages_entries.get().into_iter().map(|cp| {
+                                            view! {
+                                                <option prop:selected = page_request.get().page_size == cp value = cp.to_string()>{cp}</option>
+                                            }
+                                        }).collect_view()
+                                    }
+                                }
+                                </select>
+                            }.into_view()
+                        } else {
+                            view! {}.into_view()
+                        }
+                    }
+
+                    {
+                        if hasdownload {
+                            // The table stays mounted between pages, re-render the anchor with the current page.
+                            (move || view! {
+                                <DownloadCsvAnchor content = file_content.get() file_name = file_name.get()/>
+                            }).into_view()
+                        } else {
+                            view! {}.into_view()
+                        }
+                    }
+                    </div>
+                    <div class="flex justify-end join">
+                    {
+                        if hasfilter {
+                            view! {
+                                <div style="margin-bottom: 0.375rem;">
+                                    <span class="mr-2 label-text">Search: </span>
+                                    <input
+                                        type="text"
+                                        class="input input-sm input-info focus:outline-none focus:shadow-outline "
+                                        placeholder="Press enter to search"
+                                        prop:value=move || filter_text.get()
+                                        on:input=move |event| filter_text.set(event_target_value(&event))
+                                        on:change=move |_| {
+                                            let search = filter_text.get_untracked().trim().to_string();
+                                            page_request.update(|r| {
+                                                r.search = search;
+                                                r.page = 1;
+                                            });
+                                        }
+                                    />
+                                </div>
+                            }.into_view()
+                        } else {
+                            view! { <div style="margin-bottom: 0.375rem;"></div> }.into_view()
+                        }
+                    }
+                    </div>
+                </div>
+                <div>
+                <table class = "table table-xs table-zebra-zebra">
+                <thead>
+                    <tr class = "hidden text-sm uppercase text-success px924:contents">
+                    {
+                        headers.get().into_iter().enumerate().map(|(index, key_name)| {
+                            let field = sort_fields.get_untracked().get(index).cloned().unwrap_or_default();
+                            let is_sortable = !field.is_empty();
+                            let field_clone = field.clone();
+                            view! {
+                                <th class = {if is_sortable {"text-white bg-opacity-50 cursor-pointer bg-success"} else {"text-white bg-opacity-50 bg-success"}} on:click = move |_| on_sort(field_clone.clone())>
+                                <div class = "flex justify-between">
+                                    <span class = "flex-0">{key_name}</span>
+                                    <span class = "flex-0">
+                                        <Show when = move || is_sortable>
+                                            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 20 20" fill="currentColor" class="w-5 h-5">
+                                            <path fill-rule="evenodd" d="M10 3a.75.75 0 01.55.24l3.25 3.5a.75.75 0 11-1.1 1.02L10 4.852 7.3 7.76a.75.75 0 01-1.1-1.02l3.25-3.5A.75.75 0 0110 3zm-3.76 9.2a.75.75 0 011.06.04l2.7 2.908 2.7-2.908a.75.75 0 111.1 1.02l-3.25 3.5a.75.75 0 01-1.1 0l-3.25-3.5a.75.75 0 01.04-1.06z" clip-rule="evenodd" />
+                                            </svg>
+                                        </Show>
+                                    </span>
+                                </div>
+                            </th>}
+                        }).collect_view()
+                    }
+                    </tr>
+                </thead>
+                <tbody>
+                {
+                    move || {
+                        if data.get().is_empty() {
+                            return view! {
+                                <tr>
+                                    <td colspan = format!("{}", header_length)>
+                                        <span class = "opacity-50 font-extralight">{nodatamessage}</span>
+                                    </td>
+                                </tr>
+                            }.into_view();
+                        }
+                        data.get().into_iter().map(|d| {
+                            let hide_per_cell = RwSignal::new(true);
+                            let key_to_display = key_to_display_in_mobile.get();
+
+                            let header_display = d.get(&key_to_display)
+                            .map(|value| value.to_string())
+                            .unwrap_or_else(|| "Click to show more...".to_string()).replace('"', "");
+
+                            view! {
+                                <tr class="px924:hidden">
+                                    <td colspan = format!("{}", header_length)>
+                                        <button class = "flex justify-start w-full gap-2 border-l-2 rounded-none btn btn-ghost btn-md bg-base-100 border-l-success" on:click = move |_| hide_per_cell.update(|c| *c = !*c) >
+                                            <div class = "text-xs">
+                                                {&header_display}
+                                            </div>
+                                            <div class = "flex justify-end flex-1">
+                                                <Show when = move || hide_per_cell.get() fallback = move || view! {<ArrowDown />}>
+                                                    <ArrowUp />
+                                                </Show>
+                                            </div>
+                                        </button>
+                                    </td>
+                                </tr>
+
+                                // MOBILE VIEW
+
+                                <TableRowMobile _val = d.clone() dksource = keys.get() keys = color.get() currency = currency.get() mobile_view = true hide_per_cell headers />
+
+                                // DESKTOP VIEW
+
+                                <tr class = "uppercase hover:opacity-50">
+                                    <div class="hidden px924:contents">
+                                        <TableCell _val = d dksource = keys.get() keys = color.get() currency = currency.get() />
+                                    </div>
+                                </tr>
+                            }
+                        }).collect_view()
+                    }
+                }
+                </tbody>
+                <tfoot>
+                    <tr>
+                        <td colspan = {headers.get().len()}>
+                            <TablePagination size = page_count selected_page = selected_page entries_start = entries_start entries_end = entries_end total_entries = total_entries_signal />
+                        </td>
+                    </tr>
+                </tfoot>
+                </table>
+                </div>
+            </div>
+        </Show>
+    }
+}
@@ -1,4 +1,36 @@
 use leptos::*;
+use wasm_bindgen::JsValue;
+use web_sys::{
+    js_sys::{Array, Uint8Array},
+    Blob, BlobPropertyBag,
+};
+
+/// Returns an object URL of a file with the content, to download or open it in a new tab.
+pub fn create_file_url(content: &str, mime_type: &str) -> String {
+    let uint8arr = Uint8Array::new(&unsafe { Uint8Array::view(content.as_bytes()) }.into());
+    let array = Array::new();
+    array.push(&uint8arr.buffer());
+    let file = Blob::new_with_u8_array_sequence_and_options(
+        &JsValue::from(array),
+        BlobPropertyBag::new().type_(mime_type),
+    )
+    .unwrap();
+    web_sys::Url::create_object_url_with_blob(&file).unwrap()
+}
+
+/// Downloads a file with the content under the file name.
+pub fn download_file(content: &str, file_name: &str, mime_type: &str) {
+    let doc = leptos_dom::document();
+    let hyperlink = wasm_bindgen::JsCast::dyn_into::<web_sys::HtmlAnchorElement>(
+        doc.create_element("a").unwrap(),
+    )
+    .unwrap();
+    hyperlink.set_download(file_name);
+    hyperlink.set_href(&create_file_url(content, mime_type));
+    hyperlink.click();
+    hyperlink.remove();
+}
+
 #[allow(non_snake_case)]
 #[component]
 pub fn DownloadCsvAnchor(
@@ -6,11 +38,6 @@ pub fn DownloadCsvAnchor(
     file_name: String,
     #[prop(optional)] button_name: String,
 ) -> impl IntoView {
-    use wasm_bindgen::JsValue;
-    use web_sys::{
-        js_sys::{Array, Uint8Array},
-        Blob, BlobPropertyBag,
-    };
     let new_file_name = move || {
         let utc = chrono::Utc::now();
         let utc_local = utc.with_timezone(&chrono::Local);
@@ -21,26 +48,7 @@ pub fn DownloadCsvAnchor(
         true => button_name,
         false => String::from("Download"),
     };
-    let download = move || {
-        let uint8arr = Uint8Array::new(&unsafe { Uint8Array::view(&content.as_bytes()) }.into());
-        let array = Array::new();
-        array.push(&uint8arr.buffer());
-        let file = Blob::new_with_u8_array_sequence_and_options(
-            &JsValue::from(array),
-            BlobPropertyBag::new().type_("text/csv"),
-        )
-        .unwrap();
-        let doc = leptos_dom::document();
-        let hyperlink = wasm_bindgen::JsCast::dyn_into::<web_sys::HtmlAnchorElement>(
-            doc.create_element("a").unwrap(),
-        )
-        .unwrap();
-        hyperlink.set_download(new_file_name().as_str());
-        let url = web_sys::Url::create_object_url_with_blob(&file).unwrap();
-        hyperlink.set_href(&url);
-        hyperlink.click();
-        hyperlink.remove();
-    };
+    let download = move || download_file(&content, &new_file_name(), "text/csv");
     view! {
         <div>
             <button
@@ -3,12 +3,14 @@ pub mod arrow_up;
 pub mod confirm_all_quotes_modal;
 pub mod confirm_batch_quotes_modal;
 pub mod data_table_local;
+pub mod data_table_server;
 pub mod download_anchor;
 pub mod error_modal;
 pub mod icons;
 pub mod loading_button;
 pub mod menu_button;
 pub mod not_found;
+pub mod not_permitted;
 pub mod please_login;
 pub mod side_nav_menu;
 pub mod sidebar;
@@ -22,4 +24,8 @@ pub mod success_modal;
 pub mod confirm_modal;
 pub mod success_modal_redirect;
 pub mod data_table_header;
-pub mod select;
\ No newline at end of file
+pub mod select;
+pub mod spot_price_age;
+pub mod payoff_chart;
+pub mod tfa_status;
+pub mod copy_button;
@@ -0,0 +1,21 @@
+use leptos::*;
+use leptos_router::Form;
+
+/// Shown instead of a page the role of the user is not allowed to use.
+#[allow(non_snake_case)]
+#[component]
+pub fn NotPermitted() -> impl IntoView {
+    view! {
+        <div class="main-content hero bg-base-200 rounded-lg">
+            <div class="hero-content text-center">
+                <div class="max-w-md">
+                    <h1 class="text-4xl font-semibold font-urbanis">NOT PERMITTED</h1>
+                    <p class="py-6 font-light font-urbanist">Your role is not allowed to use this page. Please contact an administrator.</p>
+                    <Form action="/" method="get">
+                        <button class="btn btn-sm btn-success">Back to Home</button>
+                    </Form>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,148 @@
+use leptos::*;
+
+use crate::utilities::{number_util::{format_currency, format_number_en}, option_pricer::PayoffLeg};
+
+const CHART_WIDTH: f64 = 600.0;
+const CHART_HEIGHT: f64 = 240.0;
+const CHART_PADDING: f64 = 24.0;
+/// Number of spots sampled between the bounds of the chart.
+const CHART_SAMPLES: usize = 120;
+/// Spot range drawn around the strikes and the current spot, as a fraction of them.
+const CHART_SPOT_RANGE: f64 = 0.5;
+
+/// This struct holds the sampled curves of a `PayoffChart`.
+
+#[derive(Debug, Clone, PartialEq, Default)]
+struct PayoffCurves {
+    spots: Vec<f64>,
+    at_expiry: Vec<f64>,
+    today: Vec<f64>,
+    min_spot: f64,
+    max_spot: f64,
+    min_pnl: f64,
+    max_pnl: f64,
+}
+
+impl PayoffCurves {
+    fn new(legs: &[PayoffLeg], spot: f64) -> Option<Self> {
+        let strikes = legs.iter().map(|l| l.strike).filter(|s| *s > 0.0);
+        let (low, high) = strikes
+            .chain((spot > 0.0).then_some(spot))
+            .fold((f64::MAX, f64::MIN), |(low, high), s| (low.min(s), high.max(s)));
+        if legs.is_empty() || low > high {
+            return None;
+        }
+        let min_spot = low * (1.0 - CHART_SPOT_RANGE);
+        let max_spot = high * (1.0 + CHART_SPOT_RANGE);
+        let spots = (0..=CHART_SAMPLES)
+            .map(|i| min_spot + (max_spot - min_spot) * i as f64 / CHART_SAMPLES as f64)
+            .collect::<Vec<f64>>();
+        let at_expiry = spots
+            .iter()
+            .map(|s| legs.iter().map(|l| l.get_payoff_at_expiry(*s)).sum())
+            .collect::<Vec<f64>>();
+        let today = spots
+            .iter()
+            .map(|s| legs.iter().map(|l| l.get_mark_to_model(*s)).sum())
+            .collect::<Vec<f64>>();
+        // The zero line is always shown so a fully positive or negative structure still reads correctly.
+        let (min_pnl, max_pnl) = at_expiry
+            .iter()
+            .chain(today.iter())
+            .fold((0.0_f64, 0.0_f64), |(low, high), p| (low.min(*p), high.max(*p)));
+        Some(Self {
+            spots,
+            at_expiry,
+            today,
+            min_spot,
+            max_spot,
+            min_pnl,
+            max_pnl: if max_pnl > min_pnl { max_pnl } else { min_pnl + 1.0 },
+        })
+    }
+
+    fn get_x(&self, spot: f64) -> f64 {
+        CHART_PADDING + (spot - self.min_spot) / (self.max_spot - self.min_spot) * (CHART_WIDTH - 2.0 * CHART_PADDING)
+    }
+
+    fn get_y(&self, pnl: f64) -> f64 {
+        CHART_HEIGHT - CHART_PADDING - (pnl - self.min_pnl) / (self.max_pnl - self.min_pnl) * (CHART_HEIGHT - 2.0 * CHART_PADDING)
+    }
+
+    fn get_points(&self, pnls: &[f64]) -> String {
+        self.spots
+            .iter()
+            .zip(pnls.iter())
+            .map(|(s, p)| format!("{:.2},{:.2}", self.get_x(*s), self.get_y(*p)))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+/// Component for the payoff chart of an option structure.
+/// Draws the pnl at expiry and today's mark-to-model pnl of the legs against the spot.
+/// The pnl is in the quote currency of the pair.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn PayoffChart(
+    /// The legs of the structure.
+    #[prop(into)]
+    legs: Signal<Vec<PayoffLeg>>,
+    /// The current spot, marked on the chart.
+    #[prop(into)]
+    spot: Signal<f64>,
+    /// The quote currency of the pair, shown next to the pnl.
+    #[prop(optional, into)]
+    currency: String,
+) -> impl IntoView {
+    let curves = Signal::derive(move || PayoffCurves::new(&legs.get(), spot.get()));
+
+    view! {
+        {
+            move || match curves.get() {
+                Some(c) => {
+                    let zero_y = c.get_y(0.0);
+                    let spot_x = c.get_x(spot.get());
+                    let strikes = legs.get().into_iter().map(|l| l.strike).filter(|s| *s > 0.0).collect::<Vec<f64>>();
+                    view! {
+                        <div class = "w-full p-2 rounded bg-base-100">
+                            <div class = "flex justify-between text-xs">
+                                <div class = "flex gap-4">
+                                    <span class = "text-success">"— At Expiry"</span>
+                                    <span class = "text-warning">"- - Today"</span>
+                                </div>
+                                <span class = "opacity-50 font-extralight">{format!("PnL {}", currency)}</span>
+                            </div>
+                            <svg class = "w-full h-auto" viewBox = format!("0 0 {} {}", CHART_WIDTH, CHART_HEIGHT) preserveAspectRatio = "none">
+                                <line x1 = CHART_PADDING y1 = zero_y x2 = CHART_WIDTH - CHART_PADDING y2 = zero_y class = "stroke-current opacity-30" stroke-width = "1" />
+                                {
+                                    strikes.into_iter().map(|s| {
+                                        let x = c.get_x(s);
+                                        view! {
+                                            <line x1 = x y1 = CHART_PADDING x2 = x y2 = CHART_HEIGHT - CHART_PADDING class = "stroke-current opacity-20" stroke-width = "1" stroke-dasharray = "2 4" />
+                                        }
+                                    }).collect_view()
+                                }
+                                {
+                                    (spot.get() > 0.0).then(|| view! {
+                                        <line x1 = spot_x y1 = CHART_PADDING x2 = spot_x y2 = CHART_HEIGHT - CHART_PADDING class = "stroke-info" stroke-width = "1" stroke-dasharray = "4 4" />
+                                    })
+                                }
+                                <polyline points = c.get_points(&c.at_expiry) fill = "none" class = "stroke-success" stroke-width = "2" />
+                                <polyline points = c.get_points(&c.today) fill = "none" class = "stroke-warning" stroke-width = "2" stroke-dasharray = "6 4" />
+                                <text x = CHART_PADDING y = CHART_PADDING - 8.0 class = "text-xs fill-current opacity-50">{format_currency(c.max_pnl, 2)}</text>
+                                <text x = CHART_PADDING y = CHART_HEIGHT - 8.0 class = "text-xs fill-current opacity-50">{format_number_en(c.min_spot.to_string(), 2)}</text>
+                                <text x = CHART_WIDTH - CHART_PADDING y = CHART_HEIGHT - 8.0 text-anchor = "end" class = "text-xs fill-current opacity-50">{format_number_en(c.max_spot.to_string(), 2)}</text>
+                                <text x = CHART_PADDING y = CHART_HEIGHT - CHART_PADDING - 4.0 class = "text-xs fill-current opacity-50">{format_currency(c.min_pnl, 2)}</text>
+                            </svg>
+                        </div>
+                    }.into_view()
+                }
+                None => view! {
+                    <div class = "p-2 text-xs opacity-50 font-extralight">No option legs to chart.</div>
+                }.into_view(),
+            }
+        }
+    }
+}
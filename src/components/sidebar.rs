@@ -1,12 +1,14 @@
 use crate::{
+    commons::{models::user::UserPermissions, services::session::logout},
     components::{
         icons::Icon,
         side_nav_menu::{SideNavigationMainMenu, SideNavigationSubMenu},
+        tfa_status::TfaStatus,
     },
-    utilities::cookies::{set_jabra_cookie, JabraCookie},
+    Permissions, Refetcher,
 };
 use leptos::*;
-use leptos_router::use_location;
+use leptos_router::{use_location, use_navigate};
 
 const UNSELECTED_CLASS: &str = "font-normal justify-start capitalize w-full rounded flex flex-row gap-2 items-center px-2 py-1 hover:bg-success hover:bg-opacity-15";
 const UNSELECTED_CLASS_MOBILE: &str = "flex justify-center rounded px-2 py-1 hover:bg-base-100";
@@ -15,19 +17,19 @@ const UNSELECTED_CLASS_MOBILE: &str = "flex justify-center rounded px-2 py-1 hov
 #[component]
 pub fn Sidebar() -> impl IntoView {
     let location = use_location().pathname;
-
-    let logout_action = create_action(move |_input: &()| async move {
-        let result = logout().await;
-
-        match result {
-            Ok(res) => {
-                if res {
-                    true
-                } else {
-                    false
-                }
-            }
-            Err(_e) => false,
+    let refetcher = expect_context::<Refetcher>().0;
+    let permissions = expect_context::<Permissions>().0;
+    let navigate = use_navigate();
+
+    let logout_action = create_action(move |_input: &()| {
+        let navigate = navigate.clone();
+        async move {
+            let result = logout().await;
+            // The pages are unmounted when the session is checked again, which drops their resources.
+            permissions.set(UserPermissions::default());
+            refetcher.update(|v| *v = !*v);
+            navigate("/login", Default::default());
+            result.unwrap_or(false)
         }
     });
 
@@ -128,6 +130,20 @@ pub fn Sidebar() -> impl IntoView {
                             icon_title="COUNTERPARTIES".to_string()
                         />
 
+                        <SideNavigationSubMenu
+                            location=location.get()
+                            anchor_url="/counterparties/manage".to_string()
+                            title="Manage Counterparties".to_string()
+                            icon_title="COUNTERPARTIES".to_string()
+                        />
+
+                        <SideNavigationSubMenu
+                            location=location.get()
+                            anchor_url="/audit".to_string()
+                            title="Audit".to_string()
+                            icon_title="AUDIT".to_string()
+                        />
+
                         <div class="divider divider-ghost mt-0 mb-0"></div>
 
                         <Suspense>
@@ -139,10 +155,15 @@ pub fn Sidebar() -> impl IntoView {
                                     .and_then(|a| {
                                         if a.clone() == true {
                                             view! {
-                                                <form on:submit=move |_| {
+                                                <form on:submit=move |ev| {
+                                                    ev.prevent_default();
                                                     logout_action.dispatch(());
                                                 }>
 
+                                                    <div class="px-2 pb-1 hidden px924:block">
+                                                        <TfaStatus/>
+                                                    </div>
+
                                                     <div class="text-sm items-center hidden px924:block rounded">
                                                         <button class=UNSELECTED_CLASS type="submit">
                                                             <Icon
@@ -193,10 +214,3 @@ pub fn Sidebar() -> impl IntoView {
         }
     }
 }
-
-pub async fn logout() -> Result<bool, ServerFnError> {
-    let (_cookie, set_cookie) = leptos_use::use_cookie::<String, leptos_use::utils::FromToStringCodec>("admin_portal_csr");
-    set_cookie(Some("".to_string()));
-
-    Ok(true)
-}
@@ -0,0 +1,44 @@
+use leptos::*;
+use leptos_use::use_interval_fn;
+
+use crate::commons::services::spot_stream::{SpotPrices, SPOT_STALE_AFTER_SECONDS};
+
+/// Shows the age of the last tick of the live spot of a currency pair.
+/// The badge turns into a warning when no tick was received for `SPOT_STALE_AFTER_SECONDS`.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn SpotPriceAge(pair: Signal<String>) -> impl IntoView {
+    let spot_prices = expect_context::<SpotPrices>();
+    let now = RwSignal::new(chrono::Utc::now().timestamp_millis());
+    let _ = use_interval_fn(move || now.set(chrono::Utc::now().timestamp_millis()), 1000);
+
+    let age = move || {
+        spot_prices
+            .get_last_updated(pair.get().as_str())
+            .map(|last_updated| ((now.get() - last_updated) / 1000).max(0))
+    };
+
+    view! {
+        {
+            move || {
+                if !spot_prices.is_enabled() {
+                    return view! {
+                        <span class = "badge badge-sm badge-ghost opacity-70">"REST SPOT"</span>
+                    }.into_view();
+                }
+                match age() {
+                    Some(seconds) if seconds < SPOT_STALE_AFTER_SECONDS => view! {
+                        <span class = "badge badge-sm badge-success badge-outline">{format!("LIVE · {}s ago", seconds)}</span>
+                    }.into_view(),
+                    Some(seconds) => view! {
+                        <span class = "badge badge-sm badge-warning" title = "The spot feed has not updated recently, the greeks may be stale">{format!("STALE · {}s ago", seconds)}</span>
+                    }.into_view(),
+                    None => view! {
+                        <span class = "badge badge-sm badge-ghost opacity-70">"WAITING FOR FEED"</span>
+                    }.into_view(),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use leptos::*;
+
+use crate::commons::services::user::get_tfa_status;
+
+/// Shows whether the logged in user is enrolled in two-factor authentication.
+/// The enrolment itself is done on the Directus user page.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn TfaStatus() -> impl IntoView {
+    let tfa_resource = create_local_resource(|| (), move |_| async move { get_tfa_status().await });
+
+    view! {
+        <Transition fallback=|| ()>
+            {move || match tfa_resource.get() {
+                Some(Ok(true)) => view! {
+                    <span class="badge badge-sm badge-success badge-outline">"2FA ENABLED"</span>
+                }.into_view(),
+                Some(Ok(false)) => view! {
+                    <span
+                        class="badge badge-sm badge-warning"
+                        title="Enable two-factor authentication on your Directus user page"
+                    >
+                        "2FA NOT ENABLED"
+                    </span>
+                }.into_view(),
+                _ => ().into_view(),
+            }}
+        </Transition>
+    }
+}
@@ -1,3 +1,4 @@
+pub mod audit;
 pub mod counterparties;
 pub mod dashboard;
 pub mod login;
@@ -0,0 +1,208 @@
+use leptos::*;
+use serde_json::json;
+
+use crate::commons::models::audit::{AuditAction, AuditFilter, AuditLogResponse, ExtractedAuditLog};
+use crate::commons::models::defaults::PageRequest;
+use crate::commons::services::audit::{export_audit_logs, get_audit_logs};
+use crate::components::component_size::ComponentSize;
+use crate::components::component_type::ComponentType;
+use crate::components::data_table_server::ServerDataTable;
+use crate::components::default_none::DefaultNone;
+use crate::components::loading_spinners::Spinners;
+
+/// Component for the Audit page.
+/// Lists the state-changing actions of the admin portal, the logs are filtered and paginated by the server.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn AuditPage() -> impl IntoView {
+    let filter = RwSignal::new(AuditFilter::default());
+    let page_request = RwSignal::new(PageRequest::default());
+    let no_data_message = RwSignal::new(String::from("No Audit Logs Available"));
+
+    // A new filter starts from its first page.
+    create_effect(move |_| {
+        filter.track();
+        if page_request.get_untracked().page != 1 {
+            page_request.update(|r| r.page = 1);
+        }
+    });
+
+    let audit_logs_resource = create_local_resource(
+        move || (filter.get(), page_request.get()),
+        move |(filter, request)| get_audit_logs(filter, request),
+    );
+    // The export is not paginated, it follows the filter only.
+    let export_resource = create_local_resource(move || filter.get(), export_audit_logs);
+    let audit_logs = RwSignal::new(AuditLogResponse::default());
+    let has_error = RwSignal::new(false);
+
+    let json_value = Signal::derive(move || {
+        audit_logs
+            .get()
+            .extract()
+            .into_iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<serde_json::Value>, _>>()
+            .expect("Failed to serialize to JSON")
+    });
+    let total_entries = Signal::derive(move || audit_logs.get().get_filter_count());
+
+    let color_vec = RwSignal::new(vec![json!(
+        { "action" : [{ "key" : "Reject", "style" : "text-error" },
+        { "key" : "Approve", "style" : "text-success" }] }
+    )]);
+    let headersource = RwSignal::new(vec![
+        String::from("Timestamp"),
+        String::from("Actor"),
+        String::from("Action"),
+        String::from("Collection"),
+        String::from("Target Ids"),
+        String::from("Group Ids"),
+        String::from("Before"),
+        String::from("After"),
+    ]);
+    let datakeysource = RwSignal::new(vec![
+        String::from("timestamp"),
+        String::from("actor"),
+        String::from("action"),
+        String::from("collection"),
+        String::from("target_ids"),
+        String::from("group_ids"),
+        String::from("before"),
+        String::from("after"),
+    ]);
+    let sort_fields = RwSignal::new(
+        datakeysource
+            .get_untracked()
+            .iter()
+            .map(|k| ExtractedAuditLog::get_sort_field(k))
+            .collect::<Vec<String>>(),
+    );
+    let key_to_display_in_mobile = RwSignal::new(String::from("action"));
+    let download_file_name = RwSignal::new(String::from("AUDIT_LOG"));
+    let file_content = RwSignal::new(String::new());
+
+    create_effect(move |_| {
+        if let Some(Ok(logs)) = export_resource.get() {
+            file_content.set(logs.extract_csv());
+        }
+    });
+
+    view! {
+        <div class="p-4">
+            <div class="pb-5 ml-2 text-xl font-bold text-white">
+                <span>Audit</span>
+            </div>
+            <div class="flex flex-wrap items-end justify-start gap-4 ml-4 flex-0">
+                <div class="flex flex-col gap-1">
+                    <label class="text-xs opacity-70" for="audit_actor">Actor</label>
+                    <input
+                        class="input input-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                        type="text"
+                        id="audit_actor"
+                        name="audit_actor"
+                        placeholder="User id"
+                        prop:value=move || filter.get().actor
+                        on:change=move |event| filter.update(|f| f.actor = event_target_value(&event))
+                    />
+                </div>
+                <div class="flex flex-col gap-1">
+                    <label class="text-xs opacity-70" for="audit_action">Action</label>
+                    <select
+                        class="select w-full text-sm rounded shadow-md select-sm hover:shadow-md"
+                        id="audit_action"
+                        name="audit_action"
+                        on:change=move |event| filter.update(|f| f.action = event_target_value(&event))
+                    >
+                        <option value="" selected=move || filter.get().action.is_empty()>All</option>
+                        {
+                            AuditAction::ALL
+                                .into_iter()
+                                .map(|a| {
+                                    view! {
+                                        <option value=a.as_str() selected=move || filter.get().action == a.as_str()>
+                                            {a.get_name()}
+                                        </option>
+                                    }
+                                })
+                                .collect_view()
+                        }
+                    </select>
+                </div>
+                <div class="flex flex-col gap-1">
+                    <label class="text-xs opacity-70" for="audit_date_from">From</label>
+                    <input
+                        class="input input-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                        type="date"
+                        id="audit_date_from"
+                        name="audit_date_from"
+                        prop:value=move || filter.get().date_from
+                        prop:max=move || filter.get().date_to
+                        on:change=move |event| filter.update(|f| f.date_from = event_target_value(&event))
+                    />
+                </div>
+                <div class="flex flex-col gap-1">
+                    <label class="text-xs opacity-70" for="audit_date_to">To</label>
+                    <input
+                        class="input input-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                        type="date"
+                        id="audit_date_to"
+                        name="audit_date_to"
+                        prop:value=move || filter.get().date_to
+                        prop:min=move || filter.get().date_from
+                        on:change=move |event| filter.update(|f| f.date_to = event_target_value(&event))
+                    />
+                </div>
+                <button
+                    class="btn btn-sm btn-ghost"
+                    prop:disabled=move || filter.get() == AuditFilter::default()
+                    on:click=move |_| filter.set(AuditFilter::default())
+                >
+                    CLEAR
+                </button>
+            </div>
+            <Transition fallback=move || {
+                view! {
+                    <div class="items-center mt-5">
+                        <div class="flex justify-center ">
+                            <Spinners size=ComponentSize::SMALL _type=ComponentType::SUCCESS />
+                        </div>
+                    </div>
+                }
+            }>
+                {move || match audit_logs_resource.get() {
+                    Some(Ok(logs)) => {
+                        audit_logs.set(logs);
+                        has_error.set(false);
+                    }
+                    Some(Err(_)) => has_error.set(true),
+                    None => {}
+                }}
+                <Show
+                    when=move || !has_error.get()
+                    fallback=move || {
+                        let text = RwSignal::new(String::from("Cannot connect to server. Please refresh page."));
+                        view! { <DefaultNone text = text/> }
+                    }
+                >
+                    <ServerDataTable
+                        headers=headersource
+                        key_to_display_in_mobile
+                        keys=datakeysource
+                        sort_fields=sort_fields
+                        data=json_value
+                        total_entries=total_entries
+                        page_request=page_request
+                        color=color_vec
+                        hasdownload=true
+                        nodatamessage=no_data_message
+                        file_content=file_content
+                        file_name=download_file_name
+                        haspageslenght=true
+                    />
+                </Show>
+            </Transition>
+        </div>
+    }
+}
@@ -2,8 +2,11 @@ mod models;
 pub mod page;
 pub mod account_overview;
 pub mod loans;
+pub mod manage;
 pub mod positions;
 pub mod quotes;
+pub mod record_transaction;
+pub mod statement;
 pub mod trade_history;
 pub mod transfers;
 mod services;
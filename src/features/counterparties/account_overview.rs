@@ -1,10 +1,14 @@
 use leptos::*;
 
+use crate::commons::services::credit_limit::get_credit_utilisation;
 use crate::commons::services::currency::fetch_currencies;
+use crate::commons::models::credit_limit::LimitUtilisation;
 use crate::commons::models::currency::CurrencyConfigurationResponse;
 use crate::components::arrow_down::ArrowDown;
 use crate::components::arrow_up::ArrowUp;
+use crate::components::default_none::DefaultNone;
 use crate::components::icons::CurrencyIcon;
+use crate::utilities::errors::JabraError;
 use crate::utilities::number_util::format_currency_with_scale;
 use crate::utilities::number_util::format_money;
 
@@ -18,8 +22,9 @@ use super::services::fetch_overview_data;
 #[allow(non_snake_case)]
 #[component]
 pub fn AccountOverviewPage(counterparty: RwSignal<String>) -> impl IntoView {
-    let overview_resource: Resource<String, Result<PortfolioOverviewResponse, ServerFnError>> =
-        create_local_resource(counterparty, move |e| fetch_overview_data(e));
+    let overview_refetcher = expect_context::<crate::OverviewRefetcher>().0;
+    let overview_resource: Resource<(String, bool), Result<PortfolioOverviewResponse, ServerFnError>> =
+        create_local_resource(move || (counterparty.get(), overview_refetcher.get()), move |(e, _)| fetch_overview_data(e));
     let equity = RwSignal::new(0f64);
     let negative_style = move |num: f64| {
         if num < 0.0 {
@@ -77,10 +82,81 @@ pub fn AccountOverviewPage(counterparty: RwSignal<String>) -> impl IntoView {
                 }
             }
         </Suspense>
+        <CreditLimitUtilisation counterparty = counterparty />
         </div>
     }.into_view()
 }
 
+/// Component for the utilisation of the credit limits of a counterparty.
+/// The utilisation is computed from the open trades and the pending quotes, a limit that is not set shows no bar.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn CreditLimitUtilisation(counterparty: RwSignal<String>) -> impl IntoView {
+    let utilisation_resource = create_local_resource(counterparty, move |e| get_credit_utilisation(e));
+    view! {
+        <div class = "mt-4 bg-gray-500 p-4 rounded-lg bg-opacity-20">
+            <div class = "pb-3 text-base font-semibold text-white">Credit Limits (USD)</div>
+            <Suspense
+                fallback = move || view! {
+                    <div class = "flex justify-center">
+                        <crate::components::loading_spinners::Spinners size=crate::components::component_size::ComponentSize::SMALL _type=crate::components::component_type::ComponentType::SUCCESS />
+                    </div>
+                }
+            >
+            {
+                move || match utilisation_resource.get() {
+                    Some(Ok(u)) => {
+                        if u.limit.is_none() {
+                            let text = RwSignal::new(String::from("No credit limit is set for this counterparty"));
+                            return view! { <DefaultNone text = text/> }.into_view();
+                        }
+                        u.get_utilisations().into_iter().map(|l| view! { <LimitUtilisationBar utilisation = l /> }).collect_view()
+                    }
+                    Some(Err(e)) => {
+                        let text = RwSignal::new(JabraError::from_server_fn_error(&e).get_user_message());
+                        view! { <DefaultNone text = text/> }.into_view()
+                    }
+                    None => ().into_view(),
+                }
+            }
+            </Suspense>
+        </div>
+    }
+}
+
+/// Component for the bar of a single credit limit, it turns to warning from 80% and to error once the limit is breached.
+
+#[allow(non_snake_case)]
+#[component]
+fn LimitUtilisationBar(utilisation: LimitUtilisation) -> impl IntoView {
+    let percentage = utilisation.get_percentage();
+    let bar_class = match percentage {
+        Some(p) if p > 100.0 => "progress progress-error w-full",
+        Some(p) if p >= 80.0 => "progress progress-warning w-full",
+        _ => "progress progress-success w-full",
+    };
+    let limit = match utilisation.limit {
+        Some(l) => format_money(l.to_string(), ",", 2),
+        None => String::from("No limit"),
+    };
+    view! {
+        <div class = "mb-3">
+            <div class = "flex justify-between text-xs">
+                <span class = "font-semibold">{utilisation.name.clone()}</span>
+                <span>
+                    <span>{format_money(utilisation.used.to_string(), ",", 2)}</span>
+                    <span class = "opacity-50">{format!(" / {}", limit)}</span>
+                    {percentage.map(|p| view! { <span class = "ml-2 opacity-70">{format!("{:.1}%", p)}</span> })}
+                </span>
+            </div>
+            <Show when = move || percentage.is_some() fallback = || ()>
+                <progress class = bar_class value = percentage.unwrap_or_default().min(100.0) max = "100"></progress>
+            </Show>
+        </div>
+    }
+}
+
 /// Component for the Portfolio Currency.
 /// Shows the Table body for the Portfolio Overview.
 /// It takes a vector of [`PortfolioCurrency`] as input.
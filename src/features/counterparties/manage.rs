@@ -0,0 +1,286 @@
+use leptos::*;
+
+use crate::commons::models::counterparty::{CounterParty, CounterPartyRequest};
+use crate::commons::services::counterparty::{
+    create_counter_party, get_all_counter_parties, set_counter_party_active, update_counter_party,
+};
+use crate::components::component_size::ComponentSize;
+use crate::components::component_type::ComponentType;
+use crate::components::confirm_modal::ConfirmModal;
+use crate::components::default_none::DefaultNone;
+use crate::components::loading_spinners::Spinners;
+use crate::utilities::errors::JabraError;
+use crate::CounterPartiesRefetcher;
+
+/// Component for the Manage Counterparties page.
+/// Creates and edits the counterparties, which are deactivated instead of deleted so their history is kept.
+/// Every counterparty selector of the portal fetches the list again after a change.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn ManageCounterParties() -> impl IntoView {
+    let counterparties_refetcher = expect_context::<CounterPartiesRefetcher>().0;
+    let counterparties_resource =
+        create_local_resource(counterparties_refetcher, move |_| get_all_counter_parties());
+    let counterparties = RwSignal::new(Vec::<CounterParty>::default());
+
+    // The counterparty being edited, `None` when the form creates a new one.
+    let editing = RwSignal::new(Option::<u16>::None);
+    let ticker = RwSignal::new(String::new());
+    let name = RwSignal::new(String::new());
+    let short_name = RwSignal::new(String::new());
+    let is_exchange = RwSignal::new(false);
+    let form_error = RwSignal::new(Option::<String>::None);
+    let success_message = RwSignal::new(Option::<String>::None);
+
+    let reset_form = move || {
+        editing.set(None);
+        ticker.set(String::new());
+        name.set(String::new());
+        short_name.set(String::new());
+        is_exchange.set(false);
+        form_error.set(None);
+    };
+    let edit = move |cp: CounterParty| {
+        editing.set(Some(cp.id));
+        ticker.set(cp.ticker);
+        name.set(cp.name);
+        short_name.set(cp.short_name.unwrap_or_default());
+        is_exchange.set(cp.is_exchange);
+        form_error.set(None);
+        success_message.set(None);
+    };
+
+    let save_action = create_action(move |(id, request): &(Option<u16>, CounterPartyRequest)| {
+        let id = *id;
+        let request = request.clone();
+        async move {
+            let ticker = request.ticker.clone();
+            let result = match id {
+                Some(id) => update_counter_party(id, request).await,
+                None => create_counter_party(request).await,
+            };
+            match result {
+                Ok(_) => {
+                    reset_form();
+                    success_message.set(Some(match id {
+                        Some(_) => format!("Counterparty {} updated.", ticker),
+                        None => format!("Counterparty {} created.", ticker),
+                    }));
+                    counterparties_refetcher.update(|v| *v = !*v);
+                }
+                Err(e) => form_error.set(Some(JabraError::from_server_fn_error(&e).get_user_message())),
+            }
+        }
+    });
+    let status_action = create_action(move |(id, is_active): &(u16, bool)| {
+        let id = *id;
+        let is_active = *is_active;
+        async move {
+            match set_counter_party_active(id, is_active).await {
+                Ok(_) => {
+                    success_message.set(None);
+                    counterparties_refetcher.update(|v| *v = !*v);
+                }
+                Err(e) => form_error.set(Some(JabraError::from_server_fn_error(&e).get_user_message())),
+            }
+        }
+    });
+    let is_saving = save_action.pending();
+    let is_changing_status = status_action.pending();
+
+    // The status change waiting for confirmation, as the counterparty id and its new status.
+    let status_change = RwSignal::new(Option::<(u16, bool)>::None);
+    let (show_confirm_modal, set_show_confirm_modal) = create_signal(false);
+    let confirm_status_change = move || {
+        if let Some(change) = status_change.get_untracked() {
+            status_action.dispatch(change);
+        }
+    };
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        success_message.set(None);
+        let request = CounterPartyRequest::new(ticker.get(), name.get(), short_name.get(), is_exchange.get());
+        match request.validate(&counterparties.get_untracked(), editing.get_untracked()) {
+            Ok(_) => {
+                form_error.set(None);
+                save_action.dispatch((editing.get_untracked(), request));
+            }
+            Err(message) => form_error.set(Some(message)),
+        }
+    };
+
+    view! {
+        <div class="p-4">
+            <div class="pb-5 ml-2 text-xl font-bold text-white">
+                <span>Manage Counterparties</span>
+            </div>
+            <form class="p-4 mb-4 border border-success border-opacity-40 rounded-xl bg-base-300 bg-opacity-50" on:submit=on_submit>
+                <div class="pb-3 text-sm font-semibold text-success">
+                    {move || match editing.get() {
+                        Some(_) => "EDIT COUNTERPARTY",
+                        None => "NEW COUNTERPARTY",
+                    }}
+                </div>
+                <div class="flex flex-wrap items-end gap-4">
+                    <div class="flex flex-col gap-1">
+                        <label class="text-xs opacity-70" for="counterparty_ticker">Ticker</label>
+                        <input
+                            class="input input-sm text-sm uppercase rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                            type="text"
+                            id="counterparty_ticker"
+                            name="counterparty_ticker"
+                            required
+                            prop:value=move || ticker.get()
+                            on:input=move |event| ticker.set(event_target_value(&event).to_uppercase())
+                        />
+                    </div>
+                    <div class="flex flex-col gap-1">
+                        <label class="text-xs opacity-70" for="counterparty_name">Name</label>
+                        <input
+                            class="input input-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                            type="text"
+                            id="counterparty_name"
+                            name="counterparty_name"
+                            required
+                            prop:value=move || name.get()
+                            on:input=move |event| name.set(event_target_value(&event))
+                        />
+                    </div>
+                    <div class="flex flex-col gap-1">
+                        <label class="text-xs opacity-70" for="counterparty_short_name">Short Name</label>
+                        <input
+                            class="input input-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                            type="text"
+                            id="counterparty_short_name"
+                            name="counterparty_short_name"
+                            prop:value=move || short_name.get()
+                            on:input=move |event| short_name.set(event_target_value(&event))
+                        />
+                    </div>
+                    <label class="flex items-center gap-2 cursor-pointer label" for="counterparty_is_exchange">
+                        <input
+                            class="checkbox checkbox-sm checkbox-success"
+                            type="checkbox"
+                            id="counterparty_is_exchange"
+                            name="counterparty_is_exchange"
+                            prop:checked=move || is_exchange.get()
+                            on:change=move |event| is_exchange.set(event_target_checked(&event))
+                        />
+                        <span class="text-xs label-text">Exchange</span>
+                    </label>
+                    <button type="submit" class="btn btn-sm btn-success rounded" prop:disabled=is_saving>
+                        {move || if is_saving.get() {
+                            view! { <span class="loading loading-spinner loading-sm"></span> }.into_view()
+                        } else {
+                            match editing.get() {
+                                Some(_) => "SAVE".into_view(),
+                                None => "CREATE".into_view(),
+                            }
+                        }}
+                    </button>
+                    <Show when=move || editing.get().is_some()>
+                        <button type="button" class="btn btn-sm btn-ghost rounded" on:click=move |_| reset_form()>
+                            CANCEL
+                        </button>
+                    </Show>
+                </div>
+                {move || form_error.get().map(|message| view! {
+                    <p class="pt-2 text-sm text-error">{message}</p>
+                })}
+                {move || success_message.get().map(|message| view! {
+                    <p class="pt-2 text-sm text-success">{message}</p>
+                })}
+            </form>
+            <Transition fallback=move || {
+                view! {
+                    <div class="items-center mt-5">
+                        <div class="flex justify-center ">
+                            <Spinners size=ComponentSize::SMALL _type=ComponentType::SUCCESS />
+                        </div>
+                    </div>
+                }
+            }>
+                {move || match counterparties_resource.get() {
+                    Some(Ok(res)) => {
+                        counterparties.set(res.data.clone());
+                        if res.data.is_empty() {
+                            let text = RwSignal::new(String::from("No Counterparties Available"));
+                            return view! { <DefaultNone text = text/> }.into_view();
+                        }
+                        view! {
+                            <div class="overflow-auto border border-success border-opacity-40">
+                                <table class="table table-xs table-zebra-zebra">
+                                    <thead>
+                                        <tr class="font-light text-center text-success bg-base-300">
+                                            <th>"Ticker"</th>
+                                            <th>"Name"</th>
+                                            <th>"Short Name"</th>
+                                            <th>"Exchange"</th>
+                                            <th>"Status"</th>
+                                            <th></th>
+                                        </tr>
+                                    </thead>
+                                    <tbody class="text-center font-extralight">
+                                        {res.data.into_iter().map(|cp| {
+                                            let id = cp.id;
+                                            let is_active = cp.is_active;
+                                            let counterparty = cp.clone();
+                                            view! {
+                                                <tr class:opacity-50=!is_active>
+                                                    <td class="font-semibold">{cp.ticker}</td>
+                                                    <td>{cp.name}</td>
+                                                    <td>{cp.short_name.unwrap_or_default()}</td>
+                                                    <td>{if cp.is_exchange { "Yes" } else { "No" }}</td>
+                                                    <td>
+                                                        <span class=if is_active { "text-success" } else { "text-error" }>
+                                                            {if is_active { "Active" } else { "Inactive" }}
+                                                        </span>
+                                                    </td>
+                                                    <td class="flex justify-end gap-2">
+                                                        <button
+                                                            class="rounded btn btn-xs btn-ghost"
+                                                            on:click=move |_| edit(counterparty.clone())
+                                                        >
+                                                            EDIT
+                                                        </button>
+                                                        <button
+                                                            class=if is_active { "rounded btn btn-xs btn-error" } else { "rounded btn btn-xs btn-success" }
+                                                            prop:disabled=is_changing_status
+                                                            on:click=move |_| {
+                                                                status_change.set(Some((id, !is_active)));
+                                                                set_show_confirm_modal.set(true);
+                                                            }
+                                                        >
+                                                            {if is_active { "DEACTIVATE" } else { "REACTIVATE" }}
+                                                        </button>
+                                                    </td>
+                                                </tr>
+                                            }
+                                        }).collect_view()}
+                                    </tbody>
+                                </table>
+                            </div>
+                        }.into_view()
+                    }
+                    Some(Err(e)) => {
+                        let text = RwSignal::new(JabraError::from_server_fn_error(&e).get_user_message());
+                        view! { <DefaultNone text = text/> }.into_view()
+                    }
+                    None => ().into_view(),
+                }}
+            </Transition>
+            {move || {
+                view! {
+                    <ConfirmModal
+                        when=show_confirm_modal.get()
+                        write_signal=set_show_confirm_modal
+                        function=confirm_status_change
+                        action=status_action
+                    />
+                }
+            }}
+        </div>
+    }
+}
@@ -1,4 +1,15 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
+
+use crate::commons::models::counterparty::CounterParty;
+use crate::commons::models::loan::Loan;
+use crate::commons::models::trade::Trade;
+use crate::commons::models::wallet::{TransferApprovalStatus, WalletTransaction};
+use crate::utilities::date_util::format_utc_str_to_local_str;
+use crate::utilities::number_util::format_currency_with_scale;
+use crate::utilities::string_util::escape_html;
+
 /// Account Overview Response
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PortfolioOverviewResponse {
@@ -73,4 +84,347 @@ impl PortfolioCurrency {
             interest_payments,
         }
     }
-}
\ No newline at end of file
+}
+/// The movements of a single currency in a statement.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CurrencyStatement {
+    /// The name of the currency
+    pub currency: String,
+    /// The number of decimals the amounts of this currency are shown with
+    pub display_scale: u8,
+    /// The balance at the start of the period
+    pub opening_balance: f64,
+    /// The deposits of the period
+    pub deposits: f64,
+    /// The withdrawals of the period, as a positive amount
+    pub withdrawals: f64,
+    /// The other transfers of the period, signed
+    pub transfers: f64,
+    /// The fees of the wallet transactions of the period, as a positive amount
+    pub fees: f64,
+    /// The profit and loss of the trades closed in the period, in this currency
+    pub realised_pnl: f64,
+    /// The balance at the end of the period
+    pub closing_balance: f64,
+}
+
+impl CurrencyStatement {
+    fn new(currency: String, display_scale: u8) -> CurrencyStatement {
+        CurrencyStatement {
+            currency,
+            display_scale,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the change of the balance over the period.
+    pub fn get_movement(&self) -> f64 {
+        self.deposits - self.withdrawals + self.transfers - self.fees + self.realised_pnl
+    }
+}
+
+/// Statement of a counterparty for a period, ready to be printed.
+/// The period is given as UTC timestamps, `start` inclusive and `end` exclusive.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CounterPartyStatement {
+    /// The ticker of the counterparty
+    pub ticker: String,
+    /// The name of the counterparty
+    pub name: String,
+    /// The start of the period
+    pub start: String,
+    /// The end of the period
+    pub end: String,
+    /// The time the statement was generated, in local time
+    pub generated_at: String,
+    /// The balances and movements per currency
+    pub currencies: Vec<CurrencyStatement>,
+    /// The trades of the period
+    pub trades: Vec<Trade>,
+    /// The wallet transactions of the period
+    pub transactions: Vec<WalletTransaction>,
+    /// The loans traded in the period
+    pub loans: Vec<Loan>,
+}
+
+impl CounterPartyStatement {
+    /// Create a new CounterPartyStatement
+    ///
+    /// The balances of the portfolio overview are the current ones, so the closing balances are found by taking back
+    /// the wallet transactions and realised PnL booked after the period, and the opening balances by taking back those of the period.
+    ///
+    /// # Arguments
+    ///
+    /// * `counterparty` - The counterparty of the statement
+    /// * `start` - The start of the period, inclusive
+    /// * `end` - The end of the period, exclusive
+    /// * `overview` - The current portfolio overview of the counterparty
+    /// * `trades` - The trades of the counterparty since the start of the period
+    /// * `transactions` - The wallet transactions of the counterparty since the start of the period
+    /// * `loans` - The loans of the counterparty traded in the period
+    ///
+    /// # Returns
+    ///
+    /// * `CounterPartyStatement` - The new CounterPartyStatement
+    pub fn new(
+        counterparty: &CounterParty,
+        start: String,
+        end: String,
+        overview: PortfolioOverviewResponse,
+        trades: Vec<Trade>,
+        transactions: Vec<WalletTransaction>,
+        loans: Vec<Loan>,
+    ) -> CounterPartyStatement {
+        let mut period = BTreeMap::<String, CurrencyStatement>::new();
+        let mut after = BTreeMap::<String, CurrencyStatement>::new();
+        let mut scales = BTreeMap::<String, u8>::new();
+
+        // Pending and rejected transactions have not moved any funds.
+        let transactions = transactions
+            .into_iter()
+            .filter(|t| {
+                let status = t.get_approval_status();
+                !status.is_pending() && status != TransferApprovalStatus::Rejected
+            })
+            .collect::<Vec<WalletTransaction>>();
+        for t in transactions.iter() {
+            let currency = t.currency_id.ticker.clone();
+            scales.insert(currency.clone(), t.currency_id.display_scale);
+            let target = if is_before(&t.venue_transaction_datetime, &end) { &mut period } else { &mut after };
+            let s = target
+                .entry(currency.clone())
+                .or_insert_with(|| CurrencyStatement::new(currency, t.currency_id.display_scale));
+            match t.transaction_type.to_uppercase().as_str() {
+                "DEPOSIT" => s.deposits += t.amount.abs(),
+                "WITHDRAWAL" => s.withdrawals += t.amount.abs(),
+                _ => s.transfers += t.amount,
+            }
+            s.fees += t.fee_amount.abs();
+        }
+        for t in trades.iter().filter(|t| t.activity != "open") {
+            let (Some(pnl), Some(currency)) = (t.pnl, t.pnl_ccy.clone()) else {
+                continue;
+            };
+            let date = t.date_created.clone().unwrap_or_default();
+            let target = if is_before(&date, &end) { &mut period } else { &mut after };
+            let scale = scales.get(&currency).copied().unwrap_or(6);
+            target
+                .entry(currency.clone())
+                .or_insert_with(|| CurrencyStatement::new(currency, scale))
+                .realised_pnl += pnl;
+        }
+
+        for c in overview.currencies.iter() {
+            let scale = scales.get(&c.currency).copied().unwrap_or(6);
+            period
+                .entry(c.currency.clone())
+                .or_insert_with(|| CurrencyStatement::new(c.currency.clone(), scale));
+        }
+        let currencies = period
+            .into_values()
+            .map(|mut s| {
+                let current = overview
+                    .currencies
+                    .iter()
+                    .find(|c| c.currency == s.currency)
+                    .map_or(0.0, |c| c.balance);
+                let movement_after = after.get(&s.currency).map_or(0.0, |a| a.get_movement());
+                s.closing_balance = current - movement_after;
+                s.opening_balance = s.closing_balance - s.get_movement();
+                s
+            })
+            .collect();
+
+        CounterPartyStatement {
+            ticker: counterparty.ticker.clone(),
+            name: counterparty.name.clone(),
+            generated_at: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+            currencies,
+            trades: trades
+                .into_iter()
+                .filter(|t| is_before(&t.date_created.clone().unwrap_or_default(), &end))
+                .collect(),
+            transactions: transactions
+                .into_iter()
+                .filter(|t| is_before(&t.venue_transaction_datetime, &end))
+                .collect(),
+            loans,
+            start,
+            end,
+        }
+    }
+
+    /// Returns the file name of the statement, without the extension.
+    pub fn get_file_name(&self) -> String {
+        format!("statement_{}_{}_{}", self.ticker, &self.start[..10], &self.get_last_day())
+    }
+
+    /// Returns the last day of the period, the day before its exclusive end.
+    pub fn get_last_day(&self) -> String {
+        chrono::NaiveDate::parse_from_str(&self.end[..10], "%Y-%m-%d")
+            .map(|d| (d - chrono::Duration::days(1)).format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|_| self.end[..10].to_string())
+    }
+
+    /// Renders the statement as a standalone HTML page, styled for printing.
+    /// When `print` is set, the print dialog opens once the page is loaded so it can be saved as a PDF.
+    pub fn to_html(&self, print: bool) -> String {
+        let scale = |currency: &str| {
+            self.currencies
+                .iter()
+                .find(|c| c.currency == currency)
+                .map_or(6, |c| c.display_scale)
+        };
+        let amount = |value: f64, scale: u8| format_currency_with_scale(value, scale, ",");
+
+        let balances = self
+            .currencies
+            .iter()
+            .map(|c| {
+                format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    escape_html(&c.currency),
+                    amount(c.opening_balance, c.display_scale),
+                    amount(c.deposits, c.display_scale),
+                    amount(c.withdrawals, c.display_scale),
+                    amount(c.transfers, c.display_scale),
+                    amount(c.fees, c.display_scale),
+                    amount(c.realised_pnl, c.display_scale),
+                    amount(c.closing_balance, c.display_scale),
+                )
+            })
+            .collect::<String>();
+        let trades = self
+            .trades
+            .iter()
+            .map(|t| {
+                let pnl = match (t.pnl, t.pnl_ccy.clone()) {
+                    (Some(pnl), Some(currency)) if t.activity != "open" => {
+                        format!("{} {}", amount(pnl, scale(&currency)), escape_html(&currency))
+                    }
+                    _ => String::from("- -"),
+                };
+                format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    escape_html(&format_utc_str_to_local_str(t.date_created.clone().unwrap_or_default())),
+                    escape_html(&t.venue_instrument_name),
+                    escape_html(&t.side),
+                    amount(t.amount.unwrap_or_default(), t.pair_id.base.display_scale),
+                    amount(t.px_in_quote_ccy.unwrap_or_default(), t.pair_id.quote.display_scale),
+                    escape_html(&t.activity),
+                    pnl,
+                )
+            })
+            .collect::<String>();
+        let transactions = self
+            .transactions
+            .iter()
+            .map(|t| {
+                format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    escape_html(&format_utc_str_to_local_str(t.venue_transaction_datetime.clone())),
+                    escape_html(&t.transaction_type),
+                    escape_html(&t.currency_id.ticker),
+                    amount(t.amount, t.currency_id.display_scale),
+                    amount(t.fee_amount, t.currency_id.display_scale),
+                    escape_html(&t.description),
+                )
+            })
+            .collect::<String>();
+        let loans = self
+            .loans
+            .iter()
+            .map(|l| {
+                format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}%</td><td>{}%</td><td>{}</td></tr>",
+                    escape_html(&l.trade_date),
+                    escape_html(&l.pair_id.name),
+                    escape_html(&l.transaction_type),
+                    amount(l.base_ccy_amount, l.base_ccy_id.display_scale),
+                    l.interest_rate,
+                    l.loan_to_value,
+                    escape_html(&l.status),
+                )
+            })
+            .collect::<String>();
+        let section = |title: &str, headers: &[&str], rows: String| {
+            let body = if rows.is_empty() {
+                format!("<tr><td colspan=\"{}\" class=\"empty\">No records in this period</td></tr>", headers.len())
+            } else {
+                rows
+            };
+            format!(
+                "<h2>{}</h2><table><thead><tr>{}</tr></thead><tbody>{}</tbody></table>",
+                title,
+                headers.iter().map(|h| format!("<th>{}</th>", h)).collect::<String>(),
+                body
+            )
+        };
+
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>JABRA Statement - {name} - {start} to {last_day}</title>
+<style>
+body {{ font-family: Helvetica, Arial, sans-serif; color: #1f2937; margin: 32px; font-size: 12px; }}
+header {{ display: flex; justify-content: space-between; align-items: flex-end; border-bottom: 3px solid #36d399; padding-bottom: 12px; }}
+.brand {{ font-size: 28px; font-weight: 800; letter-spacing: 4px; color: #111827; }}
+.brand span {{ color: #36d399; }}
+.meta {{ text-align: right; }}
+h1 {{ font-size: 18px; margin: 24px 0 4px; }}
+h2 {{ font-size: 14px; margin: 24px 0 8px; color: #047857; text-transform: uppercase; letter-spacing: 1px; }}
+table {{ width: 100%; border-collapse: collapse; }}
+th {{ background: #ecfdf5; text-align: left; padding: 6px; border-bottom: 1px solid #a7f3d0; }}
+td {{ padding: 5px 6px; border-bottom: 1px solid #e5e7eb; }}
+td.empty {{ color: #9ca3af; text-align: center; }}
+footer {{ margin-top: 32px; color: #6b7280; font-size: 10px; }}
+@media print {{ body {{ margin: 0; }} tr {{ page-break-inside: avoid; }} }}
+</style>
+</head>
+<body>
+<header>
+<div class="brand">JAB<span>RA</span></div>
+<div class="meta"><div>Statement generated {generated_at}</div><div>Period {start} to {last_day} (UTC)</div></div>
+</header>
+<h1>{name} ({ticker})</h1>
+{balances}
+{trades}
+{transactions}
+{loans}
+<footer>The opening and closing balances are derived from the current balances and the transactions and realised PnL booked since. Realised PnL is the PnL of the trades closed in the period.</footer>
+{script}
+</body>
+</html>"#,
+            name = escape_html(&self.name),
+            ticker = escape_html(&self.ticker),
+            start = &self.start[..10],
+            last_day = self.get_last_day(),
+            generated_at = self.generated_at,
+            balances = section(
+                "Balances",
+                &["Currency", "Opening Balance", "Deposits", "Withdrawals", "Transfers", "Fees", "Realised PnL", "Closing Balance"],
+                balances
+            ),
+            trades = section("Trades", &["Date", "Instrument", "Side", "Size", "Price", "Activity", "Realised PnL"], trades),
+            transactions = section(
+                "Wallet Transactions",
+                &["Time", "Action", "Currency", "Amount", "Fee Amount", "Description"],
+                transactions
+            ),
+            loans = section(
+                "Loans",
+                &["Trade Date", "Pair", "Type", "Amount", "Interest Rate", "LTV", "Status"],
+                loans
+            ),
+            script = if print { "<script>window.onload = function () { window.print(); };</script>" } else { "" },
+        )
+    }
+}
+
+/// Returns `true` when the timestamp is before the bound, both in the ISO format of Directus.
+fn is_before(timestamp: &str, bound: &str) -> bool {
+    let length = timestamp.len().min(bound.len()).min(19);
+    timestamp[..length] < bound[..length]
+}
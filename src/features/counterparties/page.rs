@@ -1,15 +1,16 @@
 use leptos::*;
 
-use crate::{commons::{models::counterparty::{CounterParty, GetCounterPartiesResponse}, services::counterparty::get_counter_parties}, components::{component_size::ComponentSize, component_type::ComponentType, loading_spinners::Spinners}, features::counterparties::{account_overview::AccountOverviewPage, loans::CounterPartyLoansPage, positions::CounterPartyPositionsPage, quotes::CounterPartyQuotesPage, trade_history::CounterPartyTradeHistoryPage, transfers::CounterPartyTransfersPage}};
+use crate::{commons::{models::counterparty::{CounterParty, GetCounterPartiesResponse}, services::counterparty::get_all_counter_parties}, components::{component_size::ComponentSize, component_type::ComponentType, loading_spinners::Spinners}, features::counterparties::{account_overview::AccountOverviewPage, loans::CounterPartyLoansPage, positions::CounterPartyPositionsPage, quotes::CounterPartyQuotesPage, statement::GenerateStatement, trade_history::CounterPartyTradeHistoryPage, transfers::CounterPartyTransfersPage}};
 
 #[allow(non_snake_case)]
 #[component]
 pub fn CounterParties() -> impl IntoView {
     let counterparties = RwSignal::new(Vec::<CounterParty>::default());
+    let counterparties_refetcher = expect_context::<crate::CounterPartiesRefetcher>().0;
     let counterparties_resource: Resource<
-        (),
+        bool,
         Result<GetCounterPartiesResponse, ServerFnError>,
-    > = create_local_resource(|| (), move |_| get_counter_parties());
+    > = create_local_resource(counterparties_refetcher, move |_| get_all_counter_parties());
 
     view!{
         <Suspense
@@ -78,8 +79,9 @@ pub fn CounterPartyPage(counterparties: RwSignal<Vec<CounterParty>>) -> impl Int
                                     move || {
                                         counterparty.set(counterparties.get().first().unwrap().ticker.clone());
                                         counterparties.get().into_iter().map(|cp| {
+                                            let name = cp.get_display_name();
                                             view! {
-                                                <option value = {cp.ticker}>{cp.name}</option>
+                                                <option value = {cp.ticker}>{name}</option>
                                             }
                                         }).collect_view()
                                     }
@@ -107,10 +109,13 @@ pub fn CounterPartyPage(counterparties: RwSignal<Vec<CounterParty>>) -> impl Int
                             </select>
                         </div>
                     </div>
+                    <div class = "flex-0">
+                        <GenerateStatement counterparty = counterparty counterparties = counterparties/>
+                    </div>
                 </div>
             </div>
             <div>
-                <CounterPartyPageManager counterparty = counterparty oper = oper/>
+                <CounterPartyPageManager counterparty = counterparty counterparties = counterparties oper = oper/>
             </div>
         </div>
     }
@@ -123,6 +128,7 @@ pub fn CounterPartyPage(counterparties: RwSignal<Vec<CounterParty>>) -> impl Int
 #[component]
 pub fn CounterPartyPageManager(
     counterparty: RwSignal<String>,
+    counterparties: RwSignal<Vec<CounterParty>>,
     oper: RwSignal<String>,
 ) -> impl IntoView {
     view! {
@@ -135,7 +141,7 @@ pub fn CounterPartyPageManager(
                         "Quotes" => view!{<CounterPartyQuotesPage counterparty = counterparty/>},
                         "Loans" => view!{<CounterPartyLoansPage counterparty = counterparty/>},
                         "Trade History" => view!{<CounterPartyTradeHistoryPage counterparty = counterparty/>},
-                        "Transfers" => view!{<CounterPartyTransfersPage counterparty = counterparty/>},
+                        "Transfers" => view!{<CounterPartyTransfersPage counterparty = counterparty counterparties = counterparties/>},
                         _ => view! {<span class = "opacity-50">Page not available!</span>}.into_view(),
                     }
                 }
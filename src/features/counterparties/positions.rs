@@ -1,5 +1,5 @@
 use leptos::*;
-use crate::{commons::services::coinbase::get_spot_price, features::trades::positions::page::PerCurrencyPosition};
+use crate::features::trades::positions::page::ActivePairsPositions;
 
 
 
@@ -9,9 +9,6 @@ use crate::{commons::services::coinbase::get_spot_price, features::trades::posit
 #[allow(non_snake_case)]
 #[component]
 pub fn CounterPartyPositionsPage(counterparty: RwSignal<String>) -> impl IntoView {
-    let btc_spot_price_resource = create_local_resource(|| (), move |_| get_spot_price(String::from("BTC-USD")));
-    let eth_spot_price_resource = create_local_resource(|| (), move |_| get_spot_price(String::from("ETH-USD")));
-
     // let trade_history_resource: Resource<String, Result<TradeHistory, ServerFnError>> =
     //     Resource::new(counterparty, move |e| get_positions(e));
     // let filtered_trade_table =
@@ -34,47 +31,7 @@ pub fn CounterPartyPositionsPage(counterparty: RwSignal<String>) -> impl IntoVie
             //     }
             //     </div>
             // </div>
-            <Suspense
-                fallback = move || view! {
-                        <div class = "items-center mt-5">
-                            <div class = "flex justify-center ">
-                                <crate::components::loading_spinners::Spinners size=crate::components::component_size::ComponentSize::SMALL _type=crate::components::component_type::ComponentType::SUCCESS />
-                            </div>
-                        </div>
-                    }
-            >
-            {
-                move || {
-                    btc_spot_price_resource.and_then(|t| {
-                        let spot = t.data.amount.parse::<f64>().unwrap_or_default();
-                        view! {
-                            <PerCurrencyPosition spot = spot pair_name =  String::from("BTC/USD") currency_name = String::from("BTC") counter_party= counterparty.get() />
-                        }
-                    })
-                }
-            }
-            </Suspense>
-            <div class="px-4 opacity-50 divider divider-neutral font-extralight"></div>
-            <Suspense
-                fallback = move || view! {
-                        <div class = "items-center mt-5">
-                            <div class = "flex justify-center ">
-                                <crate::components::loading_spinners::Spinners size=crate::components::component_size::ComponentSize::SMALL _type=crate::components::component_type::ComponentType::SUCCESS />
-                            </div>
-                        </div>
-                    }
-            >
-            {
-                move || {
-                    eth_spot_price_resource.and_then(|t| {
-                        let spot = t.data.amount.parse::<f64>().unwrap_or_default();
-                        view! {
-                            <PerCurrencyPosition spot = spot pair_name =  String::from("ETH/USD") currency_name = String::from("ETH") counter_party= counterparty.get() />
-                        }
-                    })
-                }
-            }
-            </Suspense>
+            <ActivePairsPositions counter_party = counterparty />
         </div>
         // <Suspense
         //     fallback = move || view! {
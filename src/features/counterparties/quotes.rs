@@ -1,29 +1,63 @@
 use leptos::*;
 use serde_json::json;
+use crate::commons::models::defaults::PageRequest;
 use crate::commons::models::quote::ExtractedQuoteOption;
 use crate::commons::models::quote::QuoteOptionHistory;
-use crate::components::data_table_local::DataTable;
+use crate::components::data_table_server::ServerDataTable;
+use crate::components::default_none::DefaultNone;
 use crate::components::menu_button::MenuButton;
 use crate::features::counterparties::services::get_quote_history;
 
 /// Component for CounterParty Quotes Page.
-/// Has the actual view for Quotes page.
+/// Has the actual view for Quotes page, the quotes are paginated by the server.
 
 #[allow(non_snake_case)]
 #[component]
 pub fn CounterPartyQuotesPage(counterparty: RwSignal<String>) -> impl IntoView {
-    let quote_history_resource: Resource<String, Result<QuoteOptionHistory, ServerFnError>> =
-        create_local_resource(counterparty, move |e| get_quote_history(e));
-    let quotes_history_table = RwSignal::new(Vec::<ExtractedQuoteOption>::default());
     let selected_page = RwSignal::new("All".to_string());
+    let page_request = RwSignal::new(PageRequest { page_size: 15, ..Default::default() });
+    let no_data_message = RwSignal::new(String::from("No Quote History Available"));
+
+    // A new counterparty or tab starts from its first page.
+    create_effect(move |_| {
+        counterparty.track();
+        no_data_message.set(match selected_page.get().as_str() {
+            "Quote Option" => String::from("No Quote Options History Available"),
+            _ => String::from("No Quote History Available"),
+        });
+        if page_request.get_untracked().page != 1 {
+            page_request.update(|r| r.page = 1);
+        }
+    });
+
+    // Every quote is a quote option, both tabs show the same records.
+    let quote_history_resource: Resource<(String, PageRequest), Result<QuoteOptionHistory, ServerFnError>> =
+        create_local_resource(
+            move || (counterparty.get(), page_request.get()),
+            move |(ticker, request)| get_quote_history(ticker, request),
+        );
+    let quote_history = RwSignal::new(QuoteOptionHistory::default());
+    let has_error = RwSignal::new(false);
     let json_value = Signal::derive(move || {
-        quotes_history_table
+        quote_history
             .get()
+            .extract()
             .into_iter()
             .map(serde_json::to_value)
             .collect::<Result<Vec<serde_json::Value>, _>>()
             .expect("Failed to serialize to JSON")
     });
+    let total_entries = Signal::derive(move || quote_history.get().get_filter_count());
+
+    let color_vec = RwSignal::new(vec!(json!({"side":[{"key":"sell", "style":"text-error"},{"key":"buy", "style": "text-success"}],"status":[{"key":"rejected", "style":"text-error"},{"key":"approved", "style": "text-success"}],"price":[{"key":"-", "style":"text-error"},{"key":"", "style": "text-success"}],"size":[{"key":"-", "style":"text-error"},{"key":"", "style": "text-success"}]})));
+    let headersource = RwSignal::new(vec![String::from("Market"), String::from("Status"), String::from("Side"), String::from("Kind"), String::from("Type"), String::from("Size"), String::from("Price"), String::from("Date Created")]);
+    let datakeysource = RwSignal::new(vec![String::from("market"), String::from("status"), String::from("side"), String::from("kind"), String::from("trans_type"), String::from("size"), String::from("price"), String::from("date_created")]);
+    let sort_fields = RwSignal::new(datakeysource.get_untracked().iter().map(|k| ExtractedQuoteOption::get_sort_field(k)).collect::<Vec<String>>());
+    let key_to_display_in_mobile = RwSignal::new(String::from("market"));
+    let currency_vec = RwSignal::new(vec![json!({"key": "price", "value": "premium_ccy"})]);
+    let download_file_name = RwSignal::new(String::from("Quotes"));
+    let file_content = RwSignal::new(String::new());
+
     view! {
         <div  class = "py-4">
             <div class = "flex justify-between">
@@ -39,7 +73,7 @@ pub fn CounterPartyQuotesPage(counterparty: RwSignal<String>) -> impl IntoView {
                 </div>
             </div>
         </div>
-        <Suspense
+        <Transition
             fallback = move || view! {
                     <div class = "items-center mt-5">
                         <div class = "flex justify-center ">
@@ -49,92 +83,48 @@ pub fn CounterPartyQuotesPage(counterparty: RwSignal<String>) -> impl IntoView {
                 }
         >
         {
-            move || {
-                if let Some(data) = quote_history_resource.and_then(|e| {e.clone()}) {
-                    match data {
-                        Ok(t) => {
-                            let color_vec = RwSignal::new(vec!(json!({"side":[{"key":"sell", "style":"text-error"},{"key":"buy", "style": "text-success"}],"status":[{"key":"rejected", "style":"text-error"},{"key":"approved", "style": "text-success"}],"price":[{"key":"-", "style":"text-error"},{"key":"", "style": "text-success"}],"size":[{"key":"-", "style":"text-error"},{"key":"", "style": "text-success"}]})));
-                            // color_vec.push(json!({"side":[{"key":"sell", "style":"text-error"},{"key":"buy", "style": "text-success"}],"status":[{"key":"rejected", "style":"text-error"},{"key":"approved", "style": "text-success"}],"price":[{"key":"-", "style":"text-error"},{"key":"", "style": "text-success"}],"size":[{"key":"-", "style":"text-error"},{"key":"", "style": "text-success"}]}));
-                            let headersource= RwSignal::new(vec![String::from("Market"), String::from("Status"), String::from("Side"), String::from("Kind"), String::from("Type"), String::from("Size"), String::from("Price"), String::from("Date Created")]);
-                            let datakeysource= RwSignal::new(vec![String::from("market"), String::from("status"), String::from("side"), String::from("kind"), String::from("trans_type"), String::from("size"), String::from("price"), String::from("date_created")]);
-                            let key_to_display_in_mobile = RwSignal::new(String::from("market"));
-                            let number_keys= RwSignal::new(vec![String::from("side"), String::from("size"), String::from("price")]);
-                            let currency_vec = RwSignal::new(vec![json!({"key": "price", "value": "premium_ccy"})]);
-                            let row_slice = RwSignal::new(15);
-                            let no_data_message = RwSignal::new(String::from("No Quote History Available"));
-                            let download_file_name = RwSignal::new(String::from("Quotes"));
-                            let file_content = RwSignal::new(String::from("Test content"));
-                            if selected_page.get() == "All".to_string() {
-                                quotes_history_table.set(t.extract());
-                                file_content.set(t.extract_csv_by_quote_status(Option::None, false));
-                                view! {
-                                    <DataTable headers = headersource
-                                        key_to_display_in_mobile
-                                        keys = datakeysource
-                                        data = json_value
-                                        color=color_vec
-                                        currency = currency_vec
-                                        row_slice = row_slice
-                                        hasfilter = true
-                                        nodatamessage = no_data_message
-                                        hasdownload = true
-                                        file_content = file_content
-                                        file_name = download_file_name
-                                        number_keys = number_keys
-                                        haspageslenght=true
-                                    />
-                                }
-                            } else if selected_page.get() == "Quote Option".to_string() {
-                                quotes_history_table.set(t.extract());
-                                file_content.set(t.extract_csv_by_quote_status(Option::None, false));
-                                no_data_message.set(String::from("No Quote Options History Available"));
-                                view! {
-                                    <DataTable headers = headersource
-                                        key_to_display_in_mobile
-                                        keys = datakeysource
-                                        data = json_value
-                                        color=color_vec
-                                        currency = currency_vec
-                                        row_slice = row_slice
-                                        hasfilter = true
-                                        nodatamessage = no_data_message
-                                        hasdownload = true
-                                        file_content = file_content
-                                        file_name = download_file_name
-                                        number_keys = number_keys
-                                        haspageslenght=true
-                                    />
-                                }
-                            } else if selected_page.get() == "Perpetual Futures".to_string() {
-                                view! {
-                                    <div class = "p-5">
-                                        <span class = "opacity-50 font-extralight">Perpetual Future is not currently supported.</span>
-                                    </div>
-                                }.into_view()
-                            } else {
-                                view! {
-                                    <div class = "mt-5"><span class = "opacity-50 font-extralight">The selected page does not exist.</span></div>
-                                }.into_view()
-                            }
-
-                        },
-                        Err(_) => view! {
-                            <div class = "items-center mt-5">
-                                <div class = "flex justify-center ">
-                                <span class = "opacity-50 font-extralight">Cannot connect to server. Please refresh page.</span>
-                                </div>
-                            </div>
-                        }.into_view(),
-                    }
-                } else {
-                    view! {
-                        <div class = "p-5">
-                            <span class = "opacity-50 font-extralight">No quotes record found</span>
-                        </div>
-                    }.into_view()
+            move || match quote_history_resource.get() {
+                Some(Ok(t)) => {
+                    file_content.set(t.extract_csv_by_quote_status(Option::None, false));
+                    quote_history.set(t);
+                    has_error.set(false);
                 }
+                Some(Err(_)) => has_error.set(true),
+                None => {}
             }
         }
-        </Suspense>
-    }
-}
\ No newline at end of file
+        <Show
+            when = move || selected_page.get() != "Perpetual Futures"
+            fallback = move || {
+                let text = RwSignal::new(String::from("Perpetual Future is not currently supported."));
+                view! { <DefaultNone text = text/> }
+            }
+        >
+            <Show
+                when = move || !has_error.get()
+                fallback = move || {
+                    let text = RwSignal::new(String::from("Cannot connect to server. Please refresh page."));
+                    view! { <DefaultNone text = text/> }
+                }
+            >
+                <ServerDataTable headers=headersource
+                    key_to_display_in_mobile
+                    keys=datakeysource
+                    sort_fields=sort_fields
+                    data=json_value
+                    total_entries=total_entries
+                    page_request=page_request
+                    color=color_vec
+                    currency=currency_vec
+                    hasdownload=true
+                    hasfilter=true
+                    nodatamessage=no_data_message
+                    file_content = file_content
+                    file_name = download_file_name
+                    haspageslenght=true
+                />
+            </Show>
+        </Show>
+        </Transition>
+    }.into_view()
+}
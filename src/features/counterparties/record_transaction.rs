@@ -0,0 +1,311 @@
+use leptos::*;
+
+use crate::commons::models::counterparty::CounterParty;
+use crate::commons::models::currency::Currency;
+use crate::commons::models::user::Permission;
+use crate::commons::models::wallet::{WalletTransactionHistory, WalletTransactionRequest, WalletTransactionType};
+use crate::commons::services::currency::fetch_currencies;
+use crate::utilities::errors::JabraError;
+use crate::utilities::number_util::format_currency_with_scale;
+
+use super::services::{create_wallet_transaction, fetch_overview_data};
+
+/// Component for the New Transaction action of the Transfers page.
+/// Books a deposit, withdrawal, transfer or fee adjustment for the selected counterparty,
+/// with a preview of its impact on the balance of the currency. The transfers and the account overview
+/// are fetched again once it is booked.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn RecordWalletTransaction(
+    counterparty: RwSignal<String>,
+    counterparties: RwSignal<Vec<CounterParty>>,
+    transfers_resource: Resource<String, Result<WalletTransactionHistory, ServerFnError>>,
+) -> impl IntoView {
+    let permissions = expect_context::<crate::Permissions>().0;
+    let overview_refetcher = expect_context::<crate::OverviewRefetcher>().0;
+    let currencies_resource = create_local_resource(|| (), move |_| fetch_currencies());
+    let overview_resource = create_local_resource(
+        move || (counterparty.get(), overview_refetcher.get()),
+        move |(e, _)| fetch_overview_data(e),
+    );
+    let currencies = Signal::derive(move || {
+        currencies_resource
+            .get()
+            .and_then(|c| c.ok())
+            .map(|c| c.data.into_iter().filter(|c| c.is_active).collect::<Vec<Currency>>())
+            .unwrap_or_default()
+    });
+
+    let show_modal = RwSignal::new(false);
+    let transaction_type = RwSignal::new(WalletTransactionType::Deposit);
+    let currency_ticker = RwSignal::new(String::new());
+    let amount = RwSignal::new(String::new());
+    let fee_amount = RwSignal::new(String::new());
+    let txn_hash = RwSignal::new(String::new());
+    let reference = RwSignal::new(String::new());
+    let description = RwSignal::new(String::new());
+    let datetime = RwSignal::new(chrono::Local::now().format("%Y-%m-%dT%H:%M").to_string());
+    let form_error = RwSignal::new(Option::<String>::None);
+    let success_message = RwSignal::new(Option::<String>::None);
+
+    let build_request = move || {
+        let cp = counterparties
+            .get()
+            .into_iter()
+            .find(|c| c.ticker == counterparty.get())
+            .ok_or_else(|| String::from("Select a counterparty first."))?;
+        let currency = currencies
+            .get()
+            .into_iter()
+            .find(|c| c.ticker == currency_ticker.get())
+            .ok_or_else(|| String::from("Select a currency."))?;
+        WalletTransactionRequest::new(
+            &cp,
+            &currency,
+            transaction_type.get(),
+            &amount.get(),
+            &fee_amount.get(),
+            txn_hash.get(),
+            reference.get(),
+            description.get(),
+            &datetime.get(),
+        )
+    };
+    let reset_form = move || {
+        amount.set(String::new());
+        fee_amount.set(String::new());
+        txn_hash.set(String::new());
+        reference.set(String::new());
+        description.set(String::new());
+        form_error.set(None);
+    };
+
+    let save_action = create_action(move |request: &WalletTransactionRequest| {
+        let request = request.clone();
+        async move {
+            let name = WalletTransactionType::from_value(&request.transaction_type).map_or("Transaction", |t| t.get_name());
+            let requires_approval = request.approval_status.is_some();
+            match create_wallet_transaction(request).await {
+                Ok(_) => {
+                    reset_form();
+                    success_message.set(Some(if requires_approval {
+                        format!("{} booked, it counts once approved on the Pending Approval tab.", name)
+                    } else {
+                        format!("{} booked.", name)
+                    }));
+                    transfers_resource.refetch();
+                    overview_refetcher.update(|v| *v = !*v);
+                }
+                Err(e) => form_error.set(Some(JabraError::from_server_fn_error(&e).get_user_message())),
+            }
+        }
+    });
+    let is_saving = save_action.pending();
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        success_message.set(None);
+        match build_request() {
+            Ok(request) => {
+                form_error.set(None);
+                save_action.dispatch(request);
+            }
+            Err(message) => form_error.set(Some(message)),
+        }
+    };
+    let close = move |_| {
+        show_modal.set(false);
+        reset_form();
+        success_message.set(None);
+    };
+
+    let preview = move || {
+        let Ok(request) = build_request() else {
+            return ().into_view();
+        };
+        let currency = currency_ticker.get();
+        let scale = currencies
+            .get()
+            .iter()
+            .find(|c| c.ticker == currency)
+            .map_or(6, |c| c.display_scale);
+        let (balance, available_balance) = overview_resource
+            .get()
+            .and_then(|o| o.ok())
+            .and_then(|o| o.currencies.into_iter().find(|c| c.currency == currency))
+            .map_or((0.0, 0.0), |c| (c.balance, c.available_balance));
+        let impact = request.get_balance_impact();
+        let exceeds_available = impact < 0.0 && available_balance + impact < 0.0;
+        let format = move |value: f64| format!("{} {}", format_currency_with_scale(value, scale, ","), currency);
+        view! {
+            <div class="p-3 mt-3 text-xs rounded-lg bg-base-300">
+                <div class="pb-1 font-semibold text-success">BALANCE IMPACT</div>
+                <div class="flex justify-between"><span class="opacity-70">Balance</span><span>{format(balance)}</span></div>
+                <div class="flex justify-between">
+                    <span class="opacity-70">Impact, net of the fee</span>
+                    <span class:text-success = impact >= 0.0 class:text-error = impact < 0.0>{format(impact)}</span>
+                </div>
+                <div class="flex justify-between font-semibold"><span class="opacity-70">Balance after</span><span>{format(balance + impact)}</span></div>
+                <div class="flex justify-between"><span class="opacity-70">Available balance after</span><span class:text-error = exceeds_available>{format(available_balance + impact)}</span></div>
+                <Show when = move || exceeds_available fallback = || ()>
+                    <p class="pt-1 text-error">The transaction exceeds the available balance.</p>
+                </Show>
+                <Show when = move || request.approval_status.is_some() fallback = || ()>
+                    <p class="pt-1 opacity-70">It counts once approved by two admins on the Pending Approval tab.</p>
+                </Show>
+            </div>
+        }
+        .into_view()
+    };
+
+    view! {
+        <button
+            class = "btn btn-sm btn-success rounded-lg"
+            prop:disabled = move || !permissions.get().has(Permission::RecordWalletTransactions)
+            on:click = move |_| show_modal.set(true)
+        >
+            NEW TRANSACTION
+        </button>
+        <Show when = move || show_modal.get() fallback = || ()>
+            <div class="blur-bg">
+                <div class="modal-top-middle">
+                    <form class="modal-box rounded-box" on:submit=on_submit>
+                        <h3 class="text-2xl font-bold">NEW TRANSACTION</h3>
+                        <p class="py-2 text-sm opacity-70">{move || format!("Wallet transaction of {}.", counterparty.get())}</p>
+                        <div class="grid grid-cols-2 gap-3">
+                            <div class="flex flex-col gap-1">
+                                <label class="text-xs opacity-70" for="transaction_type">Type</label>
+                                <select
+                                    class="select select-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                                    id="transaction_type"
+                                    name="transaction_type"
+                                    on:change=move |event| {
+                                        if let Some(t) = WalletTransactionType::from_value(&event_target_value(&event)) {
+                                            transaction_type.set(t);
+                                        }
+                                    }
+                                >
+                                    {WalletTransactionType::ALL.into_iter().map(|t| view! {
+                                        <option value=t.as_str() selected=move || transaction_type.get() == t>{t.get_name()}</option>
+                                    }).collect_view()}
+                                </select>
+                            </div>
+                            <div class="flex flex-col gap-1">
+                                <label class="text-xs opacity-70" for="transaction_currency">Currency</label>
+                                <select
+                                    class="select select-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                                    id="transaction_currency"
+                                    name="transaction_currency"
+                                    required
+                                    on:change=move |event| currency_ticker.set(event_target_value(&event))
+                                >
+                                    <option value="" disabled selected=move || currency_ticker.get().is_empty()>Select</option>
+                                    {move || currencies.get().into_iter().map(|c| {
+                                        let ticker = c.ticker.clone();
+                                        view! {
+                                            <option value=c.ticker.clone() selected=move || currency_ticker.get() == ticker>{c.ticker}</option>
+                                        }
+                                    }).collect_view()}
+                                </select>
+                            </div>
+                            <div class="flex flex-col gap-1">
+                                <label class="text-xs opacity-70" for="transaction_amount">
+                                    {move || match transaction_type.get() {
+                                        WalletTransactionType::Deposit | WalletTransactionType::Withdrawal => "Amount",
+                                        _ => "Amount, signed",
+                                    }}
+                                </label>
+                                <input
+                                    class="input input-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                                    type="text"
+                                    inputmode="decimal"
+                                    id="transaction_amount"
+                                    name="transaction_amount"
+                                    required
+                                    prop:value=move || amount.get()
+                                    on:input=move |event| amount.set(event_target_value(&event))
+                                />
+                            </div>
+                            <div class="flex flex-col gap-1">
+                                <label class="text-xs opacity-70" for="transaction_fee_amount">Fee Amount</label>
+                                <input
+                                    class="input input-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                                    type="text"
+                                    inputmode="decimal"
+                                    id="transaction_fee_amount"
+                                    name="transaction_fee_amount"
+                                    placeholder="0"
+                                    prop:value=move || fee_amount.get()
+                                    on:input=move |event| fee_amount.set(event_target_value(&event))
+                                />
+                            </div>
+                            <div class="flex flex-col gap-1">
+                                <label class="text-xs opacity-70" for="transaction_txn_hash">Txn Hash</label>
+                                <input
+                                    class="input input-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                                    type="text"
+                                    id="transaction_txn_hash"
+                                    name="transaction_txn_hash"
+                                    prop:value=move || txn_hash.get()
+                                    on:input=move |event| txn_hash.set(event_target_value(&event))
+                                />
+                            </div>
+                            <div class="flex flex-col gap-1">
+                                <label class="text-xs opacity-70" for="transaction_reference">Reference</label>
+                                <input
+                                    class="input input-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                                    type="text"
+                                    id="transaction_reference"
+                                    name="transaction_reference"
+                                    prop:value=move || reference.get()
+                                    on:input=move |event| reference.set(event_target_value(&event))
+                                />
+                            </div>
+                            <div class="flex flex-col gap-1">
+                                <label class="text-xs opacity-70" for="transaction_datetime">Time</label>
+                                <input
+                                    class="input input-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                                    type="datetime-local"
+                                    id="transaction_datetime"
+                                    name="transaction_datetime"
+                                    required
+                                    prop:value=move || datetime.get()
+                                    on:change=move |event| datetime.set(event_target_value(&event))
+                                />
+                            </div>
+                            <div class="flex flex-col gap-1">
+                                <label class="text-xs opacity-70" for="transaction_description">Description</label>
+                                <input
+                                    class="input input-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                                    type="text"
+                                    id="transaction_description"
+                                    name="transaction_description"
+                                    prop:value=move || description.get()
+                                    on:input=move |event| description.set(event_target_value(&event))
+                                />
+                            </div>
+                        </div>
+                        {preview}
+                        {move || form_error.get().map(|message| view! {
+                            <p class="pt-2 text-sm text-error">{message}</p>
+                        })}
+                        {move || success_message.get().map(|message| view! {
+                            <p class="pt-2 text-sm text-success">{message}</p>
+                        })}
+                        <div class="modal-action">
+                            <button type="button" class="rounded btn btn-ghost btn-sm" title="Close" on:click=close>Close</button>
+                            <button type="submit" class="rounded btn btn-success btn-sm" title="Book" prop:disabled=is_saving>
+                                {move || if is_saving.get() {
+                                    view! { <span class="loading loading-spinner loading-sm"></span> }.into_view()
+                                } else {
+                                    "Book".into_view()
+                                }}
+                            </button>
+                        </div>
+                    </form>
+                </div>
+            </div>
+        </Show>
+    }
+}
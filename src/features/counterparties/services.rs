@@ -1,49 +1,68 @@
 
 use leptos::ServerFnError;
+use serde_json::json;
 
+use crate::commons::models::audit::{AuditAction, AuditLogRequest};
+use crate::commons::models::counterparty::CounterParty;
 use crate::commons::models::defaults::BlankRequest;
+use crate::commons::models::defaults::FilterOperator;
+use crate::commons::models::defaults::PageRequest;
+use crate::commons::models::defaults::QueryBuilder;
 use crate::commons::models::loan::GetLoanHistory;
 use crate::commons::models::loan::Loan;
 use crate::commons::models::quote::QuoteOption;
 use crate::commons::models::quote::QuoteOptionHistory;
 use crate::commons::models::trade::Trade;
 use crate::commons::models::trade::TradeHistory;
+use crate::commons::models::wallet::TransferApprovalRequest;
+use crate::commons::models::wallet::TransferApprovalResponse;
+use crate::commons::models::wallet::TransferApprovalStatus;
+use crate::commons::models::wallet::TransferApprovalUpdate;
 use crate::commons::models::wallet::WalletTransaction;
 use crate::commons::models::wallet::WalletTransactionHistory;
-use crate::utilities::cookies::get_jabra_cookie;
-use crate::utilities::cookies::JabraCookie;
-use crate::utilities::http_wrapper::call_and_parse;
+use crate::commons::models::wallet::WalletTransactionRequest;
+use crate::commons::services::audit::record_audit;
+use crate::utilities::cookies::get_cookie_user_id;
+use crate::utilities::errors::JabraError;
+use crate::utilities::http_wrapper::ApiClient;
+use crate::utilities::http_wrapper::Backend;
 use crate::utilities::http_wrapper::HttpMethod;
+use crate::utilities::http_wrapper::COOKIE_NAME;
 
+use super::models::CounterPartyStatement;
 use super::models::PortfolioOverviewResponse;
 
-/// Server function for getting the trade history based on the ticker.
+/// Server function for getting a page of the trade history based on the ticker,
+/// optionally filtered by the instrument kind.
 
 pub async fn get_trade_history(
     ticker: String,
+    instrument_kind: Option<String>,
+    page: PageRequest,
 ) -> Result<TradeHistory, ServerFnError> {
     log::info!("Called: {:?}", ticker);
 
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/trade?filter[counterparty_id][ticker][_eq]={}&sort[]=-date_created&filter[party_a][_neq]=null&filter[party_b][_neq]=null&fields={}", url.unwrap_or_default(), ticker, Trade::get_query());
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
+    let mut query = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!(ticker))
+        .filter("party_a", FilterOperator::NNull, json!(true))
+        .filter("party_b", FilterOperator::NNull, json!(true));
+    if let Some(kind) = instrument_kind {
+        query = query.filter("instrument_kind", FilterOperator::Eq, json!(kind));
+    }
+    let path = query
+        .sort("-date_created")
+        .fields(Trade::get_query())
+        .paginate(&page)
+        .to_path("trade");
 
     let response =
-        call_and_parse::<BlankRequest, TradeHistory>(Option::None, path, headers, HttpMethod::GET)
+        ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, TradeHistory>(Option::None, path, HttpMethod::GET)
             .await;
     match response {
         Ok(res) => Ok(res),
         Err(e) => {
             log::error!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
@@ -54,22 +73,15 @@ pub async fn fetch_transfers_data(
 ) -> Result<WalletTransactionHistory, ServerFnError> {
     log::info!("Called: {:?}", ticker);
 
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/wallet_transaction?filter[counterparty_id][ticker][_eq]={}&sort[]=-date_created&fields={}", url.unwrap_or_default(), ticker, WalletTransaction::get_query());
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
+    let path = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!(ticker))
+        .sort("-date_created")
+        .fields(WalletTransaction::get_query())
+        .to_path("wallet_transaction");
 
-    let response = call_and_parse::<BlankRequest, WalletTransactionHistory>(
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, WalletTransactionHistory>(
         Option::None,
         path,
-        headers,
         HttpMethod::GET,
     )
     .await;
@@ -77,39 +89,33 @@ pub async fn fetch_transfers_data(
         Ok(res) => Ok(res),
         Err(e) => {
             log::error!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
 
-/// Server function for getting the quote history based on the ticker.
+/// Server function for getting a page of the quote history based on the ticker.
 
 pub async fn get_quote_history(
     ticker: String,
+    page: PageRequest,
 ) -> Result<QuoteOptionHistory, ServerFnError> {
     log::info!("Called: {:?}", ticker);
 
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
-    let path = format!(
-        "{}/items/quotes_option?sort[]=-date_created&filter[quote_expiry][_neq]=null&filter[quote_status][_neq]=active&filter[counterparty_id][ticker][_eq]={}&filter[party_a][_neq]=null&filter[party_b][_neq]=null&fields={}",
-        url.unwrap_or_default(),
-        ticker,
-        QuoteOption::get_query()
-    );
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
+    let path = QueryBuilder::new()
+        .filter("quote_expiry", FilterOperator::NNull, json!(true))
+        .filter("quote_status", FilterOperator::Neq, json!("active"))
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!(ticker))
+        .filter("party_a", FilterOperator::NNull, json!(true))
+        .filter("party_b", FilterOperator::NNull, json!(true))
+        .sort("-date_created")
+        .fields(QuoteOption::get_query())
+        .paginate(&page)
+        .to_path("quotes_option");
 
-    let response = call_and_parse::<BlankRequest, QuoteOptionHistory>(
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, QuoteOptionHistory>(
         Option::None,
         path,
-        headers,
         HttpMethod::GET,
     )
     .await;
@@ -117,7 +123,7 @@ pub async fn get_quote_history(
         Ok(res) => Ok(res),
         Err(e) => {
             log::error!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
@@ -129,27 +135,15 @@ pub async fn get_loans(
 ) -> Result<GetLoanHistory, ServerFnError> {
     log::info!("Called: {:?}", ticker);
 
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
-    let path = format!(
-        "{}/items/loan?sort[]=-date_created&filter[counterparty_id][ticker][_eq]={}&fields={}",
-        url.unwrap_or_default(),
-        ticker,
-        Loan::get_query()
-    );
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
+    let path = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!(ticker))
+        .sort("-date_created")
+        .fields(Loan::get_query())
+        .to_path("loan");
 
-    let response = call_and_parse::<BlankRequest, GetLoanHistory>(
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, GetLoanHistory>(
         Option::None,
         path,
-        headers,
         HttpMethod::GET,
     )
     .await;
@@ -157,7 +151,7 @@ pub async fn get_loans(
         Ok(res) => Ok(res),
         Err(e) => {
             log::error!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
@@ -170,22 +164,12 @@ pub async fn fetch_overview_data(
 ) -> Result<PortfolioOverviewResponse, ServerFnError> {
     log::info!("Called: {:?}", ticker);
 
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    // let url = std::env::var("WASMCLOUDURL").unwrap();
-    let url = option_env!("WASMCLOUDURL");
 
     let path = format!(
-        "{}/portfolios/summaries?counterparty={}&currency=USD",
-        url.unwrap_or_default(), ticker
+        "/portfolios/summaries?counterparty={}&currency=USD",
+        ticker
     );
-    let mut headers = reqwest::header::HeaderMap::new();
     // log::info!("Bearer: {:?}", bearer.clone());
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
 
     // let mut headers = reqwasm::http::Headers::new();
     // headers.append("Authorization", &bearer);
@@ -211,11 +195,9 @@ pub async fn fetch_overview_data(
     //     Err(e) => Err(crate::common::errors::JabraError::from(e))
     // };
 
-
-    let response = call_and_parse::<BlankRequest, PortfolioOverviewResponse>(
+    let response = ApiClient::new(Backend::WasmCloud).call_and_parse::<BlankRequest, PortfolioOverviewResponse>(
         Option::None,
         path,
-        headers,
         HttpMethod::GET,
     )
     .await;
@@ -223,7 +205,581 @@ pub async fn fetch_overview_data(
         Ok(res) => Ok(res),
         Err(e) => {
             log::error!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+/// Server function to get the statement of a counterparty for a period.
+/// `start` and `end` are UTC timestamps, the end is exclusive.
+/// Everything booked since the start is fetched, so the balances can be rolled back from the current ones.
+
+pub async fn get_counter_party_statement(
+    counterparty: CounterParty,
+    start: String,
+    end: String,
+) -> Result<CounterPartyStatement, ServerFnError> {
+    let ticker = counterparty.ticker.clone();
+    let overview = fetch_overview_data(ticker.clone()).await?;
+
+    let trades_path = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!(ticker))
+        .filter("party_a", FilterOperator::NNull, json!(true))
+        .filter("party_b", FilterOperator::NNull, json!(true))
+        .filter("date_created", FilterOperator::Gte, json!(start))
+        .sort("date_created")
+        .fields(Trade::get_query())
+        .limit(-1)
+        .to_path("trade");
+    let trades = match ApiClient::new(Backend::Directus)
+        .call_and_parse::<BlankRequest, TradeHistory>(Option::None, trades_path, HttpMethod::GET)
+        .await
+    {
+        Ok(res) => res.data,
+        Err(e) => {
+            log::error!("error fetching the trades of the statement: {:?}", e);
+            return Err(ServerFnError::from(e));
+        }
+    };
+
+    let transactions_path = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!(ticker))
+        .filter("venue_transaction_datetime", FilterOperator::Gte, json!(start))
+        .sort("venue_transaction_datetime")
+        .fields(WalletTransaction::get_query())
+        .limit(-1)
+        .to_path("wallet_transaction");
+    let transactions = match ApiClient::new(Backend::Directus)
+        .call_and_parse::<BlankRequest, WalletTransactionHistory>(Option::None, transactions_path, HttpMethod::GET)
+        .await
+    {
+        Ok(res) => res.data,
+        Err(e) => {
+            log::error!("error fetching the wallet transactions of the statement: {:?}", e);
+            return Err(ServerFnError::from(e));
+        }
+    };
+
+    let loans_path = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!(ticker))
+        .filter("trade_date", FilterOperator::Gte, json!(start))
+        .filter("trade_date", FilterOperator::Lt, json!(end))
+        .sort("trade_date")
+        .fields(Loan::get_query())
+        .limit(-1)
+        .to_path("loan");
+    let loans = match ApiClient::new(Backend::Directus)
+        .call_and_parse::<BlankRequest, GetLoanHistory>(Option::None, loans_path, HttpMethod::GET)
+        .await
+    {
+        Ok(res) => res.data,
+        Err(e) => {
+            log::error!("error fetching the loans of the statement: {:?}", e);
+            return Err(ServerFnError::from(e));
+        }
+    };
+
+    Ok(CounterPartyStatement::new(&counterparty, start, end, overview, trades, transactions, loans))
+}
+
+/// Server function to book a wallet transaction of a counterparty.
+/// Deposits and withdrawals are created as pending, they count once approved on the Pending Approval tab.
+
+pub async fn create_wallet_transaction(request: WalletTransactionRequest) -> Result<bool, ServerFnError> {
+    let audit = AuditLogRequest::new(AuditAction::CreateWalletTransaction, "wallet_transaction", json!(request));
+    let path = String::from("/items/wallet_transaction");
+
+    let response = ApiClient::new(Backend::Directus)
+        .call::<WalletTransactionRequest>(Some(request), path, HttpMethod::POST)
+        .await;
+    match response {
+        Ok(res) => {
+            record_audit(audit).await;
+            Ok(res)
+        }
+        Err(e) => {
+            log::error!("error creating the wallet transaction: {:?}", e);
+            Err(ServerFnError::from(e))
         }
     }
-}
\ No newline at end of file
+}
+
+/// Server function for getting the deposits and withdrawals of a counterparty that wait for an approval.
+
+pub async fn get_pending_transfers(ticker: String) -> Result<WalletTransactionHistory, ServerFnError> {
+    let path = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!(ticker))
+        .filter(
+            "approval_status",
+            FilterOperator::In,
+            json!([TransferApprovalStatus::Pending.as_str(), TransferApprovalStatus::FirstApproved.as_str()]),
+        )
+        .sort("date_created")
+        .fields(WalletTransaction::get_query())
+        .limit(-1)
+        .to_path("wallet_transaction");
+
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, WalletTransactionHistory>(
+        Option::None,
+        path,
+        HttpMethod::GET,
+    )
+    .await;
+    match response {
+        Ok(res) => Ok(res),
+        Err(e) => {
+            log::error!("error fetching the pending transfers of {}: {:?}", ticker, e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+/// Server function to approve a deposit or withdrawal, with the four-eyes rule.
+/// The first approval moves a pending transaction to `first_approved`, the confirmation by a second admin to `approved`.
+/// The admin who approved first cannot confirm, and a withdrawal is checked against the available balance at both steps.
+
+pub async fn approve_transfer(id: u32) -> Result<bool, ServerFnError> {
+    let transaction = get_wallet_transaction(id).await?;
+    let user_id = get_cookie_user_id(COOKIE_NAME);
+    let (action, request) = match transaction.get_approval_status() {
+        TransferApprovalStatus::Pending => (
+            AuditAction::ApproveTransfer,
+            TransferApprovalRequest {
+                approval_status: TransferApprovalStatus::FirstApproved.as_str().to_string(),
+                first_approved_by: Some(user_id.clone()),
+                approved_by: None,
+                rejected_by: None,
+            },
+        ),
+        TransferApprovalStatus::FirstApproved => {
+            if transaction.first_approved_by.as_deref() == Some(user_id.as_str()) {
+                return Err(ServerFnError::from(JabraError::ApprovalError(String::from(
+                    "You approved this transaction already, a second admin has to confirm it.",
+                ))));
+            }
+            (
+                AuditAction::ConfirmTransfer,
+                TransferApprovalRequest {
+                    approval_status: TransferApprovalStatus::Approved.as_str().to_string(),
+                    first_approved_by: None,
+                    approved_by: Some(user_id.clone()),
+                    rejected_by: None,
+                },
+            )
+        }
+        status => {
+            return Err(ServerFnError::from(JabraError::ApprovalError(format!(
+                "This transaction is no longer pending, its status is {}.",
+                status.get_name()
+            ))));
+        }
+    };
+    if transaction.is_withdrawal() {
+        check_available_balance(&transaction).await?;
+    }
+    update_transfer_approval(transaction, action, request).await
+}
+
+/// Server function to reject a deposit or withdrawal that waits for an approval, at either step.
+
+pub async fn reject_transfer(id: u32) -> Result<bool, ServerFnError> {
+    let transaction = get_wallet_transaction(id).await?;
+    if !transaction.get_approval_status().is_pending() {
+        return Err(ServerFnError::from(JabraError::ApprovalError(format!(
+            "This transaction is no longer pending, its status is {}.",
+            transaction.get_approval_status().get_name()
+        ))));
+    }
+    let request = TransferApprovalRequest {
+        approval_status: TransferApprovalStatus::Rejected.as_str().to_string(),
+        first_approved_by: None,
+        approved_by: None,
+        rejected_by: Some(get_cookie_user_id(COOKIE_NAME)),
+    };
+    update_transfer_approval(transaction, AuditAction::RejectTransfer, request).await
+}
+
+async fn get_wallet_transaction(id: u32) -> Result<WalletTransaction, ServerFnError> {
+    let path = QueryBuilder::new()
+        .filter("id", FilterOperator::Eq, json!(id))
+        .fields(WalletTransaction::get_query())
+        .limit(1)
+        .to_path("wallet_transaction");
+
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, WalletTransactionHistory>(
+        Option::None,
+        path,
+        HttpMethod::GET,
+    )
+    .await;
+    match response {
+        Ok(res) => res
+            .data
+            .into_iter()
+            .next()
+            .ok_or_else(|| ServerFnError::from(JabraError::NoDataFoundError)),
+        Err(e) => {
+            log::error!("error fetching the wallet transaction {}: {:?}", id, e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+/// Rejects a withdrawal larger than the available balance of the counterparty in its currency.
+
+async fn check_available_balance(transaction: &WalletTransaction) -> Result<(), ServerFnError> {
+    let overview = fetch_overview_data(transaction.counterparty_id.ticker.clone()).await?;
+    let currency = transaction.currency_id.ticker.clone();
+    let available_balance = overview
+        .currencies
+        .iter()
+        .find(|c| c.currency == currency)
+        .map_or(0.0, |c| c.available_balance);
+    if transaction.amount.abs() > available_balance {
+        return Err(ServerFnError::from(JabraError::ApprovalError(format!(
+            "The withdrawal of {} {} exceeds the available balance of {} {}.",
+            transaction.amount.abs(),
+            currency,
+            available_balance,
+            currency
+        ))));
+    }
+    Ok(())
+}
+
+/// Writes a step of the approval, only if the transaction still has the approval status and first approver it was checked against.
+/// An update that matches no transaction, because another admin changed it in between, is refused.
+/// This only narrows the race of the portal, the Directus update permission on `wallet_transaction` has to allow
+/// the change only from `pending` or `first_approved`, and not by the `first_approved_by` admin, for a guarantee on the server.
+
+async fn update_transfer_approval(
+    transaction: WalletTransaction,
+    action: AuditAction,
+    request: TransferApprovalRequest,
+) -> Result<bool, ServerFnError> {
+    let id = transaction.id;
+    let audit = AuditLogRequest::new(action, "wallet_transaction", json!(request)).with_before(json!([{
+        "id": id,
+        "approval_status": transaction.approval_status,
+        "first_approved_by": transaction.first_approved_by,
+        "approved_by": transaction.approved_by,
+        "rejected_by": transaction.rejected_by,
+        "amount": transaction.amount,
+        "transaction_type": transaction.transaction_type,
+    }]));
+    let update = TransferApprovalUpdate::new(&transaction, request);
+
+    let response = ApiClient::new(Backend::Directus)
+        .call_and_parse::<TransferApprovalUpdate, TransferApprovalResponse>(
+            Some(update),
+            String::from("/items/wallet_transaction"),
+            HttpMethod::PATCH,
+        )
+        .await;
+    match response {
+        Ok(res) if res.data.is_empty() => Err(ServerFnError::from(JabraError::ApprovalError(String::from(
+            "This transaction was changed by another admin, please reload it.",
+        )))),
+        Ok(_) => {
+            record_audit(audit).await;
+            Ok(true)
+        }
+        Err(e) => {
+            log::error!("error updating the approval of wallet transaction {}: {:?}", id, e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::commons::models::counterparty::GetCounterPartiesResponse;
+    use crate::features::trades::history::page::get_instrument_kind;
+    use crate::utilities::mock_backend::{assert_error_paths, fixture, get_error, url, MockBackend, USER_ID};
+
+    fn get_page() -> PageRequest {
+        PageRequest {
+            page: 1,
+            page_size: 15,
+            sort: String::new(),
+            search: String::new(),
+        }
+    }
+
+    fn get_acme() -> CounterParty {
+        serde_json::from_str::<GetCounterPartiesResponse>(fixture!("counterparties.json"))
+            .unwrap()
+            .data
+            .into_iter()
+            .find(|c| c.ticker == "ACME")
+            .unwrap()
+    }
+
+    fn get_deposit() -> WalletTransactionRequest {
+        WalletTransactionRequest {
+            counterparty_id: 2,
+            currency_id: 2,
+            transaction_type: String::from("deposit"),
+            amount: 1000.0,
+            fee_amount: 0.0,
+            txn_hash: String::from("0x9a4e"),
+            reference: String::from("DEP-303"),
+            description: String::from("Margin top up"),
+            venue_transaction_datetime: String::from("2030-01-02T08:00:00.000Z"),
+            is_submitted: false,
+            approval_status: Some(String::from("pending")),
+        }
+    }
+
+    /// Serves the pending withdrawal of `fixtures/wallet_transactions_pending.json` with the given changes.
+    fn serve_pending_withdrawal(backend: &MockBackend, changes: serde_json::Value) {
+        let mut transactions = serde_json::from_str::<serde_json::Value>(fixture!("wallet_transactions_pending.json")).unwrap();
+        for (key, value) in changes.as_object().unwrap() {
+            transactions["data"][0][key] = value.clone();
+        }
+        backend.respond(
+            HttpMethod::GET,
+            url(Backend::Directus, "/items/wallet_transaction"),
+            200,
+            &transactions.to_string(),
+        );
+    }
+
+    fn get_approval_updates(backend: &MockBackend) -> Vec<serde_json::Value> {
+        backend
+            .requests_to(HttpMethod::PATCH, &url(Backend::Directus, "/items/wallet_transaction"))
+            .iter()
+            .map(|r| r.get_body()["data"].clone())
+            .collect()
+    }
+
+    fn get_approval_filters(backend: &MockBackend) -> Vec<serde_json::Value> {
+        backend
+            .requests_to(HttpMethod::PATCH, &url(Backend::Directus, "/items/wallet_transaction"))
+            .iter()
+            .map(|r| r.get_body()["query"]["filter"].clone())
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_trade_history_fetches_a_page_of_the_trades_of_the_counterparty() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let trades = get_trade_history(String::from("ACME"), get_instrument_kind("Option"), get_page()).await.unwrap();
+        assert_eq!(trades.data[0].id, 101);
+        assert_eq!(trades.get_filter_count(), 1);
+        let request = &backend.requests()[0];
+        assert_eq!(request.get_filter()["_and"][3], json!({"instrument_kind": {"_eq": "option"}}));
+        assert_eq!(request.get_param("limit"), "15");
+        assert_eq!(request.get_param("meta"), "filter_count");
+    }
+
+    #[wasm_bindgen_test]
+    async fn fetch_transfers_data_fetches_the_wallet_transactions() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let transfers = fetch_transfers_data(String::from("ACME")).await.unwrap();
+        assert_eq!(transfers.data[0].reference, "DEP-301");
+        assert_eq!(
+            backend.requests()[0].get_filter(),
+            json!({"counterparty_id": {"ticker": {"_eq": "ACME"}}})
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_quote_history_fetches_a_page_of_the_closed_quotes() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let quotes = get_quote_history(String::from("ACME"), get_page()).await.unwrap();
+        assert_eq!(quotes.data[0].id, 501);
+        assert_eq!(quotes.get_filter_count(), 1);
+        let request = &backend.requests()[0];
+        assert_eq!(request.get_filter()["_and"][1], json!({"quote_status": {"_neq": "active"}}));
+        assert_eq!(request.get_param("offset"), "0");
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_loans_fetches_the_loans_of_the_counterparty() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let loans = get_loans(String::from("ACME")).await.unwrap();
+        assert_eq!(loans.data[0].id, 401);
+    }
+
+    #[wasm_bindgen_test]
+    async fn fetch_overview_data_asks_wasmcloud_for_the_usd_summary() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let overview = fetch_overview_data(String::from("ACME")).await.unwrap();
+        assert_eq!(overview.total_equity, 250_150.0);
+        let request = &backend.requests()[0];
+        assert_eq!(request.endpoint, url(Backend::WasmCloud, "/portfolios/summaries"));
+        assert_eq!(request.get_param("counterparty"), "ACME");
+        assert_eq!(request.get_param("currency"), "USD");
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_counter_party_statement_collects_the_bookings_of_the_period() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let start = String::from("2030-01-01T00:00:00.000Z");
+        let end = String::from("2030-02-01T00:00:00.000Z");
+        let statement = get_counter_party_statement(get_acme(), start.clone(), end.clone()).await.unwrap();
+        assert_eq!(statement.ticker, "ACME");
+        assert_eq!(statement.trades.len(), 1);
+        assert_eq!(statement.transactions.len(), 1);
+        assert_eq!(statement.loans.len(), 1);
+        let loans = backend.requests_to(HttpMethod::GET, &url(Backend::Directus, "/items/loan"));
+        assert_eq!(loans[0].get_filter()["_and"][1], json!({"trade_date": {"_gte": start}}));
+        assert_eq!(loans[0].get_filter()["_and"][2], json!({"trade_date": {"_lt": end}}));
+    }
+
+    #[wasm_bindgen_test]
+    async fn create_wallet_transaction_posts_the_request_and_records_the_audit() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        assert!(create_wallet_transaction(get_deposit()).await.unwrap());
+        let created = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/wallet_transaction"));
+        assert_eq!(created[0].get_body(), json!(get_deposit()));
+        assert_eq!(backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log")).len(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_pending_transfers_fetches_the_transactions_waiting_for_an_approval() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures().respond(
+            HttpMethod::GET,
+            url(Backend::Directus, "/items/wallet_transaction"),
+            200,
+            fixture!("wallet_transactions_pending.json"),
+        );
+        let transfers = get_pending_transfers(String::from("ACME")).await.unwrap();
+        assert_eq!(transfers.data[0].id, 302);
+        assert_eq!(
+            backend.requests()[0].get_filter()["_and"][1],
+            json!({"approval_status": {"_in": ["pending", "first_approved"]}})
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn approve_transfer_records_the_first_approval() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        serve_pending_withdrawal(&backend, json!({}));
+        assert!(approve_transfer(302).await.unwrap());
+        assert_eq!(
+            get_approval_updates(&backend),
+            vec![json!({"approval_status": "first_approved", "first_approved_by": USER_ID})]
+        );
+        assert_eq!(
+            get_approval_filters(&backend),
+            vec![json!({"_and": [
+                {"id": {"_eq": 302}},
+                {"approval_status": {"_eq": "pending"}},
+                {"first_approved_by": {"_null": true}},
+            ]})]
+        );
+        assert_eq!(backend.requests_to(HttpMethod::GET, &url(Backend::WasmCloud, "/portfolios/summaries")).len(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    async fn approve_transfer_is_confirmed_by_a_second_admin() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        serve_pending_withdrawal(
+            &backend,
+            json!({"approval_status": "first_approved", "first_approved_by": "2a1d0c4b-first-approver"}),
+        );
+        assert!(approve_transfer(302).await.unwrap());
+        assert_eq!(
+            get_approval_updates(&backend),
+            vec![json!({"approval_status": "approved", "approved_by": USER_ID})]
+        );
+        assert_eq!(
+            get_approval_filters(&backend),
+            vec![json!({"_and": [
+                {"id": {"_eq": 302}},
+                {"approval_status": {"_eq": "first_approved"}},
+                {"first_approved_by": {"_eq": "2a1d0c4b-first-approver"}},
+            ]})]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn approve_transfer_refuses_a_transaction_changed_by_another_admin() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures().respond(
+            HttpMethod::PATCH,
+            url(Backend::Directus, "/items/wallet_transaction"),
+            200,
+            r#"{"data": []}"#,
+        );
+        serve_pending_withdrawal(&backend, json!({}));
+        assert!(matches!(get_error(approve_transfer(302).await), JabraError::ApprovalError(_)));
+        assert!(backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log")).is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn approve_transfer_cannot_be_confirmed_by_the_first_approver() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        serve_pending_withdrawal(&backend, json!({"approval_status": "first_approved", "first_approved_by": USER_ID}));
+        assert!(matches!(get_error(approve_transfer(302).await), JabraError::ApprovalError(_)));
+        assert!(get_approval_updates(&backend).is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn approve_transfer_refuses_a_withdrawal_above_the_available_balance() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        serve_pending_withdrawal(&backend, json!({"amount": 500000.0}));
+        match get_error(approve_transfer(302).await) {
+            JabraError::ApprovalError(message) => assert!(message.contains("exceeds the available balance"), "{}", message),
+            e => panic!("expected an approval error, got {:?}", e),
+        }
+        assert!(get_approval_updates(&backend).is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn reject_transfer_records_the_rejecting_admin() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        serve_pending_withdrawal(&backend, json!({}));
+        assert!(reject_transfer(302).await.unwrap());
+        assert_eq!(
+            get_approval_updates(&backend),
+            vec![json!({"approval_status": "rejected", "rejected_by": USER_ID})]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn reject_transfer_refuses_a_transaction_that_is_no_longer_pending() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        serve_pending_withdrawal(&backend, json!({"approval_status": "approved"}));
+        assert!(matches!(get_error(reject_transfer(302).await), JabraError::ApprovalError(_)));
+    }
+
+    #[wasm_bindgen_test]
+    async fn counterparty_services_return_the_errors_of_the_backend() {
+        assert_error_paths(|| get_trade_history(String::from("ACME"), None, get_page())).await;
+        assert_error_paths(|| fetch_transfers_data(String::from("ACME"))).await;
+        assert_error_paths(|| get_quote_history(String::from("ACME"), get_page())).await;
+        assert_error_paths(|| get_loans(String::from("ACME"))).await;
+        assert_error_paths(|| fetch_overview_data(String::from("ACME"))).await;
+        assert_error_paths(|| {
+            get_counter_party_statement(
+                get_acme(),
+                String::from("2030-01-01T00:00:00.000Z"),
+                String::from("2030-02-01T00:00:00.000Z"),
+            )
+        })
+        .await;
+        assert_error_paths(|| create_wallet_transaction(get_deposit())).await;
+        assert_error_paths(|| get_pending_transfers(String::from("ACME"))).await;
+        assert_error_paths(|| approve_transfer(302)).await;
+        assert_error_paths(|| reject_transfer(302)).await;
+    }
+}
@@ -0,0 +1,170 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use leptos::*;
+
+use crate::commons::models::counterparty::CounterParty;
+use crate::components::download_anchor::{create_file_url, download_file};
+use crate::utilities::errors::JabraError;
+
+use super::models::CounterPartyStatement;
+use super::services::get_counter_party_statement;
+
+/// Component for the Generate Statement action of the Counterparty page.
+/// Fetches the balances, trades, wallet transactions and loans of the selected counterparty for a period,
+/// then downloads the statement as HTML or opens it in a new tab with the print dialog, to be saved as a PDF.
+/// The period defaults to the previous month.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn GenerateStatement(
+    counterparty: RwSignal<String>,
+    counterparties: RwSignal<Vec<CounterParty>>,
+) -> impl IntoView {
+    let today = chrono::Utc::now().date_naive();
+    let first_of_month = today.with_day(1).unwrap_or(today);
+    let last_month_end = first_of_month - Duration::days(1);
+    let last_month_start = last_month_end.with_day(1).unwrap_or(last_month_end);
+
+    let show_modal = RwSignal::new(false);
+    let start_date = RwSignal::new(last_month_start.format("%Y-%m-%d").to_string());
+    let end_date = RwSignal::new(last_month_end.format("%Y-%m-%d").to_string());
+    let statement = RwSignal::new(Option::<CounterPartyStatement>::None);
+    let error_message = RwSignal::new(Option::<String>::None);
+
+    let generate_action = create_action(move |(cp, start, end): &(CounterParty, String, String)| {
+        let cp = cp.clone();
+        let start = start.clone();
+        let end = end.clone();
+        async move {
+            match get_counter_party_statement(cp, start, end).await {
+                Ok(res) => statement.set(Some(res)),
+                Err(e) => error_message.set(Some(JabraError::from_server_fn_error(&e).get_user_message())),
+            }
+        }
+    });
+    let is_generating = generate_action.pending();
+
+    let generate = move |_| {
+        statement.set(None);
+        error_message.set(None);
+        let Some(cp) = counterparties
+            .get_untracked()
+            .into_iter()
+            .find(|c| c.ticker == counterparty.get_untracked())
+        else {
+            error_message.set(Some(String::from("Select a counterparty first.")));
+            return;
+        };
+        let parse = |date: String| NaiveDate::parse_from_str(&date, "%Y-%m-%d");
+        match (parse(start_date.get_untracked()), parse(end_date.get_untracked())) {
+            (Ok(start), Ok(end)) if start <= end => {
+                generate_action.dispatch((
+                    cp,
+                    format!("{}T00:00:00", start.format("%Y-%m-%d")),
+                    format!("{}T00:00:00", (end + Duration::days(1)).format("%Y-%m-%d")),
+                ));
+            }
+            (Ok(_), Ok(_)) => error_message.set(Some(String::from("The start date must be before the end date."))),
+            _ => error_message.set(Some(String::from("Enter a valid start and end date."))),
+        }
+    };
+    let download_html = move |_| {
+        if let Some(s) = statement.get_untracked() {
+            download_file(&s.to_html(false), &format!("{}.html", s.get_file_name()), "text/html");
+        }
+    };
+    let print_pdf = move |_| {
+        if let Some(s) = statement.get_untracked() {
+            let url = create_file_url(&s.to_html(true), "text/html");
+            if let Err(e) = window().open_with_url_and_target(&url, "_blank") {
+                log::error!("error opening the statement: {:?}", e);
+                error_message.set(Some(String::from("The statement could not be opened, allow pop-ups and try again.")));
+            }
+        }
+    };
+    let close = move |_| {
+        show_modal.set(false);
+        statement.set(None);
+        error_message.set(None);
+    };
+
+    view! {
+        <button
+            class = "btn btn-sm btn-success rounded-lg"
+            on:click = move |_| show_modal.set(true)
+        >
+            GENERATE STATEMENT
+        </button>
+        <Show when = move || show_modal.get() fallback = || ()>
+            <div class="blur-bg">
+                <div class="modal-top-middle">
+                    <div class="modal-box rounded-box">
+                        <h3 class="text-2xl font-bold">STATEMENT</h3>
+                        <p class="py-2 text-sm opacity-70">{move || format!("Statement of {} for the period, in UTC.", counterparty.get())}</p>
+                        <div class="flex flex-wrap gap-4">
+                            <div class="flex flex-col gap-1">
+                                <label class="text-xs opacity-70" for="statement_start_date">From</label>
+                                <input
+                                    class="input input-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                                    type="date"
+                                    id="statement_start_date"
+                                    name="statement_start_date"
+                                    prop:value=move || start_date.get()
+                                    on:change=move |event| {
+                                        start_date.set(event_target_value(&event));
+                                        statement.set(None);
+                                    }
+                                />
+                            </div>
+                            <div class="flex flex-col gap-1">
+                                <label class="text-xs opacity-70" for="statement_end_date">To</label>
+                                <input
+                                    class="input input-sm text-sm rounded shadow-md border-gray-800 hover:shadow-sm hover:shadow-success"
+                                    type="date"
+                                    id="statement_end_date"
+                                    name="statement_end_date"
+                                    prop:value=move || end_date.get()
+                                    on:change=move |event| {
+                                        end_date.set(event_target_value(&event));
+                                        statement.set(None);
+                                    }
+                                />
+                            </div>
+                        </div>
+                        {move || error_message.get().map(|message| view! {
+                            <p class="pt-2 text-sm text-error">{message}</p>
+                        })}
+                        {move || statement.get().map(|s| view! {
+                            <p class="pt-2 text-sm text-success">
+                                {format!(
+                                    "Statement ready: {} currencies, {} trades, {} wallet transactions, {} loans.",
+                                    s.currencies.len(),
+                                    s.trades.len(),
+                                    s.transactions.len(),
+                                    s.loans.len()
+                                )}
+                            </p>
+                        })}
+                        <div class="modal-action">
+                            <button class="rounded btn btn-ghost btn-sm" title="Close" on:click=close>Close</button>
+                            <Show
+                                when = move || statement.get().is_some()
+                                fallback = move || view! {
+                                    <button class="rounded btn btn-success btn-sm" title="Generate" prop:disabled=is_generating on:click=generate>
+                                        {move || if is_generating.get() {
+                                            view! { <span class="loading loading-spinner loading-sm"></span> }.into_view()
+                                        } else {
+                                            "Generate".into_view()
+                                        }}
+                                    </button>
+                                }
+                            >
+                                <button class="rounded btn btn-outline btn-success btn-sm" title="Download HTML" on:click=download_html>HTML</button>
+                                <button class="rounded btn btn-success btn-sm" title="Print or save as PDF" on:click=print_pdf>PDF</button>
+                            </Show>
+                        </div>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
@@ -1,20 +1,33 @@
 use leptos::*;
 
-use crate::commons::models::wallet::WalletTransaction;
+use crate::commons::models::counterparty::CounterParty;
+use crate::commons::models::user::Permission;
+use crate::commons::models::wallet::TransferApprovalStatus;
 use crate::commons::models::wallet::WalletTransactionHistory;
 
 use crate::commons::models::wallet::ExtractedWalletTransaction;
+use crate::components::confirm_modal::ConfirmModal;
+use crate::components::copy_button::CopyButton;
 use crate::components::data_table_local::DataTable;
+use crate::components::default_none::DefaultNone;
 use crate::components::menu_button::MenuButton;
+use crate::utilities::cookies::get_cookie_user_id;
+use crate::utilities::errors::JabraError;
+use crate::utilities::http_wrapper::COOKIE_NAME;
+use crate::utilities::number_util::format_currency_with_scale;
 
-use super::services::fetch_transfers_data;
+use super::record_transaction::RecordWalletTransaction;
+use super::services::{approve_transfer, fetch_overview_data, fetch_transfers_data, get_pending_transfers, reject_transfer};
 
 /// Component for CounterParty Transfers Page.
 /// Has the actual view for the Transfers page.
 
 #[allow(non_snake_case)]
 #[component]
-pub fn CounterPartyTransfersPage(counterparty: RwSignal<String>) -> impl IntoView {
+pub fn CounterPartyTransfersPage(
+    counterparty: RwSignal<String>,
+    counterparties: RwSignal<Vec<CounterParty>>,
+) -> impl IntoView {
     let transfers_resource: Resource<String, Result<WalletTransactionHistory, ServerFnError>> =
         create_local_resource(counterparty, move |e| fetch_transfers_data(e));
     let data_table = RwSignal::new(Vec::<ExtractedWalletTransaction>::default());
@@ -33,7 +46,7 @@ pub fn CounterPartyTransfersPage(counterparty: RwSignal<String>) -> impl IntoVie
                 <div class = "flex justify-between">
                     <div class = "flex flex-wrap justify-start gap-4 flex-0">
                     {
-                        let page_keys = vec![String::from("All"), String::from("Deposit"), String::from("Withdrawal"), String::from("Transfer")];
+                        let page_keys = vec![String::from("All"), String::from("Deposit"), String::from("Withdrawal"), String::from("Transfer"), String::from("Pending Approval")];
                         page_keys.into_iter().map(|k| {
                             view! {
                                 <MenuButton selected_page = selected_page page = k.clone() name = k.clone() />
@@ -41,6 +54,9 @@ pub fn CounterPartyTransfersPage(counterparty: RwSignal<String>) -> impl IntoVie
                         }).collect_view()
                     }
                     </div>
+                    <div class = "flex-0">
+                        <RecordWalletTransaction counterparty = counterparty counterparties = counterparties transfers_resource = transfers_resource/>
+                    </div>
                 </div>
             </div>
             <Suspense
@@ -57,9 +73,9 @@ pub fn CounterPartyTransfersPage(counterparty: RwSignal<String>) -> impl IntoVie
                     if let Some(data) = transfers_resource.and_then(|tr| {tr.clone()}) {
                         match data {
                             Ok(t) => {
-                                let headersource = RwSignal::new(vec![String::from("Action"), String::from("Amount"), String::from("Currency"), String::from("Fee Amount"), String::from("Description"), String::from("Time")]);
-                                let keysource = RwSignal::new(vec![String::from("action"), String::from("amount"), String::from("currency"), String::from("fee_amount"),String::from("description"), String::from("time")]);
-                                let number_keys = RwSignal::new(vec![String::from("action"), String::from("amount"), String::from("currency"), String::from("fee_amount"),String::from("description"), String::from("time")]);
+                                let headersource = RwSignal::new(vec![String::from("Action"), String::from("Amount"), String::from("Currency"), String::from("Fee Amount"), String::from("Description"), String::from("Txn Hash"), String::from("Reference"), String::from("Submitted"), String::from("Approval"), String::from("Time")]);
+                                let keysource = RwSignal::new(vec![String::from("action"), String::from("amount"), String::from("currency"), String::from("fee_amount"),String::from("description"), String::from("txn_hash"), String::from("reference"), String::from("submitted"), String::from("approval_status"), String::from("time")]);
+                                let number_keys = RwSignal::new(vec![String::from("action"), String::from("amount"), String::from("currency"), String::from("fee_amount"),String::from("description"), String::from("txn_hash"), String::from("reference"), String::from("submitted"), String::from("approval_status"), String::from("time")]);
                                 let row_slice = RwSignal::new(15);
                                 let no_data_message = RwSignal::new(String::from("No transaction record found"));
                                 if selected_page.get() == "All".to_string() {
@@ -122,6 +138,10 @@ pub fn CounterPartyTransfersPage(counterparty: RwSignal<String>) -> impl IntoVie
                                         // pagesheight=RwSignal::new(String::from("max-h-[580px]"))
                                         />
                                    }
+                                } else if selected_page.get() == "Pending Approval".to_string() {
+                                   view! {
+                                        <PendingTransfers counterparty = counterparty transfers_resource = transfers_resource />
+                                   }
                                 } else {
                                     view! {
                                         <div class = "items-center mt-5">
@@ -151,4 +171,193 @@ pub fn CounterPartyTransfersPage(counterparty: RwSignal<String>) -> impl IntoVie
             }
             </Suspense>
         }.into_view()
-}
\ No newline at end of file
+}
+
+/// Component for the queue of the deposits and withdrawals that wait for an approval.
+/// A transaction is approved by a first admin, then confirmed by a second one (four-eyes rule),
+/// the admin who approved first cannot confirm it. Withdrawals show the available balance they are checked against.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn PendingTransfers(
+    counterparty: RwSignal<String>,
+    transfers_resource: Resource<String, Result<WalletTransactionHistory, ServerFnError>>,
+) -> impl IntoView {
+    let permissions = expect_context::<crate::Permissions>().0;
+    let can_approve = move || permissions.get().has(Permission::ApproveTransfers);
+    let user_id = get_cookie_user_id(COOKIE_NAME);
+
+    let pending_resource = create_local_resource(counterparty, move |e| get_pending_transfers(e));
+    let overview_resource = create_local_resource(counterparty, move |e| fetch_overview_data(e));
+    let get_available_balance = move |currency: &str| {
+        overview_resource
+            .get()
+            .and_then(|o| o.ok())
+            .and_then(|o| o.currencies.into_iter().find(|c| c.currency == currency))
+            .map(|c| c.available_balance)
+    };
+
+    let message = RwSignal::new(Option::<(bool, String)>::None);
+    // The transaction waiting for confirmation, as its id and `true` to approve or `false` to reject.
+    let pending_change = RwSignal::new(Option::<(u32, bool)>::None);
+    let (show_confirm_modal, set_show_confirm_modal) = create_signal(false);
+    let approval_action = create_action(move |(id, approve): &(u32, bool)| {
+        let id = *id;
+        let approve = *approve;
+        async move {
+            let result = if approve { approve_transfer(id).await } else { reject_transfer(id).await };
+            match result {
+                Ok(_) => {
+                    message.set(Some((true, String::from(if approve { "The transaction was approved." } else { "The transaction was rejected." }))));
+                    pending_resource.refetch();
+                    overview_resource.refetch();
+                    transfers_resource.refetch();
+                }
+                Err(e) => message.set(Some((false, JabraError::from_server_fn_error(&e).get_user_message()))),
+            }
+        }
+    });
+    let is_pending = approval_action.pending();
+    let confirm_change = move || {
+        if let Some(change) = pending_change.get_untracked() {
+            approval_action.dispatch(change);
+        }
+    };
+
+    view! {
+        {move || message.get().map(|(success, text)| view! {
+            <p class = "px-4 pb-2 text-sm" class:text-success = success class:text-error = !success>{text}</p>
+        })}
+        <Transition
+            fallback = move || view! {
+                <div class = "items-center mt-5">
+                    <div class = "flex justify-center ">
+                        <crate::components::loading_spinners::Spinners size=crate::components::component_size::ComponentSize::SMALL _type=crate::components::component_type::ComponentType::SUCCESS />
+                    </div>
+                </div>
+            }
+        >
+        {
+            move || match pending_resource.get() {
+                Some(Ok(res)) => {
+                    if res.data.is_empty() {
+                        let text = RwSignal::new(String::from("No deposit or withdrawal waits for an approval"));
+                        return view! { <DefaultNone text = text/> }.into_view();
+                    }
+                    view! {
+                        <div class = "overflow-auto border border-success border-opacity-40">
+                            <table class = "table table-xs table-zebra-zebra">
+                                <thead>
+                                    <tr class = "font-light text-center text-success bg-base-300">
+                                        <th>"Time"</th>
+                                        <th>"Action"</th>
+                                        <th>"Amount"</th>
+                                        <th>"Currency"</th>
+                                        <th>"Fee Amount"</th>
+                                        <th>"Available Balance"</th>
+                                        <th>"Txn Hash"</th>
+                                        <th>"Reference"</th>
+                                        <th>"Status"</th>
+                                        <th></th>
+                                    </tr>
+                                </thead>
+                                <tbody class = "text-center font-extralight">
+                                {
+                                    res.data.into_iter().map(|t| {
+                                        let id = t.id;
+                                        let status = t.get_approval_status();
+                                        let scale = t.currency_id.display_scale;
+                                        let currency = t.currency_id.ticker.clone();
+                                        let amount = t.amount.abs();
+                                        let is_withdrawal = t.is_withdrawal();
+                                        let approved_by_me = t.first_approved_by.as_deref() == Some(user_id.as_str());
+                                        let available_balance = {
+                                            let currency = currency.clone();
+                                            move || {
+                                                if !is_withdrawal {
+                                                    return view! { <span class = "opacity-50">"- -"</span> }.into_view();
+                                                }
+                                                match get_available_balance(&currency) {
+                                                    Some(b) => view! {
+                                                        <span class:text-error = amount > b>{format_currency_with_scale(b, scale, ",")}</span>
+                                                    }.into_view(),
+                                                    None => view! { <span class = "opacity-50">"- -"</span> }.into_view(),
+                                                }
+                                            }
+                                        };
+                                        view! {
+                                            <tr>
+                                                <td>{crate::utilities::date_util::format_utc_str_to_local_str(t.venue_transaction_datetime.clone())}</td>
+                                                <td>{t.transaction_type.clone()}</td>
+                                                <td>{format_currency_with_scale(t.amount, scale, ",")}</td>
+                                                <td>{currency.clone()}</td>
+                                                <td>{format_currency_with_scale(t.fee_amount, scale, ",")}</td>
+                                                <td>{available_balance}</td>
+                                                <td>
+                                                    {if t.txn_hash.is_empty() {
+                                                        view! { <span class = "opacity-50">"- -"</span> }.into_view()
+                                                    } else {
+                                                        view! { <CopyButton text = t.txn_hash.clone() shorten = true /> }.into_view()
+                                                    }}
+                                                </td>
+                                                <td>{t.reference.clone()}</td>
+                                                <td>
+                                                    <span class:text-warning = status == TransferApprovalStatus::FirstApproved>{status.get_name()}</span>
+                                                    <Show when = move || approved_by_me fallback = || ()>
+                                                        <div class = "text-xs opacity-50">"Approved by you"</div>
+                                                    </Show>
+                                                </td>
+                                                <td class = "flex justify-end gap-2">
+                                                    <button
+                                                        class = "rounded btn btn-xs btn-success"
+                                                        title = if approved_by_me { "A second admin has to confirm" } else { "" }
+                                                        prop:disabled = move || !can_approve() || approved_by_me || is_pending.get()
+                                                        on:click = move |_| {
+                                                            message.set(None);
+                                                            pending_change.set(Some((id, true)));
+                                                            set_show_confirm_modal.set(true);
+                                                        }
+                                                    >
+                                                        {if status == TransferApprovalStatus::FirstApproved { "CONFIRM" } else { "APPROVE" }}
+                                                    </button>
+                                                    <button
+                                                        class = "rounded btn btn-xs btn-error"
+                                                        prop:disabled = move || !can_approve() || is_pending.get()
+                                                        on:click = move |_| {
+                                                            message.set(None);
+                                                            pending_change.set(Some((id, false)));
+                                                            set_show_confirm_modal.set(true);
+                                                        }
+                                                    >
+                                                        REJECT
+                                                    </button>
+                                                </td>
+                                            </tr>
+                                        }
+                                    }).collect_view()
+                                }
+                                </tbody>
+                            </table>
+                        </div>
+                    }.into_view()
+                }
+                Some(Err(e)) => {
+                    let text = RwSignal::new(JabraError::from_server_fn_error(&e).get_user_message());
+                    view! { <DefaultNone text = text/> }.into_view()
+                }
+                None => ().into_view(),
+            }
+        }
+        </Transition>
+        {move || {
+            view! {
+                <ConfirmModal
+                    when = show_confirm_modal.get()
+                    write_signal = set_show_confirm_modal
+                    function = confirm_change
+                    action = approval_action
+                />
+            }
+        }}
+    }
+}
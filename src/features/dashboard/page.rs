@@ -2,12 +2,14 @@ use leptos::*;
 use leptos_router::use_location;
 
 use crate::{
+    commons::models::user::Permission,
     components::{
-        loading_button::LoadingButton, not_found::NotFound, please_login::PleaseLogin,
-        sidebar::Sidebar,
+        loading_button::LoadingButton, not_found::NotFound, not_permitted::NotPermitted,
+        please_login::PleaseLogin, sidebar::Sidebar,
     },
     features::{
-        counterparties::page::CounterParties,
+        audit::page::AuditPage,
+        counterparties::{manage::ManageCounterParties, page::CounterParties},
         login::page::Login,
         quotes::{active::page::ActiveQuotes, builder::page::QuoteBuilder},
         riskslide::page::RiskSlide,
@@ -18,10 +20,21 @@ use crate::{
     },
 };
 
+/// Returns the permission needed to open a page, `None` when every logged in user can open it.
+pub fn get_route_permission(path: &str) -> Option<Permission> {
+    match path {
+        "/trades/termsheets" => Some(Permission::ApproveTermSheets),
+        "/audit" => Some(Permission::ViewAuditLog),
+        "/counterparties/manage" => Some(Permission::ManageCounterparties),
+        _ => None,
+    }
+}
+
 #[allow(non_snake_case)]
 #[component]
 pub fn PageManager() -> impl IntoView {
     let location = use_location().pathname;
+    let permissions = expect_context::<crate::Permissions>().0;
 
     view! {
         <div class="main-content gap-2 flex">
@@ -69,6 +82,10 @@ pub fn PageManager() -> impl IntoView {
                                         "/login" => view! { <Login/> },
                                         _ => view! { <PleaseLogin/> },
                                     }
+                                } else if get_route_permission(location.get().as_str())
+                                    .is_some_and(|p| !permissions.get().has(p))
+                                {
+                                    view! { <NotPermitted/> }
                                 } else {
                                     match location.get().as_str() {
                                         "/" => view! { <QuoteBuilder/> },
@@ -86,9 +103,11 @@ pub fn PageManager() -> impl IntoView {
                                         "/trades/expiring" => view! { <ExpiringTrades/> },
                                         "/quotes/builder" => view! { <QuoteBuilder/> },
                                         "/counterparties" => view! { <CounterParties/> },
+                                        "/counterparties/manage" => view! { <ManageCounterParties/> },
                                         "/trades/positions" => view! { <Positions/> },
                                         "/trades/history" => view! { <TradeHistoryPage/> },
                                         "/riskslide" => view! { <RiskSlide/> },
+                                        "/audit" => view! { <AuditPage/> },
                                         _ => view! { <NotFound/> },
                                     }
                                 }
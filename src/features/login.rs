@@ -1,3 +1,4 @@
 mod models;
 pub mod page;
+pub mod watchdog;
 mod services;
@@ -40,11 +40,16 @@ pub fn Login() -> impl IntoView {
 #[component]
 pub fn LoginIsland() -> impl IntoView {
     let login_has_error = create_rw_signal(false);
-    let login_action = create_action(move |(userid, password): &(String, String)| {
+    let login_error_message = create_rw_signal(String::from("Your email or password is not valid."));
+    // Set when the user has two-factor authentication, the form then asks for the one-time password.
+    let otp_required = create_rw_signal(false);
+    let login_action = create_action(move |(userid, password, otp): &(String, String, Option<String>)| {
         let userid_clone = userid.clone();
         let password_clone = password.clone();
+        let otp_clone = otp.clone();
         async move {
-            let result = directus_login(userid_clone, password_clone).await;
+            let has_otp = otp_clone.is_some();
+            let result = directus_login(userid_clone, password_clone, otp_clone).await;
             let navigate = leptos_router::use_navigate();
 
             match result {
@@ -57,7 +62,24 @@ pub fn LoginIsland() -> impl IntoView {
                         false
                     }
                 }
-                Err(_e) => {login_has_error.set(true);false},
+                Err(e) => {
+                    match JabraError::from_server_fn_error(&e) {
+                        JabraError::OtpRequiredError if !has_otp => otp_required.set(true),
+                        JabraError::OtpRequiredError => {
+                            login_error_message.set(String::from("The one-time password is not valid."));
+                            login_has_error.set(true);
+                        }
+                        JabraError::LoginError => {
+                            login_error_message.set(String::from("Your email or password is not valid."));
+                            login_has_error.set(true);
+                        }
+                        error => {
+                            login_error_message.set(error.get_user_message());
+                            login_has_error.set(true);
+                        }
+                    }
+                    false
+                }
             }
         }
     });
@@ -86,13 +108,15 @@ pub fn LoginIsland() -> impl IntoView {
 
     let email_ref = create_node_ref::<Input>();
     let pass_ref = create_node_ref::<Input>();
+    let otp_ref = create_node_ref::<Input>();
 
     view! {
         <form on:submit=move |ev| {
             ev.prevent_default();
             let userid = email_ref.get().expect("input to exist");
             let password = pass_ref.get().expect("input to exist");
-            login_action.dispatch((userid.value(), password.value()));
+            let otp = otp_ref.get().map(|o| o.value()).filter(|o| !o.is_empty());
+            login_action.dispatch((userid.value(), password.value(), otp));
         }>
 
             <label for="userid" class="label">
@@ -118,6 +142,23 @@ pub fn LoginIsland() -> impl IntoView {
                 required
                 node_ref=pass_ref
             />
+            <Show when=move || otp_required.get()>
+                <label for="otp" class="label">
+                    <span class="label-text">One-time password</span>
+                </label>
+                <input
+                    type="text"
+                    name="otp"
+                    class="input input-sm w-full bg-white rounded hover:shadow-md text-black border-gray-800 shadow-md tracking-widest"
+                    placeholder="6-digit code of your authenticator app"
+                    inputmode="numeric"
+                    autocomplete="one-time-code"
+                    pattern="[0-9]{6}"
+                    maxlength="6"
+                    required
+                    node_ref=otp_ref
+                />
+            </Show>
             <label class="label">
                 <a href="#" class="label-text-alt link link-hover">
                     Forgot password?
@@ -153,7 +194,7 @@ pub fn LoginIsland() -> impl IntoView {
             <StatusModal
                 signal=login_has_error
                 title="ERROR!".to_string()
-                description="Your email or password is not valid.".to_string()
+                description=login_error_message.get_untracked()
                 status=ComponentStatus::Error
                 position=Position::TopMiddle
             />
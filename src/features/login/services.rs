@@ -1,16 +1,26 @@
-use crate::{commons::models::login::{DirectusLoginRequest, DirectusLoginResponse}, utilities::{cookies::{set_jabra_cookie, JabraCookie}, errors::JabraError, http_wrapper::{call_and_parse, HttpMethod}}};
+use crate::{commons::{models::login::{DirectusLoginRequest, DirectusLoginResponse}, services::user::get_user_permissions}, utilities::{configuration::get_config, cookies::{set_jabra_cookie, JabraCookie}, errors::JabraError, http_wrapper::{call_and_parse, HttpMethod}}};
 use leptos::*;
 
-pub async fn directus_login(userid: String, password: String) -> Result<bool, ServerFnError> {
-    let url = option_env!("DIRECTUSURL");
+/// Directus error code of a login without, or with an invalid, one-time password.
+const INVALID_OTP_CODE: &str = "INVALID_OTP";
+
+/// Directus error code of a login with a wrong email or password.
+const INVALID_CREDENTIALS_CODE: &str = "INVALID_CREDENTIALS";
+
+/// Logs in to Directus and stores the session cookie.
+/// `otp` is the one-time password of the users with two-factor authentication, when it is missing or invalid
+/// the login fails with a `JabraError::OtpRequiredError`.
+/// Refused credentials fail with a `JabraError::LoginError`, any other error of Directus is returned as is,
+/// so a busy or unreachable server is not reported as a wrong password.
+pub async fn directus_login(userid: String, password: String, otp: Option<String>) -> Result<bool, ServerFnError> {
     // let url = if let Ok(var) = std::env::var("DIRECTUSURL") {
     //     var
     // } else {
     //     "".to_string()
     // };
-    let path = format!("{}/auth/login", url.unwrap_or_default());
+    let path = format!("{}/auth/login", get_config().directus_url);
     let email = userid.clone();
-    let login_request = DirectusLoginRequest::new(userid.into(), password.into());
+    let login_request = DirectusLoginRequest::new(userid.into(), password.into()).with_otp(otp);
     let response = call_and_parse::<DirectusLoginRequest, DirectusLoginResponse>(
         Some(login_request),
         path,
@@ -30,21 +40,120 @@ pub async fn directus_login(userid: String, password: String) -> Result<bool, Se
             let expiration_time =
                 chrono::Utc::now().timestamp_millis() + res.data.expires - 600_000;
 
+            // A user whose permissions cannot be fetched can still log in, but every restricted action stays hidden.
+            let permissions = get_user_permissions(res.data.access_token.clone())
+                .await
+                .unwrap_or_default();
+
             let jabra_cookie = JabraCookie::new(
                 email,
                 res.data.access_token.clone(),
                 res.data.refresh_token,
                 expiration_time,
-            );
+            )
+            .with_permissions(permissions);
             set_jabra_cookie(jabra_cookie, "admin_portal_csr".to_string()).await;
 
             Ok(true)
         }
         Err(e) => {
             log::info!("Login Error: {}", e.to_string());
-            Err(ServerFnError::ServerError(
-                JabraError::LoginError.to_string(),
-            ))
+            if e.get_code() == Some(INVALID_OTP_CODE) {
+                return Err(ServerFnError::from(JabraError::OtpRequiredError));
+            }
+            let is_invalid_credentials = e.get_code() == Some(INVALID_CREDENTIALS_CODE)
+                || matches!(&e, JabraError::Unauthorized(details) if details.status == 401);
+            if is_invalid_credentials {
+                return Err(ServerFnError::from(JabraError::LoginError));
+            }
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use serde_json::json;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::cookies::get_session_cookie;
+    use crate::utilities::http_wrapper::{Backend, COOKIE_NAME};
+    use crate::utilities::mock_backend::{fixture, get_error, settle, url, MockBackend, ACCESS_TOKEN, REFRESH_TOKEN};
+
+    #[wasm_bindgen_test]
+    async fn directus_login_stores_the_session_with_the_permissions() {
+        let backend = MockBackend::start().await;
+        backend.sign_out().await;
+        backend.serve_fixtures();
+        let result = directus_login(String::from("ada.trader@example.com"), String::from("secret"), None).await;
+        assert!(result.unwrap());
+        settle().await;
+
+        let login = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/auth/login"));
+        assert_eq!(login[0].get_body(), json!({"email": "ada.trader@example.com", "password": "secret"}));
+        let cookie = get_session_cookie(COOKIE_NAME).expect("the session is stored");
+        assert_eq!(cookie.user_id, "ada.trader@example.com");
+        assert_eq!(cookie.access_token, ACCESS_TOKEN);
+        assert_eq!(cookie.refresh_token, REFRESH_TOKEN);
+        assert!(!cookie.is_expired());
+        assert_eq!(cookie.permissions.role, "Administrator");
+    }
+
+    #[wasm_bindgen_test]
+    async fn directus_login_sends_the_one_time_password() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let result =
+            directus_login(String::from("ada.trader@example.com"), String::from("secret"), Some(String::from("123456"))).await;
+        assert!(result.unwrap());
+        let login = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/auth/login"));
+        assert_eq!(login[0].get_body()["otp"], "123456");
+    }
+
+    #[wasm_bindgen_test]
+    async fn directus_login_refuses_invalid_credentials() {
+        let backend = MockBackend::start().await;
+        backend.sign_out().await;
+        backend.serve_fixtures().respond(
+            HttpMethod::POST,
+            url(Backend::Directus, "/auth/login"),
+            401,
+            fixture!("error_invalid_credentials.json"),
+        );
+        let result = directus_login(String::from("ada.trader@example.com"), String::from("wrong"), None).await;
+        assert_eq!(get_error(result), JabraError::LoginError);
+        assert!(get_session_cookie(COOKIE_NAME).is_none());
+    }
+
+    #[wasm_bindgen_test]
+    async fn directus_login_asks_for_the_one_time_password() {
+        let backend = MockBackend::start().await;
+        backend.sign_out().await;
+        backend.serve_fixtures().respond(
+            HttpMethod::POST,
+            url(Backend::Directus, "/auth/login"),
+            401,
+            fixture!("error_invalid_otp.json"),
+        );
+        let result = directus_login(String::from("ada.trader@example.com"), String::from("secret"), None).await;
+        assert_eq!(get_error(result), JabraError::OtpRequiredError);
+    }
+
+    #[wasm_bindgen_test]
+    async fn directus_login_fails_when_the_system_is_busy() {
+        let backend = MockBackend::start().await;
+        backend.sign_out().await;
+        backend.fail_all(503, "");
+        let result = directus_login(String::from("ada.trader@example.com"), String::from("secret"), None).await;
+        let error = get_error(result);
+        assert!(error.is_retryable());
+        match error {
+            JabraError::APIResponseError(details) => {
+                assert_eq!(details.status, 503);
+                assert_eq!(details.messages, vec![String::from("System is busy")]);
+            }
+            e => panic!("expected an API response error, got {:?}", e),
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,225 @@
+use leptos::{html::Input, *};
+use leptos_router::use_navigate;
+use leptos_use::use_interval_fn;
+
+use crate::commons::models::user::UserPermissions;
+use crate::commons::services::session::on_logout_broadcast;
+use crate::utilities::configuration::get_config;
+use crate::utilities::cookies::{get_cookie_permissions, get_cookie_user_id, get_session_cookie, remove_jabra_cookie};
+use crate::utilities::errors::JabraError;
+use crate::utilities::http_wrapper::{refresh_session, COOKIE_NAME};
+use crate::{CheckCookie, Permissions, Refetcher, SessionExpired};
+
+use super::services::directus_login;
+
+/// Minutes before the session expires when the token is refreshed, when not set in the configuration.
+pub const DEFAULT_SESSION_WARNING_MINUTES: i64 = 5;
+/// Interval between two checks of the session.
+const WATCHDOG_INTERVAL_MILLIS: u64 = 30_000;
+
+/// Watches the session of the logged in user in the background.
+///
+/// The token is refreshed `session_warning_minutes` before the session expires. When it cannot be refreshed the
+/// user is warned until it expires, and a re-login modal is then opened over the current page, so the unsaved
+/// forms of the page are kept once the user is logged in again.
+/// A logout in another tab ends the session of this tab too.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn SessionWatchdog() -> impl IntoView {
+    let auth_resource = expect_context::<CheckCookie>().0;
+    let session_expired = expect_context::<SessionExpired>().0;
+    let permissions = expect_context::<Permissions>().0;
+    let refetcher = expect_context::<Refetcher>().0;
+
+    let warning_millis = get_config()
+        .session_warning_minutes
+        .unwrap_or(DEFAULT_SESSION_WARNING_MINUTES)
+        * 60_000;
+    // The minutes left before the session expires, set when the token could not be refreshed ahead.
+    let minutes_left = RwSignal::new(Option::<i64>::None);
+    let refreshing = store_value(false);
+
+    let is_logged_in = move || matches!(auth_resource.get(), Some(Ok(true)));
+
+    let check_session = move || {
+        if refreshing.get_value()
+            || session_expired.get_untracked()
+            || !matches!(auth_resource.get_untracked(), Some(Ok(true)))
+        {
+            return;
+        }
+        let Some(cookie) = get_session_cookie(COOKIE_NAME) else {
+            return;
+        };
+        let time_left = cookie.expires_in - chrono::Utc::now().timestamp_millis();
+        if time_left > warning_millis {
+            minutes_left.set(None);
+            return;
+        }
+        refreshing.set_value(true);
+        spawn_local(async move {
+            match refresh_session(cookie).await {
+                Ok(_) => minutes_left.set(None),
+                Err(e) => {
+                    log::error!("error refreshing the session ahead of its expiry: {:?}", e);
+                    if time_left <= 0 {
+                        minutes_left.set(None);
+                        session_expired.set(true);
+                    } else {
+                        minutes_left.set(Some((time_left + 59_999) / 60_000));
+                    }
+                }
+            }
+            refreshing.set_value(false);
+        });
+    };
+    let _ = use_interval_fn(check_session, WATCHDOG_INTERVAL_MILLIS);
+
+    // The cookie is already removed by the tab that logged out.
+    let navigate = use_navigate();
+    on_logout_broadcast(move || {
+        minutes_left.set(None);
+        session_expired.set(false);
+        permissions.set(UserPermissions::default());
+        refetcher.update(|v| *v = !*v);
+        navigate("/login", Default::default());
+    });
+
+    let email_ref = create_node_ref::<Input>();
+    let pass_ref = create_node_ref::<Input>();
+    let otp_ref = create_node_ref::<Input>();
+    let login_error = RwSignal::new(Option::<String>::None);
+    let otp_required = RwSignal::new(false);
+    let login_action = create_action(move |(userid, password, otp): &(String, String, Option<String>)| {
+        let userid = userid.clone();
+        let password = password.clone();
+        let otp = otp.clone();
+        async move {
+            let has_otp = otp.is_some();
+            match directus_login(userid, password, otp).await {
+                Ok(true) => {
+                    // The page is not rendered again unless the permissions of the user changed.
+                    let new_permissions = get_cookie_permissions(COOKIE_NAME);
+                    if permissions.get_untracked() != new_permissions {
+                        permissions.set(new_permissions);
+                    }
+                    login_error.set(None);
+                    minutes_left.set(None);
+                    session_expired.set(false);
+                }
+                Ok(false) => login_error.set(Some(JabraError::LoginError.to_string())),
+                Err(e) => match JabraError::from_server_fn_error(&e) {
+                    JabraError::OtpRequiredError if !has_otp => otp_required.set(true),
+                    error => login_error.set(Some(error.get_user_message())),
+                },
+            }
+        }
+    });
+    let is_pending = login_action.pending();
+
+    let sign_out = move |_| {
+        remove_jabra_cookie(COOKIE_NAME);
+        minutes_left.set(None);
+        session_expired.set(false);
+        refetcher.update(|v| *v = !*v);
+    };
+
+    view! {
+        <Show when=move || is_logged_in() && !session_expired.get() && minutes_left.get().is_some()>
+            <div class="toast toast-end z-50">
+                <div role="alert" class="alert alert-warning shadow-lg">
+                    <span class="text-sm">
+                        {move || format!(
+                            "Your session expires in {} min and could not be renewed.",
+                            minutes_left.get().unwrap_or_default(),
+                        )}
+                    </span>
+                    <div class="flex gap-2">
+                        <button class="btn btn-sm" on:click=move |_| check_session()>
+                            STAY SIGNED IN
+                        </button>
+                        <button class="btn btn-sm btn-ghost" on:click=move |_| session_expired.set(true)>
+                            SIGN IN AGAIN
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+        <Show when=move || is_logged_in() && session_expired.get()>
+            <div class="blur-bg">
+                <div class="modal-center">
+                    <div class="modal-box bg-base-100 rounded-box">
+                        <h3 class="font-bold text-2xl">Your session has ended</h3>
+                        <p class="py-2 text-sm opacity-70">
+                            Log in again to continue, the changes on this page are kept.
+                        </p>
+                        <form on:submit=move |ev| {
+                            ev.prevent_default();
+                            let userid = email_ref.get().expect("input to exist");
+                            let password = pass_ref.get().expect("input to exist");
+                            let otp = otp_ref.get().map(|o| o.value()).filter(|o| !o.is_empty());
+                            login_action.dispatch((userid.value(), password.value(), otp));
+                        }>
+                            <label for="relogin_userid" class="label">
+                                <span class="label-text">Email</span>
+                            </label>
+                            <input
+                                type="text"
+                                id="relogin_userid"
+                                name="userid"
+                                class="input input-sm w-full bg-white rounded hover:shadow-md text-black border-gray-800 shadow-md"
+                                prop:value=get_cookie_user_id(COOKIE_NAME)
+                                required
+                                node_ref=email_ref
+                            />
+                            <label for="relogin_password" class="label">
+                                <span class="label-text">Password</span>
+                            </label>
+                            <input
+                                type="password"
+                                id="relogin_password"
+                                name="password"
+                                class="input input-sm w-full bg-white rounded hover:shadow-md text-black border-gray-800 shadow-md"
+                                required
+                                node_ref=pass_ref
+                            />
+                            <Show when=move || otp_required.get()>
+                                <label for="relogin_otp" class="label">
+                                    <span class="label-text">One-time password</span>
+                                </label>
+                                <input
+                                    type="text"
+                                    id="relogin_otp"
+                                    name="otp"
+                                    class="input input-sm w-full bg-white rounded hover:shadow-md text-black border-gray-800 shadow-md tracking-widest"
+                                    inputmode="numeric"
+                                    autocomplete="one-time-code"
+                                    pattern="[0-9]{6}"
+                                    maxlength="6"
+                                    required
+                                    node_ref=otp_ref
+                                />
+                            </Show>
+                            {move || login_error.get().map(|message| view! {
+                                <p class="pt-2 text-sm text-error">{message}</p>
+                            })}
+                            <div class="modal-action">
+                                <button type="button" class="btn btn-sm btn-ghost rounded" on:click=sign_out>
+                                    SIGN OUT
+                                </button>
+                                <button type="submit" class="btn btn-sm btn-success rounded" prop:disabled=is_pending>
+                                    {move || if is_pending.get() {
+                                        view! { <span class="loading loading-spinner loading-sm"></span> }.into_view()
+                                    } else {
+                                        "LOG IN".into_view()
+                                    }}
+                                </button>
+                            </div>
+                        </form>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
@@ -5,6 +5,7 @@ use leptos::html::Q;
 use leptos::*;
 use crate::commons::models::counterparty::CounterParty;
 use crate::commons::models::counterparty::GetCounterPartiesResponse;
+use crate::commons::models::user::Permission;
 use crate::commons::models::quote::{
     ApproveTradeQuoteResponse, ModifyQuoteResponse, QuoteOption, QuotesOptionForStatusChange,
     QuotesOptionsForModification,
@@ -23,8 +24,10 @@ use crate::components::confirm_batch_quotes_modal::ConfirmModalBatchQuotes;
 use crate::components::data_table_local::*;
 use crate::components::error_modal::ErrorModal;
 use crate::components::loading_spinners::Spinners;
+use crate::components::payoff_chart::PayoffChart;
 use crate::components::success_refetch_modal::SuccessModalRefetch;
 use crate::components::success_then_refetch_modal::SuccessModalWithRefetch;
+use crate::utilities::errors::JabraError;
 use crate::utilities::date_util::{
     calculate_time_difference, convert_utc_to_local, format_date, parse_str_to_utc_datetime_str,
 };
@@ -34,8 +37,9 @@ use serde_json::Value;
 #[allow(non_snake_case)]
 #[component]
 pub fn ActiveQuotes() -> impl IntoView {
-    let counterparties_resource: Resource<(), Result<GetCounterPartiesResponse, ServerFnError>> =
-        create_local_resource(|| (), move |_| get_counter_parties());
+    let counterparties_refetcher = expect_context::<crate::CounterPartiesRefetcher>().0;
+    let counterparties_resource: Resource<bool, Result<GetCounterPartiesResponse, ServerFnError>> =
+        create_local_resource(counterparties_refetcher, move |_| get_counter_parties());
     let counterparties = RwSignal::new(Vec::<CounterParty>::default());
     view! {
         <Suspense>
@@ -76,11 +80,14 @@ pub fn ActiveQuotesPage(counterparties: RwSignal<Vec<CounterParty>>) -> impl Int
     let sort_asc_pending = RwSignal::new(true);
     let has_pending = RwSignal::new(false);
 
+    // The approval blocked by the credit limits, with the breached limits, until it is overridden or cancelled.
+    let credit_limit_breach = create_rw_signal(Option::<(Vec<QuotesOptionForStatusChange>, Vec<String>)>::None);
+
     let approve_reject_quotes_option_action: Action<
-        (String, Vec<QuotesOptionForStatusChange>),
+        (String, Vec<QuotesOptionForStatusChange>, Option<String>),
         (),
     > = create_action(
-        move |(status, req): &(String, Vec<QuotesOptionForStatusChange>)| {
+        move |(status, req, override_reason): &(String, Vec<QuotesOptionForStatusChange>, Option<String>)| {
             let stat = if status.clone() == "approved" {
                 "approval".to_string()
             } else {
@@ -88,9 +95,10 @@ pub fn ActiveQuotesPage(counterparties: RwSignal<Vec<CounterParty>>) -> impl Int
             };
             // let request = ApproveTradeQuoteRequest::new(group_ids.clone(), status.clone());
             let request = req.clone();
+            let override_reason = override_reason.clone();
             async move {
                 // approve_trade_quote(request).await
-                let result = approve_reject_quotes_option(request).await;
+                let result = approve_reject_quotes_option(request.clone(), override_reason).await;
                 match result {
                     Ok(res) => {
                         if res {
@@ -107,14 +115,18 @@ pub fn ActiveQuotesPage(counterparties: RwSignal<Vec<CounterParty>>) -> impl Int
                             });
                         }
                     }
-                    Err(_e) => {
-                        show_approve_quote_alert.set(true);
-                        approve_quote_response.update(|v| {
-                            v.success = false;
-                            v.message =
-                                "Your session has ended. Please relog your account.".to_string()
-                        });
-                    }
+                    Err(e) => match JabraError::from_server_fn_error(&e) {
+                        JabraError::CreditLimitBreach(breaches) => {
+                            credit_limit_breach.set(Some((request, breaches)));
+                        }
+                        error => {
+                            show_approve_quote_alert.set(true);
+                            approve_quote_response.update(|v| {
+                                v.success = false;
+                                v.message = error.get_user_message()
+                            });
+                        }
+                    },
                 }
             }
         },
@@ -181,6 +193,7 @@ pub fn ActiveQuotesPage(counterparties: RwSignal<Vec<CounterParty>>) -> impl Int
                 is_pending
                 show_approve_quote_alert
                 approve_quote_response
+                credit_limit_breach
                 counterparties
             />
             <ApprovedQuotesTable
@@ -212,12 +225,16 @@ pub fn ActiveQuotesTable(
     active_quotes_resource: Resource<(), Result<BTreeMap<String, Vec<QuoteOption>>, ServerFnError>>,
     approved_quotes_resource: Resource<(), Result<BTreeMap<String, Vec<QuoteOption>>, ServerFnError>>,
     rejected_quotes_resource: Resource<(), Result<BTreeMap<String, Vec<QuoteOption>>, ServerFnError>>,
-    approve_reject_quotes_option_action: Action<(String, Vec<QuotesOptionForStatusChange>), ()>,
+    approve_reject_quotes_option_action: Action<(String, Vec<QuotesOptionForStatusChange>, Option<String>), ()>,
     is_pending: ReadSignal<bool>,
     show_approve_quote_alert: RwSignal<bool>,
     approve_quote_response: RwSignal<ApproveTradeQuoteResponse>,
+    credit_limit_breach: RwSignal<Option<(Vec<QuotesOptionForStatusChange>, Vec<String>)>>,
     counterparties: RwSignal<Vec<CounterParty>>
 ) -> impl IntoView {
+    let permissions = expect_context::<crate::Permissions>().0;
+    let can_approve = move || permissions.get().has(Permission::ApproveQuotes);
+    let can_edit = move || permissions.get().has(Permission::EditQuotes);
 
     // Signal for modals
     let (show_success_modal, set_show_success_modal) = create_signal(false);
@@ -246,12 +263,14 @@ pub fn ActiveQuotesTable(
                                 <div class="hidden md:flex justify-end gap-2">
                                     <button
                                         class="btn btn-xs btn-warning"
+                                        prop:disabled=move || !can_approve()
                                         on:click=move |_| confirm_modal_reject_all.set(true)
                                     >
                                         REJECT ALL
                                     </button>
                                     <button
                                         class="btn btn-xs btn-success"
+                                        prop:disabled=move || !can_approve()
                                         on:click=move |_| confirm_modal_approve_all.set(true)
                                     >
                                         APPROVE ALL
@@ -428,6 +447,7 @@ pub fn ActiveQuotesTable(
                                                     .dispatch((
                                                         status.clone(),
                                                         quotes_option_for_status_change,
+                                                        None,
                                                     ));
                                                 set_trade_quotes_list
                                                     .update(|v| {
@@ -455,6 +475,7 @@ pub fn ActiveQuotesTable(
                                                     .dispatch((
                                                         status.clone(),
                                                         quotes_option_for_status_change.clone(),
+                                                        None,
                                                     ));
                                                 // set_trade_quotes_list
                                                 //     .update(|v| {
@@ -604,31 +625,13 @@ pub fn ActiveQuotesTable(
                                                                                     Value::String(s) => s,
                                                                                     _ => &String::from(""),
                                                                                 };
-                                                                                let party_b_ = match &tq["party_b"] {
-                                                                                    serde_json::Value::Object(party_b) => {
-                                                                                        match party_b.get("name") {
-                                                                                            Some(name) => {
-                                                                                                match name.as_str() {
-                                                                                                    Some(name_str) => name_str.to_string(),
-                                                                                                    None => String::from(""),
-                                                                                                }
-                                                                                            }
-                                                                                            None => String::from(""),
-                                                                                        }
-                                                                                    }
-                                                                                    _ => String::from(""),
-                                                                                };
+                                                                                // A quote of several legs has several rows per party in its group, so the row is found by its id.
+                                                                                let quote_option_id = tq["id"].as_u64().unwrap_or_default() as u32;
                                                                                 let filtered_data: QuoteOption = trade_quotes_list
                                                                                     .get()
                                                                                     .into_iter()
-                                                                                    .find(|item| {
-                                                                                        item.group_id == *group_id
-                                                                                            && item
-                                                                                                .party_b
-                                                                                                .as_ref()
-                                                                                                .map_or(false, |party_b| party_b.name == *party_b_)
-                                                                                    })
-                                                                                    .expect("REASON");
+                                                                                    .find(|item| item.id == quote_option_id)
+                                                                                    .unwrap_or_default();
                                                                                 let mod_quote_option = RwSignal::new(filtered_data);
                                                                                 let hide_non_jabra = counter_party != "JABRA";
                                                                                 let is_pos_class_s = if side == "Sell" {
@@ -708,15 +711,12 @@ pub fn ActiveQuotesTable(
                                                                                                         });
                                                                                                 }
                                                                                             }
-                                                                                            Err(_e) => {
+                                                                                            Err(e) => {
                                                                                                 show_modify_alert_modal.set(true);
                                                                                                 modify_quote_response
                                                                                                     .update(|v| {
                                                                                                         v.success = false;
-                                                                                                        v
-                                                                                                            .message = String::from(
-                                                                                                            "Your session has ended. Please relog your account.",
-                                                                                                        );
+                                                                                                        v.message = JabraError::from_server_fn_error(&e).get_user_message();
                                                                                                     });
                                                                                             }
                                                                                         }
@@ -934,7 +934,7 @@ pub fn ActiveQuotesTable(
                                                                                                     let checked = event_target_checked(&event);
                                                                                                     let val = event_target_value(&event);
                                                                                                     if checked {
-                                                                                                        set_group_ids.update(|v| v.push(val));
+                                                                                                        set_group_ids.update(|v| if !v.contains(&val) { v.push(val) });
                                                                                                     } else {
                                                                                                         set_group_ids.update(|v| v.retain(|x| x.ne(&val)));
                                                                                                     }
@@ -949,6 +949,7 @@ pub fn ActiveQuotesTable(
                                                                                         <td colspan="17" class="px924:hidden">
                                                                                             <button
                                                                                                 class="mr-2 btn btn-xs btn-warning"
+                                                                                                prop:disabled=move || !can_edit()
                                                                                                 on:click=move |_| show_edit_modal.set(true)
                                                                                             >
                                                                                                 EDIT
@@ -990,7 +991,7 @@ pub fn ActiveQuotesTable(
                                                                                                         let checked = event_target_checked(&event);
                                                                                                         let val = event_target_value(&event);
                                                                                                         if checked {
-                                                                                                            set_group_ids.update(|v| v.push(val));
+                                                                                                            set_group_ids.update(|v| if !v.contains(&val) { v.push(val) });
                                                                                                         } else {
                                                                                                             set_group_ids.update(|v| v.retain(|x| x.ne(&val)));
                                                                                                         }
@@ -1001,6 +1002,7 @@ pub fn ActiveQuotesTable(
                                                                                             <td>
                                                                                                 <button
                                                                                                     class="mr-2 btn btn-xs btn-warning"
+                                                                                                    prop:disabled=move || !can_edit()
                                                                                                     on:click=move |_| show_edit_modal.set(true)
                                                                                                 >
                                                                                                     EDIT
@@ -1084,14 +1086,14 @@ pub fn ActiveQuotesTable(
                                                                                 <div>
                                                                                     <button
                                                                                         class="mr-2 btn btn-xs btn-warning"
-                                                                                        prop:disabled=move || group_ids.get().is_empty()
+                                                                                        prop:disabled=move || group_ids.get().is_empty() || !can_approve()
                                                                                         on:click=move |_| confirm_modal_reject.set(true)
                                                                                     >
                                                                                         REJECT
                                                                                     </button>
                                                                                     <button
                                                                                         class="btn btn-xs btn-success"
-                                                                                        prop:disabled=move || group_ids.get().is_empty()
+                                                                                        prop:disabled=move || group_ids.get().is_empty() || !can_approve()
                                                                                         on:click=move |_| confirm_modal_approve.set(true)
                                                                                     >
                                                                                         APPROVE
@@ -1190,6 +1192,93 @@ pub fn ActiveQuotesTable(
                 false => view! { <div></div> }.into_view(),
             }}
 
+            {move || credit_limit_breach.get().map(|(request, breaches)| {
+                view! {
+                    <CreditLimitOverrideModal
+                        request
+                        breaches
+                        credit_limit_breach
+                        approve_reject_quotes_option_action
+                        can_override=can_approve()
+                        function=refetch_resource
+                    />
+                }
+            })}
+
+        </div>
+    }
+}
+
+/// Component for the modal shown when an approval would breach the credit limits of a counterparty.
+/// Lists the breached limits, the approval can be overridden with a reason, which is recorded in the audit log.
+/// Cancelling refetches the quotes, since the quotes of the approval were already taken out of the table.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn CreditLimitOverrideModal<F>(
+    request: Vec<QuotesOptionForStatusChange>,
+    breaches: Vec<String>,
+    credit_limit_breach: RwSignal<Option<(Vec<QuotesOptionForStatusChange>, Vec<String>)>>,
+    approve_reject_quotes_option_action: Action<(String, Vec<QuotesOptionForStatusChange>, Option<String>), ()>,
+    can_override: bool,
+    function: F,
+) -> impl IntoView
+where
+    F: Fn() + Clone + 'static,
+{
+    let reason = create_rw_signal(String::new());
+    let is_pending = approve_reject_quotes_option_action.pending();
+    let request = store_value(request);
+    let on_override = move |_| {
+        credit_limit_breach.set(None);
+        approve_reject_quotes_option_action.dispatch((
+            String::from("approved"),
+            request.get_value(),
+            Some(reason.get_untracked()),
+        ));
+    };
+    let on_cancel = move |_| {
+        credit_limit_breach.set(None);
+        function();
+    };
+
+    view! {
+        <div class="blur-bg">
+            <div class="modal-top-middle">
+                <div class="modal-box rounded-box">
+                    <h3 class="text-2xl font-bold text-error">CREDIT LIMIT BREACHED</h3>
+                    <p class="pt-4">The approval would breach the following credit limits:</p>
+                    <ul class="py-2 ml-5 text-sm list-disc">
+                        {breaches.into_iter().map(|b| view! { <li>{b}</li> }).collect_view()}
+                    </ul>
+                    <Show when=move || can_override fallback=|| ()>
+                        <label class="text-xs opacity-70" for="credit_limit_override_reason">Override Reason</label>
+                        <textarea
+                            class="w-full textarea textarea-bordered textarea-sm"
+                            id="credit_limit_override_reason"
+                            name="credit_limit_override_reason"
+                            placeholder="Why the limits can be exceeded"
+                            prop:value=move || reason.get()
+                            on:input=move |event| reason.set(event_target_value(&event))
+                        ></textarea>
+                    </Show>
+                    <div class="modal-action">
+                        <button class="rounded btn btn-ghost btn-sm" title="Cancel" prop:disabled=is_pending on:click=on_cancel>
+                            Cancel
+                        </button>
+                        <Show when=move || can_override fallback=|| ()>
+                            <button
+                                class="rounded btn btn-error btn-sm"
+                                title="Override"
+                                prop:disabled=move || reason.get().trim().is_empty() || is_pending.get()
+                                on:click=on_override
+                            >
+                                Override and Approve
+                            </button>
+                        </Show>
+                    </div>
+                </div>
+            </div>
         </div>
     }
 }
@@ -2080,19 +2169,16 @@ pub fn EditQuotesOptionModal(
     });
 
     let dispatch = move || {
-        let quote_options_to_edit = quotes_option_list
-            .get()
-            .into_iter()
-            .filter(|q| q.group_id == quote_option.get().group_id)
-            .collect::<Vec<QuoteOption>>();
-        let jabra_quote = quote_options_to_edit
-            .iter()
-            .find(|q| q.counterparty_id.name == "JABRA")
-            .unwrap();
-        let non_jabra_quote = quote_options_to_edit
-            .iter()
-            .find(|q| q.counterparty_id.name != "JABRA")
-            .unwrap();
+        // The edited row is the JABRA row of one leg, the counterparty row of the same leg is updated with it.
+        let jabra_quote = quote_option.get();
+        let quote_options = quotes_option_list.get();
+        let non_jabra_quote = match jabra_quote.find_other_party_row(&quote_options) {
+            Some(quote) => quote,
+            None => {
+                log::error!("no counterparty row for the quote option {}", jabra_quote.id);
+                return;
+            }
+        };
         let modified_jabra_quote = QuotesOptionsForModification::new(
             jabra_quote.id.clone(),
             quote_option_amount.get(),
@@ -2326,6 +2412,20 @@ pub fn EditQuotesOptionModal(
                                 <label class="ml-4 text-sm font-light">Good Till Cancelled</label>
                             </div>
                         </div>
+                        <PayoffChart
+                            legs=Signal::derive(move || {
+                                vec![
+                                    quote_option
+                                        .get()
+                                        .get_payoff_leg(
+                                            quote_option_amount.get(),
+                                            quote_option_px_in_quote_ccy.get(),
+                                        ),
+                                ]
+                            })
+                            spot=Signal::derive(move || quote_option.get().spot)
+                            currency=ccy2()
+                        />
                         <div class="grid grid-cols-3 gap-3">
                             <div colspan="1"></div>
                             <div colspan="1">
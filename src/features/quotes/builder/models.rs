@@ -1 +1,315 @@
+use serde::{Deserialize, Serialize};
 
+use crate::commons::models::trade::{PositionGreekRequest, PositionGreekResponse};
+use crate::utilities::date_util::{
+    format_utc_str_to_instrument_date, parse_str_to_utc_datetime_str, time_to_expiry_from_utc_str,
+};
+use crate::utilities::option_pricer::{price_position, relative_difference, LocalGreeks};
+
+/// This enum represents the strategies that can be loaded into the quote builder.
+/// Each strategy is a preset of legs which the trader can still edit afterwards.
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum QuoteStrategy {
+    Single,
+    CallSpread,
+    PutSpread,
+    Straddle,
+    Strangle,
+    RiskReversal,
+}
+
+impl QuoteStrategy {
+    pub fn all() -> Vec<QuoteStrategy> {
+        vec![
+            QuoteStrategy::Single,
+            QuoteStrategy::CallSpread,
+            QuoteStrategy::PutSpread,
+            QuoteStrategy::Straddle,
+            QuoteStrategy::Strangle,
+            QuoteStrategy::RiskReversal,
+        ]
+    }
+    pub fn name(&self) -> String {
+        match self {
+            QuoteStrategy::Single => String::from("Single"),
+            QuoteStrategy::CallSpread => String::from("Call Spread"),
+            QuoteStrategy::PutSpread => String::from("Put Spread"),
+            QuoteStrategy::Straddle => String::from("Straddle"),
+            QuoteStrategy::Strangle => String::from("Strangle"),
+            QuoteStrategy::RiskReversal => String::from("Risk Reversal"),
+        }
+    }
+    pub fn from_name(name: &str) -> QuoteStrategy {
+        QuoteStrategy::all()
+            .into_iter()
+            .find(|s| s.name() == name)
+            .unwrap_or(QuoteStrategy::Single)
+    }
+
+    /// Returns the preset legs of the strategy as `(side, option_kind, offstrike_percentage)`.
+    /// The side is always from JABRA's point of view.
+    pub fn presets(&self, side: &str) -> Vec<(String, String, f64)> {
+        let opposite = opposite_side(side);
+        match self {
+            QuoteStrategy::Single => vec![(side.to_string(), String::from("Call"), 0.0)],
+            QuoteStrategy::CallSpread => vec![
+                (side.to_string(), String::from("Call"), 0.0),
+                (opposite, String::from("Call"), 10.0),
+            ],
+            QuoteStrategy::PutSpread => vec![
+                (side.to_string(), String::from("Put"), 0.0),
+                (opposite, String::from("Put"), -10.0),
+            ],
+            QuoteStrategy::Straddle => vec![
+                (side.to_string(), String::from("Call"), 0.0),
+                (side.to_string(), String::from("Put"), 0.0),
+            ],
+            QuoteStrategy::Strangle => vec![
+                (side.to_string(), String::from("Call"), 10.0),
+                (side.to_string(), String::from("Put"), -10.0),
+            ],
+            QuoteStrategy::RiskReversal => vec![
+                (side.to_string(), String::from("Call"), 10.0),
+                (opposite, String::from("Put"), -10.0),
+            ],
+        }
+    }
+}
+
+pub fn opposite_side(side: &str) -> String {
+    if side == "Buy" {
+        String::from("Sell")
+    } else {
+        String::from("Buy")
+    }
+}
+
+/// This struct holds a single leg of the quote being built.
+/// The `expiry` is the local datetime entered by the trader ("%Y-%m-%dT%H:%M"),
+/// while the pricing fields are filled in after calling the pricer.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct QuoteLeg {
+    pub id: u16,
+    pub side: String,
+    pub option_kind: String,
+    pub offstrike_percentage: f64,
+    pub strike: f64,
+    pub amount: f64,
+    pub iv: f64,
+    pub expiry: String,
+    pub px_in_base_ccy: f64,
+    pub px_in_quote_ccy: f64,
+    pub delta: f64,
+    pub gamma: f64,
+    pub theta: f64,
+    pub vega: f64,
+    pub rho: f64,
+    pub is_priced: bool,
+}
+
+impl QuoteLeg {
+    pub fn new(
+        id: u16,
+        side: String,
+        option_kind: String,
+        offstrike_percentage: f64,
+        spot: f64,
+        amount: f64,
+        iv: f64,
+        expiry: String,
+    ) -> Self {
+        Self {
+            id,
+            side,
+            option_kind,
+            offstrike_percentage,
+            strike: get_strike_from_offstrike(spot, offstrike_percentage),
+            amount,
+            iv,
+            expiry,
+            ..Default::default()
+        }
+    }
+    pub fn get_expiry_timestamp(&self) -> String {
+        parse_str_to_utc_datetime_str(self.expiry.as_str())
+    }
+    pub fn get_ttm(&self) -> f64 {
+        time_to_expiry_from_utc_str(self.get_expiry_timestamp().as_str())
+    }
+    pub fn get_instrument_name(&self, base_currency: &str) -> String {
+        format!(
+            "{}-{}-{}-{}",
+            base_currency,
+            format_utc_str_to_instrument_date(self.get_expiry_timestamp().as_str()),
+            self.strike.round() as i64,
+            self.option_kind.chars().next().unwrap_or_default()
+        )
+    }
+
+    /// The iv is entered in vol points, the pricer expects it as a decimal.
+    pub fn to_greek_request(&self, spot: f64, r2: f64) -> PositionGreekRequest {
+        PositionGreekRequest::new(
+            self.side.clone(),
+            self.option_kind.clone(),
+            self.amount.abs(),
+            self.strike,
+            self.get_ttm(),
+            0.0,
+            Some(spot),
+            Some(r2),
+            Some(0.0),
+            Some(self.iv / 100.0),
+            Some(self.get_expiry_timestamp()),
+            Some(self.id.to_string()),
+        )
+    }
+    /// Prices the leg locally, used for instant updates while the trader edits the leg.
+    pub fn get_local_greeks(&self, spot: f64, r2: f64) -> LocalGreeks {
+        price_position(&self.to_greek_request(spot, r2))
+    }
+
+    /// Flags a leg whose server price is more than 1% away from the local price.
+    pub fn has_price_mismatch(&self, spot: f64, r2: f64) -> bool {
+        self.is_priced
+            && relative_difference(
+                self.get_local_greeks(spot, r2).px_in_quote_ccy,
+                self.px_in_quote_ccy,
+            ) > 0.01
+    }
+    pub fn set_pricing(&mut self, response: &PositionGreekResponse) {
+        self.px_in_base_ccy = response.px_in_base_ccy;
+        self.px_in_quote_ccy = response.px_in_quote_ccy;
+        self.delta = response.greeks.delta;
+        self.gamma = response.greeks.gamma;
+        self.theta = response.greeks.theta;
+        self.vega = response.greeks.vega;
+        self.rho = response.greeks.rho;
+        self.is_priced = true;
+    }
+    pub fn clear_pricing(&mut self) {
+        self.px_in_base_ccy = 0.0;
+        self.px_in_quote_ccy = 0.0;
+        self.delta = 0.0;
+        self.gamma = 0.0;
+        self.theta = 0.0;
+        self.vega = 0.0;
+        self.rho = 0.0;
+        self.is_priced = false;
+    }
+
+    /// Premium and greeks are signed by the side, a leg that JABRA sells is negative.
+    pub fn signed(&self, value: f64) -> f64 {
+        if self.side == "Sell" {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+pub fn get_strike_from_offstrike(spot: f64, offstrike_percentage: f64) -> f64 {
+    (spot * (1.0 + offstrike_percentage / 100.0)).round()
+}
+
+pub fn get_offstrike_from_strike(spot: f64, strike: f64) -> f64 {
+    if spot == 0.0 {
+        return 0.0;
+    }
+    (((strike / spot) - 1.0) * 10000.0).round() / 100.0
+}
+
+/// This struct is used to create a row in the `quotes_option` collection.
+/// Each leg creates two rows, one for JABRA and one for the counterparty, sharing the same `group_id`.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuoteOptionRequest {
+    pub amount: f64,
+    pub option_kind: String,
+    pub r1: f64,
+    pub r2: f64,
+    pub offstrike_percentage: f64,
+    pub strike: f64,
+    pub iv: f64,
+    pub px_in_base_ccy: f64,
+    pub px_in_quote_ccy: f64,
+    pub side: String,
+    pub quote_expiry: String,
+    pub expiry_timestamp: String,
+    pub quote_status: String,
+    pub instrument_name: String,
+    pub spot: f64,
+    pub ttm: f64,
+    pub gtc: bool,
+    pub group_id: String,
+    pub payout_ccy: Option<String>,
+    pub pair_id: u16,
+    pub ccy_id: u16,
+    pub counterparty_id: u16,
+    pub party_a: u16,
+    pub party_b: u16,
+}
+
+impl QuoteOptionRequest {
+    /// Builds the JABRA and counterparty rows of a leg.
+    pub fn from_leg(
+        leg: &QuoteLeg,
+        details: &QuoteDetails,
+        group_id: String,
+    ) -> (QuoteOptionRequest, QuoteOptionRequest) {
+        let jabra_row = QuoteOptionRequest {
+            amount: leg.amount,
+            option_kind: leg.option_kind.clone(),
+            r1: 0.0,
+            r2: details.r2,
+            offstrike_percentage: leg.offstrike_percentage,
+            strike: leg.strike,
+            iv: leg.iv / 100.0,
+            px_in_base_ccy: leg.px_in_base_ccy,
+            px_in_quote_ccy: leg.px_in_quote_ccy,
+            side: leg.side.clone(),
+            quote_expiry: details.quote_expiry.clone(),
+            expiry_timestamp: leg.get_expiry_timestamp(),
+            quote_status: String::from("active"),
+            instrument_name: leg.get_instrument_name(details.base_currency.as_str()),
+            spot: details.spot,
+            ttm: leg.get_ttm(),
+            gtc: details.gtc,
+            group_id,
+            payout_ccy: details.payout_ccy.clone(),
+            pair_id: details.pair_id,
+            ccy_id: details.ccy_id,
+            counterparty_id: details.jabra_id,
+            party_a: details.jabra_id,
+            party_b: details.counterparty_id,
+        };
+        let counterparty_row = QuoteOptionRequest {
+            amount: -jabra_row.amount,
+            px_in_base_ccy: -jabra_row.px_in_base_ccy,
+            px_in_quote_ccy: -jabra_row.px_in_quote_ccy,
+            side: opposite_side(leg.side.as_str()),
+            counterparty_id: details.counterparty_id,
+            party_a: details.counterparty_id,
+            party_b: details.jabra_id,
+            ..jabra_row.clone()
+        };
+        (jabra_row, counterparty_row)
+    }
+}
+
+/// This struct holds the details shared by every leg of the quote.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct QuoteDetails {
+    pub pair_id: u16,
+    pub ccy_id: u16,
+    pub base_currency: String,
+    pub jabra_id: u16,
+    pub counterparty_id: u16,
+    pub spot: f64,
+    pub r2: f64,
+    pub quote_expiry: String,
+    pub gtc: bool,
+    pub payout_ccy: Option<String>,
+}
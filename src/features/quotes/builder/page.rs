@@ -1,11 +1,823 @@
 use leptos::*;
 
+use crate::commons::models::counterparty::CounterParty;
+use crate::commons::models::currency_pair::CurrencyPair;
+use crate::commons::models::interestrates::InterestRate;
+use crate::commons::services::coinbase::get_spot_price;
+use crate::commons::services::counterparty::get_counter_parties;
+use crate::commons::services::currency_pair::get_currency_pairs;
+use crate::commons::services::interestrates::get_interest_rates;
+use crate::components::component_size::ComponentSize;
+use crate::components::component_type::ComponentType;
+use crate::components::confirm_modal::ConfirmModal;
+use crate::components::default_none::DefaultNone;
+use crate::components::error_modal::ErrorModal;
+use crate::components::loading_spinners::Spinners;
+use crate::components::success_modal::SuccessModal;
+use crate::utilities::date_util::get_expiry;
+use crate::utilities::number_util::format_currency;
+use crate::utilities::errors::JabraError;
+
+use super::models::*;
+use super::services::*;
+
 #[allow(non_snake_case)]
 #[component]
 pub fn QuoteBuilder() -> impl IntoView {
+    let currency_pairs_resource = Resource::once(move || get_currency_pairs());
+    let counterparties_refetcher = expect_context::<crate::CounterPartiesRefetcher>().0;
+    let counterparties_resource = create_local_resource(counterparties_refetcher, move |_| get_counter_parties());
+    let interest_rates_resource = Resource::once(move || get_interest_rates());
+
+    let currency_pairs = RwSignal::new(Vec::<CurrencyPair>::default());
+    let counterparties = RwSignal::new(Vec::<CounterParty>::default());
+    let interest_rate = RwSignal::new(InterestRate::default());
+
     view! {
-        <div>
-            <h1>"QuoteBuilder"</h1>
+        <div class="p-4">
+            <div class="pb-5 ml-2 text-xl font-bold text-white">
+                <span>Quote Builder</span>
+            </div>
+            <Suspense fallback=move || {
+                view! {
+                    <div class="items-center mt-5">
+                        <div class="flex justify-center">
+                            <Spinners size=ComponentSize::SMALL _type=ComponentType::SUCCESS/>
+                        </div>
+                    </div>
+                }
+            }>
+                {move || {
+                    currency_pairs_resource
+                        .and_then(|p| {
+                            currency_pairs.set(p.data.clone());
+                        });
+                    counterparties_resource
+                        .and_then(|c| {
+                            counterparties.set(c.data.clone());
+                        });
+                    interest_rates_resource
+                        .and_then(|i| {
+                            if !i.data.is_empty() {
+                                interest_rate.set(i.get_newest_interest_rate());
+                            }
+                        });
+                }}
+
+            </Suspense>
+            {move || {
+                if currency_pairs.get().is_empty() || counterparties.get().is_empty() {
+                    view! {
+                        <div class="p-5">
+                            <DefaultNone text=RwSignal::new(
+                                String::from("No Currency Pairs or Counterparties available"),
+                            )/>
+                        </div>
+                    }
+                        .into_view()
+                } else {
+                    view! {
+                        <QuoteBuilderPage
+                            currency_pairs=currency_pairs
+                            counterparties=counterparties
+                            interest_rate=interest_rate
+                        />
+                    }
+                        .into_view()
+                }
+            }}
+
         </div>
     }
 }
+
+#[allow(non_snake_case)]
+#[component]
+pub fn QuoteBuilderPage(
+    /// The list of active currency pairs to quote.
+    currency_pairs: RwSignal<Vec<CurrencyPair>>,
+    /// The list of counterparties, JABRA is always party A of the quote.
+    counterparties: RwSignal<Vec<CounterParty>>,
+    /// The newest interest rate, used as r2 when pricing.
+    interest_rate: RwSignal<InterestRate>,
+) -> impl IntoView {
+    let jabra_id = move || {
+        counterparties
+            .get()
+            .into_iter()
+            .find(|cp| cp.name == "JABRA")
+            .map(|cp| cp.id)
+            .unwrap_or_default()
+    };
+    let non_jabra_counterparties = move || {
+        counterparties
+            .get()
+            .into_iter()
+            .filter(|cp| cp.name != "JABRA")
+            .collect::<Vec<CounterParty>>()
+    };
+
+    let currency_pair = RwSignal::new(currency_pairs.get_untracked()[0].clone());
+    let counterparty_id = RwSignal::new(
+        non_jabra_counterparties()
+            .first()
+            .map(|cp| cp.id)
+            .unwrap_or_default(),
+    );
+    let strategy = RwSignal::new(QuoteStrategy::Single);
+    let side = RwSignal::new(String::from("Buy"));
+    let amount = RwSignal::new(1.0);
+    let iv = RwSignal::new(50.0);
+    let expiry = RwSignal::new(String::default());
+    let quote_expiry_in_minutes = RwSignal::new(5u16);
+    let gtc = RwSignal::new(false);
+    let payout_ccy = RwSignal::new(Option::<String>::None);
+    let legs = RwSignal::new(Vec::<QuoteLeg>::default());
+    let next_leg_id = RwSignal::new(1u16);
+    let spot = RwSignal::new(0.0);
+
+    let (show_confirm_modal, set_show_confirm_modal) = create_signal(false);
+    let (show_success_modal, set_show_success_modal) = create_signal(false);
+    let (show_error_modal, set_show_error_modal) = create_signal(false);
+    let error_message = RwSignal::new(String::default());
+    let submit_result = RwSignal::new(Option::<bool>::None);
+
+    let base_currency = move || currency_pair.get().base.ticker;
+    let quote_currency = move || currency_pair.get().quote.ticker;
+
+    let spot_resource = create_local_resource(
+        move || currency_pair.get().name,
+        move |pair| get_spot_price(pair.replace("/", "-")),
+    );
+
+    let add_leg = move |leg_side: String, option_kind: String, offstrike_percentage: f64| {
+        let id = next_leg_id.get_untracked();
+        next_leg_id.set(id + 1);
+        legs.update(|l| {
+            l.push(QuoteLeg::new(
+                id,
+                leg_side,
+                option_kind,
+                offstrike_percentage,
+                spot.get_untracked(),
+                amount.get_untracked(),
+                iv.get_untracked(),
+                expiry.get_untracked(),
+            ))
+        });
+    };
+
+    let load_strategy = move || {
+        legs.set(Vec::<QuoteLeg>::default());
+        for (leg_side, option_kind, offstrike_percentage) in
+            strategy.get_untracked().presets(side.get_untracked().as_str())
+        {
+            add_leg(leg_side, option_kind, offstrike_percentage);
+        }
+    };
+
+    // Strikes follow the spot when the pair changes, the offstrike percentage is kept.
+    create_effect(move |_| {
+        let s = spot.get();
+        legs.update(|l| {
+            for leg in l.iter_mut() {
+                leg.strike = get_strike_from_offstrike(s, leg.offstrike_percentage);
+                leg.clear_pricing();
+            }
+        });
+    });
+
+    let price_action: Action<(), ()> = create_action(move |_: &()| {
+        let legs_to_price = legs.get_untracked();
+        let current_spot = spot.get_untracked();
+        let r2 = interest_rate.get_untracked().rate;
+        async move {
+            match get_quote_legs_greeks(legs_to_price, current_spot, r2).await {
+                Ok(priced_legs) => legs.set(priced_legs),
+                Err(e) => {
+                    error_message.set(JabraError::from_server_fn_error(&e).get_user_message());
+                    submit_result.set(Some(false));
+                }
+            }
+        }
+    });
+
+    let submit_action: Action<Vec<QuoteOptionRequest>, ()> =
+        create_action(move |request: &Vec<QuoteOptionRequest>| {
+            let request = request.clone();
+            async move {
+                match post_quotes_option(request).await {
+                    Ok(res) => {
+                        submit_result.set(Some(res));
+                        if res {
+                            legs.set(Vec::<QuoteLeg>::default());
+                        }
+                    }
+                    Err(e) => {
+                        error_message.set(JabraError::from_server_fn_error(&e).get_user_message());
+                        submit_result.set(Some(false));
+                    }
+                }
+            }
+        });
+
+    let can_price = move || {
+        !legs.get().is_empty()
+            && spot.get() > 0.0
+            && legs
+                .get()
+                .iter()
+                .all(|l| l.amount > 0.0 && l.strike > 0.0 && l.iv > 0.0 && l.get_ttm() > 0.0)
+    };
+    let can_submit = move || {
+        can_price() && legs.get().iter().all(|l| l.is_priced) && counterparty_id.get() != 0
+    };
+
+    let dispatch_submit = move || {
+        let details = QuoteDetails {
+            pair_id: currency_pair.get().id,
+            ccy_id: currency_pair.get().base.id,
+            base_currency: base_currency(),
+            jabra_id: jabra_id(),
+            counterparty_id: counterparty_id.get(),
+            spot: spot.get(),
+            r2: interest_rate.get().rate,
+            quote_expiry: get_expiry(quote_expiry_in_minutes.get()),
+            gtc: gtc.get(),
+            payout_ccy: payout_ccy.get(),
+        };
+        // Every leg of the quote shares one group, so the legs are approved or rejected together.
+        let group_id = uuid::Uuid::new_v4().to_string();
+        let mut request = Vec::<QuoteOptionRequest>::default();
+        for leg in legs.get().iter() {
+            let (jabra_row, counterparty_row) =
+                QuoteOptionRequest::from_leg(leg, &details, group_id.clone());
+            request.push(jabra_row);
+            request.push(counterparty_row);
+        }
+        submit_action.dispatch(request);
+    };
+
+    let total = move |f: fn(&QuoteLeg) -> f64| {
+        legs.get().iter().map(|l| l.signed(f(l))).sum::<f64>()
+    };
+
+    view! {
+        <Suspense>
+            {move || {
+                spot_resource
+                    .and_then(|s| {
+                        spot.set(s.data.amount.parse::<f64>().unwrap_or_default());
+                    });
+            }}
+
+        </Suspense>
+        <div class="flex flex-wrap gap-3">
+            <div class="flex-auto w-full p-2 rounded lg:flex-initial lg:w-1/5 bg-base-300 bg-opacity-50">
+                <form class="flex flex-col gap-1" on:submit=|ev| ev.prevent_default()>
+                    <div class="text-lg font-bold text-white">
+                        <span>Quote Input</span>
+                    </div>
+                    <label class="block text-xs font-light" for="currency_pair">
+                        Currency Pair
+                    </label>
+                    <select
+                        class="w-full text-sm rounded shadow-md select select-sm hover:shadow-md"
+                        id="currency_pair"
+                        on:change=move |event| {
+                            let val = event_target_value(&event);
+                            if let Some(p) = currency_pairs
+                                .get()
+                                .into_iter()
+                                .find(|p| p.id.to_string() == val)
+                            {
+                                payout_ccy.set(None);
+                                currency_pair.set(p);
+                            }
+                        }
+                    >
+
+                        {move || {
+                            currency_pairs
+                                .get()
+                                .into_iter()
+                                .map(|p| {
+                                    view! {
+                                        <option
+                                            value=p.id.to_string()
+                                            prop:selected=currency_pair.get().id == p.id
+                                        >
+                                            {p.name}
+                                        </option>
+                                    }
+                                })
+                                .collect_view()
+                        }}
+
+                    </select>
+                    <label class="block text-xs font-light" for="spot">
+                        Spot
+                    </label>
+                    <input
+                        class="w-full text-sm border rounded opacity-70 pointer-events-none input input-xs border-success border-opacity-70"
+                        type="text"
+                        id="spot"
+                        readonly
+                        prop:value=move || format_currency(spot.get(), 2)
+                    />
+                    <label class="block text-xs font-light" for="counterparty">
+                        Counterparty
+                    </label>
+                    <select
+                        class="w-full text-sm rounded shadow-md select select-sm hover:shadow-md"
+                        id="counterparty"
+                        on:change=move |event| {
+                            let val = event_target_value(&event);
+                            counterparty_id.set(val.parse::<u16>().unwrap_or_default());
+                        }
+                    >
+
+                        {move || {
+                            non_jabra_counterparties()
+                                .into_iter()
+                                .map(|cp| {
+                                    view! {
+                                        <option
+                                            value=cp.id.to_string()
+                                            prop:selected=counterparty_id.get() == cp.id
+                                        >
+                                            {cp.name}
+                                        </option>
+                                    }
+                                })
+                                .collect_view()
+                        }}
+
+                    </select>
+                    <label class="block text-xs font-light" for="strategy">
+                        Strategy
+                    </label>
+                    <select
+                        class="w-full text-sm rounded shadow-md select select-sm hover:shadow-md"
+                        id="strategy"
+                        on:change=move |event| {
+                            strategy.set(QuoteStrategy::from_name(event_target_value(&event).as_str()));
+                        }
+                    >
+
+                        {QuoteStrategy::all()
+                            .into_iter()
+                            .map(|s| {
+                                view! { <option value=s.name()>{s.name()}</option> }
+                            })
+                            .collect_view()}
+
+                    </select>
+                    <label class="block text-xs font-light" for="side">
+                        JABRA Side
+                    </label>
+                    <select
+                        class="w-full text-sm rounded shadow-md select select-sm hover:shadow-md"
+                        id="side"
+                        on:change=move |event| side.set(event_target_value(&event))
+                    >
+                        <option value="Buy">Buy</option>
+                        <option value="Sell">Sell</option>
+                    </select>
+                    <label class="block text-xs font-light" for="amount">
+                        {move || format!("Notional ({})", base_currency())}
+                    </label>
+                    <input
+                        class="w-full text-sm border rounded input input-xs border-success border-opacity-70"
+                        type="number"
+                        id="amount"
+                        min="0"
+                        step="any"
+                        prop:value=amount
+                        on:change=move |event| {
+                            amount.set(event_target_value(&event).parse::<f64>().unwrap_or_default());
+                        }
+                    />
+                    <label class="block text-xs font-light" for="iv">
+                        IV (%)
+                    </label>
+                    <input
+                        class="w-full text-sm border rounded input input-xs border-success border-opacity-70"
+                        type="number"
+                        id="iv"
+                        min="0"
+                        step="any"
+                        prop:value=iv
+                        on:change=move |event| {
+                            iv.set(event_target_value(&event).parse::<f64>().unwrap_or_default());
+                        }
+                    />
+                    <label class="block text-xs font-light" for="expiry">
+                        Expiry
+                    </label>
+                    <input
+                        class="w-full text-sm border rounded input input-xs border-success border-opacity-70"
+                        type="datetime-local"
+                        id="expiry"
+                        prop:value=expiry
+                        on:change=move |event| expiry.set(event_target_value(&event))
+                    />
+                    <button
+                        class="w-full mt-2 rounded btn btn-success btn-sm"
+                        prop:disabled=move || expiry.get().is_empty() || spot.get() == 0.0
+                        on:click=move |_| load_strategy()
+                    >
+                        LOAD STRATEGY
+                    </button>
+                    <div class="mt-2 text-lg font-bold text-white">
+                        <span>Quote Settings</span>
+                    </div>
+                    <label class="block text-xs font-light" for="quote_expiry">
+                        Quote Expiry (minutes)
+                    </label>
+                    <input
+                        class="w-full text-sm border rounded input input-xs border-success border-opacity-70"
+                        type="number"
+                        id="quote_expiry"
+                        min="1"
+                        step="1"
+                        prop:value=quote_expiry_in_minutes
+                        prop:disabled=gtc
+                        on:change=move |event| {
+                            quote_expiry_in_minutes
+                                .set(event_target_value(&event).parse::<u16>().unwrap_or(5));
+                        }
+                    />
+                    <div class="flex items-center gap-2 mt-1">
+                        <input
+                            type="checkbox"
+                            class="toggle toggle-success toggle-sm"
+                            prop:checked=gtc
+                            on:change=move |event| gtc.set(event_target_checked(&event))
+                        />
+                        <label class="text-xs font-light">Good Till Cancelled</label>
+                    </div>
+                    <label class="block text-xs font-light" for="payout_ccy">
+                        Payout
+                    </label>
+                    <select
+                        class="w-full text-sm rounded shadow-md select select-sm hover:shadow-md"
+                        id="payout_ccy"
+                        on:change=move |event| {
+                            let val = event_target_value(&event);
+                            payout_ccy.set(if val.is_empty() { None } else { Some(val) });
+                        }
+                    >
+                        <option value="" prop:selected=move || payout_ccy.get().is_none()>
+                            Not Assigned
+                        </option>
+                        <option value="base">{base_currency}</option>
+                        <option value="quote">{quote_currency}</option>
+                    </select>
+                </form>
+            </div>
+            <div class="flex-auto p-2 overflow-x-auto rounded bg-base-300 bg-opacity-50">
+                <div class="flex justify-between">
+                    <div class="text-lg font-bold text-white">
+                        <span>Legs</span>
+                    </div>
+                    <button
+                        class="rounded btn btn-success btn-sm"
+                        prop:disabled=move || expiry.get().is_empty() || spot.get() == 0.0
+                        on:click=move |_| add_leg(side.get(), String::from("Call"), 0.0)
+                    >
+                        ADD LEG
+                    </button>
+                </div>
+                <Show
+                    when=move || !legs.get().is_empty()
+                    fallback=|| {
+                        view! {
+                            <div class="p-5">
+                                <DefaultNone text=RwSignal::new(
+                                    String::from("Load a strategy or add a leg to start quoting"),
+                                )/>
+                            </div>
+                        }
+                    }
+                >
+
+                    <table class="table mt-2 table-xs table-zebra-zebra">
+                        <thead>
+                            <tr class="font-semibold text-center text-white bg-success bg-opacity-30">
+                                <th>SIDE</th>
+                                <th>KIND</th>
+                                <th>OFFSTRIKE %</th>
+                                <th>STRIKE</th>
+                                <th>AMOUNT</th>
+                                <th>IV %</th>
+                                <th>EXPIRY</th>
+                                <th>INSTRUMENT</th>
+                                <th>{move || format!("LOCAL PX ({})", quote_currency())}</th>
+                                <th>{move || format!("PX ({})", base_currency())}</th>
+                                <th>{move || format!("PX ({})", quote_currency())}</th>
+                                <th>DELTA</th>
+                                <th>GAMMA</th>
+                                <th>THETA</th>
+                                <th>VEGA</th>
+                                <th>RHO</th>
+                                <th></th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            <For
+                                each=move || legs.get()
+                                key=|leg| leg.id
+                                children=move |leg| {
+                                    let id = leg.id;
+                                    let current = move || {
+                                        legs.get()
+                                            .into_iter()
+                                            .find(|l| l.id == id)
+                                            .unwrap_or_default()
+                                    };
+                                    view! {
+                                        <tr class="text-center">
+                                            <td>
+                                                <select
+                                                    class="select select-xs"
+                                                    on:change=move |event| {
+                                                        let val = event_target_value(&event);
+                                                        update_leg(legs, id, |l| l.side = val.clone());
+                                                    }
+                                                >
+                                                    <option value="Buy" prop:selected=move || current().side == "Buy">
+                                                        Buy
+                                                    </option>
+                                                    <option value="Sell" prop:selected=move || current().side == "Sell">
+                                                        Sell
+                                                    </option>
+                                                </select>
+                                            </td>
+                                            <td>
+                                                <select
+                                                    class="select select-xs"
+                                                    on:change=move |event| {
+                                                        let val = event_target_value(&event);
+                                                        update_leg(legs, id, |l| l.option_kind = val.clone());
+                                                    }
+                                                >
+                                                    <option value="Call" prop:selected=move || current().option_kind == "Call">
+                                                        Call
+                                                    </option>
+                                                    <option value="Put" prop:selected=move || current().option_kind == "Put">
+                                                        Put
+                                                    </option>
+                                                </select>
+                                            </td>
+                                            <td>
+                                                <input
+                                                    class="w-20 input input-xs"
+                                                    type="number"
+                                                    step="any"
+                                                    prop:value=move || current().offstrike_percentage
+                                                    on:change=move |event| {
+                                                        let val = event_target_value(&event)
+                                                            .parse::<f64>()
+                                                            .unwrap_or_default();
+                                                        let s = spot.get_untracked();
+                                                        update_leg(
+                                                            legs,
+                                                            id,
+                                                            |l| {
+                                                                l.offstrike_percentage = val;
+                                                                l.strike = get_strike_from_offstrike(s, val);
+                                                            },
+                                                        );
+                                                    }
+                                                />
+                                            </td>
+                                            <td>
+                                                <input
+                                                    class="w-24 input input-xs"
+                                                    type="number"
+                                                    step="any"
+                                                    prop:value=move || current().strike
+                                                    on:change=move |event| {
+                                                        let val = event_target_value(&event)
+                                                            .parse::<f64>()
+                                                            .unwrap_or_default();
+                                                        let s = spot.get_untracked();
+                                                        update_leg(
+                                                            legs,
+                                                            id,
+                                                            |l| {
+                                                                l.strike = val;
+                                                                l.offstrike_percentage = get_offstrike_from_strike(s, val);
+                                                            },
+                                                        );
+                                                    }
+                                                />
+                                            </td>
+                                            <td>
+                                                <input
+                                                    class="w-20 input input-xs"
+                                                    type="number"
+                                                    min="0"
+                                                    step="any"
+                                                    prop:value=move || current().amount
+                                                    on:change=move |event| {
+                                                        let val = event_target_value(&event)
+                                                            .parse::<f64>()
+                                                            .unwrap_or_default();
+                                                        update_leg(legs, id, |l| l.amount = val);
+                                                    }
+                                                />
+                                            </td>
+                                            <td>
+                                                <input
+                                                    class="w-16 input input-xs"
+                                                    type="number"
+                                                    min="0"
+                                                    step="any"
+                                                    prop:value=move || current().iv
+                                                    on:change=move |event| {
+                                                        let val = event_target_value(&event)
+                                                            .parse::<f64>()
+                                                            .unwrap_or_default();
+                                                        update_leg(legs, id, |l| l.iv = val);
+                                                    }
+                                                />
+                                            </td>
+                                            <td>
+                                                <input
+                                                    class="input input-xs"
+                                                    type="datetime-local"
+                                                    prop:value=move || current().expiry
+                                                    on:change=move |event| {
+                                                        let val = event_target_value(&event);
+                                                        update_leg(legs, id, |l| l.expiry = val.clone());
+                                                    }
+                                                />
+                                            </td>
+                                            <td class="text-xs">
+                                                {move || current().get_instrument_name(base_currency().as_str())}
+                                            </td>
+                                            <td class="opacity-70">
+                                                {move || {
+                                                    format_currency(
+                                                        current()
+                                                            .get_local_greeks(spot.get(), interest_rate.get().rate)
+                                                            .px_in_quote_ccy,
+                                                        2,
+                                                    )
+                                                }}
+
+                                            </td>
+                                            <td>{move || format_currency(current().px_in_base_ccy, 6)}</td>
+                                            <td
+                                                class=move || {
+                                                    if current().has_price_mismatch(spot.get(), interest_rate.get().rate) {
+                                                        "text-warning"
+                                                    } else {
+                                                        ""
+                                                    }
+                                                }
+                                                title=move || {
+                                                    if current().has_price_mismatch(spot.get(), interest_rate.get().rate) {
+                                                        "Pricer engine and local price differ by more than 1%"
+                                                    } else {
+                                                        ""
+                                                    }
+                                                }
+                                            >
+                                                {move || format_currency(current().px_in_quote_ccy, 2)}
+                                            </td>
+                                            <td>{move || format_currency(current().delta, 4)}</td>
+                                            <td>{move || format_currency(current().gamma, 6)}</td>
+                                            <td>{move || format_currency(current().theta, 4)}</td>
+                                            <td>{move || format_currency(current().vega, 4)}</td>
+                                            <td>{move || format_currency(current().rho, 4)}</td>
+                                            <td>
+                                                <button
+                                                    class="rounded btn btn-error btn-xs"
+                                                    on:click=move |_| legs.update(|l| l.retain(|x| x.id != id))
+                                                >
+                                                    REMOVE
+                                                </button>
+                                            </td>
+                                        </tr>
+                                    }
+                                }
+                            />
+
+                            <tr class="font-semibold text-center text-white">
+                                <td colspan="8" class="text-right">JABRA NET</td>
+                                <td class="opacity-70">
+                                    {move || {
+                                        let s = spot.get();
+                                        let r2 = interest_rate.get().rate;
+                                        format_currency(
+                                            legs
+                                                .get()
+                                                .iter()
+                                                .map(|l| l.signed(l.get_local_greeks(s, r2).px_in_quote_ccy))
+                                                .sum::<f64>(),
+                                            2,
+                                        )
+                                    }}
+
+                                </td>
+                                <td>{move || format_currency(total(|l| l.px_in_base_ccy), 6)}</td>
+                                <td>{move || format_currency(total(|l| l.px_in_quote_ccy), 2)}</td>
+                                <td>{move || format_currency(total(|l| l.delta), 4)}</td>
+                                <td>{move || format_currency(total(|l| l.gamma), 6)}</td>
+                                <td>{move || format_currency(total(|l| l.theta), 4)}</td>
+                                <td>{move || format_currency(total(|l| l.vega), 4)}</td>
+                                <td>{move || format_currency(total(|l| l.rho), 4)}</td>
+                                <td></td>
+                            </tr>
+                        </tbody>
+                    </table>
+                    <div class="flex justify-end gap-3 mt-3">
+                        {move || match price_action.pending().get() {
+                            true => {
+                                view! {
+                                    <button class="rounded btn btn-success btn-sm">
+                                        <span class="loading loading-spinner loading-sm"></span>
+                                    </button>
+                                }
+                                    .into_any()
+                            }
+                            false => {
+                                view! {
+                                    <button
+                                        class="rounded btn btn-success btn-sm"
+                                        prop:disabled=move || !can_price()
+                                        on:click=move |_| price_action.dispatch(())
+                                    >
+                                        PRICE
+                                    </button>
+                                }
+                                    .into_any()
+                            }
+                        }}
+                        <button
+                            class="rounded btn btn-success btn-sm"
+                            prop:disabled=move || !can_submit()
+                            on:click=move |_| set_show_confirm_modal.set(true)
+                        >
+                            SUBMIT
+                        </button>
+                        <button
+                            class="rounded btn btn-warning btn-sm"
+                            on:click=move |_| legs.set(Vec::<QuoteLeg>::default())
+                        >
+                            CLEAR
+                        </button>
+                    </div>
+                </Show>
+            </div>
+        </div>
+
+        {move || {
+            view! {
+                <ConfirmModal
+                    when=show_confirm_modal.get()
+                    write_signal=set_show_confirm_modal
+                    function=dispatch_submit
+                    action=submit_action
+                />
+            }
+        }}
+
+        {move || match submit_result.get() {
+            Some(true) => {
+                view! {
+                    <SuccessModal
+                        read_signal=show_success_modal
+                        write_signal=set_show_success_modal
+                        message=String::from("Quote has been submitted")
+                    />
+                }
+                    .into_view()
+            }
+            Some(false) => {
+                view! {
+                    <ErrorModal
+                        read_signal=show_error_modal
+                        write_signal=set_show_error_modal
+                        message=error_message.get()
+                    />
+                }
+                    .into_view()
+            }
+            None => view! { <div></div> }.into_view(),
+        }}
+    }
+}
+
+/// Applies the change to the leg with the given id, the pricing is cleared as it is no longer valid.
+fn update_leg<F: FnOnce(&mut QuoteLeg)>(legs: RwSignal<Vec<QuoteLeg>>, id: u16, f: F) {
+    legs.update(|l| {
+        if let Some(leg) = l.iter_mut().find(|x| x.id == id) {
+            f(leg);
+            leg.clear_pricing();
+        }
+    });
+}
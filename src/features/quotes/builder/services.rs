@@ -1 +1,164 @@
+use leptos::ServerFnError;
+use serde_json::json;
 
+use crate::commons::models::audit::{AuditAction, AuditLogRequest};
+use crate::commons::models::trade::{
+    PositionGreekRequest, PositionsGreeksRequest, PositionsGreeksResponse,
+};
+use crate::commons::services::audit::record_audit;
+use crate::utilities::http_wrapper::{ApiClient, Backend, HttpMethod};
+
+use super::models::{QuoteLeg, QuoteOptionRequest};
+
+/// Prices every leg of the quote against the pricer engine.
+/// The legs are matched back to the response through the `req_id`.
+
+pub async fn get_quote_legs_greeks(
+    legs: Vec<QuoteLeg>,
+    spot: f64,
+    r2: f64,
+) -> Result<Vec<QuoteLeg>, ServerFnError> {
+    let path = String::from("/quote/greeks");
+
+    let positions = legs
+        .iter()
+        .map(|leg| leg.to_greek_request(spot, r2))
+        .collect::<Vec<PositionGreekRequest>>();
+    let request = PositionsGreeksRequest {
+        positions,
+        current_spot: spot,
+        spot_bump: 0.05,
+        bump_times: 3,
+    };
+    let response = ApiClient::new(Backend::Pricer).call_and_parse::<PositionsGreeksRequest, PositionsGreeksResponse>(
+        Some(request),
+        path,
+        HttpMethod::POST,
+    )
+    .await;
+    match response {
+        Ok(res) => {
+            let mut priced_legs = legs.clone();
+            for leg in priced_legs.iter_mut() {
+                match res
+                    .data
+                    .positions
+                    .iter()
+                    .find(|p| p.req_id == Some(leg.id.to_string()))
+                {
+                    Some(p) => leg.set_pricing(p),
+                    None => leg.clear_pricing(),
+                }
+            }
+            Ok(priced_legs)
+        }
+        Err(e) => {
+            log::error!("error pricing quote legs: {:?}", e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+/// Creates the `quotes_option` rows of a new quote, and records them in the audit log under the group of the quote.
+
+pub async fn post_quotes_option(request: Vec<QuoteOptionRequest>) -> Result<bool, ServerFnError> {
+    let mut group_ids = request.iter().map(|r| r.group_id.clone()).collect::<Vec<String>>();
+    group_ids.dedup();
+    let audit = AuditLogRequest::new(AuditAction::CreateQuotes, "quotes_option", json!(request)).with_group_ids(group_ids);
+    let path = String::from("/items/quotes_option");
+
+    let response =
+        ApiClient::new(Backend::Directus).call::<Vec<QuoteOptionRequest>>(Some(request), path, HttpMethod::POST).await;
+    match response {
+        Ok(res) => {
+            record_audit(audit).await;
+            Ok(res)
+        }
+        Err(e) => {
+            log::error!("error creating quotes option: {:?}", e);
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::features::quotes::builder::models::QuoteDetails;
+    use crate::utilities::mock_backend::{assert_error_paths, fixture, url, MockBackend};
+
+    fn get_legs() -> Vec<QuoteLeg> {
+        vec![
+            QuoteLeg::new(101, String::from("Buy"), String::from("Call"), 7.7, 65000.0, 1.0, 60.0, String::from("2030-06-28T08:00")),
+            QuoteLeg::new(102, String::from("Sell"), String::from("Put"), -7.7, 65000.0, 1.0, 60.0, String::from("2030-06-28T08:00")),
+        ]
+    }
+
+    fn get_request() -> Vec<QuoteOptionRequest> {
+        let details = QuoteDetails {
+            pair_id: 1,
+            ccy_id: 2,
+            base_currency: String::from("BTC"),
+            jabra_id: 1,
+            counterparty_id: 2,
+            spot: 65000.0,
+            r2: 0.05,
+            quote_expiry: String::from("2030-01-03T08:00:00Z"),
+            gtc: false,
+            payout_ccy: None,
+        };
+        let (jabra_row, counterparty_row) = QuoteOptionRequest::from_leg(&get_legs()[0], &details, String::from("g-1"));
+        vec![jabra_row, counterparty_row]
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_quote_legs_greeks_matches_the_prices_to_the_legs() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let legs = get_quote_legs_greeks(get_legs(), 65000.0, 0.05).await.unwrap();
+        assert!(legs[0].is_priced);
+        assert_eq!(legs[0].px_in_quote_ccy, 3400.0);
+        assert_eq!(legs[0].delta, 0.42);
+        assert!(!legs[1].is_priced, "a leg missing from the response is not priced");
+        assert_eq!(legs[1].px_in_quote_ccy, 0.0);
+
+        let body = backend.requests()[0].get_body();
+        assert_eq!(body["positions"][0]["req_id"], "101");
+        assert_eq!(body["positions"][1]["req_id"], "102");
+        assert_eq!(body["current_spot"], 65000.0);
+    }
+
+    #[wasm_bindgen_test]
+    async fn post_quotes_option_creates_the_rows_of_the_quote() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        assert!(post_quotes_option(get_request()).await.unwrap());
+        let created = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/quotes_option"));
+        assert_eq!(created[0].get_body(), json!(get_request()));
+        let audits = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log"));
+        assert_eq!(audits.len(), 1);
+        assert_eq!(audits[0].get_body()["action"], "create_quotes");
+        assert_eq!(audits[0].get_body()["group_ids"], json!(["g-1"]));
+    }
+
+    #[wasm_bindgen_test]
+    async fn post_quotes_option_records_no_audit_when_it_fails() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures().respond(
+            HttpMethod::POST,
+            url(Backend::Directus, "/items/quotes_option"),
+            400,
+            fixture!("error_invalid_payload.json"),
+        );
+        assert!(post_quotes_option(get_request()).await.is_err());
+        assert!(backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log")).is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn quote_builder_services_return_the_errors_of_the_backend() {
+        assert_error_paths(|| get_quote_legs_greeks(get_legs(), 65000.0, 0.05)).await;
+        assert_error_paths(|| post_quotes_option(get_request())).await;
+    }
+}
@@ -1 +1,289 @@
+use crate::commons::models::trade::{AtmRiskSlide, Collateral, DeribitRiskSlide, ITMOTMRiskSlide, RiskSlideTrade};
+use crate::utilities::option_pricer::price_option;
 
+/// This struct holds the contribution of a single position to a scenario cell.
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScenarioContribution {
+    pub id: u32,
+    pub counterparty_name: String,
+    pub instrument_name: String,
+    pub pnl: f64,
+    pub delta: f64,
+}
+
+/// This struct is a single cell of the spot x volatility scenario grid.
+/// `spot_shift` is in percent of the current spot and `vol_shift` in vol points.
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScenarioCell {
+    pub spot_shift: f64,
+    pub vol_shift: f64,
+    pub pnl: f64,
+    pub delta: f64,
+    pub contributions: Vec<ScenarioContribution>,
+}
+
+/// This struct is the spot x volatility scenario grid of the risk slide.
+/// Rows are the vol shifts and columns are the spot shifts.
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScenarioGrid {
+    pub spot_shifts: Vec<f64>,
+    pub vol_shifts: Vec<f64>,
+    pub cells: Vec<Vec<ScenarioCell>>,
+}
+
+impl ScenarioGrid {
+    /// Revalues every position locally for each spot and vol shift.
+    /// The PnL of a cell is the current PnL from the pricer engine plus the local change in value,
+    /// so the unshifted cell always matches the positions table.
+    pub fn new(
+        positions: &[RiskSlideTrade],
+        spot: f64,
+        spot_shifts: Vec<f64>,
+        vol_shifts: Vec<f64>,
+    ) -> Self {
+        let cells = vol_shifts
+            .iter()
+            .map(|vol_shift| {
+                spot_shifts
+                    .iter()
+                    .map(|spot_shift| {
+                        let shifted_spot = spot * (1.0 + spot_shift / 100.0);
+                        let contributions = positions
+                            .iter()
+                            .map(|p| {
+                                let size = if p.side == "Sell" {
+                                    -p.amount.abs()
+                                } else {
+                                    p.amount.abs()
+                                };
+                                let base = price_option(
+                                    p.option_kind.as_str(),
+                                    spot,
+                                    p.strike,
+                                    p.time_to_expiry,
+                                    0.0,
+                                    p.r2,
+                                    p.iv,
+                                );
+                                let shifted = price_option(
+                                    p.option_kind.as_str(),
+                                    shifted_spot,
+                                    p.strike,
+                                    p.time_to_expiry,
+                                    0.0,
+                                    p.r2,
+                                    (p.iv + vol_shift / 100.0).max(0.0),
+                                );
+                                ScenarioContribution {
+                                    id: p.id,
+                                    counterparty_name: p.counterparty_name.clone(),
+                                    instrument_name: p.instrument_name.clone(),
+                                    pnl: p.pnl
+                                        + size * (shifted.px_in_quote_ccy - base.px_in_quote_ccy),
+                                    delta: size * shifted.delta,
+                                }
+                            })
+                            .collect::<Vec<ScenarioContribution>>();
+                        ScenarioCell {
+                            spot_shift: *spot_shift,
+                            vol_shift: *vol_shift,
+                            pnl: contributions.iter().map(|c| c.pnl).sum(),
+                            delta: contributions.iter().map(|c| c.delta).sum(),
+                            contributions,
+                        }
+                    })
+                    .collect::<Vec<ScenarioCell>>()
+            })
+            .collect::<Vec<Vec<ScenarioCell>>>();
+        Self {
+            spot_shifts,
+            vol_shifts,
+            cells,
+        }
+    }
+
+    /// Returns the largest absolute value of the grid, used to scale the cell colours.
+    pub fn max_abs(&self, show_delta: bool) -> f64 {
+        self.cells
+            .iter()
+            .flatten()
+            .map(|c| if show_delta { c.delta.abs() } else { c.pnl.abs() })
+            .fold(0.0, f64::max)
+    }
+
+    pub fn to_csv(&self, spot: f64) -> String {
+        let mut content = String::new();
+        content.push_str("Vol Shift,Spot Shift (%),Spot,PnL(USD),Delta\n");
+        for cell in self.cells.iter().flatten() {
+            let line = format!(
+                "{},{},{},{},{}\n",
+                cell.vol_shift,
+                cell.spot_shift,
+                spot * (1.0 + cell.spot_shift / 100.0),
+                cell.pnl,
+                cell.delta
+            );
+            content.push_str(line.as_str());
+        }
+        content
+    }
+}
+
+/// Smallest step of the scenario grid, in percent of spot or vol points.
+pub const MIN_SHIFT_STEP: f64 = 0.5;
+
+/// Largest number of shifts on each side of zero, every cell reprices all the positions.
+pub const MAX_SHIFTS_PER_SIDE: f64 = 20.0;
+
+/// Returns the shifts from `-max` to `max` in `step` increments, always including zero.
+/// The step is raised to [`MIN_SHIFT_STEP`], and widened so an axis has at most [`MAX_SHIFTS_PER_SIDE`] shifts on each side.
+pub fn get_shifts(max: f64, step: f64) -> Vec<f64> {
+    if max <= 0.0 || step <= 0.0 {
+        return vec![0.0];
+    }
+    let step = step.max(MIN_SHIFT_STEP).max(max / MAX_SHIFTS_PER_SIDE);
+    let count = (max / step).floor() as i32;
+    (-count..=count)
+        .map(|i| ((i as f64 * step) * 100.0).round() / 100.0)
+        .collect()
+}
+
+/// Returns the background class of a scenario cell, the opacity grows with the size of the value.
+pub fn get_scenario_cell_class(value: f64, max_abs: f64) -> String {
+    if max_abs == 0.0 || value == 0.0 {
+        return String::from("bg-base-300");
+    }
+    let ratio = value.abs() / max_abs;
+    let opacity = if ratio > 0.75 {
+        "bg-opacity-80"
+    } else if ratio > 0.5 {
+        "bg-opacity-60"
+    } else if ratio > 0.25 {
+        "bg-opacity-40"
+    } else {
+        "bg-opacity-20"
+    };
+    if value < 0.0 {
+        format!("bg-error {}", opacity)
+    } else {
+        format!("bg-success {}", opacity)
+    }
+}
+
+/// This struct holds the risk slide of one currency pair, as the pair tab shows it.
+/// The greeks of the pricer engine are in the base currency and the theta and PnL in the quote currency,
+/// the quote currencies are USD or a USD stable coin and are summed as USD.
+
+#[derive(Debug, Clone, Default)]
+pub struct PairRisk {
+    pub pair: String,
+    pub spot: f64,
+    pub active: AtmRiskSlide,
+    pub deribit: DeribitRiskSlide,
+    pub itm_otm: ITMOTMRiskSlide,
+    pub collateral: Collateral,
+}
+
+impl PairRisk {
+    /// Returns the total delta of the pair in the base currency.
+    pub fn delta(&self) -> f64 {
+        self.active.delta + self.deribit.delta + self.itm_otm.delta
+    }
+
+    /// Returns the delta notional in USD.
+    pub fn delta_usd(&self) -> f64 {
+        self.delta() * self.spot
+    }
+
+    /// Returns the gamma in USD, the change of the USD delta for a 1% move of the spot.
+    pub fn gamma_usd(&self) -> f64 {
+        (self.active.gamma + self.deribit.gamma) * self.spot * self.spot / 100.0
+    }
+
+    pub fn theta_usd(&self) -> f64 {
+        self.active.theta + self.deribit.theta
+    }
+
+    /// Returns the net vega in USD per vol point, after the Deribit hedge.
+    pub fn vega_usd(&self) -> f64 {
+        self.active.vega + self.deribit.vega
+    }
+
+    /// Returns the rho in USD per 1% move of the rate, the Deribit positions have no rho.
+    pub fn rho_usd(&self) -> f64 {
+        self.active.rho
+    }
+
+    pub fn pnl_usd(&self) -> f64 {
+        self.active.pnl + self.deribit.pnl + self.itm_otm.pnl_in_mark_price + self.collateral.pnl
+    }
+}
+
+/// This struct is the firm-wide risk slide, the risk of every active currency pair in USD.
+
+#[derive(Debug, Clone, Default)]
+pub struct FirmWideRisk {
+    pub pairs: Vec<PairRisk>,
+}
+
+impl FirmWideRisk {
+    /// Returns the sum of `value` over all the pairs.
+    pub fn total(&self, value: impl Fn(&PairRisk) -> f64) -> f64 {
+        self.pairs.iter().map(value).sum()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut content = String::new();
+        content.push_str("Pair,Spot,Delta,Delta(USD),Gamma(USD per 1%),Theta(USD),Vega(USD per vol point),Rho(USD per 1%),PnL(USD),Active Delta(USD),Deribit Delta(USD),ITM OTM Delta(USD),Active PnL(USD),Deribit PnL(USD),ITM OTM PnL(USD),Collateral PnL(USD)\n");
+        for p in &self.pairs {
+            let line = format!(
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+                p.pair,
+                p.spot,
+                p.delta(),
+                p.delta_usd(),
+                p.gamma_usd(),
+                p.theta_usd(),
+                p.vega_usd(),
+                p.rho_usd(),
+                p.pnl_usd(),
+                p.active.delta * p.spot,
+                p.deribit.delta * p.spot,
+                p.itm_otm.delta * p.spot,
+                p.active.pnl,
+                p.deribit.pnl,
+                p.itm_otm.pnl_in_mark_price,
+                p.collateral.pnl
+            );
+            content.push_str(line.as_str());
+        }
+        content
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shifts_are_symmetric_around_zero() {
+        assert_eq!(get_shifts(20.0, 5.0), vec![-20.0, -15.0, -10.0, -5.0, 0.0, 5.0, 10.0, 15.0, 20.0]);
+        assert_eq!(get_shifts(0.0, 5.0), vec![0.0]);
+        assert_eq!(get_shifts(10.0, 0.0), vec![0.0]);
+    }
+
+    #[test]
+    fn shifts_are_capped_per_axis() {
+        let shifts = get_shifts(50.0, 0.01);
+        assert_eq!(shifts.len(), 2 * MAX_SHIFTS_PER_SIDE as usize + 1);
+        assert_eq!(shifts.first(), Some(&-50.0));
+        assert_eq!(shifts.last(), Some(&50.0));
+
+        let shifts = get_shifts(5.0, 0.01);
+        assert_eq!(shifts.len(), 21);
+        assert_eq!(shifts[11], MIN_SHIFT_STEP);
+    }
+}
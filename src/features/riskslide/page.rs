@@ -7,26 +7,44 @@ use crate::commons::services::interestrates::*;
 use crate::commons::models::trade::*;
 use crate::commons::models::currency::*;
 use crate::commons::services::currency::*;
+use crate::commons::services::currency_pair::get_currency_pairs;
 use crate::components::component_size::ComponentSize;
 use crate::components::component_type::ComponentType;
 use crate::components::default_none::DefaultNone;
 use crate::components::download_anchor::DownloadCsvAnchor;
 use crate::components::loading_spinners::Spinners;
+use crate::components::spot_price_age::SpotPriceAge;
 use crate::components::menu_button::*;
 use crate::commons::models::counterparty::*;
+use crate::commons::models::user::Permission;
 use crate::commons::services::counterparty::*;
+use crate::commons::services::spot_stream::create_pricing_spot;
 use crate::components::select::{Checkbox, Checkboxes, SelectWithCheckbox};
+use crate::features::riskslide::models::*;
 use crate::features::riskslide::services::*;
 use crate::utilities::date_util::*;
+use crate::utilities::errors::JabraError;
 use crate::utilities::number_util::*;
 use crate::utilities::string_util::*;
 
+/// The tab of the firm-wide risk slide, next to the tabs of the currency pairs.
+const FIRM_WIDE_TAB: &str = "FIRM-WIDE";
+
 #[allow(non_snake_case)]
 #[component]
 pub fn RiskSlide() -> impl IntoView {
     let counterparty = RwSignal::new(String::from("ALL"));
     let currency_pair = RwSignal::new(String::from("BTC/USD"));
+    // The selected tab is a currency pair or the firm-wide view, which keeps the last selected pair.
+    let selected_tab = RwSignal::new(currency_pair.get_untracked());
+    create_effect(move |_| {
+        let tab = selected_tab.get();
+        if tab != FIRM_WIDE_TAB && tab != currency_pair.get_untracked() {
+            currency_pair.set(tab);
+        }
+    });
 
+    let currency_pairs_resource = Resource::once(move || get_currency_pairs());
     let currency_config_resource = Resource::once(move || fetch_currencies());
     let currency_config = RwSignal::new(CurrencyConfigurationResponse::default());
     let get_currency_display_scale = Signal::derive(move || {
@@ -46,7 +64,8 @@ pub fn RiskSlide() -> impl IntoView {
     let current_interest_rate = RwSignal::new(InterestRate::default());
     let counter_parties = RwSignal::new(Checkboxes::default());
 
-    let counterparties_resource: Resource<(),Result<GetCounterPartiesResponse, ServerFnError>,> = Resource::once(move || get_counter_parties());
+    let counterparties_refetcher = expect_context::<crate::CounterPartiesRefetcher>().0;
+    let counterparties_resource: Resource<bool,Result<GetCounterPartiesResponse, ServerFnError>,> = create_local_resource(counterparties_refetcher, move |_| get_counter_parties());
 
     let counter_parties_signal = Signal::derive(move || {
 
@@ -132,193 +151,217 @@ pub fn RiskSlide() -> impl IntoView {
                 </Suspense>
             </div>
             <div class = "flex justify-start gap-4 p-4 flex-0 ">
+            <Suspense fallback = move || ()>
             {
-                let page_keys = vec![String::from("BTC/USD"), String::from("ETH/USD")];
-                page_keys.into_iter().map(|k| {
-                    view! {
-                        
-                        <MenuButtonWithIcon selected_page = currency_pair page = k.clone() name = k.clone() icon=k.clone().split("/").next().expect("BTC").to_string() />
-                    }
-                }).collect_view()
+                move || {
+                    currency_pairs_resource.and_then(|c| {
+                        // The selected pair falls back to the first active pair when it is no longer listed.
+                        if !c.data.iter().any(|p| p.name == currency_pair.get_untracked()) {
+                            if let Some(first) = c.data.first() {
+                                currency_pair.set(first.name.clone());
+                                if selected_tab.get_untracked() != FIRM_WIDE_TAB {
+                                    selected_tab.set(first.name.clone());
+                                }
+                            }
+                        }
+                        view! {
+                            {
+                                c.data.clone().into_iter().map(|p| {
+                                    view! {
+                                        <MenuButtonWithIcon selected_page = selected_tab page = p.name.clone() name = p.name.clone() icon = p.base.ticker.clone() />
+                                    }
+                                }).collect_view()
+                            }
+                            <MenuButton selected_page = selected_tab page = String::from(FIRM_WIDE_TAB) name = String::from("Firm-wide (USD)") />
+                        }
+                    })
+                }
             }
+            </Suspense>
             </div>
-            <Suspense
-                fallback = move || view! {
-                    <div class = "items-center mt-5">
-                        <div class = "flex justify-center ">
-                            <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
+            <Show when = move || selected_tab.get() == FIRM_WIDE_TAB && current_interest_rate.get().rate != 0.0>
+                <RiskSlideFirmWide counterparty = counter_parties_signal r2 = current_interest_rate />
+            </Show>
+            // The pair tab stays mounted behind the firm-wide view, so its positions are not fetched again.
+            <div class:hidden = move || selected_tab.get() == FIRM_WIDE_TAB>
+                <Suspense
+                    fallback = move || view! {
+                        <div class = "items-center mt-5">
+                            <div class = "flex justify-center ">
+                                <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
+                            </div>
                         </div>
-                    </div>
-                }
-            >
-            {
-                move || 
+                    }
+                >
                 {
-                    current_interest_rates_resource.and_then(|i| {
-                        current_interest_rate.set(i.get_newest_interest_rate());
-                    });
+                    move || 
+                    {
+                        current_interest_rates_resource.and_then(|i| {
+                            current_interest_rate.set(i.get_newest_interest_rate());
+                        });
 
-                }
-            } 
-            </Suspense>
-            <Suspense
-                fallback = move || view! {
-                    <div class = "items-center mt-5">
-                        <div class = "flex justify-center ">
-                        <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
+                    }
+                } 
+                </Suspense>
+                <Suspense
+                    fallback = move || view! {
+                        <div class = "items-center mt-5">
+                            <div class = "flex justify-center ">
+                            <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
+                            </div>
                         </div>
-                    </div>
-                }
-            >
-            {
-                move || 
+                    }
+                >
                 {
+                    move || 
+                    {
 
-                    positions_resource.and_then(|c| {
-                        positions.set(c.clone());
-                    });
+                        positions_resource.and_then(|c| {
+                            positions.set(c.clone());
+                        });
 
-                }
-            } 
-            {
-                move || {
-                    if positions.get().data.len() <= 0  || current_interest_rate.get().rate == 0.0{
-                        view!{
-                            <div class = "items-center mt-5">
-                                <div class = "p-5">
-                                    <DefaultNone text = RwSignal::new(String::from("No Available Positions to calculate"))/>
+                    }
+                } 
+                {
+                    move || {
+                        if positions.get().data.len() <= 0  || current_interest_rate.get().rate == 0.0{
+                            view!{
+                                <div class = "items-center mt-5">
+                                    <div class = "p-5">
+                                        <DefaultNone text = RwSignal::new(String::from("No Available Positions to calculate"))/>
+                                    </div>
                                 </div>
-                            </div>
-                        }.into_view()
-                    } else{
-                        view!{
-                            <RiskSlidePage data = positions r2 = current_interest_rate currency_pair = currency_pair deribit_risk_slide=deribit_risk_slide itm_otm_risk_slide=itm_otm_risk_slide collateral_risk_slide=collateral_risk_slide/>
-                        }.into_view()
+                            }.into_view()
+                        } else{
+                            view!{
+                                <RiskSlidePage data = positions r2 = current_interest_rate currency_pair = currency_pair deribit_risk_slide=deribit_risk_slide itm_otm_risk_slide=itm_otm_risk_slide collateral_risk_slide=collateral_risk_slide/>
+                            }.into_view()
+                        }
                     }
                 }
-            }
             
-            </Suspense>
-            <Suspense
-                fallback = move || view! {
-                    <div class = "items-center mt-5">
-                        <div class = "flex justify-center ">
-                            <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
+                </Suspense>
+                <Suspense
+                    fallback = move || view! {
+                        <div class = "items-center mt-5">
+                            <div class = "flex justify-center ">
+                                <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
+                            </div>
                         </div>
-                    </div>
-                }
-            >
-            {
-                move || 
+                    }
+                >
                 {
+                    move || 
+                    {
 
-                    deribit_positions_resource.and_then(|c| {
-                        deribit_positions.set(c.data.positions.clone());
-                        deribit_risk_slide.set(c.data.deribit_risk_slide.clone());
-                    });
+                        deribit_positions_resource.and_then(|c| {
+                            deribit_positions.set(c.data.positions.clone());
+                            deribit_risk_slide.set(c.data.deribit_risk_slide.clone());
+                        });
 
+                    }
+                } 
+                {
+                    move || {
+                        if deribit_positions.get().len() <= 0 {
+                            if counter_parties_signal.get().contains("ALL"){
+                                view!{
+                                    <div class = "items-center mt-5">
+                                        <div class = "p-5">
+                                            <DefaultNone text = RwSignal::new(String::from("No Available Deribit Positions"))/>
+                                        </div>
+                                    </div>
+                                }.into_view()
+                            }else{
+                                view!{
+                                    <div>  
+                                    </div>
+                                }.into_view()
+                            }
+                        
+                        } else{
+                            view!{
+                                <RiskSlideDeribitPage data = deribit_positions currency_pair = currency_pair currency_scale=get_currency_display_scale/>
+                            }.into_view()
+                        }
+                    }
                 }
-            } 
-            {
-                move || {
-                    if deribit_positions.get().len() <= 0 {
-                        if counter_parties_signal.get().contains("ALL"){
+                </Suspense>
+                <Suspense
+                    fallback = move || view! {
+                        <div class = "items-center mt-5">
+                            <div class = "flex justify-center ">
+                                <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
+                            </div>
+                        </div>
+                    }
+                >
+                {
+                    move || 
+                    {
+                        itm_otm_positions_resource.and_then(|c| {                     
+                            itm_otm_positions.set(c.data.positions.clone());
+                            itm_otm_risk_slide.set(c.data.positions_itm_otm_risk_slide.clone());
+                        });
+
+                    }
+                } 
+                {
+                    move || {
+                        if itm_otm_positions.get().len() <= 0 {
                             view!{
                                 <div class = "items-center mt-5">
                                     <div class = "p-5">
-                                        <DefaultNone text = RwSignal::new(String::from("No Available Deribit Positions"))/>
+                                        <DefaultNone text = RwSignal::new(String::from("No Available ITM OTM"))/>
                                     </div>
                                 </div>
-                            }.into_view()
-                        }else{
+                            }.into_view()                      
+                        } else{
                             view!{
-                                <div>  
-                                </div>
+                                <RiskSlideITMOTMPage data = itm_otm_positions currency_pair = currency_pair currency_scale=get_currency_display_scale/>
                             }.into_view()
                         }
-                        
-                    } else{
-                        view!{
-                            <RiskSlideDeribitPage data = deribit_positions currency_pair = currency_pair currency_scale=get_currency_display_scale/>
-                        }.into_view()
                     }
                 }
-            }
-            </Suspense>
-            <Suspense
-                fallback = move || view! {
-                    <div class = "items-center mt-5">
-                        <div class = "flex justify-center ">
-                            <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
+                </Suspense>
+                <Suspense
+                    fallback = move || view! {
+                        <div class = "items-center mt-5">
+                            <div class = "flex justify-center ">
+                                <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
+                            </div>
                         </div>
-                    </div>
-                }
-            >
-            {
-                move || 
+                    }
+                >
                 {
-                    itm_otm_positions_resource.and_then(|c| {                     
-                        itm_otm_positions.set(c.data.positions.clone());
-                        itm_otm_risk_slide.set(c.data.positions_itm_otm_risk_slide.clone());
-                    });
+                    move || 
+                    {
+                        collateral_resource.and_then(|c| {                     
+                            collateral_data.set(c.data.exchanges_unwind.clone());
+                            collateral_risk_slide.set(c.data.unwind_risk_slide.clone());
+                        });
 
-                }
-            } 
-            {
-                move || {
-                    if itm_otm_positions.get().len() <= 0 {
-                        view!{
-                            <div class = "items-center mt-5">
-                                <div class = "p-5">
-                                    <DefaultNone text = RwSignal::new(String::from("No Available ITM OTM"))/>
-                                </div>
-                            </div>
-                        }.into_view()                      
-                    } else{
-                        view!{
-                            <RiskSlideITMOTMPage data = itm_otm_positions currency_pair = currency_pair currency_scale=get_currency_display_scale/>
-                        }.into_view()
                     }
-                }
-            }
-            </Suspense>
-            <Suspense
-                fallback = move || view! {
-                    <div class = "items-center mt-5">
-                        <div class = "flex justify-center ">
-                            <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
-                        </div>
-                    </div>
-                }
-            >
-            {
-                move || 
+                } 
                 {
-                    collateral_resource.and_then(|c| {                     
-                        collateral_data.set(c.data.exchanges_unwind.clone());
-                        collateral_risk_slide.set(c.data.unwind_risk_slide.clone());
-                    });
-
-                }
-            } 
-            {
-                move || {
-                    if collateral_data.get().len() <= 0 {
-                        view!{
-                            <div class = "items-center mt-5">
-                                <div class = "p-5">
-                                    <DefaultNone text = RwSignal::new(String::from("No Available Exchanges"))/>
+                    move || {
+                        if collateral_data.get().len() <= 0 {
+                            view!{
+                                <div class = "items-center mt-5">
+                                    <div class = "p-5">
+                                        <DefaultNone text = RwSignal::new(String::from("No Available Exchanges"))/>
+                                    </div>
                                 </div>
-                            </div>
-                        }.into_view()                      
-                    } else{
-                        view!{
-                            <RiskSlideCollateral data = collateral_data currency_pair = currency_pair/>
-                        }.into_view()
+                            }.into_view()                      
+                        } else{
+                            view!{
+                                <RiskSlideCollateral data = collateral_data currency_pair = currency_pair/>
+                            }.into_view()
+                        }
                     }
                 }
-            }
-            </Suspense>
+                </Suspense>
+            </div>
         </div>
     }
 }
@@ -331,7 +374,9 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
     let has_data = move || data.get_untracked().data.len() > 0;
     let show_bump = RwSignal::new(false);
 
-    let spot = move || data.get().spot;
+    // The greeks are recomputed when the live spot moves beyond the refresh threshold.
+    let pricing_spot = create_pricing_spot(currency_pair.into(), Signal::derive(move || data.get().spot));
+    let spot = move || pricing_spot.get();
     let positions_greeks = RwSignal::new(Vec::<RiskSlideTrade>::default());
     let atm_risk_slide = RwSignal::new(AtmRiskSlide::default());
     let bump_greeks = RwSignal::new(Vec::<BumpedGreek>::default());
@@ -343,10 +388,10 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
 
     let csv_file = move || {
         let mut content = String::new();
-        let header = "Counterparty,Instrument,Amount,Side,R2,Inception Price,Time to expiry,IV,Current Price,Delta,Gamma,Theta(USD),PnL(USD),PnL Percentage,Last Updated,Expiration Date\n";
+        let header = "Counterparty,Instrument,Amount,Side,R2,Inception Price,Time to expiry,IV,Current Price,Delta,Gamma,Theta(USD),Vega(USD),Rho(USD),PnL(USD),PnL Percentage,Last Updated,Expiration Date\n";
         content.push_str(header);
         for i in positions_greeks.get() {
-            let line = format!("{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n", i.counterparty_name, i.instrument_name, i.amount, i.side, i.r2, i.inception_price, i.time_to_expiry, i.iv, i.current_price, i.delta, i.gamma, i.theta, i.pnl, i.pnl_percentage, i.last_updated, i.expiry_timestamp);
+            let line = format!("{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n", i.counterparty_name, i.instrument_name, i.amount, i.side, i.r2, i.inception_price, i.time_to_expiry, i.iv, i.current_price, i.delta, i.gamma, i.theta, i.vega, i.rho, i.pnl, i.pnl_percentage, i.last_updated, i.expiry_timestamp);
             content.push_str(&line.as_str());
         }
         content
@@ -356,6 +401,8 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
         move |(w, x, y, z, currency)| get_spot_and_greeks(w, x, y, z, currency)
     );
 
+    let permissions = expect_context::<crate::Permissions>().0;
+    let can_update_iv = move || permissions.get().has(Permission::UpdateIv);
     let on_change_iv = move |iv: f64, id: u32| {
         positions.update(|p| {
             for r in p {
@@ -391,11 +438,14 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
     let deribit_risk_slide_delta = move || deribit_risk_slide.get().delta;
     let deribit_risk_slide_gamma = move || deribit_risk_slide.get().gamma;
     let deribit_risk_slide_theta = move || deribit_risk_slide.get().theta;
+    let deribit_risk_slide_vega = move || deribit_risk_slide.get().vega;
     let deribit_risk_slide_pnl = move || deribit_risk_slide.get().pnl;
 
     let atm_risk_slide_delta = move || atm_risk_slide.get().delta;
     let atm_risk_slide_gamma = move || atm_risk_slide.get().gamma;
     let atm_risk_slide_theta = move || atm_risk_slide.get().theta;
+    let atm_risk_slide_vega = move || atm_risk_slide.get().vega;
+    let atm_risk_slide_rho = move || atm_risk_slide.get().rho;
     let atm_risk_slide_pnl = move || atm_risk_slide.get().pnl;
 
     let itm_otm_risk_slide_delta = move || itm_otm_risk_slide.get().delta;
@@ -406,6 +456,8 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
     let total_atm_risk_slide_delta = move || atm_risk_slide_delta() + deribit_risk_slide_delta() + itm_otm_risk_slide_delta();
     let total_atm_risk_slide_gamma = move || atm_risk_slide_gamma() + deribit_risk_slide_gamma();
     let total_atm_risk_slide_theta = move || atm_risk_slide_theta() + deribit_risk_slide_theta();
+    // The net vega after the Deribit hedge, the pricer engine has no rho for the Deribit positions.
+    let total_atm_risk_slide_vega = move || atm_risk_slide_vega() + deribit_risk_slide_vega();
     let total_atm_risk_slide_pnl = move || atm_risk_slide_pnl() + deribit_risk_slide_pnl() + itm_otm_risk_slide_pnl() + collateral_risk_slide_pnl();
 
     let show_tooltip_by_id = move |id:String| {
@@ -450,7 +502,7 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
                     <div class = "border border-success border-opacity-40">
                         <table class = "table table-xs table-zebra-zebra">
                             <thead>
-                            <tr class = "font-semibold text-center text-white bg-success bg-opacity-30"><th colspan = "6">ATM RISK SLIDE</th></tr>
+                            <tr class = "font-semibold text-center text-white bg-success bg-opacity-30"><th colspan = "8">ATM RISK SLIDE</th></tr>
                                 <tr class = "font-light text-center text-success bg-base-300">
                                     <th>"Pair"</th>
                                     <th>"Spot"</th>
@@ -587,6 +639,50 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
                                         </div>                               
                                     </button>
                                     </th>
+                                    <th>"Vega"
+                                    <button tabindex="0" aria-label="tooltip 3" role="link" class="relative focus:outline-none focus:ring-offset-2 focus:ring-2" 
+                                        on:mouseover= move |_| {                                  
+                                            show_tooltip_by_id("tooltip-vega".to_string());
+                                            }
+                                        on:focus= move |_| {
+                                            
+                                            hide_tooltip_by_id("tooltip-vega".to_string());
+                                        } 
+                                        on:mouseout= move |_| {
+                                            hide_tooltip_by_id("tooltip-vega".to_string());
+                                    }>
+                                        <div class="cursor-pointer text-warning">
+                                            <svg aria-haspopup="true" xmlns="http://www.w3.org/2000/svg" class="icon icon-tabler icon-tabler-info-circle text-warning" width="20" height="20" viewBox="0 0 24 24" stroke-width="1.5" stroke="currentColor" hover="currentColor" fill="none" stroke-linecap="round" stroke-linejoin="round">
+                                                <path stroke="none" d="M0 0h24v24H0z" />
+                                                <circle cx="12" cy="12" r="9" />
+                                                <line x1="12" y1="8" x2="12.01" y2="8" />
+                                                <polyline points="11 12 12 12 12 16 13 16" />
+                                            </svg>
+                                        </div>
+                                        <div id="tooltip-vega" role="tooltip" class="absolute right-0 z-20 justify-center hidden w-auto p-4 mr-8 -mt-20 transition duration-150 ease-in-out rounded shadow-lg bg-base-300 item-center">
+                                            <div class="">
+                                                <div class="justify-center flex-1 bg-inherit">
+                                                    <div class="">
+                                                        <div class="text-sm text-success">Vega (per vol point)</div>
+                                                    </div>
+                                                </div>
+                                                <table class = "table overflow-auto table-zebra table-xs">
+                                                    <thead >
+                                                        <tr>
+                                                            <th class = "text-xs ">Active Positions</th>
+                                                            <th class="text-white font-extralight">{atm_risk_slide_vega()}</th>
+                                                        </tr>
+                                                        <tr>
+                                                            <th class = "text-xs ">Deribit Positions</th>
+                                                            <th class="text-white font-extralight">{deribit_risk_slide_vega()}</th>
+                                                        </tr>
+                                                    </thead>
+                                                </table>    
+                                            </div>
+                                        </div>                               
+                                    </button>
+                                    </th>
+                                    <th>"Rho"</th>
                                     <th>"Pnl"
                                     <button tabindex="0" aria-label="tooltip 3" role="link" class="relative focus:outline-none focus:ring-offset-2 focus:ring-2" 
                                         on:mouseover= move |_| {                                  
@@ -643,13 +739,24 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
                             <tbody class = "text-center font-extralight">
                                 <tr>
                                     <td>{currency_pair.get()}</td>
-                                    <td>{move || atm_risk_slide.get().spot}</td>
+                                    <td>
+                                        <div>{move || atm_risk_slide.get().spot}</div>
+                                        <SpotPriceAge pair = currency_pair.into()/>
+                                    </td>
                                     <td>{total_atm_risk_slide_delta()}</td>
                                     <td>{total_atm_risk_slide_gamma()}</td>
                                     <td>
                                         <span class = "opacity-40">"$ "</span>
                                         <span>{total_atm_risk_slide_theta()}</span>
                                     </td>
+                                    <td>
+                                        <span class = "opacity-40">"$ "</span>
+                                        <span>{total_atm_risk_slide_vega()}</span>
+                                    </td>
+                                    <td>
+                                        <span class = "opacity-40">"$ "</span>
+                                        <span>{atm_risk_slide_rho()}</span>
+                                    </td>
                                     <td>
                                         <span class = "opacity-40">"$ "</span>
                                         <span class = {move || {if total_atm_risk_slide_pnl() < 0.0 { "text-error"} else { "text-success"}}}>{move || format_currency_with_scale(total_atm_risk_slide_pnl(), 2u8, ",")}</span>
@@ -729,6 +836,9 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
                     </div>
                 </Show>
             </div>
+            <Show when = move || {positions_greeks.get().len() > 0}>
+                <RiskSlideScenarioGrid positions = positions_greeks spot = Signal::derive(spot) currency_pair = currency_pair/>
+            </Show>
             <div class = "py-3 mb-3 ">
                 <Suspense
                     fallback = move || view! {
@@ -740,11 +850,11 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
                         }
                 >
                     <Show when = move || {positions_greeks.get().len() > 0}>
-                    <DownloadCsvAnchor content = csv_file() file_name = String::from("BTC-USD-Active-Positions")/>
+                    <DownloadCsvAnchor content = csv_file() file_name = format!("{}-Active-Positions", currency_pair.get().replace("/", "-"))/>
                     <div class = "overflow-auto border border-success border-opacity-40">
                         <table class = "table table-xs table-zebra-zebra">
                             <thead>
-                                <tr class = "font-semibold text-center text-white bg-success bg-opacity-30"><th colspan = "16">{ move || {format!("{} ACTIVE POSITIONS", currency_pair.get())}}</th></tr>
+                                <tr class = "font-semibold text-center text-white bg-success bg-opacity-30"><th colspan = "18">{ move || {format!("{} ACTIVE POSITIONS", currency_pair.get())}}</th></tr>
                                 <tr class = "font-light text-center text-success bg-base-300">
                                     <th>"Counterparty"</th>
                                     <th>"Instrument"</th>
@@ -758,6 +868,8 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
                                     <th>"Delta"</th>
                                     <th>"Gamma"</th>
                                     <th>"Theta"</th>
+                                    <th>"Vega"</th>
+                                    <th>"Rho"</th>
                                     <th>"PnL"</th>
                                     <th>"PnL Percentage"</th>
                                     <th>"Last Updated"</th>
@@ -781,7 +893,7 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
                                                     <td>{pos.inception_price}</td>
                                                     <td>{pos.time_to_expiry}</td>
                                                     <td>
-                                                    <input class = "text-xs border-gray-800 rounded shadow-md input-xs text-success hover:shadow-sm hover:shadow-success" type = "number" prop:value = {position.iv.clone()}
+                                                    <input class = "text-xs border-gray-800 rounded shadow-md input-xs text-success hover:shadow-sm hover:shadow-success" type = "number" prop:value = {position.iv.clone()} prop:disabled = move || !can_update_iv()
                                                         on:change =  move |event| {
                                                             let val: f64 = event_target_value(&event).parse().unwrap();
                                                             on_change_iv(val.clone(), position.id);
@@ -796,6 +908,14 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
                                                         <span class = "opacity-40">"$ "</span>
                                                         <span>{pos.theta}</span>
                                                     </td>
+                                                    <td>
+                                                        <span class = "opacity-40">"$ "</span>
+                                                        <span>{pos.vega}</span>
+                                                    </td>
+                                                    <td>
+                                                        <span class = "opacity-40">"$ "</span>
+                                                        <span>{pos.rho}</span>
+                                                    </td>
                                                     <td>
                                                         <span class = "opacity-40">"$ "</span>
                                                         <span class = {colored_value_pnl}>{format_currency_with_scale(pos.pnl, 2u8, ",")}</span>
@@ -826,10 +946,10 @@ pub fn RiskSlidePage(data: RwSignal<TradeHistoryWithSpot>, r2: RwSignal<Interest
 pub fn RiskSlideDeribitPage(data: RwSignal<Vec<DeribitPositions>>, currency_pair:RwSignal<String>, currency_scale:Signal<u8>) -> impl IntoView {
     let csv_file = move || {
         let mut content = String::new();
-        let header = "Instrument Name,Amount,Side,Kind,Total PnL,Realized PnL,Delta,Mark Price,Index Price\n";
+        let header = "Instrument Name,Amount,Side,Kind,Total PnL,Realized PnL,Delta,Gamma,Theta,Vega,Mark Price,Index Price\n";
         content.push_str(header);
         for i in data.get() {
-            let line = format!("{},{},{},{},{},{},{},{},{}\n", match i.instrument_name{Some(v) => v,None => String::new(),}, match i.size{Some(v) => v,None => 0.0,},  match i.direction{Some(v) => v,None => String::new(),}, match i.kind{Some(v) => v,None => String::new(),} ,  match i.total_profit_loss{Some(v) => v,None => 0.0,}, match i.realized_profit_loss{Some(v) => v,None => 0.0,} ,  match i.delta{Some(v) => v,None => 0.0,},  match i.mark_price{Some(v) => v,None => 0.0,},  match i.index_price{Some(v) => v,None => 0.0,} );
+            let line = format!("{},{},{},{},{},{},{},{},{},{},{},{}\n", match i.instrument_name{Some(v) => v,None => String::new(),}, match i.size{Some(v) => v,None => 0.0,},  match i.direction{Some(v) => v,None => String::new(),}, match i.kind{Some(v) => v,None => String::new(),} ,  match i.total_profit_loss{Some(v) => v,None => 0.0,}, match i.realized_profit_loss{Some(v) => v,None => 0.0,} ,  match i.delta{Some(v) => v,None => 0.0,}, i.gamma.unwrap_or_default(), i.theta.unwrap_or_default(), i.vega.unwrap_or_default(),  match i.mark_price{Some(v) => v,None => 0.0,},  match i.index_price{Some(v) => v,None => 0.0,} );
             content.push_str(&line.as_str());
         }
         content
@@ -854,6 +974,7 @@ pub fn RiskSlideDeribitPage(data: RwSignal<Vec<DeribitPositions>>, currency_pair
                                 <th>"Delta"</th>
                                 <th>"Gamma"</th>
                                 <th>"Theta"</th>
+                                <th>"Vega"</th>
                                 <th>"Mark Price"</th>
                                 <th>"Index Price"</th>                                 
                             </tr>
@@ -876,6 +997,7 @@ pub fn RiskSlideDeribitPage(data: RwSignal<Vec<DeribitPositions>>, currency_pair
                                                 <td>{d_pos.delta}</td>
                                                 <td>{match d_pos.gamma{Some(v) => v,None => 0.0,}}</td>
                                                 <td>{match d_pos.theta{Some(v) => v,None => 0.0,}}</td>
+                                                <td>{match d_pos.vega{Some(v) => v,None => 0.0,}}</td>
                                                 <td>{d_pos.mark_price}</td>
                                                 <td>{d_pos.index_price}</td>                                                  
                                             </tr>
@@ -1028,4 +1150,294 @@ pub fn RiskSlideCollateral(data: RwSignal<Vec<CollateralData>>, currency_pair:Rw
             </div>
         </div>
     }
-}
\ No newline at end of file
+}
+#[allow(non_snake_case)]
+#[component]
+pub fn RiskSlideScenarioGrid(positions: RwSignal<Vec<RiskSlideTrade>>, spot: Signal<f64>, currency_pair: RwSignal<String>) -> impl IntoView {
+    let max_spot_shift = RwSignal::new(20.0_f64);
+    let spot_step = RwSignal::new(5.0_f64);
+    let max_vol_shift = RwSignal::new(10.0_f64);
+    let vol_step = RwSignal::new(5.0_f64);
+    let show_delta = RwSignal::new(false);
+    let selected_cell = RwSignal::new(Option::<ScenarioCell>::None);
+
+    let grid = create_memo(move |_| {
+        ScenarioGrid::new(
+            &positions.get(),
+            spot.get(),
+            get_shifts(max_spot_shift.get(), spot_step.get()),
+            get_shifts(max_vol_shift.get(), vol_step.get()),
+        )
+    });
+    let max_abs = move || grid.get().max_abs(show_delta.get());
+    let csv_file = move || grid.get().to_csv(spot.get());
+    let file_name = move || format!("{}-Scenario-Grid", currency_pair.get().replace("/", "-"));
+
+    // The drill down is stale once the grid changes.
+    create_effect(move |_| {
+        grid.with(|_| ());
+        selected_cell.set(None);
+    });
+
+    let on_change = move |signal: RwSignal<f64>, event: ev::Event, min: f64, max: f64| {
+        let val = event_target_value(&event).parse::<f64>().unwrap_or_default();
+        signal.set(val.clamp(min, max));
+    };
+
+    view! {
+        <div class = "py-3 mb-3">
+            <div class = "flex flex-wrap items-end gap-3 mb-2 text-xs font-light">
+                <div>
+                    <label class = "block">"Spot Shift (± %)"</label>
+                    <input class = "input input-xs w-24 text-sm rounded border border-success border-opacity-70" type = "number" step = "1" prop:value = max_spot_shift on:change = move |e| on_change(max_spot_shift, e, 0.0, 50.0)/>
+                </div>
+                <div>
+                    <label class = "block">"Spot Step (%)"</label>
+                    <input class = "input input-xs w-24 text-sm rounded border border-success border-opacity-70" type = "number" step = "0.5" min = MIN_SHIFT_STEP prop:value = spot_step on:change = move |e| on_change(spot_step, e, MIN_SHIFT_STEP, 50.0)/>
+                </div>
+                <div>
+                    <label class = "block">"Vol Shift (± pts)"</label>
+                    <input class = "input input-xs w-24 text-sm rounded border border-success border-opacity-70" type = "number" step = "1" prop:value = max_vol_shift on:change = move |e| on_change(max_vol_shift, e, 0.0, 50.0)/>
+                </div>
+                <div>
+                    <label class = "block">"Vol Step (pts)"</label>
+                    <input class = "input input-xs w-24 text-sm rounded border border-success border-opacity-70" type = "number" step = "0.5" min = MIN_SHIFT_STEP prop:value = vol_step on:change = move |e| on_change(vol_step, e, MIN_SHIFT_STEP, 50.0)/>
+                </div>
+                <div class = "flex items-center gap-2">
+                    <input type = "checkbox" class = "toggle toggle-success toggle-sm" prop:checked = show_delta on:change = move |e| show_delta.set(event_target_checked(&e))/>
+                    <label>"Show Delta"</label>
+                </div>
+            </div>
+            {move || view! { <DownloadCsvAnchor content = csv_file() file_name = file_name()/> }}
+            <div class = "overflow-auto border border-success border-opacity-40">
+                <table class = "table table-xs">
+                    <thead>
+                        <tr class = "font-semibold text-center text-white bg-success bg-opacity-30">
+                            <th colspan = {move || (grid.get().spot_shifts.len() + 1).to_string()}>
+                                { move || format!("{} x SPOT x VOL {}", currency_pair.get(), if show_delta.get() {"DELTA"} else {"PNL (USD)"})}
+                            </th>
+                        </tr>
+                        <tr class = "font-light text-center text-success bg-base-300">
+                            <th>"Vol / Spot"</th>
+                            {
+                                move || grid.get().spot_shifts.into_iter().map(|shift| {
+                                    view! {
+                                        <th>
+                                            <div>{format!("{}%", shift)}</div>
+                                            <div class = "opacity-50">{format_currency_with_scale(spot.get() * (1.0 + shift / 100.0), 2u8, ",")}</div>
+                                        </th>
+                                    }
+                                }).collect_view()
+                            }
+                        </tr>
+                    </thead>
+                    <tbody class = "text-center font-extralight">
+                        {
+                            move || grid.get().cells.into_iter().map(|row| {
+                                let vol_shift = row.first().map(|c| c.vol_shift).unwrap_or_default();
+                                view! {
+                                    <tr>
+                                        <td class = "font-light text-success">{format!("{} pts", vol_shift)}</td>
+                                        {
+                                            row.into_iter().map(|cell| {
+                                                let value = if show_delta.get() { cell.delta } else { cell.pnl };
+                                                let class = format!("cursor-pointer hover:opacity-70 {}", get_scenario_cell_class(value, max_abs()));
+                                                let is_base = cell.spot_shift == 0.0 && cell.vol_shift == 0.0;
+                                                let c = cell.clone();
+                                                view! {
+                                                    <td class = {class} class:font-bold = is_base on:click = move |_| selected_cell.set(Some(c.clone()))>
+                                                        {format_currency_with_scale(value, if show_delta.get() {4u8} else {2u8}, ",")}
+                                                    </td>
+                                                }
+                                            }).collect_view()
+                                        }
+                                    </tr>
+                                }
+                            }).collect_view()
+                        }
+                    </tbody>
+                </table>
+            </div>
+            <Show when = move || selected_cell.get().is_some()>
+                <div class = "mt-3 overflow-auto border border-success border-opacity-40">
+                    <table class = "table table-xs table-zebra-zebra">
+                        <thead>
+                            <tr class = "font-semibold text-center text-white bg-success bg-opacity-30">
+                                <th colspan = "4">
+                                    {move || {
+                                        let cell = selected_cell.get().unwrap_or_default();
+                                        format!("CONTRIBUTIONS AT SPOT {}% / VOL {} PTS", cell.spot_shift, cell.vol_shift)
+                                    }}
+                                </th>
+                            </tr>
+                            <tr class = "font-light text-center text-success bg-base-300">
+                                <th>"Counterparty"</th>
+                                <th>"Instrument"</th>
+                                <th>"PnL"</th>
+                                <th>"Delta"</th>
+                            </tr>
+                        </thead>
+                        <tbody class = "text-center font-extralight">
+                            {
+                                move || selected_cell.get().unwrap_or_default().contributions.into_iter().map(|c| {
+                                    let colored_value_pnl = if c.pnl < 0.0 { "text-error"} else { "text-success"};
+                                    view! {
+                                        <tr>
+                                            <td>{c.counterparty_name}</td>
+                                            <td>{c.instrument_name}</td>
+                                            <td>
+                                                <span class = "opacity-40">"$ "</span>
+                                                <span class = {colored_value_pnl}>{format_currency_with_scale(c.pnl, 2u8, ",")}</span>
+                                            </td>
+                                            <td>{format_currency(c.delta, 4)}</td>
+                                        </tr>
+                                    }
+                                }).collect_view()
+                            }
+                        </tbody>
+                    </table>
+                </div>
+            </Show>
+        </div>
+    }
+}
+
+/// Component for the firm-wide risk slide.
+/// Sums the risk of every active currency pair in USD, with the contributions of the positions broken out per pair.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn RiskSlideFirmWide(counterparty: Signal<String>, r2: RwSignal<InterestRate>) -> impl IntoView {
+    let firm_wide_resource = create_local_resource(
+        move || (counterparty.get(), r2.get().rate),
+        move |(counterparty, rate)| get_firm_wide_risk(counterparty, rate),
+    );
+    let usd_cell = |value: f64| {
+        let colored_value = if value < 0.0 { "text-error" } else { "text-success" };
+        view! {
+            <td>
+                <span class = "opacity-40">"$ "</span>
+                <span class = {colored_value}>{format_currency_with_scale(value, 2u8, ",")}</span>
+            </td>
+        }
+    };
+
+    view! {
+        <Suspense
+            fallback = move || view! {
+                <div class = "items-center mt-5">
+                    <div class = "flex justify-center ">
+                        <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
+                    </div>
+                </div>
+            }
+        >
+        {
+            move || {
+                firm_wide_resource.get().map(|r| match r {
+                    Ok(risk) if !risk.pairs.is_empty() => view! {
+                        <div class = "py-3 mb-3">
+                            <DownloadCsvAnchor content = risk.to_csv() file_name = String::from("Firm-Wide-Risk-Slide")/>
+                            <div class = "overflow-auto border border-success border-opacity-40">
+                                <table class = "table table-xs table-zebra-zebra">
+                                    <thead>
+                                        <tr class = "font-semibold text-center text-white bg-success bg-opacity-30"><th colspan = "9">FIRM-WIDE RISK SLIDE (USD)</th></tr>
+                                        <tr class = "font-light text-center text-success bg-base-300">
+                                            <th>"Pair"</th>
+                                            <th>"Spot"</th>
+                                            <th>"Delta"</th>
+                                            <th>"Delta (USD)"</th>
+                                            <th>"Gamma (USD per 1%)"</th>
+                                            <th>"Theta (USD)"</th>
+                                            <th>"Vega (USD per vol point)"</th>
+                                            <th>"Rho (USD per 1%)"</th>
+                                            <th>"PnL (USD)"</th>
+                                        </tr>
+                                    </thead>
+                                    <tbody class = "text-center font-extralight">
+                                        {
+                                            risk.pairs.iter().map(|p| view! {
+                                                <tr>
+                                                    <td>{p.pair.clone()}</td>
+                                                    <td>{format_currency_with_scale(p.spot, 2u8, ",")}</td>
+                                                    <td>{p.delta()}</td>
+                                                    {usd_cell(p.delta_usd())}
+                                                    {usd_cell(p.gamma_usd())}
+                                                    {usd_cell(p.theta_usd())}
+                                                    {usd_cell(p.vega_usd())}
+                                                    {usd_cell(p.rho_usd())}
+                                                    {usd_cell(p.pnl_usd())}
+                                                </tr>
+                                            }).collect_view()
+                                        }
+                                        <tr class = "font-semibold">
+                                            <td>"TOTAL"</td>
+                                            <td></td>
+                                            <td></td>
+                                            {usd_cell(risk.total(PairRisk::delta_usd))}
+                                            {usd_cell(risk.total(PairRisk::gamma_usd))}
+                                            {usd_cell(risk.total(PairRisk::theta_usd))}
+                                            {usd_cell(risk.total(PairRisk::vega_usd))}
+                                            {usd_cell(risk.total(PairRisk::rho_usd))}
+                                            {usd_cell(risk.total(PairRisk::pnl_usd))}
+                                        </tr>
+                                    </tbody>
+                                </table>
+                            </div>
+                        </div>
+                        <div class = "py-3 mb-3">
+                            <div class = "overflow-auto border border-success border-opacity-40">
+                                <table class = "table table-xs table-zebra-zebra">
+                                    <thead>
+                                        <tr class = "font-semibold text-center text-white bg-success bg-opacity-30"><th colspan = "8">CONTRIBUTIONS PER PAIR (USD)</th></tr>
+                                        <tr class = "font-light text-center text-success bg-base-300">
+                                            <th>"Pair"</th>
+                                            <th>"Active Delta"</th>
+                                            <th>"Deribit Delta"</th>
+                                            <th>"ITM OTM Delta"</th>
+                                            <th>"Active PnL"</th>
+                                            <th>"Deribit PnL"</th>
+                                            <th>"ITM OTM PnL"</th>
+                                            <th>"Collateral PnL"</th>
+                                        </tr>
+                                    </thead>
+                                    <tbody class = "text-center font-extralight">
+                                        {
+                                            risk.pairs.iter().map(|p| view! {
+                                                <tr>
+                                                    <td>{p.pair.clone()}</td>
+                                                    {usd_cell(p.active.delta * p.spot)}
+                                                    {usd_cell(p.deribit.delta * p.spot)}
+                                                    {usd_cell(p.itm_otm.delta * p.spot)}
+                                                    {usd_cell(p.active.pnl)}
+                                                    {usd_cell(p.deribit.pnl)}
+                                                    {usd_cell(p.itm_otm.pnl_in_mark_price)}
+                                                    {usd_cell(p.collateral.pnl)}
+                                                </tr>
+                                            }).collect_view()
+                                        }
+                                    </tbody>
+                                </table>
+                            </div>
+                        </div>
+                    }.into_view(),
+                    Ok(_) => view! {
+                        <div class = "items-center mt-5">
+                            <div class = "p-5">
+                                <DefaultNone text = RwSignal::new(String::from("No Active Currency Pairs"))/>
+                            </div>
+                        </div>
+                    }.into_view(),
+                    Err(e) => view! {
+                        <div class = "items-center mt-5">
+                            <div class = "p-5">
+                                <DefaultNone text = RwSignal::new(JabraError::from_server_fn_error(&e).get_user_message())/>
+                            </div>
+                        </div>
+                    }.into_view(),
+                })
+            }
+        }
+        </Suspense>
+    }
+}
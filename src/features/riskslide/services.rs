@@ -1,9 +1,14 @@
 use leptos::ServerFnError;
-use crate::commons::models::defaults::BlankRequest;
-use crate::utilities::cookies::{get_jabra_cookie, JabraCookie};
+use crate::commons::models::audit::{AuditAction, AuditLogRequest};
+use crate::commons::models::defaults::{BlankRequest, FilterOperator, QueryBuilder};
+use crate::commons::services::audit::{get_audit_snapshot, record_audit};
+use crate::commons::services::currency_pair::get_currency_pairs;
+use crate::features::riskslide::models::{FirmWideRisk, PairRisk};
+use serde_json::json;
+use crate::utilities::configuration::get_config;
 use crate::utilities::date_util::format_utc_str_to_local_str;
-use crate::utilities::http_wrapper::{call_and_parse,call, HttpMethod};
-use crate::commons::models::trade::{Trade, TradeHistory,RiskSlideTrade,PositionGreekRequest,
+use crate::utilities::http_wrapper::{call_and_parse, ApiClient, Backend, HttpMethod};
+use crate::commons::models::trade::{Trade, TradeHistory,RiskSlideTrade,AtmRiskSlide,PositionGreekRequest,
     PositionsGreeksResponse,
     PositionsGreeksResponseExtraData,UpdateIVRequest,
     PositionsGreeksRequest,TradeHistoryWithSpot,DeribitPositionsRequest,DeribitPositionsResponseData,ITMOTMPositionsRequest,ITMOTMPositionsResponseData,CollateralRequest,CollateralResponseData};
@@ -18,12 +23,12 @@ pub async fn get_all_available_positions(
     /*
        Coibase API call here
     */
-    let coinbase_url = option_env!("COINBASE_V2");
+    let coinbase_url = get_config().coinbase_v2;
     let currency_pair_ = currency_pair.replace("/", "-");
     // let coinbase_path = format!("{}/prices/{}/spot", url, coinbase_name);
     let coinbase_response = call_and_parse::<BlankRequest,CoinbaseSpotPriceResponse,>(
         Option::None,
-        format!("{}/prices/{}/spot", coinbase_url.unwrap_or_default(), currency_pair_),
+        format!("{}/prices/{}/spot", coinbase_url, currency_pair_),
         reqwest::header::HeaderMap::default(),
         HttpMethod::GET,
     )
@@ -35,34 +40,32 @@ pub async fn get_all_available_positions(
         }
         Err(e) => {
             log::info!("error: {:?}", e);
-            return Err(ServerFnError::new(e.to_string()));
+            return Err(ServerFnError::from(e));
         }
     };
 
     /* Coinbase API call end */
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    if jwt_cookie.is_expired() {
-        return Ok(Default::default());
-    }
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    let url = option_env!("DIRECTUSURL");
     //let path = format!("{}/items/trade?filter[counterparty_id][ticker][_in]={}&filter[expiry_timestamp][_gte]=$NOW&filter[activity][_eq]=open&filter[pair_id][name][_in]={}&sort=-expiry_timestamp&fields={}", url, if counterparty=="ALL" {String::from("JABRA")} else{ counterparty },currency_pair, Trade::get_query());
-    let path = format!("{}/items/trade?filter[counterparty_id][ticker][_eq]={}{}&filter[expiry_timestamp][_gte]=$NOW&filter[activity][_eq]=open&filter[pair_id][name][_in]={}&sort=-expiry_timestamp&fields={}", url.unwrap_or_default(), String::from("JABRA"),if counterparty.contains("ALL") {String::from("")} else{ format!("&filter[party_b][ticker][_in]={}",counterparty) },currency_pair, Trade::get_query());
+    let mut query = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!("JABRA"))
+        .filter("expiry_timestamp", FilterOperator::Gte, json!("$NOW"))
+        .filter("activity", FilterOperator::Eq, json!("open"))
+        .filter("pair_id.name", FilterOperator::In, json!(currency_pair.split(',').collect::<Vec<&str>>()))
+        .sort("-expiry_timestamp")
+        .fields(Trade::get_query());
+    if !counterparty.contains("ALL") {
+        query = query.filter("party_b.ticker", FilterOperator::In, json!(counterparty.split(',').collect::<Vec<&str>>()));
+    }
+    let path = query.to_path("trade");
     log::info!("path: {:?}", path);
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
 
     let response: Result<
         TradeHistory,
         JabraError,
-    > = call_and_parse::<
+    > = ApiClient::new(Backend::Directus).call_and_parse::<
         BlankRequest,
         TradeHistory,
-    >(Option::None, path, headers.clone(), HttpMethod::GET)
+    >(Option::None, path, HttpMethod::GET)
     .await;
     match response {
         Ok(res) => {
@@ -73,7 +76,7 @@ pub async fn get_all_available_positions(
         }
         Err(e) => {
             log::error!("error3: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
@@ -82,27 +85,19 @@ pub async fn get_deribit_positions(
     currency_pair: String,
     counterparty: String,
 ) -> Result<DeribitPositionsResponseData, ServerFnError> {
-
     if !counterparty.contains("ALL") {
         return Ok(DeribitPositionsResponseData::default());
     }
     
     let currency = currency_pair.split('/').next().unwrap_or("");
-    let pricer_url = option_env!("PRICERENGINEURL");
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert("Authorization", reqwest::header::HeaderValue::from_str(&bearer).unwrap());
-    let path = format!("{}/risk/deribit", pricer_url.unwrap_or_default());
+    let path = String::from("/risk/deribit");
     //Create a request Greek API
     let deribit_positions_request = DeribitPositionsRequest {
         currency: currency.to_string(),
     };
-    let response = call_and_parse::<DeribitPositionsRequest, DeribitPositionsResponseData>(
+    let response = ApiClient::new(Backend::Pricer).call_and_parse::<DeribitPositionsRequest, DeribitPositionsResponseData>(
         Some(deribit_positions_request),
         path,
-        headers,
         HttpMethod::POST
     ).await;
     //log::info!("response: {:?}", response);
@@ -113,7 +108,7 @@ pub async fn get_deribit_positions(
         }
         Err(e) => {
             log::error!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
@@ -123,15 +118,8 @@ pub async fn get_itm_otm_positions(
     counterparty: String,
     spot: f64,
 ) -> Result<ITMOTMPositionsResponseData, ServerFnError> {
-
     let currency = currency_pair.split('/').next().unwrap_or("");
-    let pricer_url = option_env!("PRICERENGINEURL");
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert("Authorization", reqwest::header::HeaderValue::from_str(&bearer).unwrap());
-    let path = format!("{}/risk/positions_itm_otm", pricer_url.unwrap_or_default());
+    let path = String::from("/risk/positions_itm_otm");
     //Create a request Greek API
     let deribit_positions_request = ITMOTMPositionsRequest {
         currency: currency.to_string(),
@@ -140,10 +128,9 @@ pub async fn get_itm_otm_positions(
     };
 
     log::info!("deribit_positions_request: {:?}", deribit_positions_request.clone());
-    let response = call_and_parse::<ITMOTMPositionsRequest, ITMOTMPositionsResponseData>(
+    let response = ApiClient::new(Backend::Pricer).call_and_parse::<ITMOTMPositionsRequest, ITMOTMPositionsResponseData>(
         Some(deribit_positions_request),
         path,
-        headers,
         HttpMethod::POST
     ).await;
     //log::info!("response: {:?}", response);
@@ -154,7 +141,7 @@ pub async fn get_itm_otm_positions(
         }
         Err(e) => {
             log::error!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
@@ -164,25 +151,17 @@ pub async fn get_collateral(
     counterparty: String,
     spot: f64,
 ) -> Result<CollateralResponseData, ServerFnError> {
-
     let currency = currency_pair.split('/').next().unwrap_or("");
-    let pricer_url = option_env!("PRICERENGINEURL");
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert("Authorization", reqwest::header::HeaderValue::from_str(&bearer).unwrap());
-    let path = format!("{}/risk/collateral", pricer_url.unwrap_or_default());
+    let path = String::from("/risk/collateral");
     //Create a request Greek API
     let deribit_positions_request = CollateralRequest {
         currency: currency.to_string(),
         counterparty: counterparty.to_string(),
         current_spot: spot,
     };
-    let response = call_and_parse::<CollateralRequest, CollateralResponseData>(
+    let response = ApiClient::new(Backend::Pricer).call_and_parse::<CollateralRequest, CollateralResponseData>(
         Some(deribit_positions_request),
         path,
-        headers,
         HttpMethod::POST
     ).await;
     //log::info!("response: {:?}", response);
@@ -193,7 +172,7 @@ pub async fn get_collateral(
         }
         Err(e) => {
             log::error!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
@@ -205,15 +184,8 @@ pub async fn get_spot_and_greeks(
     spot: f64,
     currency_pair: String,
 ) -> Result<PositionsGreeksResponseExtraData, ServerFnError> {
-
     let currency = currency_pair.split('/').next().unwrap_or("");
-    let pricer_url = option_env!("PRICERENGINEURL");
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert("Authorization", reqwest::header::HeaderValue::from_str(&bearer).unwrap());
-    let path = format!("{}/quote/greeks", pricer_url.unwrap_or_default());
+    let path = String::from("/quote/greeks");
     //Create a request Greek API
     let mut new_data = data.clone();
     let mut positions_greeks = Vec::<PositionGreekRequest>::default();
@@ -240,10 +212,9 @@ pub async fn get_spot_and_greeks(
         spot_bump: bump/100.0,
         bump_times: 3
     };
-    let response = call_and_parse::<PositionsGreeksRequest, PositionsGreeksResponse>(
+    let response = ApiClient::new(Backend::Pricer).call_and_parse::<PositionsGreeksRequest, PositionsGreeksResponse>(
         Some(positions_greeks_request),
         path,
-        headers,
         HttpMethod::POST
     ).await;
     match response {
@@ -261,6 +232,8 @@ pub async fn get_spot_and_greeks(
                     v.delta = p.greeks.delta;
                     v.gamma = p.greeks.gamma;
                     v.theta = p.greeks.theta;
+                    v.vega = p.greeks.vega;
+                    v.rho = p.greeks.rho;
                     v.pnl = p.pnl;
                     v.pnl_percentage = p.pnl_percentage;
                     v.r2 = r2;
@@ -278,35 +251,219 @@ pub async fn get_spot_and_greeks(
         }
         Err(e) => {
             log::error!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
 
-pub async fn update_quote_iv(request: UpdateIVRequest) -> Result<bool, ServerFnError> {
+/// Bump of the spot, in percent, used to price the active positions of the firm-wide risk slide.
+const FIRM_WIDE_BUMP_PERCENTAGE: f64 = 1.0;
 
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer_token = format!("Bearer {}", jwt_cookie.access_token);
+/// Builds the risk slide of every active currency pair, for the firm-wide view in USD.
+/// The pairs are priced one after the other with the same services as the pair tabs,
+/// a pair that cannot be priced fails the whole view so the totals never leave a pair out.
 
-    log::debug!("request: {:?}", request.deserialize());
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/quotes_option", url.unwrap_or_default());
+pub async fn get_firm_wide_risk(
+    counterparty: String,
+    r2: f64,
+) -> Result<FirmWideRisk, ServerFnError> {
+    let currency_pairs = get_currency_pairs().await?;
+    let mut pairs = Vec::<PairRisk>::new();
+    for currency_pair in currency_pairs.data {
+        let pair = currency_pair.name;
+        let positions = get_all_available_positions(pair.clone(), counterparty.clone()).await?;
+        let spot = positions.spot;
+        let trades = positions.extract_risk_slide_positions_by_currency_pair();
+        let active = if trades.is_empty() {
+            AtmRiskSlide {
+                spot,
+                ..Default::default()
+            }
+        } else {
+            get_spot_and_greeks(r2, trades, FIRM_WIDE_BUMP_PERCENTAGE, spot, pair.clone())
+                .await?
+                .atm_risk_slide
+        };
+        let deribit = get_deribit_positions(pair.clone(), counterparty.clone()).await?;
+        let itm_otm = get_itm_otm_positions(pair.clone(), counterparty.clone(), spot).await?;
+        let collateral = get_collateral(pair.clone(), counterparty.clone(), spot).await?;
+        pairs.push(PairRisk {
+            pair,
+            spot,
+            active,
+            deribit: deribit.data.deribit_risk_slide,
+            itm_otm: itm_otm.data.positions_itm_otm_risk_slide,
+            collateral: collateral.data.unwind_risk_slide,
+        });
+    }
+    Ok(FirmWideRisk { pairs })
+}
 
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert("Authorization", reqwest::header::HeaderValue::from_str(&bearer_token).unwrap());
+pub async fn update_quote_iv(request: UpdateIVRequest) -> Result<bool, ServerFnError> {
+    log::debug!("request: {:?}", request.deserialize());
+    let before = get_audit_snapshot(
+        "quotes_option",
+        QueryBuilder::new()
+            .filter("group_id", FilterOperator::In, json!(request.query.filter.group_id._in))
+            .fields(String::from("id, group_id, iv"))
+            .limit(-1),
+    )
+    .await;
+    let audit = AuditLogRequest::new(AuditAction::UpdateIv, "quotes_option", json!(request.data)).with_before(before);
+    let path = String::from("/items/quotes_option");
 
-    let response = call::<UpdateIVRequest>(
+    let response = ApiClient::new(Backend::Directus).call::<UpdateIVRequest>(
         Some(request),
         path,
-        headers,
         HttpMethod::PATCH
     ).await;
     match response {
-        Ok(res) => Ok(res),
+        Ok(res) => {
+            record_audit(audit).await;
+            Ok(res)
+        }
         Err(e) => {
             log::info!("error-: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::mock_backend::{assert_error_paths, coinbase_url, url, MockBackend};
+
+    async fn get_positions() -> Vec<RiskSlideTrade> {
+        get_all_available_positions(String::from("BTC/USD"), String::from("ALL"))
+            .await
+            .unwrap()
+            .extract_risk_slide_positions_by_currency_pair()
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_all_available_positions_fetches_the_open_trades_with_the_spot() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let positions = get_all_available_positions(String::from("BTC/USD"), String::from("ACME")).await.unwrap();
+        assert_eq!(positions.spot, 65000.0);
+        assert_eq!(positions.data[0].id, 101);
+        let coinbase = &backend.requests()[0];
+        assert_eq!(coinbase.endpoint, coinbase_url("/prices/BTC-USD/spot"));
+        assert_eq!(coinbase.authorization, "");
+        let trades = backend.requests_to(HttpMethod::GET, &url(Backend::Directus, "/items/trade"));
+        let filter = trades[0].get_filter();
+        assert_eq!(filter["_and"][3], json!({"pair_id": {"name": {"_in": ["BTC/USD"]}}}));
+        assert_eq!(filter["_and"][4], json!({"party_b": {"ticker": {"_in": ["ACME"]}}}));
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_all_available_positions_of_all_counterparties_has_no_counterparty_filter() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        get_positions().await;
+        let trades = backend.requests_to(HttpMethod::GET, &url(Backend::Directus, "/items/trade"));
+        assert_eq!(trades[0].get_filter()["_and"].as_array().unwrap().len(), 4);
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_deribit_positions_prices_the_hedges_of_all_counterparties_only() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let deribit = get_deribit_positions(String::from("BTC/USD"), String::from("ALL")).await.unwrap();
+        assert_eq!(deribit.data.deribit_risk_slide.delta, 0.21);
+        let requests = backend.requests();
+        assert_eq!(requests[0].endpoint, url(Backend::Pricer, "/risk/deribit"));
+        assert_eq!(requests[0].get_body(), json!({"currency": "BTC"}));
+
+        let backend = MockBackend::start().await;
+        let deribit = get_deribit_positions(String::from("BTC/USD"), String::from("ACME")).await.unwrap();
+        assert!(deribit.data.positions.is_empty());
+        assert!(backend.requests().is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_itm_otm_positions_sends_the_spot_to_the_pricer() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let itm_otm = get_itm_otm_positions(String::from("BTC/USD"), String::from("ACME"), 65000.0).await.unwrap();
+        assert_eq!(itm_otm.data.positions_itm_otm_risk_slide.delta, 0.42);
+        assert_eq!(
+            backend.requests()[0].get_body(),
+            json!({"currency": "BTC", "counterparty": "ACME", "current_spot": 65000.0})
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_collateral_sends_the_spot_to_the_pricer() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let collateral = get_collateral(String::from("BTC/USD"), String::from("ACME"), 65000.0).await.unwrap();
+        assert_eq!(collateral.data.unwind_risk_slide.total_current_usd, 250150.0);
+        assert_eq!(
+            backend.requests()[0].get_body(),
+            json!({"currency": "BTC", "counterparty": "ACME", "current_spot": 65000.0})
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_spot_and_greeks_prices_the_positions() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let positions = get_positions().await;
+        let greeks = get_spot_and_greeks(0.05, positions, 1.0, 65000.0, String::from("BTC/USD")).await.unwrap();
+        assert_eq!(greeks.atm_risk_slide.delta, 0.42);
+        assert_eq!(greeks.positions[0].current_price, 3400.0);
+        assert_eq!(greeks.positions[0].delta, 0.42);
+        assert_eq!(greeks.positions[0].pnl, 150.0);
+        assert_eq!(greeks.positions[0].r2, 0.05);
+        let pricer = backend.requests_to(HttpMethod::POST, &url(Backend::Pricer, "/quote/greeks"));
+        let body = pricer[0].get_body();
+        assert_eq!(body["positions"][0]["req_id"], "101");
+        assert_eq!(body["spot_bump"], 0.01);
+        assert_eq!(body["bump_times"], 3);
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_firm_wide_risk_prices_every_pair() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let risk = get_firm_wide_risk(String::from("ALL"), 0.05).await.unwrap();
+        assert_eq!(risk.pairs.len(), 1);
+        let pair = &risk.pairs[0];
+        assert_eq!(pair.pair, "BTC/USD");
+        assert_eq!(pair.spot, 65000.0);
+        assert_eq!(pair.active.delta, 0.42);
+        assert_eq!(pair.deribit.delta, 0.21);
+        assert_eq!(pair.itm_otm.delta, 0.42);
+        assert_eq!(pair.collateral.total_current_usd, 250150.0);
+    }
+
+    #[wasm_bindgen_test]
+    async fn update_quote_iv_patches_the_group_and_records_the_audit() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let request = UpdateIVRequest::new(vec![String::from("g-1")], 0.65);
+        assert!(update_quote_iv(request.clone()).await.unwrap());
+        let patch = backend.requests_to(HttpMethod::PATCH, &url(Backend::Directus, "/items/quotes_option"));
+        assert_eq!(patch[0].get_body(), json!(request));
+        assert_eq!(backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log")).len(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    async fn riskslide_services_return_the_errors_of_the_backend() {
+        assert_error_paths(|| get_all_available_positions(String::from("BTC/USD"), String::from("ALL"))).await;
+        assert_error_paths(|| get_deribit_positions(String::from("BTC/USD"), String::from("ALL"))).await;
+        assert_error_paths(|| get_itm_otm_positions(String::from("BTC/USD"), String::from("ACME"), 65000.0)).await;
+        assert_error_paths(|| get_collateral(String::from("BTC/USD"), String::from("ACME"), 65000.0)).await;
+        assert_error_paths(|| get_firm_wide_risk(String::from("ALL"), 0.05)).await;
+        assert_error_paths(|| update_quote_iv(UpdateIVRequest::new(vec![String::from("g-1")], 0.65))).await;
+
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let positions = get_positions().await;
+        assert_error_paths(|| get_spot_and_greeks(0.05, positions.clone(), 1.0, 65000.0, String::from("BTC/USD"))).await;
+    }
+}
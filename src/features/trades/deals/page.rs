@@ -12,6 +12,7 @@ use crate::components::success_modal_redirect::SuccessModalWithRedirection;
 use crate::utilities::date_util::{convert_utc_to_edt,get_expiration_datetime_utc,get_time_in_local_time,update_edt_time,get_datetime_in_local_time,get_date_in_local_time};
 use crate::utilities::local::get_conditional_loss;
 use crate::utilities::number_util::format_number_en;
+use crate::utilities::errors::JabraError;
 
 use super::services::*;
 use super::models::*;
@@ -53,10 +54,13 @@ pub fn Deals() -> impl IntoView {
                             submit_new_term_sheet_response.set(response);
                             submit_new_term_sheet_response_show.set(true);
                         }
-                        Err(_e) => {
+                        Err(e) => {
                             submit_new_term_sheet_response.set(SubmitNewTermSheetResponse {
                                 status: -1,
-                                message: "The transaction is failed".to_string(),
+                                message: format!(
+                                    "The transaction is failed. {}",
+                                    JabraError::from_server_fn_error(&e).get_user_message()
+                                ),
                                 refid: "N/A".to_string(),
                             });
                             submit_new_term_sheet_response_show.set(true);
@@ -1,16 +1,16 @@
 use super::models::*;
 use leptos::ServerFnError;
-use crate::utilities::cookies::{get_jabra_cookie, JabraCookie};
-use crate::utilities::http_wrapper::{call_and_parse, HttpMethod};
+use serde_json::json;
+use crate::commons::models::audit::{AuditAction, AuditLogRequest};
+use crate::commons::services::audit::record_audit;
+use crate::utilities::http_wrapper::{ApiClient, Backend, HttpMethod};
 
 pub async fn post_settlement_option(
     request: SettlementOptionRequest,
 ) -> Result<SettlementOptionResponse, ServerFnError> {
-
     // let cookie = get_cookie_value("JabraOPv1_2023").await;
     // let jwt_cookie = JabraCookie::decrypt(cookie.unwrap()).unwrap_or_default();
-    // let mut bearer = format!("Bearer {}", jwt_cookie.access_token);
-
+    //
     // // Check if token expires, this checking will be available only to actions and server action
     // // Other resources will still work due to 10 minutes buffer time
     // if jwt_cookie.is_expired() {
@@ -27,25 +27,12 @@ pub async fn post_settlement_option(
     //         }
     //     }
     // }
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    if jwt_cookie.is_expired() {
-        return Ok(Default::default());
-    }
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    let url = option_env!("JABRAAPIGATEWAY");
-    let path = format!("{}/option_pricer/settlement_template", url.unwrap_or_default());
+    let path = String::from("/option_pricer/settlement_template");
 
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
     log::info!("request: {:?}", request);
-    let response = call_and_parse::<SettlementOptionRequest, SettlementOptionResponse>(
+    let response = ApiClient::new(Backend::Gateway).call_and_parse::<SettlementOptionRequest, SettlementOptionResponse>(
         Some(request),
         path,
-        headers,
         HttpMethod::POST,
     )
     .await;
@@ -53,7 +40,7 @@ pub async fn post_settlement_option(
         Ok(res) => Ok(res),
         Err(e) => {
             log::info!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
@@ -61,11 +48,9 @@ pub async fn post_settlement_option(
 pub async fn post_submit_new_term_sheet_with_id(
     request: SubmitNewTermSheetRequestWithGroupId,
 ) -> Result<SubmitNewTermSheetResponse, ServerFnError> {
-
     // let cookie = get_cookie_value("JabraOPv1_2023").await;
     // let jwt_cookie = JabraCookie::decrypt(cookie.unwrap()).unwrap_or_default();
-    // let mut bearer = format!("Bearer {}", jwt_cookie.access_token);
-
+    //
     // // Check if token expires, this checking will be available only to actions and server action
     // // Other resources will still work due to 10 minutes buffer time
     // if jwt_cookie.is_expired() {
@@ -82,35 +67,101 @@ pub async fn post_submit_new_term_sheet_with_id(
     //         }
     //     }
     // }
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    if jwt_cookie.is_expired() {
-        return Ok(Default::default());
-    }
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
 
-    let url = option_env!("JABRAAPIGATEWAY");
-    let path = format!("{}/rfq/submit_new_termsheet", url.unwrap_or_default());
+    let audit = AuditLogRequest::new(AuditAction::SubmitTermSheet, "dcl", json!(request))
+        .with_group_ids(vec![request.group_id.clone()]);
+    let path = String::from("/rfq/submit_new_termsheet");
 
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
     log::info!("request-rfq: {:?}", request);
     let response =
-        call_and_parse::<SubmitNewTermSheetRequestWithGroupId, SubmitNewTermSheetResponse>(
+        ApiClient::new(Backend::Gateway).call_and_parse::<SubmitNewTermSheetRequestWithGroupId, SubmitNewTermSheetResponse>(
             Some(request),
             path,
-            headers,
             HttpMethod::POST,
         )
         .await;
     match response {
-        Ok(res) => Ok(res),
+        Ok(res) => {
+            record_audit(audit.with_target_ids(vec![res.refid.clone()])).await;
+            Ok(res)
+        }
         Err(e) => {
             log::info!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::mock_backend::{assert_error_paths, url, MockBackend, ACCESS_TOKEN};
+
+    fn get_settlement_request() -> SettlementOptionRequest {
+        SettlementOptionRequest {
+            spot_t1: 65000.0,
+            strike: 70000.0,
+            deposit: 100000.0,
+            ccy2_premium: 3250.0,
+            counterparty_name: String::from("Acme Capital"),
+            pair_name: String::from("BTC/USD"),
+            base_ccy: String::from("BTC"),
+            term_ccy: String::from("USD"),
+            deposit_ccy: String::from("ccy2"),
+            call_or_put: String::from("call"),
+            collateral_exchange_settlement: String::from("cash"),
+            jabra_side: String::from("sell"),
+            ..Default::default()
+        }
+    }
+
+    fn get_term_sheet_request() -> SubmitNewTermSheetRequestWithGroupId {
+        SubmitNewTermSheetRequestWithGroupId {
+            counterparty_name: String::from("Acme Capital"),
+            pair_name: String::from("BTC/USD"),
+            base_ccy: String::from("BTC"),
+            term_ccy: String::from("USD"),
+            deposit_amount: 100000.0,
+            deposit_ccy: String::from("USD"),
+            spot_t1: 65000.0,
+            strike: 70000.0,
+            group_id: String::from("g-1"),
+            ..Default::default()
         }
     }
-}
\ No newline at end of file
+
+    #[wasm_bindgen_test]
+    async fn post_settlement_option_fetches_the_settlement_template() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let response = post_settlement_option(get_settlement_request()).await.unwrap();
+        assert_eq!(response.data[0].settlement_condition, "ITM");
+        assert!(response.data[0].if_exercised);
+        let request = &backend.requests()[0];
+        assert_eq!(request.endpoint, url(Backend::Gateway, "/option_pricer/settlement_template"));
+        assert_eq!(request.get_body(), json!(get_settlement_request()));
+        assert_eq!(request.authorization, format!("Bearer {}", ACCESS_TOKEN));
+    }
+
+    #[wasm_bindgen_test]
+    async fn post_submit_new_term_sheet_with_id_returns_the_reference() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let response = post_submit_new_term_sheet_with_id(get_term_sheet_request()).await.unwrap();
+        assert_eq!(response.refid, "TS-2030-0001");
+        assert_eq!(backend.requests()[0].get_body()["group_id"], "g-1");
+        let audits = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log"));
+        assert_eq!(audits.len(), 1);
+        assert_eq!(audits[0].get_body()["action"], "submit_term_sheet");
+        assert_eq!(audits[0].get_body()["target_ids"], json!(["TS-2030-0001"]));
+        assert_eq!(audits[0].get_body()["group_ids"], json!(["g-1"]));
+    }
+
+    #[wasm_bindgen_test]
+    async fn deals_services_return_the_errors_of_the_backend() {
+        assert_error_paths(|| post_settlement_option(get_settlement_request())).await;
+        assert_error_paths(|| post_submit_new_term_sheet_with_id(get_term_sheet_request())).await;
+    }
+}
@@ -1,32 +1,55 @@
 use leptos::ServerFnError;
+use serde_json::json;
 
-use crate::{commons::models::{defaults::BlankRequest, trade::{Trade, TradeHistory}}, utilities::{cookies::{get_jabra_cookie, JabraCookie}, http_wrapper::{call_and_parse, HttpMethod}}};
-
+use crate::{commons::models::{defaults::{BlankRequest, FilterOperator, QueryBuilder}, trade::{Trade, TradeHistory}}, utilities::http_wrapper::{ApiClient, Backend, HttpMethod}};
 
 pub async fn get_trade_history() -> Result<TradeHistory, ServerFnError> {
-
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/trade?filter[counterparty_id][ticker][_eq]=JABRA&sort[]=-expiry_timestamp&filter[party_a][_nnull]=true&filter[party_b][_nnull]=true&filter[expiry_timestamp][_gte]=$NOW(-15)&filter[expiry_timestamp][_lte]=$NOW(7)&filter[trade_type][_eq]=trade&fields={}", url.unwrap_or_default(), Trade::get_query());
+    let path = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!("JABRA"))
+        .filter("party_a", FilterOperator::NNull, json!(true))
+        .filter("party_b", FilterOperator::NNull, json!(true))
+        .filter("expiry_timestamp", FilterOperator::Gte, json!("$NOW(-15)"))
+        .filter("expiry_timestamp", FilterOperator::Lte, json!("$NOW(7)"))
+        .filter("trade_type", FilterOperator::Eq, json!("trade"))
+        .sort("-expiry_timestamp")
+        .fields(Trade::get_query())
+        .to_path("trade");
     //log::info!("path: {:?}", path);
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
 
-    let response = call_and_parse::<
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<
         BlankRequest,
         TradeHistory,
-    >(Option::None, path, headers, HttpMethod::GET)
+    >(Option::None, path, HttpMethod::GET)
     .await;
     match response {
         Ok(res) => Ok(res),
         Err(e) => {
             log::error!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::mock_backend::{assert_error_paths, MockBackend};
+
+    #[wasm_bindgen_test]
+    async fn get_trade_history_fetches_the_trades_expiring_within_a_week() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let trades = get_trade_history().await.unwrap();
+        assert_eq!(trades.data[0].id, 101);
+        let filter = backend.requests()[0].get_filter();
+        assert_eq!(filter["_and"][3], json!({"expiry_timestamp": {"_gte": "$NOW(-15)"}}));
+        assert_eq!(filter["_and"][4], json!({"expiry_timestamp": {"_lte": "$NOW(7)"}}));
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_trade_history_returns_the_errors_of_the_backend() {
+        assert_error_paths(get_trade_history).await;
+    }
+}
@@ -1,34 +1,86 @@
 use leptos::ServerFnError;
-use crate::commons::models::defaults::BlankRequest;
+use crate::commons::models::defaults::{BlankRequest, FilterOperator, PageRequest, QueryBuilder};
 use crate::commons::models::trade::{Trade, TradeHistory};
-use crate::utilities::cookies::{get_jabra_cookie, JabraCookie};
-use crate::utilities::http_wrapper::{call_and_parse, HttpMethod};
-
-pub async fn get_trade_history() -> Result<TradeHistory, ServerFnError> {
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    if jwt_cookie.is_expired() {
-        return Ok(Default::default());
+use crate::utilities::http_wrapper::{ApiClient, Backend, HttpMethod};
+use serde_json::json;
+
+/// Gets a page of JABRA's trade history, optionally filtered by the instrument kind.
+/// The number of matching trades is returned in the `meta` of the response.
+
+pub async fn get_trade_history(
+    instrument_kind: Option<String>,
+    page: PageRequest,
+) -> Result<TradeHistory, ServerFnError> {
+    let mut query = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!("JABRA"))
+        .filter("party_a", FilterOperator::NNull, json!(true))
+        .filter("party_b", FilterOperator::NNull, json!(true));
+    if let Some(kind) = instrument_kind {
+        query = query.filter("instrument_kind", FilterOperator::Eq, json!(kind));
     }
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/trade?filter[counterparty_id][ticker][_eq]={}&sort[]=-date_created&filter[party_a][_neq]=null&filter[party_b][_neq]=null&fields={}&limit=100", url.unwrap_or_default(), String::from("JABRA"), Trade::get_query());
+    let path = query
+        .sort("-date_created")
+        .fields(Trade::get_query())
+        .paginate(&page)
+        .to_path("trade");
     // log::info!("path: {:?}", path);
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
 
     let response =
-        call_and_parse::<BlankRequest, TradeHistory>(Option::None, path, headers, HttpMethod::GET)
+        ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, TradeHistory>(Option::None, path, HttpMethod::GET)
             .await;
     match response {
         Ok(res) => Ok(res),
         Err(e) => {
             log::error!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
+        }
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::features::trades::history::page::get_instrument_kind;
+    use crate::utilities::mock_backend::{assert_error_paths, MockBackend};
+
+    fn get_page() -> PageRequest {
+        PageRequest {
+            page: 2,
+            page_size: 25,
+            sort: String::new(),
+            search: String::new(),
         }
     }
+
+    #[wasm_bindgen_test]
+    async fn get_trade_history_fetches_a_page_of_the_trades() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let trades = get_trade_history(get_instrument_kind("Option"), get_page()).await.unwrap();
+        assert_eq!(trades.data[0].id, 101);
+        assert_eq!(trades.get_filter_count(), 1);
+        let request = &backend.requests()[0];
+        assert_eq!(request.get_filter()["_and"][3], json!({"instrument_kind": {"_eq": "option"}}));
+        assert_eq!(request.get_param("limit"), "25");
+        assert_eq!(request.get_param("offset"), "25");
+        assert_eq!(request.get_param("sort"), "-date_created");
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_trade_history_filters_each_tab_by_the_stored_instrument_kind() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        get_trade_history(get_instrument_kind("Spot"), get_page()).await.unwrap();
+        get_trade_history(get_instrument_kind("All"), get_page()).await.unwrap();
+        let requests = backend.requests();
+        assert_eq!(requests[0].get_filter()["_and"][3], json!({"instrument_kind": {"_eq": "spot"}}));
+        assert_eq!(requests[1].get_filter()["_and"].as_array().unwrap().len(), 3);
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_trade_history_returns_the_errors_of_the_backend() {
+        assert_error_paths(|| get_trade_history(None, get_page())).await;
+    }
 }
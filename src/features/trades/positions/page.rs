@@ -2,93 +2,100 @@ use std::collections::HashMap;
 
 use leptos::*;
 
-use crate::{commons::{models::trade::{sort, ExtractedTrade}, services::coinbase::get_spot_price}, components::{arrow_down::ArrowDown, arrow_up::ArrowUp, component_size::ComponentSize, component_type::ComponentType, data_table_header::GenericDataTableHeader, icons::CurrencyIcon, loading_spinners::Spinners, menu_button::MenuButton}, features::trades::positions::services::get_positions_with_live_pnl};
+use crate::{commons::{models::{currency_pair::CurrencyPair, trade::{sort, ExtractedTrade}}, services::{coinbase::get_spot_price, currency_pair::get_currency_pairs, spot_stream::create_pricing_spot}}, components::{arrow_down::ArrowDown, arrow_up::ArrowUp, component_size::ComponentSize, component_type::ComponentType, data_table_header::GenericDataTableHeader, icons::CurrencyIcon, loading_spinners::Spinners, menu_button::MenuButton, payoff_chart::PayoffChart, spot_price_age::SpotPriceAge}, features::trades::positions::services::get_positions_with_live_pnl, utilities::{number_util::format_currency, option_pricer::PayoffLeg}};
 
 #[allow(non_snake_case)]
 #[component]
 pub fn Positions() -> impl IntoView {
-
-    let btc_spot_price_resource = create_local_resource(|| (), move |_| get_spot_price(String::from("BTC-USD")));
-    let eth_spot_price_resource = create_local_resource(|| (), move |_| get_spot_price(String::from("ETH-USD")));
-
     view! {
         <div class = "p-4">
             <div class="pb-5 ml-2 text-xl font-bold text-white">
                 <span>Positions</span>
             </div>
-            // <Suspense
-            //     fallback = move || view! {
-            //             <div class = "items-center mt-5">
-            //                 <div class = "flex justify-center ">
-            //                 <span class="loading loading-bars loading-sm text-success"></span>
-            //                 </div>
-            //             </div>
-            //         }
-            // >
-            // {
-            //     move || {
-            //         positions_resource.and_then(|c| {
-            //             positions.set(c.clone());
-            //             if positions.get().data.len() <= 0 {
-            //                 view!{
-            //                     <div class = "items-center mt-5">
-            //                         <div class = "p-5">
-            //                             <span class="opacity-50 font-extralight">No trade record found</span>
-            //                         </div>
-            //                     </div>
-            //                 }.into_view()
-            //             } else {
-            //                 view!{
-            //                     <PositionsPage data = positions/>
-            //                 }.into_view()
-            //             }
-            //         })
+            <ActivePairsPositions counter_party = Signal::derive(|| String::from("JABRA")) />
+        </div>
+    }
+}
 
-            //     }
-            // }
-            // </Suspense>
-            <Suspense
-                fallback = move || view! {
-                        <div class = "items-center mt-5">
-                            <div class = "flex justify-center ">
-                                <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
-                            </div>
+/// Component for the positions of every active currency pair of Directus.
+/// Listing a new pair needs no code change, each pair has its own spot and display scale.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn ActivePairsPositions(#[prop(into)] counter_party: Signal<String>) -> impl IntoView {
+    let currency_pairs_resource = create_local_resource(|| (), move |_| get_currency_pairs());
+
+    view! {
+        <Suspense
+            fallback = move || view! {
+                    <div class = "items-center mt-5">
+                        <div class = "flex justify-center ">
+                            <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
                         </div>
+                    </div>
+                }
+        >
+        {
+            move || {
+                currency_pairs_resource.and_then(|c| {
+                    let counter_party = counter_party.get();
+                    if c.data.is_empty() {
+                        return view! {
+                            <div class = "mt-5"><span class = "opacity-50 font-extralight">No active currency pair.</span></div>
+                        }.into_view();
                     }
-            >
-            {
-                move || {
-                    btc_spot_price_resource.and_then(|t| {
-                        let spot = t.data.amount.parse::<f64>().unwrap_or_default();
+                    c.data.clone().into_iter().enumerate().map(|(i, pair)| {
                         view! {
-                            <PerCurrencyPosition spot = spot pair_name =  String::from("BTC/USD") currency_name = String::from("BTC") counter_party= String::from("JABRA") />
+                            <Show when = move || i > 0>
+                                <div class="px-4 opacity-50 divider divider-neutral font-extralight"></div>
+                            </Show>
+                            <PairPositions pair = pair counter_party = counter_party.clone() />
                         }
-                    })
-                }
+                    }).collect_view()
+                })
             }
-            </Suspense>
-            <div class="px-4 opacity-50 divider divider-neutral font-extralight"></div>
-            <Suspense
-                fallback = move || view! {
-                        <div class = "items-center mt-5">
-                            <div class = "flex justify-center ">
-                                <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
-                            </div>
+        }
+        </Suspense>
+    }
+}
+
+/// Component for the positions of one currency pair, priced with the spot of the pair.
+
+#[allow(non_snake_case)]
+#[component]
+pub fn PairPositions(pair: CurrencyPair, counter_party: String) -> impl IntoView {
+    let spot_product = pair.get_spot_product();
+    let spot_price_resource = create_local_resource(move || spot_product.clone(), get_spot_price);
+
+    view! {
+        <Suspense
+            fallback = move || view! {
+                    <div class = "items-center mt-5">
+                        <div class = "flex justify-center ">
+                            <Spinners size = ComponentSize::SMALL _type = ComponentType::SUCCESS />
                         </div>
-                    }
-            >
-            {
-                move || {
-                    eth_spot_price_resource.and_then(|t| {
-                        let spot = t.data.amount.parse::<f64>().unwrap_or_default();
-                        view! {
-                            <PerCurrencyPosition spot = spot pair_name =  String::from("ETH/USD") currency_name = String::from("ETH") counter_party= String::from("JABRA") />
-                        }
-                    })
+                    </div>
                 }
+        >
+        {
+            let pair = pair.clone();
+            let counter_party = counter_party.clone();
+            move || {
+                spot_price_resource.and_then(|t| {
+                    let spot = t.data.amount.parse::<f64>().unwrap_or_default();
+                    view! {
+                        <PerCurrencyPosition
+                            spot = spot
+                            pair_name = pair.name.clone()
+                            currency_name = pair.base.ticker.clone()
+                            counter_party = counter_party.clone()
+                            display_scale = pair.base.display_scale
+                        />
+                    }
+                })
             }
-            </Suspense>
-        </div>
+        }
+        </Suspense>
     }
 }
 
@@ -112,19 +119,25 @@ pub fn PerCurrencyPosition(
     pair_name: String,
     currency_name: String,
     counter_party: String,
+    /// The display scale of the base currency, used for the sizes.
+    display_scale: u8,
 ) -> impl IntoView {
     let pair = RwSignal::new(pair_name);
     let pair_display_name = move || pair.get().replace("/", "-");
-    let trade_history_resource = create_local_resource(|| (), move |_| {
-        get_positions_with_live_pnl(pair.get(), spot, counter_party.clone())
+    // The live pnl is recomputed when the live spot moves beyond the refresh threshold.
+    let pricing_spot = create_pricing_spot(pair.into(), Signal::derive(move || spot));
+    let trade_history_resource = create_local_resource(move || pricing_spot.get(), move |spot| {
+        get_positions_with_live_pnl(pair.get_untracked(), spot, counter_party.clone())
     });
     let selected_page = RwSignal::new("All".to_string());
     let all_data = RwSignal::new(HashMap::<String, Vec<ExtractedTrade>>::default());
     let option_data = RwSignal::new(HashMap::<String, Vec<ExtractedTrade>>::default());
+    let payoff_legs = RwSignal::new(HashMap::<String, Vec<PayoffLeg>>::default());
     let currency = RwSignal::new(currency_name);
+    let quote_currency = RwSignal::new(pair.get_untracked().split('/').nth(1).unwrap_or_default().to_string());
     let show = RwSignal::new(false);
     view! {
-        <Suspense
+        <Transition
             fallback = move || view! {
                     <div class = "items-center mt-5">
                         <div class = "flex justify-center ">
@@ -137,12 +150,14 @@ pub fn PerCurrencyPosition(
             move || {
                 trade_history_resource.and_then(|t| {
                     show.set(t.data.len() > 0);
+                    payoff_legs.set(t.extract_payoff_legs_by_date());
                     view!{
                         <div class = "flex justify-between pb-2">
                             <div class = "flex items-center gap-4 text-center">
                                 <CurrencyIcon name = currency.get() class = "w-7 h-7".to_string() />
                                 <div class = "text-base font-bold">{pair_display_name()}</div>
                                 <div class = "text-xs text-gray-500">{format!("{} Positions", t.data.len())}</div>
+                                <SpotPriceAge pair = pair.into()/>
                             </div>
                             <Show when = move  || show.get()>
                                 <div class = "flex justify-between">
@@ -164,13 +179,13 @@ pub fn PerCurrencyPosition(
                                 // filtered_trade_table.set(t.extract_group());
                                 all_data.set(t.extract_and_group_by_date());
                                     view! {
-                                        <FilteredByDateDataTable data = all_data />
+                                        <FilteredByDateDataTable data = all_data payoff_legs spot = pricing_spot quote_currency display_scale />
                                     }
                             } else if selected_page.get() == "Option".to_string() {
                                 // option_filtered_trade_table.set(t.extract_group_by_type(String::from("Option")));
                                 option_data.set(t.extract_by_instrument_kind_and_group_by_date(String::from("Option")));
                                     view! {
-                                        <FilteredByDateDataTable data = option_data />
+                                        <FilteredByDateDataTable data = option_data payoff_legs spot = pricing_spot quote_currency display_scale />
                                     }
                             } else if selected_page.get() == "Perpetual Futures".to_string() {
                                 view! {
@@ -186,7 +201,7 @@ pub fn PerCurrencyPosition(
                 })
             }
         }
-        </Suspense>
+        </Transition>
     }
 }
 
@@ -194,6 +209,12 @@ pub fn PerCurrencyPosition(
 #[component]
 pub fn FilteredByDateDataTable(
     data: RwSignal<HashMap<String, Vec<ExtractedTrade>>>,
+    /// The payoff legs of the option trades, keyed like `data`.
+    payoff_legs: RwSignal<HashMap<String, Vec<PayoffLeg>>>,
+    spot: Signal<f64>,
+    quote_currency: RwSignal<String>,
+    /// The display scale of the base currency, used for the sizes.
+    display_scale: u8,
 ) -> impl IntoView {
     let selected_header = RwSignal::new(String::from(""));
     let sort_asc = RwSignal::new(true);
@@ -222,6 +243,8 @@ pub fn FilteredByDateDataTable(
                              data.get().into_iter().map(|(k, v)| {
                                  let hide_per_date = RwSignal::new(true);
                                  let tr = move || sort(v.clone(), sort_asc.get(), selected_header.get());
+                                 let date = k.clone();
+                                 let legs = Signal::derive(move || payoff_legs.get().get(&date).cloned().unwrap_or_default());
                                  view! {
                                      <tr class = "bg-base-100">
                                          <td colspan = "9">
@@ -235,6 +258,13 @@ pub fn FilteredByDateDataTable(
                                              </button>
                                          </td>
                                      </tr>
+                                     <Show when = move || !hide_per_date.get() && !legs.get().is_empty()>
+                                         <tr class = "bg-base-100">
+                                             <td colspan = "9">
+                                                 <PayoffChart legs = legs spot = spot currency = quote_currency.get_untracked() />
+                                             </td>
+                                         </tr>
+                                     </Show>
                                      {
                                          move || {
                                              tr().iter().map(|d| {
@@ -242,7 +272,7 @@ pub fn FilteredByDateDataTable(
 
                                                  let amount_size: f64 = d.size.clone().parse::<f64>().unwrap();
                                                  let class_size = if amount_size >= 0.0 {"text-success"} else {"text-error"};
-                                                 let value_size = if amount_size >= 0.0 {format!("+{}",d.size)} else {format!("{}", d.size)};
+                                                 let value_size = if amount_size >= 0.0 {format!("+{}", format_currency(amount_size, display_scale))} else {format_currency(amount_size, display_scale)};
 
                                                  let amount_price: f64 = d.price.clone().parse::<f64>().unwrap();
                                                  let class_price = if amount_price >= 0.0 {"text-success"} else {"text-error"};
@@ -265,7 +295,7 @@ pub fn FilteredByDateDataTable(
                                                             <td><span class = {class_price}>{value_price.clone()}</span><span class = "text-xs opacity-50 font-extralight">{format!(" {}", d.premium_ccy)}</span></td>
                                                             // <td><span>{d.live_pnl.clone()}</span><span class = "text-xs opacity-50 font-extralight">{format!(" {}", d.live_pnl_ccy.clone())}</span></td>
                                                             <td>{d.date_created.clone()}</td>
-                                                            <td><span class = {class_live_pnl}>{value_live_pnl.clone()}</span><span class = "text-xs opacity-50 font-extralight">USD</span></td>
+                                                            <td><span class = {class_live_pnl}>{value_live_pnl.clone()}</span><span class = "text-xs opacity-50 font-extralight">{quote_currency.get_untracked()}</span></td>
                                                             <td><span class = {class_live_pnl_pctg}>{value_live_pnl_pctg.clone()}</span><span class = "text-xs opacity-50 font-extralight">"%"</span></td>
                                                             <td>{d.last_updated.clone()}</td>
                                                         </div>
@@ -298,7 +328,7 @@ pub fn FilteredByDateDataTable(
                                                         <td colspan = "9" class="px924:hidden"><span class="mr-2 text-sm text-success px924:hidden">DATE CREATED: </span><span class = "text-xs px924:hidden">{format!(" {}", d.date_created.clone())}</span></td>
                                                     </tr>
                                                     <tr prop:hidden = move || hide_per_date.get() class = "uppercase">
-                                                        <td colspan = "9" class="px924:hidden"><span class="mr-2 text-sm text-success px924:hidden">PNL: </span><span class = {format!("px924:hidden {}", class_live_pnl)}>{value_live_pnl}</span><span class = "text-xs opacity-50 font-extralight">USD</span></td>
+                                                        <td colspan = "9" class="px924:hidden"><span class="mr-2 text-sm text-success px924:hidden">PNL: </span><span class = {format!("px924:hidden {}", class_live_pnl)}>{value_live_pnl}</span><span class = "text-xs opacity-50 font-extralight">{quote_currency.get_untracked()}</span></td>
                                                     </tr>
                                                     <tr prop:hidden = move || hide_per_date.get() class = "uppercase">
                                                         <td colspan = "9" class="px924:hidden"><span class="mr-2 text-sm text-success px924:hidden">PNL PERCENTAGE: </span><span class = {format!("px924:hidden {}", class_live_pnl_pctg)}>{value_live_pnl_pctg}</span><span class = "text-xs opacity-50 font-extralight">"%"</span></td>
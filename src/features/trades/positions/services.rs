@@ -1,30 +1,24 @@
 use leptos::ServerFnError;
+use serde_json::json;
 
-use crate::{commons::models::{defaults::BlankRequest, trade::{PositionGreekRequest, PositionsGreeksRequest, PositionsGreeksResponse, Trade, TradeHistory}}, utilities::{cookies::{get_jabra_cookie, JabraCookie}, date_util::time_to_expiry, http_wrapper::{call_and_parse, HttpMethod}}};
-
+use crate::{commons::models::{defaults::{BlankRequest, FilterOperator, QueryBuilder}, trade::{PositionGreekRequest, PositionsGreeksRequest, PositionsGreeksResponse, Trade, TradeHistory}}, utilities::{date_util::time_to_expiry, http_wrapper::{call_and_parse, ApiClient, Backend, HttpMethod}}};
 
 pub async fn get_positions_with_live_pnl(
     pair: String,
     spot: f64,
     countery_party: String,
 ) -> Result<TradeHistory, ServerFnError> {
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    if jwt_cookie.is_expired() {
-        return Ok(Default::default());
-    }
-    let bearer_token = format!("Bearer {}", jwt_cookie.access_token);
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/trade?filter[counterparty_id][ticker][_eq]={}&filter[expiry_timestamp][_gte]=$NOW&filter[activity][_eq]=open&filter[pair_id][name][_eq]={}&sort[]=-expiry_timestamp&fields={}", url.unwrap_or_default(), countery_party, pair, Trade::get_query());
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer_token).unwrap(),
-    );
+    let path = QueryBuilder::new()
+        .filter("counterparty_id.ticker", FilterOperator::Eq, json!(countery_party))
+        .filter("expiry_timestamp", FilterOperator::Gte, json!("$NOW"))
+        .filter("activity", FilterOperator::Eq, json!("open"))
+        .filter("pair_id.name", FilterOperator::Eq, json!(pair))
+        .sort("-expiry_timestamp")
+        .fields(Trade::get_query())
+        .to_path("trade");
 
     let response =
-        call_and_parse::<BlankRequest, TradeHistory>(Option::None, path, headers, HttpMethod::GET)
+        ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, TradeHistory>(Option::None, path, HttpMethod::GET)
             .await;
     match response {
         Ok(res) => {
@@ -55,18 +49,10 @@ pub async fn get_positions_with_live_pnl(
                 spot_bump: 0.05,
                 bump_times: 3,
             };
-            // let pricer_url = std::env::var("PRICERENGINEURL").unwrap();
-            let pricer_url = option_env!("PRICERENGINEURL");
-            let pricer_path = format!("{}/quote/greeks", pricer_url.unwrap_or_default());
-            let mut pricer_headers = reqwest::header::HeaderMap::new();
-            pricer_headers.insert(
-                "Authorization",
-                reqwest::header::HeaderValue::from_str(&bearer_token).unwrap(),
-            );
-            let resp = call_and_parse::<PositionsGreeksRequest, PositionsGreeksResponse>(
+            let pricer_path = String::from("/quote/greeks");
+            let resp = ApiClient::new(Backend::Pricer).call_and_parse::<PositionsGreeksRequest, PositionsGreeksResponse>(
                 Some(positions_greeks_request),
                 pricer_path,
-                pricer_headers,
                 HttpMethod::POST,
             )
             .await;
@@ -83,18 +69,46 @@ pub async fn get_positions_with_live_pnl(
                             v.pnl = Some(p.pnl);
                         }
                     }
-                    Ok(TradeHistory { data: new_data })
+                    Ok(TradeHistory { data: new_data, meta: None })
                 }
                 Err(e) => {
                     log::error!("error: {:?}", e);
-                    Err(ServerFnError::new(e.to_string()))
+                    Err(ServerFnError::from(e))
                 }
             }
         }
         Err(e) => {
             log::error!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
     // Err(ServerFnError::new(e.to_string()))
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::mock_backend::{assert_error_paths, url, MockBackend};
+
+    #[wasm_bindgen_test]
+    async fn get_positions_with_live_pnl_prices_the_open_positions() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let positions = get_positions_with_live_pnl(String::from("BTC/USD"), 65000.0, String::from("JABRA")).await.unwrap();
+        assert_eq!(positions.data[0].pnl, Some(150.0));
+        assert_eq!(positions.data[0].live_pnl_percentage, Some(4.6));
+        let trades = backend.requests_to(HttpMethod::GET, &url(Backend::Directus, "/items/trade"));
+        assert_eq!(trades[0].get_filter()["_and"][3], json!({"pair_id": {"name": {"_eq": "BTC/USD"}}}));
+        let pricer = backend.requests_to(HttpMethod::POST, &url(Backend::Pricer, "/quote/greeks"));
+        let body = pricer[0].get_body();
+        assert_eq!(body["current_spot"], 65000.0);
+        assert_eq!(body["positions"][0]["req_id"], "101");
+    }
+
+    #[wasm_bindgen_test]
+    async fn get_positions_with_live_pnl_returns_the_errors_of_the_backend() {
+        assert_error_paths(|| get_positions_with_live_pnl(String::from("BTC/USD"), 65000.0, String::from("JABRA"))).await;
+    }
+}
@@ -1,4 +1,16 @@
 use serde::{Deserialize, Serialize};
+
+use crate::commons::models::trade::Trade;
+
+/// A page of the recent trades, grouped by counterparty in the order of the page.
+#[derive(Debug, Clone, Default)]
+pub struct RecentTradesPage {
+    /// The trades of each counterparty, keyed by `name~id`.
+    pub groups: Vec<(String, Vec<Trade>)>,
+    /// The number of recent trades of every page.
+    pub total_entries: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct CreateDealResponse {
     pub success: bool,
@@ -1,14 +1,15 @@
-use std::collections::HashMap;
-
 use base64::engine::general_purpose;
 use base64::Engine;
 use leptos::*;
 use leptos_router::Form;
 
+use crate::commons::models::defaults::PageRequest;
 use crate::commons::models::trade::Trade;
-use super::models::{TradeDataForModification, TradeFilterForModification, TradeForModification, TradeGroupidForModification, TradeQueryForModification, ModifyTradeResponse};
+use crate::commons::models::user::Permission;
+use super::models::{RecentTradesPage, TradeDataForModification, TradeFilterForModification, TradeForModification, TradeGroupidForModification, TradeQueryForModification, ModifyTradeResponse};
 use crate::components::component_size::ComponentSize;
 use crate::components::component_type::ComponentType;
+use crate::components::data_table_local::TablePagination;
 use crate::components::error_modal::ErrorModal;
 use crate::components::loading_spinners::Spinners;
 use crate::components::success_then_refetch_modal::SuccessModalWithRefetch;
@@ -16,13 +17,42 @@ use crate::features::trades::recents::models::Deals;
 use crate::utilities::configuration::get_environment;
 use crate::utilities::date_util::{convert_utc_to_local, get_expiry};
 use crate::features::trades::recents::services::fetch_recent_trades;
+use crate::utilities::errors::JabraError;
 use super::services::edit_trade;
 
 #[allow(non_snake_case)]
 #[component]
 pub fn RecentTrades() -> impl IntoView {
     let environment_resource = create_local_resource(|| (), move |_| async move { get_environment().await });
-    let recent_trades_resource: Resource<(), Result<HashMap<String, Vec<Trade>>, ServerFnError>> = create_local_resource(|| (), move |_| async move { fetch_recent_trades().await });
+    let permissions = expect_context::<crate::Permissions>().0;
+    let can_edit_trades = move || permissions.get().has(Permission::EditTrades);
+    let page_request = RwSignal::new(PageRequest::default());
+    let recent_trades_resource: Resource<PageRequest, Result<RecentTradesPage, ServerFnError>> = create_local_resource(move || page_request.get(), move |request| async move { fetch_recent_trades(request).await });
+
+    // The pagination component works on its own signals, keep them in sync with the page request.
+    let selected_page = RwSignal::new(1);
+    let entries_start = RwSignal::new(0);
+    let entries_end = RwSignal::new(0);
+    let total_entries = RwSignal::new(0);
+    create_effect(move |_| {
+        let page = selected_page.get();
+        if page_request.get_untracked().page != page {
+            page_request.update(|r| r.page = page);
+        }
+    });
+    create_effect(move |_| {
+        if let Some(Ok(res)) = recent_trades_resource.get() {
+            let request = page_request.get_untracked();
+            let start = request.get_offset();
+            entries_start.set(start);
+            entries_end.set(start + res.groups.iter().map(|(_, trades)| trades.len()).sum::<usize>());
+            total_entries.set(res.total_entries);
+        }
+    });
+    let page_count = Signal::derive(move || {
+        let page_size = page_request.get().page_size.max(1);
+        (total_entries.get() + page_size - 1) / page_size
+    });
     view! {
         <div class="p-4">
             <div class="pb-5 ml-2 text-xl font-bold text-white">
@@ -33,10 +63,10 @@ pub fn RecentTrades() -> impl IntoView {
                         {
                         move || {
                             recent_trades_resource.and_then(|e| {
-                                if e.is_empty() {
+                                if e.groups.is_empty() {
                                     return view! {<div class = "flex justify-center border-b border-b-gray-700"><p>No available trades</p></div>}.into_view();
                                 }
-                                e.into_iter().map(|(counter_party, trade_quotes)| {
+                                e.groups.iter().map(|(counter_party, trade_quotes)| {
 
                                     let counterparty_name = counter_party.split("~").collect::<Vec<&str>>()[0].to_string();
                                     let _counterparty_id = counter_party.split("~").collect::<Vec<&str>>()[1].to_string();
@@ -151,11 +181,11 @@ pub fn RecentTrades() -> impl IntoView {
                                                                                             });
                                                                                         }
                                                                                     },
-                                                                                    Err(_e) => {
+                                                                                    Err(e) => {
                                                                                         show_modify_alert_modal.set(true);
                                                                                         modify_trade_response.update(|v| {
                                                                                             v.success = false;
-                                                                                            v.message = String::from("Your session has ended. Please relog your account.")
+                                                                                            v.message = JabraError::from_server_fn_error(&e).get_user_message()
                                                                                         });
                                                                                     }
                                                                                 }
@@ -188,7 +218,7 @@ pub fn RecentTrades() -> impl IntoView {
                                                                             environment_resource.map(|e| envi =  e.to_uppercase() );
                                                                              if envi==String::from("DEVELOPMENT"){
                                                                                  view!{
-                                                                                    <button class = "mr-2 btn btn-xs btn-warning" on:click = move |_| show_edit_modal.set(true) >EDIT</button>
+                                                                                    <button class = "mr-2 btn btn-xs btn-warning" prop:disabled = move || !can_edit_trades() on:click = move |_| show_edit_modal.set(true) >EDIT</button>
                                                                                 }
                                                                            }else{
                                                                                 view!{
@@ -248,6 +278,7 @@ pub fn RecentTrades() -> impl IntoView {
                         }
                     }
                     </Transition>
+                    <TablePagination size = page_count selected_page = selected_page entries_start = entries_start entries_end = entries_end total_entries = total_entries />
                 </div>
         </div>
     }
@@ -1,49 +1,51 @@
 
 use leptos::*;
 
-use crate::commons::models::defaults::BlankRequest;
+use crate::commons::models::audit::{AuditAction, AuditLogRequest};
+use crate::commons::models::defaults::{BlankRequest, FilterOperator, PageRequest, QueryBuilder};
+use crate::commons::services::audit::{get_audit_snapshot, record_audit};
 use crate::commons::models::trade::{Trade, TradeHistory};
-use crate::utilities::cookies::{get_jabra_cookie, refresh_token, set_jabra_cookie, JabraCookie};
-use crate::utilities::http_wrapper::{call, call_and_parse, HttpMethod};
-use super::models::TradeForModification;
+use crate::utilities::http_wrapper::{ApiClient, Backend, HttpMethod};
+use super::models::{RecentTradesPage, TradeForModification};
 
-use std::collections::HashMap;
+use serde_json::json;
 
-pub async fn fetch_recent_trades() -> Result<HashMap<String, Vec<Trade>>, ServerFnError> {
+/// Fetches a page of the open trades without a term sheet.
+/// The trades are sorted by counterparty so that a counterparty only spans consecutive pages.
 
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    if jwt_cookie.is_expired() {
-        return Ok(Default::default());
-    }
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/trade?filter[party_b][_null]=false&filter[party_a][_null]=false&filter[has_termsheet][_eq]=false&limit=-1&filter[expiry_timestamp][_gte]=$NOW()&filter[party_a][ticker][_eq]=JABRA&filter[trade_type][_eq]=trade&fields={}", url.unwrap_or_default(), Trade::get_query());
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
+pub async fn fetch_recent_trades(page: PageRequest) -> Result<RecentTradesPage, ServerFnError> {
+    let path = QueryBuilder::new()
+        .filter("party_b", FilterOperator::Null, json!(false))
+        .filter("party_a", FilterOperator::Null, json!(false))
+        .filter("has_termsheet", FilterOperator::Eq, json!(false))
+        .filter("expiry_timestamp", FilterOperator::Gte, json!("$NOW()"))
+        .filter("party_a.ticker", FilterOperator::Eq, json!("JABRA"))
+        .filter("trade_type", FilterOperator::Eq, json!("trade"))
+        .sort("party_b.name")
+        .sort("-date_created")
+        .fields(Trade::get_query())
+        .paginate(&page)
+        .to_path("trade");
 
     let response =
-        call_and_parse::<BlankRequest, TradeHistory>(Option::None, path, headers, HttpMethod::GET)
+        ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, TradeHistory>(Option::None, path, HttpMethod::GET)
             .await;
     match response {
         Ok(res) => {
-            let mut trade_quotes_map: HashMap<String, Vec<Trade>> = HashMap::new();
+            let total_entries = res.get_filter_count();
+            let mut groups: Vec<(String, Vec<Trade>)> = vec![];
             for trade_quote in res.data {
                 let key = format!("{}~{}", trade_quote.party_b.name, trade_quote.party_b.id);
-                trade_quotes_map
-                    .entry(key)
-                    .or_insert(vec![])
-                    .push(trade_quote);
+                match groups.iter_mut().find(|(k, _)| *k == key) {
+                    Some((_, trades)) => trades.push(trade_quote),
+                    None => groups.push((key, vec![trade_quote])),
+                }
             }
-            Ok(trade_quotes_map)
+            Ok(RecentTradesPage { groups, total_entries })
         }
         Err(e) => {
             log::info!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
@@ -51,47 +53,96 @@ pub async fn fetch_recent_trades() -> Result<HashMap<String, Vec<Trade>>, Server
 pub async fn edit_trade(request: TradeForModification) -> Result<bool, ServerFnError> {
     log::info!("request: {:?}", request);
 
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let mut bearer = format!("Bearer {}", jwt_cookie.access_token);
-
-    // Check if token expires, this checking will be available only to actions and server action
-    // Other resources will still work due to 10 minutes buffer time
-    if jwt_cookie.is_expired() {
-        let refresh = refresh_token(jwt_cookie.user_id, jwt_cookie.refresh_token).await;
-        match refresh {
-            Ok(r) => {
-                bearer = format!("Bearer {}", r.access_token);
-                set_jabra_cookie(r, "admin_portal_csr".to_string()).await;
-            }
-            Err(e) => {
-                log::error!("error-token: {:?}", e);
-                // directus_wrapper::set_jabra_cookie(None, "admin_portal_csr".to_string()).await;
-                return Err(ServerFnError::ServerError(e.to_string()));
-            }
-        }
-    }
     // log::debug!("request: {:?}", request.deserialize());
-    // let url = std::env::var("DIRECTUSURL").unwrap();
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/trade", url.unwrap_or_default());
-
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
+    let before = get_audit_snapshot(
+        "trade",
+        QueryBuilder::new()
+            .filter("group_id", FilterOperator::Eq, json!(request.query.filter.group_id._eq))
+            .fields(String::from("id, group_id, expiry_timestamp"))
+            .limit(-1),
+    )
+    .await;
+    let audit = AuditLogRequest::new(AuditAction::EditTradeExpiry, "trade", json!(request.data)).with_before(before);
+    let path = String::from("/items/trade");
 
     let response =
-        call::<TradeForModification>(Some(request), path, headers, HttpMethod::PATCH).await;
+        ApiClient::new(Backend::Directus).call::<TradeForModification>(Some(request), path, HttpMethod::PATCH).await;
     match response {
-        Ok(res) => Ok(res),
+        Ok(res) => {
+            record_audit(audit).await;
+            Ok(res)
+        }
         Err(e) => {
             log::info!("error-: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
     // // Err(ServerFnError::ServerError("Transaction failed".to_string()))
     // log::info!("request: {:?}", request);
     // Ok(true)
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::features::trades::recents::models::{
+        TradeDataForModification, TradeFilterForModification, TradeGroupidForModification, TradeQueryForModification,
+    };
+    use crate::utilities::mock_backend::{assert_error_paths, url, MockBackend};
+
+    fn get_page() -> PageRequest {
+        PageRequest {
+            page: 1,
+            page_size: 10,
+            sort: String::new(),
+            search: String::new(),
+        }
+    }
+
+    fn get_modification() -> TradeForModification {
+        TradeForModification::new(
+            TradeQueryForModification {
+                filter: TradeFilterForModification {
+                    group_id: TradeGroupidForModification { _eq: String::from("g-1") },
+                },
+            },
+            TradeDataForModification {
+                expiry_timestamp: String::from("2030-07-26T08:00:00Z"),
+            },
+        )
+    }
+
+    #[wasm_bindgen_test]
+    async fn fetch_recent_trades_groups_the_trades_by_counterparty() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let page = fetch_recent_trades(get_page()).await.unwrap();
+        assert_eq!(page.total_entries, 1);
+        assert_eq!(page.groups[0].0, "Acme Capital~2");
+        assert_eq!(page.groups[0].1[0].id, 101);
+        let request = &backend.requests()[0];
+        assert_eq!(request.get_param("sort"), "party_b.name,-date_created");
+        assert_eq!(request.get_param("limit"), "10");
+    }
+
+    #[wasm_bindgen_test]
+    async fn edit_trade_patches_the_group_and_records_the_audit() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        assert!(edit_trade(get_modification()).await.unwrap());
+        let patch = backend.requests_to(HttpMethod::PATCH, &url(Backend::Directus, "/items/trade"));
+        assert_eq!(
+            patch[0].get_body(),
+            json!({"query": {"filter": {"group_id": {"_eq": "g-1"}}}, "data": {"expiry_timestamp": "2030-07-26T08:00:00Z"}})
+        );
+        assert_eq!(backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log")).len(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    async fn recents_services_return_the_errors_of_the_backend() {
+        assert_error_paths(|| fetch_recent_trades(get_page())).await;
+        assert_error_paths(|| edit_trade(get_modification())).await;
+    }
+}
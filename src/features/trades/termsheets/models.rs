@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::utilities::{date_util::parse_timestamp, option_pricer::PayoffLeg};
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BlankRequest;
 
@@ -74,6 +76,31 @@ pub struct GetNewTermSheetData {
     pub conditional_loss_limit_event: Option<String>,
 }
 
+impl GetNewTermSheetData {
+    /// Returns the option sold by the counterparty in the dual currency deposit.
+    /// A base currency deposit sells a call, a term currency deposit sells a put, both on the base amount.
+    pub fn get_payoff_legs(&self) -> Vec<PayoffLeg> {
+        if self.strike <= 0.0 {
+            return vec![];
+        }
+        let is_base_deposit = self.deposit_ccy_id.ticker == self.base_ccy_id.ticker;
+        let ttm = parse_timestamp(self.expiry_date.as_str())
+            .map(|dt| (dt - chrono::Utc::now().naive_utc()).num_seconds() as f64 / 86400.0)
+            .unwrap_or_default()
+            .max(0.0);
+        vec![PayoffLeg {
+            strike: self.strike,
+            side: String::from("Sell"),
+            option_kind: String::from(if is_base_deposit { "Call" } else { "Put" }),
+            amount: if is_base_deposit { self.deposit_amount } else { self.deposit_amount / self.strike },
+            premium: self.px_in_quote_ccy.abs(),
+            ttm,
+            iv: self.iv_t1,
+            r2: self.r2,
+        }]
+    }
+}
+
 /// Struct for the Counterparty Id.
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1,6 +1,6 @@
 use leptos::*;
 
-use crate::{components::{component_size::ComponentSize, component_type::ComponentType, default_none::DefaultNone, error_modal::ErrorModal, loading_spinners::Spinners, success_refetch_modal::SuccessModalRefetch}, features::trades::termsheets::{models::{ApproveRejectTermSheetRequest, ApproveRejectTermSheetResponse}, services::{download_termsheet, fetch_new_term_sheet_list, post_approve_term_sheet}}, utilities::{date_util::convert_utc_to_local, number_util::format_number_en}};
+use crate::{commons::models::user::Permission, components::{component_size::ComponentSize, component_type::ComponentType, default_none::DefaultNone, error_modal::ErrorModal, loading_spinners::Spinners, payoff_chart::PayoffChart, success_refetch_modal::SuccessModalRefetch}, features::trades::termsheets::{models::{ApproveRejectTermSheetRequest, ApproveRejectTermSheetResponse}, services::{download_termsheet, fetch_new_term_sheet_list, post_approve_term_sheet}}, utilities::{date_util::convert_utc_to_local, errors::JabraError, number_util::format_number_en}};
 
 use super::models::{GetNewTermSheetData, GetNewTermSheetResponse};
 
@@ -44,9 +44,13 @@ pub fn TermSheets() -> impl IntoView {
                             false
                         }
                     },
-                    Err(_e) => {
+                    Err(e) => {
                         show_notif_ar.set(true);
-                        let message = format!("{} Failed", message);
+                        let message = format!(
+                            "{} Failed. {}",
+                            message,
+                            JabraError::from_server_fn_error(&e).get_user_message()
+                        );
                         set_approve_reject_response.set(ApproveRejectTermSheetResponse::new(
                             false, message,
                         ));
@@ -204,6 +208,8 @@ pub fn TermSheetDetails(
     let (show_error_modal, set_show_error_modal) = create_signal(false);
 
     let is_pending = approve_reject_action.pending();
+    let permissions = expect_context::<crate::Permissions>().0;
+    let can_approve = move || permissions.get().has(Permission::ApproveTermSheets);
     // let pseudo_pending = create_rw_signal(false);
 
     // Checks if an action has a value, then sets the show_modal to false, and resets the action_value to None
@@ -290,10 +296,16 @@ pub fn TermSheetDetails(
                     }
                 }).collect_view()
         }
+        <div class = "flex">
+            <div class = "flex-initial w-1/3 p-2 font-semibold"><p>Payoff</p></div>
+            <div class = "flex-initial w-2/3">
+                <PayoffChart legs = Signal::derive(move || term_sheet_data.get().get_payoff_legs()) spot = Signal::derive(move || term_sheet_data.get().spot_t1) currency = term_sheet_data.get().term_ccy_id.ticker />
+            </div>
+        </div>
         <div class = "flex">
             <div class = "flex-initial w-1/2 my-3">
-                <button class = "mr-6 rounded-lg btn btn-primary btn-xs" on:click = move |_| set_show_confirm_modal_approve.set(true)>APPROVE</button>
-                <button class = "mr-6 rounded-lg btn btn-error btn-xs" on:click = move |_| set_show_confirm_modal_reject.set(true)>REJECT</button>
+                <button class = "mr-6 rounded-lg btn btn-primary btn-xs" prop:disabled = move || !can_approve() on:click = move |_| set_show_confirm_modal_approve.set(true)>APPROVE</button>
+                <button class = "mr-6 rounded-lg btn btn-error btn-xs" prop:disabled = move || !can_approve() on:click = move |_| set_show_confirm_modal_reject.set(true)>REJECT</button>
                 <Transition fallback = move || view! {<span class="inline-block loading loading-bars loading-xs"></span>} >
                     {
                         move || {
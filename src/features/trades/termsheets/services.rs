@@ -1,30 +1,22 @@
 use leptos::ServerFnError;
-use crate::commons::models::defaults::BlankRequest;
-use crate::utilities::cookies::{get_jabra_cookie, JabraCookie,set_jabra_cookie,refresh_token};
-use crate::utilities::http_wrapper::{call_and_parse,call, HttpMethod};
+use crate::commons::models::audit::{AuditAction, AuditLogRequest};
+use crate::commons::models::defaults::{BlankRequest, FilterOperator, QueryBuilder};
+use crate::commons::services::audit::{get_audit_snapshot, record_audit};
+use serde_json::json;
+use crate::utilities::http_wrapper::{ApiClient, Backend, HttpMethod};
 
 use super::models::{ApproveRejectTermSheetRequest, GetNewTermSheetResponse, TermSheetApprovalStatus};
 
 pub async fn fetch_new_term_sheet_list() -> Result<GetNewTermSheetResponse, ServerFnError> {
+    let path = QueryBuilder::new()
+        .filter("term_sheet_status", FilterOperator::Eq, json!("New"))
+        .sort("-id")
+        .fields(String::from("id,reference_id,counterparty_id.name,pair_id.name,base_ccy_id.ticker,term_ccy_id.ticker,deposit_ccy_id.ticker,deal_date,expiry_date,deposit_amount,spot_t1,strike,r2,r1,iv_t1,collateral_setting_method,collateral_exchange_settlement,exchange_rate_determining_agent,term_sheet,term_sheet_status,instrument_type,conditional_loss_limit_event,stop_loss_level,px_in_base_ccy,px_in_quote_ccy,dcl_settlement_details.settlement_template_id,dcl_settlement_details.settlement_condition,dcl_settlement_details.settlement_value"))
+        .to_path("dcl");
 
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    if jwt_cookie.is_expired() {
-        return Ok(GetNewTermSheetResponse::default());
-    }
-    let bearer = format!("Bearer {}", jwt_cookie.access_token);
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/dcl?filter[term_sheet_status][_eq]=New&sort[]=-id&fields=id,reference_id,counterparty_id.name,pair_id.name,base_ccy_id.ticker,term_ccy_id.ticker,deposit_ccy_id.ticker,deal_date,expiry_date,deposit_amount,spot_t1,strike,r2,r1,iv_t1,collateral_setting_method,collateral_exchange_settlement,exchange_rate_determining_agent,term_sheet,term_sheet_status,instrument_type,conditional_loss_limit_event,stop_loss_level,px_in_base_ccy,px_in_quote_ccy,dcl_settlement_details.settlement_template_id,dcl_settlement_details.settlement_condition,dcl_settlement_details.settlement_value", url.unwrap_or_default());
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
-
-    let response = call_and_parse::<BlankRequest, GetNewTermSheetResponse>(
+    let response = ApiClient::new(Backend::Directus).call_and_parse::<BlankRequest, GetNewTermSheetResponse>(
         Option::None,
         path,
-        headers,
         HttpMethod::GET,
     )
     .await;
@@ -32,7 +24,7 @@ pub async fn fetch_new_term_sheet_list() -> Result<GetNewTermSheetResponse, Serv
         Ok(res) => Ok(res),
         Err(e) => {
             log::info!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
@@ -43,46 +35,37 @@ pub async fn fetch_new_term_sheet_list() -> Result<GetNewTermSheetResponse, Serv
 pub async fn post_approve_term_sheet(
     request: ApproveRejectTermSheetRequest,
 ) -> Result<bool, ServerFnError> {
-
-    let cookie = get_jabra_cookie("admin_portal_csr".to_string()).await;
-    let jwt_cookie = JabraCookie::decrypt(cookie).unwrap_or_default();
-    let mut bearer = format!("Bearer {}", jwt_cookie.access_token);
-
-    // Check if token expires, this checking will be available only to actions and server action
-    // Other resources will still work due to 10 minutes buffer time
-    if jwt_cookie.is_expired() {
-        let refresh =
-            refresh_token(jwt_cookie.user_id, jwt_cookie.refresh_token).await;
-        match refresh {
-            Ok(r) => {
-                bearer = format!("Bearer {}", r.access_token);
-                set_jabra_cookie(r, "admin_portal_csr".to_string()).await;
-            }
-            Err(e) => {
-                // directus_wrapper::set_jabra_cookie(None, "JabraOPv1_2023".to_string()).await;
-                return Err(ServerFnError::new(e.to_string()));
-            }
-        }
-    }
-    let url = option_env!("DIRECTUSURL");
-    let path = format!("{}/items/dcl/{}", url.unwrap_or_default(), request.id);
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        reqwest::header::HeaderValue::from_str(&bearer).unwrap(),
-    );
+    let path = format!("/items/dcl/{}", request.id);
     let termsheet_status = TermSheetApprovalStatus {
         term_sheet_status: request.status.to_string(),
     };
+    let action = if request.status == "Approve" {
+        AuditAction::ApproveTermSheet
+    } else {
+        AuditAction::RejectTermSheet
+    };
+    let before = get_audit_snapshot(
+        "dcl",
+        QueryBuilder::new()
+            .filter("id", FilterOperator::Eq, json!(request.id))
+            .fields(String::from("id, reference_id, term_sheet_status")),
+    )
+    .await;
+    let audit = AuditLogRequest::new(action, "dcl", json!(termsheet_status))
+        .with_target_ids(vec![request.id.to_string()])
+        .with_before(before);
 
     let response =
-        call::<TermSheetApprovalStatus>(Some(termsheet_status), path, headers, HttpMethod::PATCH)
+        ApiClient::new(Backend::Directus).call::<TermSheetApprovalStatus>(Some(termsheet_status), path, HttpMethod::PATCH)
             .await;
     match response {
-        Ok(res) => Ok(res),
+        Ok(res) => {
+            record_audit(audit).await;
+            Ok(res)
+        }
         Err(e) => {
             log::info!("error: {:?}", e);
-            Err(ServerFnError::new(e.to_string()))
+            Err(ServerFnError::from(e))
         }
     }
 }
@@ -91,7 +74,55 @@ pub async fn post_approve_term_sheet(
 /// Accepts [`String`] and returns [`String`].
 
 pub async fn download_termsheet(file_id: String) -> Result<String, ServerFnError> {
-    let url = option_env!("JABRAAPIGATEWAYPUB");
-    let path = format!("{}/{}/{}", url.unwrap_or_default(), "option_pricer/dl_termsheet", file_id);
+    let path = format!("{}/{}/{}", Backend::GatewayPublic.base_url(), "option_pricer/dl_termsheet", file_id);
     Ok(path)
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::utilities::mock_backend::{assert_error_paths, url, MockBackend};
+
+    fn get_approval() -> ApproveRejectTermSheetRequest {
+        ApproveRejectTermSheetRequest {
+            id: 7,
+            status: String::from("Approve"),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    async fn fetch_new_term_sheet_list_fetches_the_new_term_sheets() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        let term_sheets = fetch_new_term_sheet_list().await.unwrap();
+        assert_eq!(term_sheets.data[0].id, 7);
+        assert_eq!(term_sheets.data[0].reference_id, "TS-2030-0001");
+        assert_eq!(backend.requests()[0].get_filter(), json!({"term_sheet_status": {"_eq": "New"}}));
+    }
+
+    #[wasm_bindgen_test]
+    async fn post_approve_term_sheet_patches_the_status_and_records_the_audit() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        assert!(post_approve_term_sheet(get_approval()).await.unwrap());
+        let patch = backend.requests_to(HttpMethod::PATCH, &url(Backend::Directus, "/items/dcl/7"));
+        assert_eq!(patch[0].get_body(), json!({"term_sheet_status": "Approve"}));
+        let audits = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/items/audit_log"));
+        assert_eq!(audits[0].get_body()["target_ids"], json!(["7"]));
+    }
+
+    #[wasm_bindgen_test]
+    async fn download_termsheet_links_to_the_public_gateway() {
+        MockBackend::start().await;
+        let link = download_termsheet(String::from("f-1")).await.unwrap();
+        assert_eq!(link, url(Backend::GatewayPublic, "/option_pricer/dl_termsheet/f-1"));
+    }
+
+    #[wasm_bindgen_test]
+    async fn termsheets_services_return_the_errors_of_the_backend() {
+        assert_error_paths(fetch_new_term_sheet_list).await;
+        assert_error_paths(|| post_approve_term_sheet(get_approval())).await;
+    }
+}
@@ -12,13 +12,17 @@ mod components;
 mod features;
 mod utilities;
 
+use crate::commons::models::user::UserPermissions;
+use crate::commons::services::spot_stream::SpotPrices;
 use crate::features::dashboard::page::PageManager;
+use crate::features::login::watchdog::SessionWatchdog;
 // Top-Level pages
 // use crate::pages::home::Home;
 // use crate::pages::not_found::NotFound;
 use crate::components::not_found::NotFound;
-use crate::utilities::configuration::get_environment;
-use crate::utilities::cookies::check_server_cookie;
+use crate::utilities::configuration::load_config;
+use crate::utilities::cookies::{check_server_cookie, get_cookie_permissions, remove_jabra_cookie};
+use crate::utilities::http_wrapper::{provide_session_expired_signal, COOKIE_NAME};
 
 #[derive(Copy, Clone)]
 pub struct Refetcher(pub RwSignal<bool>);
@@ -29,6 +33,37 @@ pub struct HasError(pub RwSignal<bool>);
 #[derive(Copy, Clone)]
 pub struct CheckCookie(pub Resource<bool, Result<bool, ServerFnError>>);
 
+#[derive(Copy, Clone)]
+pub struct SessionExpired(pub RwSignal<bool>);
+
+/// The permissions of the logged in user, used to hide the actions and pages they are not allowed to use.
+#[derive(Copy, Clone)]
+pub struct Permissions(pub RwSignal<UserPermissions>);
+
+/// Toggled after a counterparty is created or edited, so every counterparty selector fetches the list again.
+#[derive(Copy, Clone)]
+pub struct CounterPartiesRefetcher(pub RwSignal<bool>);
+
+/// Toggled after a wallet transaction is booked, so the account overview fetches the balances again.
+#[derive(Copy, Clone)]
+pub struct OverviewRefetcher(pub RwSignal<bool>);
+
+/// Shown instead of the app when `config.json` is missing or invalid.
+#[component]
+#[allow(non_snake_case)]
+fn ConfigurationError(message: String) -> impl IntoView {
+    view! {
+        <div class="flex items-center justify-center p-4 min-h-screen">
+            <div role="alert" class="alert alert-error max-w-xl">
+                <div>
+                    <h3 class="font-bold">The admin portal is not configured</h3>
+                    <div class="text-sm">{message}</div>
+                </div>
+            </div>
+        </div>
+    }
+}
+
 /// An app router which renders the homepage and handles 404's
 #[component]
 #[allow(non_snake_case)]
@@ -44,20 +79,24 @@ pub fn App() -> impl IntoView {
     let md = screen_width.between(Md, Lg);
     let lg = screen_width.ge(Lg);
 
-    let environment_resource =
-        create_local_resource(|| (), move |_| async move { get_environment().await });
+    // The runtime configuration has to be loaded before any request is made.
+    let config_resource = create_local_resource(|| (), move |_| async move { load_config().await });
 
     let refetcher = create_rw_signal(false);
     let has_error = create_rw_signal(false);
+    let session_expired = create_rw_signal(false);
+    let permissions = create_rw_signal(UserPermissions::default());
+    let counterparties_refetcher = create_rw_signal(false);
+    let overview_refetcher = create_rw_signal(false);
 
     let auth_resource: Resource<bool, Result<bool, ServerFnError>> =
         create_local_resource(refetcher, move |_| async move {
             check_server_cookie("admin_portal_csr".to_string()).await
         });
 
-    let environment = move || match environment_resource.get() {
-        Some(env) => env,
-        None => "".to_string(),
+    let environment = move || match config_resource.get() {
+        Some(Ok(config)) => config.project_environment.to_uppercase(),
+        _ => "".to_string(),
     };
 
     let responsive_stamp = move || match (xs.get(), sm.get(), md.get(), lg.get()) {
@@ -71,6 +110,36 @@ pub fn App() -> impl IntoView {
     provide_context(Refetcher(refetcher));
     provide_context(HasError(has_error));
     provide_context(CheckCookie(auth_resource));
+    provide_context(SessionExpired(session_expired));
+    provide_context(Permissions(permissions));
+    provide_context(CounterPartiesRefetcher(counterparties_refetcher));
+    provide_context(OverviewRefetcher(overview_refetcher));
+    provide_context(SpotPrices::new());
+    provide_session_expired_signal(session_expired);
+
+    // The cookie can only be decrypted once the configuration is loaded, so check it again.
+    create_effect(move |_| {
+        if let Some(Ok(_)) = config_resource.get() {
+            refetcher.update(|v| *v = !*v);
+        }
+    });
+
+    // The permissions are read from the cookie every time the session is checked, e.g. after a login.
+    create_effect(move |_| match auth_resource.get() {
+        Some(Ok(true)) => permissions.set(get_cookie_permissions(COOKIE_NAME)),
+        Some(_) => permissions.set(UserPermissions::default()),
+        None => {}
+    });
+
+    // When the session can no longer be refreshed before the user logged in, drop the cookie and check it again
+    // so the login page is shown. A logged in user gets the re-login modal of the `SessionWatchdog` instead.
+    create_effect(move |_| {
+        if session_expired.get() && !matches!(auth_resource.get_untracked(), Some(Ok(true))) {
+            remove_jabra_cookie(COOKIE_NAME);
+            session_expired.set(false);
+            refetcher.update(|v| *v = !*v);
+        }
+    });
 
     view! {
         <Html lang="en" dir="ltr" attr:data-theme="darkpurple"/>
@@ -87,22 +156,44 @@ pub fn App() -> impl IntoView {
 
                 {move || { responsive_stamp() }} <div class="min-h-screen">
 
-                    <Routes>
-                        <Route path="/" view=PageManager/>
-                        <Route path="/login" view=PageManager/>
-                        <Route path="/home" view=PageManager/>
-                        <Route path="/trades/deals" view=PageManager/>
-                        <Route path="/quotes/active" view=PageManager/>
-                        <Route path="/trades/recents" view=PageManager/>
-                        <Route path="/trades/expiring" view=PageManager/>
-                        <Route path="/trades/termsheets" view=PageManager/>
-                        <Route path="/quotes/builder" view=PageManager/>
-                        <Route path="/counterparties" view=PageManager/>
-                        <Route path="/trades/positions" view=PageManager/>
-                        <Route path="/trades/history" view=PageManager/>
-                        <Route path="/riskslide" view=PageManager/>
-                        <Route path="/*any" view=NotFound/>
-                    </Routes>
+                    <Suspense fallback=move || {
+                        view! {
+                            <div class="flex justify-center items-center min-h-screen">
+                                <span class="loading loading-bars loading-lg"></span>
+                            </div>
+                        }
+                    }>
+                        {move || match config_resource.get() {
+                            Some(Ok(_)) => {
+                                view! {
+                                    <Routes>
+                                        <Route path="/" view=PageManager/>
+                                        <Route path="/login" view=PageManager/>
+                                        <Route path="/home" view=PageManager/>
+                                        <Route path="/trades/deals" view=PageManager/>
+                                        <Route path="/quotes/active" view=PageManager/>
+                                        <Route path="/trades/recents" view=PageManager/>
+                                        <Route path="/trades/expiring" view=PageManager/>
+                                        <Route path="/trades/termsheets" view=PageManager/>
+                                        <Route path="/quotes/builder" view=PageManager/>
+                                        <Route path="/counterparties" view=PageManager/>
+                                        <Route path="/counterparties/manage" view=PageManager/>
+                                        <Route path="/trades/positions" view=PageManager/>
+                                        <Route path="/trades/history" view=PageManager/>
+                                        <Route path="/riskslide" view=PageManager/>
+                                        <Route path="/audit" view=PageManager/>
+                                        <Route path="/*any" view=NotFound/>
+                                    </Routes>
+                                    <SessionWatchdog/>
+                                }
+                                    .into_view()
+                            }
+                            Some(Err(e)) => {
+                                view! { <ConfigurationError message=e.to_string()/> }.into_view()
+                            }
+                            None => ().into_view(),
+                        }}
+                    </Suspense>
                 </div>
             </main>
         </Router>
@@ -1,4 +1,129 @@
+use std::cell::RefCell;
+
+use serde::{Deserialize, Serialize};
+
+use super::errors::JabraError;
+
+/// The path of the runtime configuration, served next to `index.html`.
+const CONFIG_PATH: &str = "/config.json";
+
+/// This struct holds the runtime configuration of the admin portal.
+/// It is loaded from `config.json` when the app starts, so the same build can be promoted across environments.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct AppConfig {
+    #[serde(default)]
+    pub directus_url: String,
+    #[serde(default)]
+    pub pricer_engine_url: String,
+    #[serde(default)]
+    pub wasmcloud_url: String,
+    #[serde(default)]
+    pub jabra_api_gateway: String,
+    #[serde(default)]
+    pub jabra_api_gateway_pub: String,
+    #[serde(default)]
+    pub coinbase_v2: String,
+    #[serde(default)]
+    pub jabra_key: String,
+    /// Keys of the previous rotations, only used to read the cookies encrypted before the rotation.
+    #[serde(default)]
+    pub jabra_previous_keys: Vec<String>,
+    /// Ticker WebSocket feed of the spot prices, the REST spot is used when empty.
+    #[serde(default)]
+    pub spot_stream_url: String,
+    /// Move of the spot, in percent, after which the greeks are recomputed.
+    #[serde(default)]
+    pub spot_refresh_threshold: Option<f64>,
+    /// Minutes before the session expires when the token is refreshed, and the user warned if it cannot be.
+    #[serde(default)]
+    pub session_warning_minutes: Option<i64>,
+    #[serde(default)]
+    pub project_environment: String,
+}
+
+impl AppConfig {
+    /// Returns the names of the required settings that are missing or empty.
+    pub fn get_missing_settings(&self) -> Vec<String> {
+        [
+            ("directus_url", &self.directus_url),
+            ("pricer_engine_url", &self.pricer_engine_url),
+            ("wasmcloud_url", &self.wasmcloud_url),
+            ("jabra_api_gateway", &self.jabra_api_gateway),
+            ("jabra_api_gateway_pub", &self.jabra_api_gateway_pub),
+            ("coinbase_v2", &self.coinbase_v2),
+            ("jabra_key", &self.jabra_key),
+        ]
+        .iter()
+        .filter(|(_, value)| value.trim().is_empty())
+        .map(|(name, _)| name.to_string())
+        .collect()
+    }
+
+    /// Removes the trailing slash of the urls, the service paths always start with one.
+    fn normalize(mut self) -> Self {
+        for url in [
+            &mut self.directus_url,
+            &mut self.pricer_engine_url,
+            &mut self.wasmcloud_url,
+            &mut self.jabra_api_gateway,
+            &mut self.jabra_api_gateway_pub,
+            &mut self.coinbase_v2,
+            &mut self.spot_stream_url,
+        ] {
+            *url = url.trim().trim_end_matches('/').to_string();
+        }
+        self
+    }
+}
+
+thread_local! {
+    static CONFIG: RefCell<Option<AppConfig>> = RefCell::new(None);
+}
+
+/// Fetches and validates `config.json`, then stores it for `get_config`.
+/// This is called once by the `App` before anything else is rendered.
+pub async fn load_config() -> Result<AppConfig, JabraError> {
+    let response = gloo_net::http::Request::get(CONFIG_PATH)
+        .send()
+        .await
+        .map_err(|e| JabraError::ConfigurationError(format!("{} could not be loaded: {}", CONFIG_PATH, e)))?;
+    if !response.ok() {
+        return Err(JabraError::ConfigurationError(format!(
+            "{} could not be loaded: {}",
+            CONFIG_PATH,
+            response.status()
+        )));
+    }
+    let config = response
+        .json::<AppConfig>()
+        .await
+        .map_err(|e| JabraError::ConfigurationError(format!("{} is not valid: {}", CONFIG_PATH, e)))?
+        .normalize();
+
+    let missing_settings = config.get_missing_settings();
+    if !missing_settings.is_empty() {
+        return Err(JabraError::ConfigurationError(format!(
+            "{} is missing: {}",
+            CONFIG_PATH,
+            missing_settings.join(", ")
+        )));
+    }
+    CONFIG.with(|c| *c.borrow_mut() = Some(config.clone()));
+    Ok(config)
+}
+
+/// Returns the loaded configuration, or an empty one if `load_config` has not succeeded.
+pub fn get_config() -> AppConfig {
+    CONFIG.with(|c| c.borrow().clone().unwrap_or_default())
+}
+
+/// Stores the configuration without fetching `config.json`, for the tests.
+#[cfg(test)]
+pub fn set_config(config: AppConfig) {
+    CONFIG.with(|c| *c.borrow_mut() = Some(config.normalize()));
+}
+
 pub async fn get_environment() -> String {
-    let env = option_env!("PROJECT_ENVIRONMENT");
-    return env.unwrap_or_default().to_string().to_uppercase();
+    get_config().project_environment.to_uppercase()
 }
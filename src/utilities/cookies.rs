@@ -1,9 +1,11 @@
-use crate::commons::models::login::DirectusLoginResponse;
+use crate::commons::models::{login::DirectusLoginResponse, user::UserPermissions};
 use leptos::*;
 use leptos_use::{use_cookie, utils::FromToStringCodec};
 use serde::{Deserialize, Serialize};
 
+use super::configuration::get_config;
 use super::errors::JabraError;
+use super::http_wrapper::parse_error;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct JabraCookie {
@@ -11,6 +13,9 @@ pub struct JabraCookie {
     pub access_token: String,
     pub refresh_token: String,
     pub expires_in: i64,
+    /// The permissions of the user, fetched once at login and kept when the token is refreshed.
+    #[serde(default)]
+    pub permissions: UserPermissions,
 }
 
 impl JabraCookie {
@@ -25,16 +30,23 @@ impl JabraCookie {
             access_token,
             refresh_token,
             expires_in,
+            permissions: UserPermissions::default(),
         }
     }
 
-    pub fn encrypt(&self) -> String {
-        let cookie_string = serde_json::to_string(self).unwrap();
+    pub fn with_permissions(mut self, permissions: UserPermissions) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    pub fn encrypt(&self) -> Result<String, JabraError> {
+        let cookie_string =
+            serde_json::to_string(self).map_err(|e| JabraError::SerializationError(e.to_string()))?;
         super::encryption::enc(cookie_string)
     }
 
     pub fn decrypt(encrypted_text: String) -> Result<Self, JabraError> {
-        let decrypted_text = super::encryption::dec(encrypted_text);
+        let decrypted_text = super::encryption::dec(encrypted_text)?;
         match serde_json::from_str(&decrypted_text) {
             Ok(cookie) => Ok(cookie),
             Err(_e) => Err(JabraError::CookieFetchError),
@@ -56,8 +68,38 @@ impl JabraCookie {
 
 pub async fn set_jabra_cookie(jabra_cookie: JabraCookie, cookie_name: String) {
     let (_cookie, set_cookie) = use_cookie::<String, FromToStringCodec>(&cookie_name);
-    let encrypted_cookie = jabra_cookie.encrypt();
-    set_cookie(Some(encrypted_cookie));
+    match jabra_cookie.encrypt() {
+        Ok(encrypted_cookie) => set_cookie(Some(encrypted_cookie)),
+        Err(e) => log::error!("error encrypting the session cookie: {:?}", e),
+    }
+}
+
+pub fn remove_jabra_cookie(cookie_name: &str) {
+    let (_cookie, set_cookie) = use_cookie::<String, FromToStringCodec>(cookie_name);
+    set_cookie(Some("".to_string()));
+}
+
+/// Returns the decrypted session cookie, `None` when there is no valid session.
+pub fn get_session_cookie(cookie_name: &str) -> Option<JabraCookie> {
+    let (cookie, _set_cookie) = use_cookie::<String, FromToStringCodec>(cookie_name);
+    match cookie.get_untracked() {
+        Some(val) if !val.is_empty() => JabraCookie::decrypt(val).ok(),
+        _ => None,
+    }
+}
+
+/// Returns the permissions stored in the session cookie, or no permissions when there is no valid session.
+pub fn get_cookie_permissions(cookie_name: &str) -> UserPermissions {
+    get_session_cookie(cookie_name)
+        .map(|c| c.permissions)
+        .unwrap_or_default()
+}
+
+/// Returns the user of the session cookie, empty when there is no valid session.
+pub fn get_cookie_user_id(cookie_name: &str) -> String {
+    get_session_cookie(cookie_name)
+        .map(|c| c.user_id)
+        .unwrap_or_default()
 }
 
 pub async fn get_jabra_cookie(cookie_name: String) -> String {
@@ -99,9 +141,7 @@ pub async fn refresh_token(
     // } else {
     //     "".to_string()
     // };
-    let url = option_env!("DIRECTUSURL");
-
-    let path = format!("{}/auth/refresh", url.unwrap_or_default());
+    let path = format!("{}/auth/refresh", get_config().directus_url);
     let json_body = serde_json::json!({
         "refresh_token": refresh_token,
         "mode": "json"
@@ -133,6 +173,6 @@ pub async fn refresh_token(
             Err(e) => Err(JabraError::from(e)),
         }
     } else {
-        Err(JabraError::APIResponseError(response.status().to_string()))
+        Err(parse_error(response).await)
     }
 }
@@ -234,12 +234,28 @@ pub fn get_date_in_local_time(utc_date_time: String) -> String {
     }
 }
 
-
 pub fn get_current_local_time() -> String {
-
     let utc = Utc::now();
     let utc_local = utc.with_timezone(&Local);
     let formatted_local = utc_local.format("%Y-%m-%d %H:%M:%S").to_string();
 
     return formatted_local;
-}
\ No newline at end of file
+}
+
+pub fn time_to_expiry_from_utc_str(date_expiry: &str) -> f64 {
+    match NaiveDateTime::parse_from_str(date_expiry, "%Y-%m-%dT%H:%M:%SZ") {
+        Ok(dt) => {
+            let difference = Utc.from_utc_datetime(&dt) - Utc::now();
+            let temp_ttm = difference.num_seconds() as f64 / 86400.0;
+            (temp_ttm * 100.0).round() / 100.0
+        }
+        Err(_) => 0.0,
+    }
+}
+
+pub fn format_utc_str_to_instrument_date(utc_date_str: &str) -> String {
+    match NaiveDateTime::parse_from_str(utc_date_str, "%Y-%m-%dT%H:%M:%SZ") {
+        Ok(dt) => dt.format("%d%b%y").to_string().to_uppercase(),
+        Err(_) => String::from(""),
+    }
+}
@@ -1,15 +1,200 @@
-pub fn enc(plain_text: String) -> String {
-    use magic_crypt::{new_magic_crypt, MagicCryptTrait};
-    let encryption_key = option_env!("JABRAKEY");
-    let magic_crypt = new_magic_crypt!(encryption_key.unwrap_or_default(), 256);
-    magic_crypt.encrypt_str_to_base64(plain_text)
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+use super::configuration::get_config;
+use super::errors::JabraError;
+
+/// Version of the envelope, the encrypted text is `v1.<key id>.<base64 of the nonce and the ciphertext>`.
+const ENVELOPE_VERSION: &str = "v1";
+/// Length of the AES-GCM nonce in bytes.
+const NONCE_LENGTH: usize = 12;
+
+/// An AES-256-GCM key derived from a `jabra_key`.
+/// The key id is derived separately, so the envelope tells which key to use without revealing it.
+struct CookieKey {
+    id: String,
+    cipher: Aes256Gcm,
 }
 
-pub fn dec(encrypted_text: String) -> String {
+impl CookieKey {
+    fn new(secret: &str) -> Self {
+        let key = Sha256::digest(format!("jabra-cookie-key:{}", secret).as_bytes());
+        let id = Sha256::digest(format!("jabra-cookie-id:{}", secret).as_bytes())[..4]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        Self {
+            id,
+            cipher: Aes256Gcm::new(&key),
+        }
+    }
+}
+
+/// Returns the secrets of the current key, first, and of the previous keys that are still accepted.
+fn get_secrets() -> Result<Vec<String>, JabraError> {
+    let config = get_config();
+    if config.jabra_key.trim().is_empty() {
+        return Err(JabraError::ConfigurationError(String::from(
+            "The cookie encryption key is not configured",
+        )));
+    }
+    Ok(std::iter::once(config.jabra_key)
+        .chain(config.jabra_previous_keys.into_iter().filter(|k| !k.trim().is_empty()))
+        .collect())
+}
+
+/// Encrypts and authenticates the text with the current key.
+pub fn enc(plain_text: String) -> Result<String, JabraError> {
+    let secrets = get_secrets()?;
+    let key = CookieKey::new(&secrets[0]);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = key
+        .cipher
+        .encrypt(&nonce, plain_text.as_bytes())
+        .map_err(|e| JabraError::SerializationError(format!("cookie encryption failed: {}", e)))?;
+    let payload = [nonce.as_slice(), ciphertext.as_slice()].concat();
+    Ok(format!("{}.{}.{}", ENVELOPE_VERSION, key.id, URL_SAFE_NO_PAD.encode(payload)))
+}
+
+/// Decrypts a text encrypted by `enc` with the current or a previous key.
+/// Cookies of the former magic-crypt encryption are still read, they are encrypted again on the next token refresh.
+/// A corrupted or tampered text, or one of an unknown key, is rejected with a `CookieFetchError`.
+pub fn dec(encrypted_text: String) -> Result<String, JabraError> {
+    let secrets = get_secrets()?;
+    let Some((version, envelope)) = encrypted_text.split_once('.') else {
+        return dec_legacy(&encrypted_text, &secrets);
+    };
+    if version != ENVELOPE_VERSION {
+        log::warn!("cookie rejected, unknown envelope version {}", version);
+        return Err(JabraError::CookieFetchError);
+    }
+    let (key_id, payload) = envelope.split_once('.').ok_or(JabraError::CookieFetchError)?;
+    let Some(key) = secrets.iter().map(|s| CookieKey::new(s)).find(|k| k.id == key_id) else {
+        log::warn!("cookie rejected, it was encrypted with a key that is no longer accepted");
+        return Err(JabraError::CookieFetchError);
+    };
+    let payload = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|_| JabraError::CookieFetchError)?;
+    if payload.len() <= NONCE_LENGTH {
+        return Err(JabraError::CookieFetchError);
+    }
+    let (nonce, ciphertext) = payload.split_at(NONCE_LENGTH);
+    let plain_text = key
+        .cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| {
+            log::warn!("cookie rejected, it is corrupted or was tampered with");
+            JabraError::CookieFetchError
+        })?;
+    String::from_utf8(plain_text).map_err(|_| JabraError::CookieFetchError)
+}
+
+fn dec_legacy(encrypted_text: &str, secrets: &[String]) -> Result<String, JabraError> {
     use magic_crypt::{new_magic_crypt, MagicCryptTrait};
-    let encryption_key = option_env!("JABRAKEY");
-    let magic_crypt = new_magic_crypt!(encryption_key.unwrap_or_default(), 256);
-    magic_crypt
-        .decrypt_base64_to_string(encrypted_text)
-        .unwrap()
+    secrets
+        .iter()
+        .find_map(|secret| {
+            new_magic_crypt!(secret, 256)
+                .decrypt_base64_to_string(encrypted_text)
+                .ok()
+        })
+        .ok_or(JabraError::CookieFetchError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utilities::configuration::{set_config, AppConfig};
+
+    fn use_keys(jabra_key: &str, jabra_previous_keys: &[&str]) {
+        set_config(AppConfig {
+            jabra_key: jabra_key.to_string(),
+            jabra_previous_keys: jabra_previous_keys.iter().map(|k| k.to_string()).collect(),
+            ..Default::default()
+        });
+    }
+
+    /// Replaces one part of the `v1.<key id>.<payload>` envelope.
+    fn with_part(encrypted_text: &str, index: usize, part: String) -> String {
+        let mut parts = encrypted_text.split('.').map(String::from).collect::<Vec<String>>();
+        parts[index] = part;
+        parts.join(".")
+    }
+
+    #[test]
+    fn dec_reads_the_text_of_enc() {
+        use_keys("current-key", &[]);
+        let encrypted_text = enc(String::from("{\"user_id\":\"ada\"}")).unwrap();
+        assert!(encrypted_text.starts_with("v1."));
+        assert!(!encrypted_text.contains("ada"));
+        assert_eq!(dec(encrypted_text).unwrap(), "{\"user_id\":\"ada\"}");
+    }
+
+    #[test]
+    fn enc_uses_a_new_nonce_every_time() {
+        use_keys("current-key", &[]);
+        assert_ne!(enc(String::from("ada")).unwrap(), enc(String::from("ada")).unwrap());
+    }
+
+    #[test]
+    fn dec_rejects_a_tampered_ciphertext() {
+        use_keys("current-key", &[]);
+        let encrypted_text = enc(String::from("ada")).unwrap();
+        let mut payload = URL_SAFE_NO_PAD.decode(encrypted_text.split('.').nth(2).unwrap()).unwrap();
+        let last = payload.len() - 1;
+        payload[last] ^= 0x01;
+        let tampered = with_part(&encrypted_text, 2, URL_SAFE_NO_PAD.encode(payload));
+        assert_eq!(dec(tampered), Err(JabraError::CookieFetchError));
+    }
+
+    #[test]
+    fn dec_rejects_a_changed_key_id() {
+        use_keys("current-key", &[]);
+        let encrypted_text = enc(String::from("ada")).unwrap();
+        let changed = with_part(&encrypted_text, 1, String::from("00000000"));
+        assert_eq!(dec(changed), Err(JabraError::CookieFetchError));
+    }
+
+    #[test]
+    fn dec_rejects_an_unknown_envelope_version() {
+        use_keys("current-key", &[]);
+        let encrypted_text = enc(String::from("ada")).unwrap();
+        assert_eq!(dec(with_part(&encrypted_text, 0, String::from("v2"))), Err(JabraError::CookieFetchError));
+    }
+
+    #[test]
+    fn dec_reads_the_cookies_of_a_previous_key_until_it_is_removed() {
+        use_keys("old-key", &[]);
+        let encrypted_text = enc(String::from("ada")).unwrap();
+
+        use_keys("new-key", &["old-key"]);
+        assert_eq!(dec(encrypted_text.clone()).unwrap(), "ada");
+        let reencrypted_text = enc(String::from("ada")).unwrap();
+        assert_ne!(reencrypted_text.split('.').nth(1), encrypted_text.split('.').nth(1));
+
+        use_keys("new-key", &[]);
+        assert_eq!(dec(encrypted_text), Err(JabraError::CookieFetchError));
+        assert_eq!(dec(reencrypted_text).unwrap(), "ada");
+    }
+
+    #[test]
+    fn dec_reads_the_cookies_of_magic_crypt() {
+        use magic_crypt::{new_magic_crypt, MagicCryptTrait};
+        use_keys("current-key", &["old-key"]);
+        let legacy_text = new_magic_crypt!("old-key", 256).encrypt_str_to_base64("ada");
+        assert_eq!(dec(legacy_text).unwrap(), "ada");
+        let unknown_text = new_magic_crypt!("unknown-key", 256).encrypt_str_to_base64("ada");
+        assert_eq!(dec(unknown_text), Err(JabraError::CookieFetchError));
+    }
+
+    #[test]
+    fn enc_and_dec_need_a_key() {
+        use_keys(" ", &["old-key"]);
+        assert!(matches!(enc(String::from("ada")), Err(JabraError::ConfigurationError(_))));
+        assert!(matches!(dec(String::from("v1.00000000.AAAA")), Err(JabraError::ConfigurationError(_))));
+    }
 }
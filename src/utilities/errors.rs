@@ -1,8 +1,32 @@
 use leptos::*;
 use serde::{Deserialize, Serialize};
 
+/// The details of a failed API response.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ApiErrorDetails {
+    /// The HTTP status of the response.
+    pub status: u16,
+    /// The Directus `extensions.code` of the first error, if any.
+    pub code: Option<String>,
+    /// The messages of all the errors of the response.
+    pub messages: Vec<String>,
+    /// The path of the endpoint that failed.
+    pub endpoint: String,
+}
+
+impl ApiErrorDetails {
+    /// Returns the messages joined in one line, or the fallback when the API sent none.
+    pub fn get_message(&self, fallback: &str) -> String {
+        if self.messages.is_empty() {
+            fallback.to_string()
+        } else {
+            self.messages.join(" ")
+        }
+    }
+}
+
 /// Enum representing the different errors that can occur when interacting with the Jabra API.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum JabraError {
     /// Error when fetching the cookie from the Jabra API.
     #[serde(rename = "CookieFetchError")]
@@ -10,6 +34,9 @@ pub enum JabraError {
     /// Error when the username or password does not match.
     #[serde(rename = "LoginError")]
     LoginError,
+    /// Error when the user has two-factor authentication and the one-time password is missing or invalid.
+    #[serde(rename = "OtpRequiredError")]
+    OtpRequiredError,
     /// Error when no data is found.
     #[serde(rename = "NoDataFoundError")]
     NoDataFoundError,
@@ -19,10 +46,139 @@ pub enum JabraError {
     /// Error when making a request to the Jabra API.
     #[serde(rename = "ReqwestError")]
     ReqwestError(String),
-    /// Error when the API response is not successful.
+    /// Error when the API response is not successful, and not one of the errors below.
     #[serde(rename = "APIResponseError")]
-    APIResponseError(String),
+    APIResponseError(ApiErrorDetails),
+    /// Error when the API does not accept the token of the session.
+    #[serde(rename = "Unauthorized")]
+    Unauthorized(ApiErrorDetails),
+    /// Error when the user is not allowed to access the collection or field.
+    #[serde(rename = "Forbidden")]
+    Forbidden(ApiErrorDetails),
+    /// Error when the record or endpoint does not exist.
+    #[serde(rename = "NotFound")]
+    NotFound(ApiErrorDetails),
+    /// Error when the API rejects the payload of the request.
+    #[serde(rename = "Validation")]
+    Validation(ApiErrorDetails),
+    /// Error when too many requests were sent.
+    #[serde(rename = "RateLimited")]
+    RateLimited(ApiErrorDetails),
+    /// Error when the API did not answer in time, holds the endpoint.
+    #[serde(rename = "Timeout")]
+    Timeout(String),
+    /// Error when the session can no longer be refreshed.
+    #[serde(rename = "SessionExpiredError")]
+    SessionExpiredError,
+    /// Error when the runtime configuration is missing or invalid.
+    #[serde(rename = "ConfigurationError")]
+    ConfigurationError(String),
+    /// Error when approving the quotes would breach the credit limits, holds a message per breached limit.
+    #[serde(rename = "CreditLimitBreach")]
+    CreditLimitBreach(Vec<String>),
+    /// Error when a step of an approval workflow is not allowed, holds the reason.
+    #[serde(rename = "ApprovalError")]
+    ApprovalError(String),
 }
+
+impl JabraError {
+    /// Builds the error of a failed response from its HTTP status and body.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The HTTP status of the response
+    /// * `endpoint` - The path of the endpoint that failed
+    /// * `body` - The body of the response, a Directus `ErrorResponse` or empty
+    ///
+    /// # Returns
+    ///
+    /// The JabraError matching the status and the Directus error code.
+    pub fn from_response(status: u16, endpoint: String, body: &str) -> Self {
+        let mut details = ApiErrorDetails {
+            status,
+            endpoint,
+            ..Default::default()
+        };
+        if body.trim().is_empty() {
+            details.messages.push(String::from("System is busy"));
+            return JabraError::APIResponseError(details);
+        }
+        match ErrorResponse::de(body) {
+            Ok(res) => {
+                details.code = res.errors.first().and_then(|e| e.extensions.code.clone());
+                details.messages = res.errors.into_iter().map(|e| e.message).collect();
+            }
+            Err(e) => log::error!("error parsing the error response of {}: {:?}", details.endpoint, e),
+        }
+        match (status, details.code.as_deref()) {
+            (401, _) | (_, Some("TOKEN_EXPIRED")) | (_, Some("INVALID_TOKEN")) => JabraError::Unauthorized(details),
+            (403, _) | (_, Some("FORBIDDEN")) => JabraError::Forbidden(details),
+            (404, _) | (_, Some("ROUTE_NOT_FOUND")) => JabraError::NotFound(details),
+            (400, _) | (422, _) | (_, Some("INVALID_PAYLOAD")) | (_, Some("FAILED_VALIDATION")) => {
+                JabraError::Validation(details)
+            }
+            (429, _) | (_, Some("REQUESTS_EXCEEDED")) => JabraError::RateLimited(details),
+            (408, _) | (504, _) => JabraError::Timeout(details.endpoint),
+            _ => JabraError::APIResponseError(details),
+        }
+    }
+
+    /// Returns the Directus error code of a failed API response.
+    pub fn get_code(&self) -> Option<&str> {
+        match self {
+            JabraError::APIResponseError(details)
+            | JabraError::Unauthorized(details)
+            | JabraError::Forbidden(details)
+            | JabraError::NotFound(details)
+            | JabraError::Validation(details)
+            | JabraError::RateLimited(details) => details.code.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` when the same request may succeed if it is sent again later.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            JabraError::ReqwestError(_) | JabraError::RateLimited(_) | JabraError::Timeout(_) => true,
+            JabraError::APIResponseError(details) => details.status >= 500,
+            _ => false,
+        }
+    }
+
+    /// Returns the message shown to the user on the pages.
+    pub fn get_user_message(&self) -> String {
+        match self {
+            JabraError::CookieFetchError | JabraError::SessionExpiredError | JabraError::Unauthorized(_) => {
+                "Your session has ended. Please relog your account.".to_string()
+            }
+            JabraError::Forbidden(_) => "You do not have permission to perform this action.".to_string(),
+            JabraError::NotFound(_) => "The record was not found, it may have been removed.".to_string(),
+            JabraError::Validation(details) => {
+                format!("The request was rejected: {}", details.get_message("invalid data."))
+            }
+            JabraError::RateLimited(_) => "Too many requests. Please wait a moment and try again.".to_string(),
+            JabraError::Timeout(_) => "The server took too long to respond. Please try again.".to_string(),
+            JabraError::ReqwestError(_) => "Cannot connect to server. Please try again.".to_string(),
+            JabraError::APIResponseError(details) => details.get_message("System is busy. Please try again."),
+            JabraError::CreditLimitBreach(breaches) => {
+                format!("The approval would breach the credit limits. {}.", breaches.join(". "))
+            }
+            JabraError::ApprovalError(reason) => reason.to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Recovers the JabraError of a service from its `ServerFnError`.
+    /// Errors that were not raised by a JabraError are returned as a `ReqwestError` with their message.
+    pub fn from_server_fn_error(error: &ServerFnError) -> Self {
+        match error {
+            ServerFnError::ServerError(message) => serde_json::from_str::<JabraError>(message)
+                .unwrap_or_else(|_| JabraError::ReqwestError(message.clone())),
+            e => JabraError::ReqwestError(e.to_string()),
+        }
+    }
+}
+
 impl ToString for JabraError {
     /// Convert the JabraError to a string.
     ///
@@ -33,10 +189,27 @@ impl ToString for JabraError {
         match self {
             JabraError::CookieFetchError => "Cookie not found".to_string(),
             JabraError::LoginError => "Username or Password does not matched".to_string(),
+            JabraError::OtpRequiredError => "A valid one-time password is required".to_string(),
             JabraError::NoDataFoundError => "Data does not load correctly".to_string(),
             JabraError::SerializationError(e) => e.to_string(),
             JabraError::ReqwestError(e) => e.to_string(),
-            JabraError::APIResponseError(message) => message.to_string(),
+            JabraError::APIResponseError(details)
+            | JabraError::Unauthorized(details)
+            | JabraError::Forbidden(details)
+            | JabraError::NotFound(details)
+            | JabraError::Validation(details)
+            | JabraError::RateLimited(details) => format!(
+                "{} {} ({}): {}",
+                details.status,
+                details.endpoint,
+                details.code.clone().unwrap_or_default(),
+                details.get_message("no message")
+            ),
+            JabraError::Timeout(endpoint) => format!("{} timed out", endpoint),
+            JabraError::SessionExpiredError => "Session has expired, please login again".to_string(),
+            JabraError::ConfigurationError(message) => message.to_string(),
+            JabraError::CreditLimitBreach(breaches) => format!("Credit limits breached: {}", breaches.join("; ")),
+            JabraError::ApprovalError(reason) => format!("Approval not allowed: {}", reason),
         }
     }
 }
@@ -44,6 +217,10 @@ impl ToString for JabraError {
 // Create From implementation for reqwest::Error, since it does not allow Clone
 impl From<reqwest::Error> for JabraError {
     fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            let endpoint = error.url().map(|u| u.path().to_string()).unwrap_or_default();
+            return JabraError::Timeout(endpoint);
+        }
         JabraError::ReqwestError(error.to_string())
     }
 }
@@ -54,6 +231,16 @@ impl From<SerializationError> for JabraError {
     }
 }
 
+// The services return a ServerFnError, the JabraError is kept as JSON so the pages can recover it.
+impl From<JabraError> for ServerFnError {
+    fn from(error: JabraError) -> Self {
+        match serde_json::to_string(&error) {
+            Ok(json) => ServerFnError::ServerError(json),
+            Err(_) => ServerFnError::ServerError(error.to_string()),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ErrorResponse {
     pub errors: Vec<Error>,
@@ -62,10 +249,12 @@ pub struct ErrorResponse {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Error {
     pub message: String,
+    #[serde(default)]
     pub extensions: Extension,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct Extension {
-    pub code: String,
+    #[serde(default)]
+    pub code: Option<String>,
 }
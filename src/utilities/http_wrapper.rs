@@ -1,6 +1,17 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+use super::configuration::get_config;
+use super::cookies::{get_jabra_cookie, get_session_cookie, refresh_token, set_jabra_cookie, JabraCookie};
 use super::errors::{ErrorResponse, JabraError};
 use leptos::*;
 
+/// The name of the cookie holding the session of the admin portal.
+pub const COOKIE_NAME: &str = "admin_portal_csr";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum HttpMethod {
     POST,
     GET,
@@ -29,55 +40,10 @@ where
     Request: serde::Serialize,
     Response: Serializable,
 {
-    let client = reqwest::Client::new();
-    let response = match method {
-        HttpMethod::GET => {
-            let path = match request {
-                Some(req) => {
-                    let query_string = serde_urlencoded::to_string(req);
-                    match query_string {
-                        Ok(query_string) => format!("{}?{}", url, query_string),
-                        _ => url,
-                    }
-                }
-                None => url,
-            };
-            client.get(&path).headers(headers).send().await
-        }
-        HttpMethod::POST => {
-            client
-                .post(url)
-                .headers(headers)
-                .json(&request.unwrap())
-                .send()
-                .await
-        }
-        HttpMethod::PATCH => {
-            client
-                .patch(url)
-                .headers(headers)
-                .json(&request.unwrap())
-                .send()
-                .await
-        }
-    };
+    let response = send_request(request.as_ref(), url.as_str(), headers, method).await;
     // log::info!("Response: {:?}", response);
     match response {
-        Ok(res) => {
-            if res.status() == reqwest::StatusCode::OK {
-                let response = res.text().await.map_err(|e| JabraError::from(e))?;
-                Response::de(&response).map_err(|e| JabraError::from(e))
-            } else {
-                // Err(JabraError::APIResponseError(res.status().to_string()))
-                let res_text = res.text().await.map_err(|e| JabraError::from(e))?;
-                if res_text.len() == 0 {
-                    return Err(JabraError::APIResponseError(String::from("System is busy")));
-                }
-                let res_json: ErrorResponse = ErrorResponse::de(res_text.as_str()).map_err(|e| JabraError::from(e))?;
-                log::info!("RES_JSON: {:?}", res_json);
-                Err(JabraError::APIResponseError(res_json.errors[0].message.clone().to_string()))
-            }
-        }
+        Ok(res) => parse_response::<Response>(res).await,
         Err(e) => Err(JabraError::from(e)),
     }
 }
@@ -102,21 +68,38 @@ pub async fn call<Request>(
     headers: reqwest::header::HeaderMap,
     method: HttpMethod,
 ) -> Result<bool, JabraError>
+where
+    Request: serde::Serialize,
+{
+    let response = send_request(request.as_ref(), url.as_str(), headers, method).await;
+    match response {
+        Ok(res) => parse_status(res).await,
+        Err(e) => Err(JabraError::from(e)),
+    }
+}
+
+/// Sends the request with the given method, GET requests send the body as a query string.
+async fn send_request<Request>(
+    request: Option<&Request>,
+    url: &str,
+    headers: reqwest::header::HeaderMap,
+    method: HttpMethod,
+) -> Result<reqwest::Response, reqwest::Error>
 where
     Request: serde::Serialize,
 {
     let client = reqwest::Client::new();
-    let response = match method {
+    match method {
         HttpMethod::GET => {
             let path = match request {
                 Some(req) => {
                     let query_string = serde_urlencoded::to_string(req);
                     match query_string {
                         Ok(query_string) => format!("{}?{}", url, query_string),
-                        _ => url,
+                        _ => url.to_string(),
                     }
                 }
-                None => url,
+                None => url.to_string(),
             };
             client.get(&path).headers(headers).send().await
         }
@@ -136,21 +119,385 @@ where
                 .send()
                 .await
         }
-    };
-    match response {
-        Ok(res) => {
-            if res.status().is_success() {
-                Ok(true)
+    }
+}
+
+/// Parses a successful response body, or the error message of the API.
+async fn parse_response<Response>(res: reqwest::Response) -> Result<Response, JabraError>
+where
+    Response: Serializable,
+{
+    if res.status() == reqwest::StatusCode::OK {
+        let response = res.text().await.map_err(|e| JabraError::from(e))?;
+        Response::de(&response).map_err(|e| JabraError::from(e))
+    } else {
+        Err(parse_error(res).await)
+    }
+}
+
+/// Returns `true` when the response is successful, or the error message of the API.
+async fn parse_status(res: reqwest::Response) -> Result<bool, JabraError> {
+    if res.status().is_success() {
+        Ok(true)
+    } else {
+        Err(parse_error(res).await)
+    }
+}
+
+/// Returns the JabraError of a failed response, with its status, Directus error code, messages and endpoint.
+pub(crate) async fn parse_error(res: reqwest::Response) -> JabraError {
+    let status = res.status().as_u16();
+    let endpoint = res.url().path().to_string();
+    match res.text().await {
+        Ok(text) => JabraError::from_response(status, endpoint, text.as_str()),
+        Err(e) => JabraError::from(e),
+    }
+}
+
+thread_local! {
+    static SESSION_EXPIRED: RefCell<Option<RwSignal<bool>>> = RefCell::new(None);
+}
+
+/// Registers the signal that is set to `true` when the session can no longer be refreshed.
+/// This is called once by the `App`.
+pub fn provide_session_expired_signal(signal: RwSignal<bool>) {
+    SESSION_EXPIRED.with(|s| *s.borrow_mut() = Some(signal));
+}
+
+fn emit_session_expired() {
+    SESSION_EXPIRED.with(|s| {
+        if let Some(signal) = *s.borrow() {
+            signal.set(true);
+        }
+    });
+}
+
+/// The backends used by the admin portal.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    Directus,
+    Pricer,
+    Gateway,
+    GatewayPublic,
+    WasmCloud,
+}
+
+impl Backend {
+    pub fn base_url(&self) -> String {
+        let config = get_config();
+        match self {
+            Backend::Directus => config.directus_url,
+            Backend::Pricer => config.pricer_engine_url,
+            Backend::Gateway => config.jabra_api_gateway,
+            Backend::GatewayPublic => config.jabra_api_gateway_pub,
+            Backend::WasmCloud => config.wasmcloud_url,
+        }
+    }
+}
+
+/// An authenticated client for the backends of the admin portal.
+///
+/// The client reads the session cookie and injects the bearer token on every request.
+/// The token is refreshed when the cookie is expired, or when the backend answers `401 Unauthorized`,
+/// in which case the request is retried once. When the refresh fails the session expired signal is emitted.
+/// The requests sent at the same time share one refresh, see [`refresh_session`].
+///
+/// # Example
+///
+/// ```ignore
+/// let response = ApiClient::new(Backend::Directus)
+///     .call_and_parse::<BlankRequest, InterestRatesResponse>(None, path, HttpMethod::GET)
+///     .await;
+/// ```
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ApiClient {
+    backend: Backend,
+}
+
+impl ApiClient {
+    pub fn new(backend: Backend) -> Self {
+        Self { backend }
+    }
+
+    /// Sends the request to `path`, relative to the url of the backend, and parses the response.
+    pub async fn call_and_parse<Request, Response>(
+        &self,
+        request: Option<Request>,
+        path: String,
+        method: HttpMethod,
+    ) -> Result<Response, JabraError>
+    where
+        Request: serde::Serialize,
+        Response: Serializable,
+    {
+        let res = self.send(request, path, method).await?;
+        parse_response::<Response>(res).await
+    }
+
+    /// Sends the request to `path`, relative to the url of the backend, and returns the success of the request.
+    pub async fn call<Request>(
+        &self,
+        request: Option<Request>,
+        path: String,
+        method: HttpMethod,
+    ) -> Result<bool, JabraError>
+    where
+        Request: serde::Serialize,
+    {
+        let res = self.send(request, path, method).await?;
+        parse_status(res).await
+    }
+
+    async fn send<Request>(
+        &self,
+        request: Option<Request>,
+        path: String,
+        method: HttpMethod,
+    ) -> Result<reqwest::Response, JabraError>
+    where
+        Request: serde::Serialize,
+    {
+        let base_url = self.backend.base_url();
+        if base_url.is_empty() {
+            return Err(JabraError::ConfigurationError(format!(
+                "The url of {:?} is not configured",
+                self.backend
+            )));
+        }
+        let url = format!("{}{}", base_url, path);
+        let session = get_valid_session().await?;
+        let headers = get_authorization_headers(&session)?;
+        let res = send_request(request.as_ref(), url.as_str(), headers, method).await?;
+        if res.status() != reqwest::StatusCode::UNAUTHORIZED {
+            return Ok(res);
+        }
+        log::info!("unauthorized, refreshing the token and retrying: {}", path);
+        let session = refresh_or_expire(session).await?;
+        let headers = get_authorization_headers(&session)?;
+        let res = send_request(request.as_ref(), url.as_str(), headers, method).await?;
+        if res.status() == reqwest::StatusCode::UNAUTHORIZED {
+            emit_session_expired();
+            return Err(JabraError::SessionExpiredError);
+        }
+        Ok(res)
+    }
+}
+
+thread_local! {
+    static REFRESH_LOCK: RefCell<RefreshLock> = RefCell::new(RefreshLock::default());
+}
+
+/// Serializes the refreshes of the token. Directus rotates the refresh token on every use,
+/// so concurrent refreshes with the same token would fail for all but the first caller.
+
+#[derive(Default)]
+struct RefreshLock {
+    locked: bool,
+    waiters: Vec<Waker>,
+}
+
+/// Held while a refresh is in flight, the next waiting caller is woken when it is dropped.
+struct RefreshGuard;
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        let waiters = REFRESH_LOCK.with(|lock| {
+            let mut lock = lock.borrow_mut();
+            lock.locked = false;
+            std::mem::take(&mut lock.waiters)
+        });
+        waiters.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// Resolves to the [`RefreshGuard`] once no other refresh is in flight.
+struct AcquireRefresh;
+
+impl Future for AcquireRefresh {
+    type Output = RefreshGuard;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<RefreshGuard> {
+        REFRESH_LOCK.with(|lock| {
+            let mut lock = lock.borrow_mut();
+            if lock.locked {
+                lock.waiters.push(cx.waker().clone());
+                Poll::Pending
             } else {
-                let res_text = res.text().await.map_err(|e| JabraError::from(e))?;
-                if res_text.len() == 0 {
-                    return Err(JabraError::APIResponseError(String::from("System is busy")));
-                }
-                let res_json: ErrorResponse = ErrorResponse::de(res_text.as_str()).map_err(|e| JabraError::from(e))?;
-                log::info!("RES_JSON: {:?}", res_json);
-                Err(JabraError::APIResponseError(res_json.errors[0].message.clone().to_string()))
+                lock.locked = true;
+                Poll::Ready(RefreshGuard)
             }
+        })
+    }
+}
+
+/// Refreshes the token of the session and stores it in the cookie, keeping the permissions of the user.
+/// Only one refresh runs at a time. When the cookie was already refreshed by another caller while this one waited,
+/// the refreshed session is returned instead of using the rotated refresh token of `jwt_cookie` again.
+pub async fn refresh_session(jwt_cookie: JabraCookie) -> Result<JabraCookie, JabraError> {
+    let _guard = AcquireRefresh.await;
+    if let Some(current) = get_session_cookie(COOKIE_NAME) {
+        if current.refresh_token != jwt_cookie.refresh_token && !current.is_expired() {
+            return Ok(current);
         }
-        Err(e) => Err(JabraError::from(e)),
+    }
+    let refreshed = refresh_token(jwt_cookie.user_id, jwt_cookie.refresh_token)
+        .await?
+        .with_permissions(jwt_cookie.permissions);
+    set_jabra_cookie(refreshed.clone(), COOKIE_NAME.to_string()).await;
+    Ok(refreshed)
+}
+
+/// Returns the session of the cookie, refreshed first when its token is expired.
+async fn get_valid_session() -> Result<JabraCookie, JabraError> {
+    let cookie = get_jabra_cookie(COOKIE_NAME.to_string()).await;
+    let jwt_cookie = match JabraCookie::decrypt(cookie) {
+        Ok(c) => c,
+        Err(_) => {
+            emit_session_expired();
+            return Err(JabraError::SessionExpiredError);
+        }
+    };
+    if jwt_cookie.is_expired() {
+        return refresh_or_expire(jwt_cookie).await;
+    }
+    Ok(jwt_cookie)
+}
+
+/// Refreshes the session, emitting the session expired signal when it cannot be refreshed.
+async fn refresh_or_expire(jwt_cookie: JabraCookie) -> Result<JabraCookie, JabraError> {
+    match refresh_session(jwt_cookie).await {
+        Ok(r) => Ok(r),
+        Err(e) => {
+            log::error!("error-token: {:?}", e);
+            emit_session_expired();
+            Err(JabraError::SessionExpiredError)
+        }
+    }
+}
+
+/// Returns the headers with the bearer token of the session.
+fn get_authorization_headers(jwt_cookie: &JabraCookie) -> Result<reqwest::header::HeaderMap, JabraError> {
+    let bearer = format!("Bearer {}", jwt_cookie.access_token);
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(
+        "Authorization",
+        reqwest::header::HeaderValue::from_str(&bearer)
+            .map_err(|e| JabraError::SerializationError(e.to_string()))?,
+    );
+    Ok(headers)
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use std::rc::Rc;
+
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+    use crate::commons::models::defaults::BlankRequest;
+    use crate::utilities::mock_backend::{
+        fixture, settle, url, MockBackend, ACCESS_TOKEN, REFRESHED_ACCESS_TOKEN, REFRESHED_REFRESH_TOKEN,
+        REFRESH_TOKEN,
+    };
+
+    async fn get_currencies() -> Result<bool, JabraError> {
+        ApiClient::new(Backend::Directus)
+            .call::<BlankRequest>(None, String::from("/items/supported_ccy"), HttpMethod::GET)
+            .await
+    }
+
+    fn get_refreshes(backend: &MockBackend) -> usize {
+        backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/auth/refresh")).len()
+    }
+
+    #[wasm_bindgen_test]
+    async fn api_client_sends_the_bearer_token_of_the_session() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        assert!(get_currencies().await.unwrap());
+        assert_eq!(backend.requests()[0].authorization, format!("Bearer {}", ACCESS_TOKEN));
+        assert_eq!(get_refreshes(&backend), 0);
+    }
+
+    #[wasm_bindgen_test]
+    async fn api_client_refreshes_an_expired_session_before_the_request() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        backend.expire_session().await;
+        assert!(get_currencies().await.unwrap());
+        let requests = backend.requests();
+        assert_eq!(requests[0].endpoint, url(Backend::Directus, "/auth/refresh"));
+        assert_eq!(requests[0].get_body()["refresh_token"], REFRESH_TOKEN);
+        assert_eq!(requests[1].authorization, format!("Bearer {}", REFRESHED_ACCESS_TOKEN));
+        settle().await;
+        let cookie = get_session_cookie(COOKIE_NAME).unwrap();
+        assert_eq!(cookie.refresh_token, REFRESHED_REFRESH_TOKEN);
+        assert!(!cookie.is_expired());
+    }
+
+    #[wasm_bindgen_test]
+    async fn api_client_retries_once_after_refreshing_on_unauthorized() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures().respond_once(
+            HttpMethod::GET,
+            url(Backend::Directus, "/items/supported_ccy"),
+            401,
+            fixture!("error_token_expired.json"),
+        );
+        assert!(get_currencies().await.unwrap());
+        let currencies = backend.requests_to(HttpMethod::GET, &url(Backend::Directus, "/items/supported_ccy"));
+        assert_eq!(currencies.len(), 2);
+        assert_eq!(currencies[0].authorization, format!("Bearer {}", ACCESS_TOKEN));
+        assert_eq!(currencies[1].authorization, format!("Bearer {}", REFRESHED_ACCESS_TOKEN));
+        assert_eq!(get_refreshes(&backend), 1);
+    }
+
+    #[wasm_bindgen_test]
+    async fn api_client_expires_the_session_when_the_retry_is_unauthorized() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures().respond(
+            HttpMethod::GET,
+            url(Backend::Directus, "/items/supported_ccy"),
+            401,
+            fixture!("error_token_expired.json"),
+        );
+        let result = get_currencies().await;
+        assert_eq!(result, Err(JabraError::SessionExpiredError));
+        assert_eq!(backend.requests_to(HttpMethod::GET, &url(Backend::Directus, "/items/supported_ccy")).len(), 2);
+    }
+
+    #[wasm_bindgen_test]
+    async fn api_client_shares_one_refresh_between_concurrent_requests() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        backend.expire_session().await;
+        let results = Rc::new(RefCell::new(Vec::<Result<bool, JabraError>>::new()));
+        for _ in 0..3 {
+            let results = results.clone();
+            spawn_local(async move {
+                let result = get_currencies().await;
+                results.borrow_mut().push(result);
+            });
+        }
+        while results.borrow().len() < 3 {
+            settle().await;
+        }
+        assert!(results.borrow().iter().all(|r| r == &Ok(true)));
+        assert_eq!(get_refreshes(&backend), 1);
+        let currencies = backend.requests_to(HttpMethod::GET, &url(Backend::Directus, "/items/supported_ccy"));
+        assert!(currencies
+            .iter()
+            .all(|r| r.authorization == format!("Bearer {}", REFRESHED_ACCESS_TOKEN)));
+    }
+
+    #[wasm_bindgen_test]
+    async fn api_client_expires_the_session_without_a_cookie() {
+        let backend = MockBackend::start().await;
+        backend.serve_fixtures();
+        backend.sign_out().await;
+        assert_eq!(get_currencies().await, Err(JabraError::SessionExpiredError));
+        assert!(backend.requests().is_empty());
     }
 }
@@ -0,0 +1,365 @@
+use std::collections::HashMap;
+use std::future::Future;
+
+use leptos::ServerFnError;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use super::configuration::{set_config, AppConfig};
+use super::cookies::{remove_jabra_cookie, set_jabra_cookie, JabraCookie};
+use super::errors::JabraError;
+use super::http_wrapper::{Backend, HttpMethod, COOKIE_NAME};
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+/// The user, tokens and key of the session used by the tests.
+pub const USER_ID: &str = "7b3e2a52-8f0d-4c1e-9a51-3d1f2c6b9e10";
+pub const ACCESS_TOKEN: &str = "test-access-token";
+pub const REFRESH_TOKEN: &str = "test-refresh-token";
+/// The tokens returned by `fixtures/auth_refresh.json`.
+pub const REFRESHED_ACCESS_TOKEN: &str = "refreshed-access-token";
+pub const REFRESHED_REFRESH_TOKEN: &str = "refreshed-refresh-token";
+
+/// Returns the content of a recorded response of the `fixtures` directory.
+macro_rules! fixture {
+    ($name:literal) => {
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/", $name))
+    };
+}
+pub(crate) use fixture;
+
+// Replaces `fetch` so every request of reqwest is answered by the registered routes.
+// The routes are matched on the method and the url without its query string. A route registered `once` answers first
+// and is dropped after its answer, otherwise the last registered route wins. Unmatched requests get the fallback route,
+// or a Directus `ROUTE_NOT_FOUND` error.
+#[wasm_bindgen(inline_js = r#"
+const state = { routes: [], requests: [], fallback: null, installed: false };
+
+export function mock_reset() {
+    state.routes = [];
+    state.requests = [];
+    state.fallback = null;
+    if (state.installed) {
+        return;
+    }
+    state.installed = true;
+    globalThis.fetch = async (input, init) => {
+        const request = new Request(input, init);
+        const url = new URL(request.url);
+        const endpoint = url.origin + url.pathname;
+        state.requests.push({
+            method: request.method,
+            endpoint,
+            params: Object.fromEntries(url.searchParams),
+            body: request.method === "GET" ? "" : await request.text(),
+            authorization: request.headers.get("Authorization") || "",
+        });
+        const matches = (r) => r.method === request.method && r.endpoint === endpoint;
+        let index = state.routes.findIndex((r) => r.once && matches(r));
+        if (index < 0) {
+            index = state.routes.findLastIndex(matches);
+        }
+        const route = index >= 0 ? state.routes[index] : state.fallback;
+        if (route === null) {
+            const error = { errors: [{ message: "No fixture for " + request.method + " " + endpoint, extensions: { code: "ROUTE_NOT_FOUND" } }] };
+            return new Response(JSON.stringify(error), { status: 404 });
+        }
+        if (index >= 0 && route.once) {
+            state.routes.splice(index, 1);
+        }
+        const body = route.body === "" || route.status === 204 ? null : route.body;
+        return new Response(body, { status: route.status, headers: { "Content-Type": "application/json" } });
+    };
+}
+
+export function mock_route(method, endpoint, status, body, once) {
+    state.routes.push({ method, endpoint, status, body, once });
+}
+
+export function mock_fallback(status, body) {
+    state.fallback = { method: "", endpoint: "", status, body, once: false };
+}
+
+export function mock_requests() {
+    return JSON.stringify(state.requests);
+}
+"#)]
+extern "C" {
+    fn mock_reset();
+    fn mock_route(method: &str, endpoint: &str, status: u16, body: &str, once: bool);
+    fn mock_fallback(status: u16, body: &str);
+    fn mock_requests() -> String;
+}
+
+/// A request received by the stand-in backend.
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// The url of the request without its query string.
+    pub endpoint: String,
+    /// The decoded parameters of the query string.
+    pub params: HashMap<String, String>,
+    pub body: String,
+    pub authorization: String,
+}
+
+impl RecordedRequest {
+    /// Returns the decoded parameter of the query string, empty when it was not sent.
+    pub fn get_param(&self, name: &str) -> &str {
+        self.params.get(name).map(String::as_str).unwrap_or_default()
+    }
+
+    /// Returns the Directus filter of the query string, `null` when it was not sent.
+    pub fn get_filter(&self) -> serde_json::Value {
+        serde_json::from_str(self.get_param("filter")).unwrap_or(serde_json::Value::Null)
+    }
+
+    /// Returns the JSON body of the request, `null` when it has none.
+    pub fn get_body(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).unwrap_or(serde_json::Value::Null)
+    }
+}
+
+/// A stand-in for the Directus, pricer, gateway and Coinbase backends, answering with the recorded responses
+/// of the `fixtures` directory. Only one backend runs at a time, `start` drops the routes of the previous test.
+///
+/// # Example
+///
+/// ```ignore
+/// let backend = MockBackend::start().await;
+/// backend.serve_fixtures();
+/// backend.respond(HttpMethod::GET, url(Backend::Directus, "/items/counterparty"), 200, fixture!("counterparties_active.json"));
+/// let counterparties = get_counter_parties().await.unwrap();
+/// ```
+
+pub struct MockBackend;
+
+impl MockBackend {
+    /// Installs the backend with no routes, the test configuration and a signed in user.
+    pub async fn start() -> Self {
+        mock_reset();
+        set_config(get_test_config());
+        let backend = MockBackend;
+        backend.sign_in().await;
+        backend
+    }
+
+    /// Answers every endpoint used by the services with its recorded response.
+    pub fn serve_fixtures(&self) -> &Self {
+        let directus = |path: &str| url(Backend::Directus, path);
+        self.respond(HttpMethod::GET, directus("/items/counterparty"), 200, fixture!("counterparties.json"))
+            .respond(HttpMethod::GET, directus("/items/supported_ccy"), 200, fixture!("currencies.json"))
+            .respond(HttpMethod::GET, directus("/items/currency_pair"), 200, fixture!("currency_pairs.json"))
+            .respond(HttpMethod::GET, directus("/items/interest_rates"), 200, fixture!("interest_rates.json"))
+            .respond(HttpMethod::GET, directus("/items/credit_limit"), 200, fixture!("credit_limits.json"))
+            .respond(HttpMethod::GET, directus("/items/audit_log"), 200, fixture!("audit_logs.json"))
+            .respond(HttpMethod::GET, directus("/items/quotes_option"), 200, fixture!("quotes_option.json"))
+            .respond(HttpMethod::GET, directus("/items/trade"), 200, fixture!("trades.json"))
+            .respond(HttpMethod::GET, directus("/items/wallet_transaction"), 200, fixture!("wallet_transactions.json"))
+            .respond(HttpMethod::GET, directus("/items/loan"), 200, fixture!("loans.json"))
+            .respond(HttpMethod::GET, directus("/items/dcl"), 200, fixture!("dcl.json"))
+            .respond(HttpMethod::GET, directus("/users/me"), 200, fixture!("users_me.json"))
+            .respond(HttpMethod::GET, directus("/permissions/me"), 200, fixture!("permissions_me.json"))
+            .respond(HttpMethod::POST, directus("/auth/login"), 200, fixture!("auth_login.json"))
+            .respond(HttpMethod::POST, directus("/auth/refresh"), 200, fixture!("auth_refresh.json"))
+            .respond(HttpMethod::POST, directus("/auth/logout"), 204, "")
+            .respond(HttpMethod::POST, directus("/items/audit_log"), 204, "")
+            .respond(HttpMethod::POST, directus("/items/counterparty"), 200, fixture!("counterparty.json"))
+            .respond(HttpMethod::POST, directus("/items/interest_rates"), 204, "")
+            .respond(HttpMethod::POST, directus("/items/quotes_option"), 200, fixture!("quotes_option_created.json"))
+            .respond(HttpMethod::POST, directus("/items/wallet_transaction"), 204, "")
+            .respond(HttpMethod::PATCH, directus("/items/counterparty/2"), 204, "")
+            .respond(HttpMethod::PATCH, directus("/items/quotes_option"), 204, "")
+            .respond(HttpMethod::PATCH, directus("/items/trade"), 204, "")
+            .respond(HttpMethod::PATCH, directus("/items/wallet_transaction"), 200, fixture!("wallet_transaction_updated.json"))
+            .respond(HttpMethod::PATCH, directus("/items/dcl/7"), 204, "")
+            .respond(HttpMethod::GET, coinbase_url("/prices/BTC-USD/spot"), 200, fixture!("coinbase_spot.json"))
+            .respond(HttpMethod::POST, url(Backend::Pricer, "/quote/greeks"), 200, fixture!("quote_greeks.json"))
+            .respond(HttpMethod::POST, url(Backend::Pricer, "/risk/collateral"), 200, fixture!("risk_collateral.json"))
+            .respond(HttpMethod::POST, url(Backend::Pricer, "/risk/deribit"), 200, fixture!("risk_deribit.json"))
+            .respond(
+                HttpMethod::POST,
+                url(Backend::Pricer, "/risk/positions_itm_otm"),
+                200,
+                fixture!("risk_positions_itm_otm.json"),
+            )
+            .respond(
+                HttpMethod::POST,
+                url(Backend::Gateway, "/option_pricer/settlement_template"),
+                200,
+                fixture!("settlement_template.json"),
+            )
+            .respond(
+                HttpMethod::POST,
+                url(Backend::Gateway, "/rfq/submit_new_termsheet"),
+                200,
+                fixture!("submit_new_termsheet.json"),
+            )
+            .respond(
+                HttpMethod::GET,
+                url(Backend::WasmCloud, "/portfolios/summaries"),
+                200,
+                fixture!("portfolio_summary.json"),
+            )
+    }
+
+    /// Answers every request to `url` with `status` and `body`, an empty body is sent as no body.
+    pub fn respond(&self, method: HttpMethod, url: String, status: u16, body: &str) -> &Self {
+        mock_route(&format!("{:?}", method), &url, status, body, false);
+        self
+    }
+
+    /// Answers the next request to `url` only, before the routes registered with `respond`.
+    /// Several routes registered `once` answer in their order.
+    pub fn respond_once(&self, method: HttpMethod, url: String, status: u16, body: &str) -> &Self {
+        mock_route(&format!("{:?}", method), &url, status, body, true);
+        self
+    }
+
+    /// Answers every request without a route with `status` and `body`.
+    pub fn fail_all(&self, status: u16, body: &str) -> &Self {
+        mock_fallback(status, body);
+        self
+    }
+
+    /// Returns the requests received since `start`, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        serde_json::from_str(&mock_requests()).expect("the recorded requests are valid JSON")
+    }
+
+    /// Returns the requests received on `url` with `method`.
+    pub fn requests_to(&self, method: HttpMethod, url: &str) -> Vec<RecordedRequest> {
+        let method = format!("{:?}", method);
+        self.requests()
+            .into_iter()
+            .filter(|r| r.method == method && r.endpoint == url)
+            .collect()
+    }
+
+    /// Stores a valid session for the test user.
+    pub async fn sign_in(&self) {
+        let expires_in = chrono::Utc::now().timestamp_millis() + 3_600_000;
+        set_session(expires_in).await;
+    }
+
+    /// Stores a session whose token expired a minute ago, the next authenticated request refreshes it first.
+    pub async fn expire_session(&self) {
+        let expires_in = chrono::Utc::now().timestamp_millis() - 60_000;
+        set_session(expires_in).await;
+    }
+
+    /// Drops the session cookie.
+    pub async fn sign_out(&self) {
+        remove_jabra_cookie(COOKIE_NAME);
+        settle().await;
+    }
+}
+
+/// Returns the url of `path` on the backend, as the services build it.
+pub fn url(backend: Backend, path: &str) -> String {
+    format!("{}{}", backend.base_url(), path)
+}
+
+/// Returns the url of `path` on Coinbase.
+pub fn coinbase_url(path: &str) -> String {
+    format!("{}{}", get_test_config().coinbase_v2, path)
+}
+
+fn get_test_config() -> AppConfig {
+    AppConfig {
+        directus_url: String::from("http://directus.mock"),
+        pricer_engine_url: String::from("http://pricer.mock"),
+        wasmcloud_url: String::from("http://wasmcloud.mock"),
+        jabra_api_gateway: String::from("http://gateway.mock"),
+        jabra_api_gateway_pub: String::from("http://gateway-pub.mock"),
+        coinbase_v2: String::from("http://coinbase.mock/v2"),
+        jabra_key: String::from("test-cookie-key"),
+        project_environment: String::from("test"),
+        ..Default::default()
+    }
+}
+
+async fn set_session(expires_in: i64) {
+    let cookie = JabraCookie::new(
+        USER_ID.to_string(),
+        ACCESS_TOKEN.to_string(),
+        REFRESH_TOKEN.to_string(),
+        expires_in,
+    );
+    set_jabra_cookie(cookie, COOKIE_NAME.to_string()).await;
+    settle().await;
+}
+
+/// Lets the effects writing the cookie run before the test continues.
+pub async fn settle() {
+    gloo_timers::future::TimeoutFuture::new(0).await;
+}
+
+/// Returns the JabraError of a failed service.
+pub fn get_error<T>(result: Result<T, ServerFnError>) -> JabraError {
+    match result {
+        Ok(_) => panic!("expected the service to fail"),
+        Err(e) => JabraError::from_server_fn_error(&e),
+    }
+}
+
+/// Checks that the service returns the errors of the backend: a Directus `ErrorResponse` as a validation error
+/// with its message, and an empty body as "System is busy".
+pub async fn assert_api_errors<T, F, Fut>(service: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, ServerFnError>>,
+{
+    let backend = MockBackend::start().await;
+    backend.fail_all(400, fixture!("error_invalid_payload.json"));
+    match get_error(service().await) {
+        JabraError::Validation(details) => {
+            assert_eq!(details.status, 400);
+            assert_eq!(details.code.as_deref(), Some("INVALID_PAYLOAD"));
+            assert_eq!(details.messages, vec![String::from("Invalid payload. \"amount\" is required.")]);
+        }
+        e => panic!("expected a validation error, got {:?}", e),
+    }
+
+    let backend = MockBackend::start().await;
+    backend.fail_all(503, "");
+    match get_error(service().await) {
+        JabraError::APIResponseError(details) => {
+            assert_eq!(details.status, 503);
+            assert_eq!(details.messages, vec![String::from("System is busy")]);
+        }
+        e => panic!("expected an API response error, got {:?}", e),
+    }
+}
+
+/// Checks that the service ends the session when its cookie is expired and Directus refuses the refresh.
+/// The other endpoints answer with their fixtures, so the unauthenticated calls of the service still succeed.
+pub async fn assert_session_expired<T, F, Fut>(service: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, ServerFnError>>,
+{
+    let backend = MockBackend::start().await;
+    backend.serve_fixtures().respond(
+        HttpMethod::POST,
+        url(Backend::Directus, "/auth/refresh"),
+        401,
+        fixture!("error_token_expired.json"),
+    );
+    backend.expire_session().await;
+    assert_eq!(get_error(service().await), JabraError::SessionExpiredError);
+    let refreshes = backend.requests_to(HttpMethod::POST, &url(Backend::Directus, "/auth/refresh"));
+    assert!(!refreshes.is_empty(), "the expired token is refreshed before the request");
+    assert!(refreshes.iter().all(|r| r.get_body()["refresh_token"] == REFRESH_TOKEN));
+}
+
+/// Checks all the error paths of an authenticated service.
+pub async fn assert_error_paths<T, F, Fut>(service: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, ServerFnError>>,
+{
+    assert_api_errors(&service).await;
+    assert_session_expired(&service).await;
+}
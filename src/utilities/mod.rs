@@ -4,6 +4,9 @@ pub mod date_util;
 pub mod encryption;
 pub mod errors;
 pub mod http_wrapper;
+#[cfg(all(test, target_arch = "wasm32"))]
+pub mod mock_backend;
 pub mod number_util;
 pub mod local;
+pub mod option_pricer;
 pub mod string_util;
\ No newline at end of file
@@ -0,0 +1,358 @@
+use std::f64::consts::{PI, SQRT_2};
+
+use crate::commons::models::{defaults::Greeks, trade::PositionGreekRequest};
+
+/// Number of days in a year used to convert `ttm` (in days) to years, same as the pricer engine.
+const DAYS_IN_YEAR: f64 = 365.0;
+
+/// This struct is the result of pricing an option locally.
+/// Prices are for the whole amount, greeks are signed by the side (a sold option is negative).
+/// Theta is per day, vega is per 1 vol point and rho is per 1% move of r2.
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LocalGreeks {
+    pub px_in_base_ccy: f64,
+    pub px_in_quote_ccy: f64,
+    pub delta: f64,
+    pub gamma: f64,
+    pub theta: f64,
+    pub vega: f64,
+    pub rho: f64,
+}
+
+impl LocalGreeks {
+    pub fn to_greeks(&self) -> Greeks {
+        Greeks {
+            delta: self.delta,
+            gamma: self.gamma,
+            theta: self.theta,
+            vega: self.vega,
+            rho: self.rho,
+        }
+    }
+}
+
+/// Standard normal probability density function.
+pub fn norm_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * PI).sqrt()
+}
+
+/// Standard normal cumulative distribution function.
+pub fn norm_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / SQRT_2)
+}
+
+/// Complementary error function, Numerical Recipes `erfcc` (fractional error below 1.2e-7).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587
+                                        + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+/// Prices a single unit of a european option using Garman-Kohlhagen.
+/// `r1` is the base currency rate, `r2` the quote currency rate, `iv` and the rates are decimals
+/// and `ttm` is in days. Returns the unsigned per unit price and greeks in the quote currency.
+pub fn price_option(
+    option_kind: &str,
+    spot: f64,
+    strike: f64,
+    ttm: f64,
+    r1: f64,
+    r2: f64,
+    iv: f64,
+) -> LocalGreeks {
+    let is_call = option_kind.eq_ignore_ascii_case("call");
+    let t = ttm / DAYS_IN_YEAR;
+
+    if spot <= 0.0 || strike <= 0.0 {
+        return LocalGreeks::default();
+    }
+    // Expired or zero vol options are worth their intrinsic value only.
+    if t <= 0.0 || iv <= 0.0 {
+        let intrinsic = if is_call {
+            (spot - strike).max(0.0)
+        } else {
+            (strike - spot).max(0.0)
+        };
+        let delta = match (is_call, spot > strike, spot < strike) {
+            (true, true, _) => 1.0,
+            (false, _, true) => -1.0,
+            _ => 0.0,
+        };
+        return LocalGreeks {
+            px_in_base_ccy: intrinsic / spot,
+            px_in_quote_ccy: intrinsic,
+            delta,
+            ..Default::default()
+        };
+    }
+
+    let sqrt_t = t.sqrt();
+    let d1 = ((spot / strike).ln() + (r2 - r1 + 0.5 * iv * iv) * t) / (iv * sqrt_t);
+    let d2 = d1 - iv * sqrt_t;
+    let df1 = (-r1 * t).exp();
+    let df2 = (-r2 * t).exp();
+    let pdf_d1 = norm_pdf(d1);
+
+    let (px, delta, theta_carry, rho) = if is_call {
+        (
+            spot * df1 * norm_cdf(d1) - strike * df2 * norm_cdf(d2),
+            df1 * norm_cdf(d1),
+            r1 * spot * df1 * norm_cdf(d1) - r2 * strike * df2 * norm_cdf(d2),
+            strike * t * df2 * norm_cdf(d2),
+        )
+    } else {
+        (
+            strike * df2 * norm_cdf(-d2) - spot * df1 * norm_cdf(-d1),
+            -df1 * norm_cdf(-d1),
+            r2 * strike * df2 * norm_cdf(-d2) - r1 * spot * df1 * norm_cdf(-d1),
+            -strike * t * df2 * norm_cdf(-d2),
+        )
+    };
+    let theta = -(spot * df1 * pdf_d1 * iv) / (2.0 * sqrt_t) + theta_carry;
+
+    LocalGreeks {
+        px_in_base_ccy: px / spot,
+        px_in_quote_ccy: px,
+        delta,
+        gamma: df1 * pdf_d1 / (spot * iv * sqrt_t),
+        theta: theta / DAYS_IN_YEAR,
+        vega: spot * df1 * pdf_d1 * sqrt_t / 100.0,
+        rho: rho / 100.0,
+    }
+}
+
+/// Prices a position from the same inputs sent to the pricer engine's `/quote/greeks`.
+/// Missing market data falls back to zero, like the pricer request defaults.
+pub fn price_position(request: &PositionGreekRequest) -> LocalGreeks {
+    let unit = price_option(
+        request.option_kind.as_str(),
+        request.spot.unwrap_or_default(),
+        request.strike,
+        request.ttm,
+        request.r1.unwrap_or_default(),
+        request.r2.unwrap_or_default(),
+        request.iv.unwrap_or_default(),
+    );
+    let size = if request.side.eq_ignore_ascii_case("sell") {
+        -request.amount.abs()
+    } else {
+        request.amount.abs()
+    };
+    LocalGreeks {
+        px_in_base_ccy: unit.px_in_base_ccy * request.amount.abs(),
+        px_in_quote_ccy: unit.px_in_quote_ccy * request.amount.abs(),
+        delta: unit.delta * size,
+        gamma: unit.gamma * size,
+        theta: unit.theta * size,
+        vega: unit.vega * size,
+        rho: unit.rho * size,
+    }
+}
+
+/// Returns the relative difference between a local and a server value, used to flag mismatches.
+pub fn relative_difference(local: f64, server: f64) -> f64 {
+    if server == 0.0 {
+        return if local == 0.0 { 0.0 } else { 1.0 };
+    }
+    ((local - server) / server).abs()
+}
+
+/// This struct is a leg of an option structure drawn by the `PayoffChart`.
+/// `premium` is the premium of the whole leg in the quote currency, as stored in `px_in_quote_ccy`,
+/// `iv` and `r2` are decimals and `ttm` is in days.
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PayoffLeg {
+    pub strike: f64,
+    pub side: String,
+    pub option_kind: String,
+    pub amount: f64,
+    pub premium: f64,
+    pub ttm: f64,
+    pub iv: f64,
+    pub r2: f64,
+}
+
+impl PayoffLeg {
+    /// Returns the sign of the side, a sold leg is negative.
+    fn get_sign(&self) -> f64 {
+        if self.side.eq_ignore_ascii_case("sell") {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Returns the pnl of the leg at expiry for a spot, in the quote currency.
+    pub fn get_payoff_at_expiry(&self, spot: f64) -> f64 {
+        let intrinsic = price_option(self.option_kind.as_str(), spot, self.strike, 0.0, 0.0, 0.0, 0.0);
+        self.get_pnl(intrinsic.px_in_quote_ccy)
+    }
+
+    /// Returns the pnl of the leg today for a spot, marked with the leg's own vol and rates.
+    pub fn get_mark_to_model(&self, spot: f64) -> f64 {
+        let price = price_option(self.option_kind.as_str(), spot, self.strike, self.ttm, 0.0, self.r2, self.iv);
+        self.get_pnl(price.px_in_quote_ccy)
+    }
+
+    /// Returns the pnl of the leg for a unit price, the premium is paid once for the whole leg.
+    fn get_pnl(&self, unit_price: f64) -> f64 {
+        (unit_price * self.amount.abs() - self.premium.abs()) * self.get_sign()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f64 = 1e-4;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < TOLERANCE, "expected {}, got {}", expected, actual);
+    }
+
+    fn request(side: &str, option_kind: &str, amount: f64) -> PositionGreekRequest {
+        PositionGreekRequest::new(
+            side.to_string(),
+            option_kind.to_string(),
+            amount,
+            100.0,
+            365.0,
+            0.0,
+            Some(100.0),
+            Some(0.05),
+            Some(0.0),
+            Some(0.2),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn prices_match_the_reference_values() {
+        let call = price_option("Call", 100.0, 100.0, 365.0, 0.0, 0.05, 0.2);
+        let put = price_option("Put", 100.0, 100.0, 365.0, 0.0, 0.05, 0.2);
+        assert_close(call.px_in_quote_ccy, 10.4506);
+        assert_close(put.px_in_quote_ccy, 5.5735);
+        assert_close(call.px_in_base_ccy, 0.104506);
+    }
+
+    #[test]
+    fn prices_respect_put_call_parity() {
+        for (spot, strike, ttm, r1, r2, iv) in [
+            (100.0, 100.0, 365.0, 0.0, 0.05, 0.2),
+            (62_000.0, 70_000.0, 30.0, 0.01, 0.045, 0.65),
+            (3_100.0, 2_500.0, 7.0, 0.02, 0.0, 0.8),
+        ] {
+            let t = ttm / 365.0;
+            let call = price_option("Call", spot, strike, ttm, r1, r2, iv);
+            let put = price_option("Put", spot, strike, ttm, r1, r2, iv);
+            let forward = spot * (-r1 * t).exp() - strike * (-r2 * t).exp();
+            assert!(
+                (call.px_in_quote_ccy - put.px_in_quote_ccy - forward).abs() < spot * 1e-6,
+                "parity broken for spot {} and strike {}",
+                spot,
+                strike
+            );
+        }
+    }
+
+    #[test]
+    fn greeks_have_the_expected_sign_and_size() {
+        let call = price_option("Call", 100.0, 100.0, 365.0, 0.0, 0.05, 0.2);
+        let put = price_option("Put", 100.0, 100.0, 365.0, 0.0, 0.05, 0.2);
+
+        assert_close(call.delta, 0.6368);
+        assert_close(put.delta, -0.3632);
+        assert_close(call.delta - put.delta, 1.0);
+
+        assert_close(call.gamma, 0.018762);
+        assert_close(put.gamma, call.gamma);
+
+        assert_close(call.vega, 0.375240);
+        assert_close(put.vega, call.vega);
+
+        assert_close(call.rho, 0.532325);
+        assert_close(put.rho, -0.418905);
+
+        assert!(call.theta < 0.0 && put.theta < 0.0);
+    }
+
+    #[test]
+    fn expired_options_are_worth_their_intrinsic_value() {
+        let call = price_option("Call", 110.0, 100.0, 0.0, 0.0, 0.05, 0.2);
+        let put = price_option("Put", 110.0, 100.0, 0.0, 0.0, 0.05, 0.2);
+        assert_close(call.px_in_quote_ccy, 10.0);
+        assert_close(call.delta, 1.0);
+        assert_close(put.px_in_quote_ccy, 0.0);
+        assert_close(put.delta, 0.0);
+    }
+
+    #[test]
+    fn positions_scale_by_amount_and_side() {
+        let unit = price_option("Call", 100.0, 100.0, 365.0, 0.0, 0.05, 0.2);
+        let bought = price_position(&request("Buy", "Call", 3.0));
+        let sold = price_position(&request("Sell", "Call", 3.0));
+
+        assert_close(bought.px_in_quote_ccy, unit.px_in_quote_ccy * 3.0);
+        assert_close(bought.delta, unit.delta * 3.0);
+        assert_close(bought.gamma, unit.gamma * 3.0);
+        assert_close(bought.vega, unit.vega * 3.0);
+        assert_close(bought.rho, unit.rho * 3.0);
+        assert_close(bought.theta, unit.theta * 3.0);
+
+        // The price stays positive for a sold option, the greeks flip sign.
+        assert_close(sold.px_in_quote_ccy, bought.px_in_quote_ccy);
+        assert_close(sold.delta, -bought.delta);
+        assert_close(sold.gamma, -bought.gamma);
+        assert_close(sold.vega, -bought.vega);
+        assert_close(sold.rho, -bought.rho);
+        assert_close(sold.theta, -bought.theta);
+
+        // A negative amount is read by its size, the side alone gives the sign.
+        assert_eq!(price_position(&request("Sell", "Call", -3.0)), sold);
+    }
+
+    #[test]
+    fn payoff_counts_the_premium_of_the_leg_once() {
+        let leg = PayoffLeg {
+            strike: 100.0,
+            side: String::from("Buy"),
+            option_kind: String::from("Call"),
+            amount: 10.0,
+            premium: 50.0,
+            ..Default::default()
+        };
+        assert_close(leg.get_payoff_at_expiry(90.0), -50.0);
+        assert_close(leg.get_payoff_at_expiry(110.0), 50.0);
+
+        let sold = PayoffLeg { side: String::from("Sell"), ..leg };
+        assert_close(sold.get_payoff_at_expiry(90.0), 50.0);
+        assert_close(sold.get_payoff_at_expiry(110.0), -50.0);
+    }
+
+    #[test]
+    fn relative_difference_handles_a_zero_server_value() {
+        assert_eq!(relative_difference(0.0, 0.0), 0.0);
+        assert_eq!(relative_difference(0.5, 0.0), 1.0);
+        assert_eq!(relative_difference(-0.5, 0.0), 1.0);
+        assert_close(relative_difference(101.0, 100.0), 0.01);
+        assert_close(relative_difference(99.0, 100.0), 0.01);
+    }
+}